@@ -0,0 +1,93 @@
+//! Hot-path benchmarks on synthetic account sets
+//! Run with `cargo bench` (1M-account cases take a while to set up)
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use solana_holder_bot::churn::diff_holders;
+use solana_holder_bot::quality::{identical_amount_groups, MIN_AMOUNT_CLUSTER};
+use solana_holder_bot::rpc_client::TOKEN_PROGRAM_ID;
+use solana_holder_bot::token_monitor::encode_token_account;
+use solana_holder_bot::{calculate_stats, extract_balances, extract_holders};
+use solana_sdk::account::Account;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashSet;
+use std::str::FromStr;
+
+const SIZES: &[usize] = &[10_000, 100_000, 1_000_000];
+
+/// `count` token accounts for one mint: ~10% zero balances, ~5% owners with two accounts
+fn synthetic_accounts(count: usize) -> Vec<(Pubkey, Account)> {
+    let mint = Pubkey::new_unique();
+    let token_program = Pubkey::from_str(TOKEN_PROGRAM_ID).unwrap();
+    let mut owner = Pubkey::new_unique();
+    (0..count)
+        .map(|i| {
+            if i % 20 != 0 {
+                owner = Pubkey::new_unique();
+            }
+            let amount = if i % 10 == 0 { 0 } else { (i as u64 % 1_000) * 1_000_000 };
+            let account = Account {
+                lamports: 2_039_280,
+                data: encode_token_account(&mint, &owner, amount),
+                owner: token_program,
+                executable: false,
+                rent_epoch: 0,
+            };
+            (Pubkey::new_unique(), account)
+        })
+        .collect()
+}
+
+fn bench_extract(c: &mut Criterion) {
+    let mut group = c.benchmark_group("extract");
+    group.sample_size(10);
+    for &size in SIZES {
+        let accounts = synthetic_accounts(size);
+        group.throughput(Throughput::Elements(size as u64));
+        group.bench_with_input(BenchmarkId::new("extract_holders", size), &accounts, |b, accounts| {
+            b.iter(|| extract_holders(black_box(accounts)).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("extract_balances", size), &accounts, |b, accounts| {
+            b.iter(|| extract_balances(black_box(accounts)))
+        });
+    }
+    group.finish();
+}
+
+fn bench_distribution(c: &mut Criterion) {
+    let mut group = c.benchmark_group("distribution");
+    group.sample_size(10);
+    for &size in SIZES {
+        let balances = extract_balances(&synthetic_accounts(size));
+        group.throughput(Throughput::Elements(size as u64));
+        group.bench_with_input(BenchmarkId::new("identical_amount_groups", size), &balances, |b, balances| {
+            b.iter(|| identical_amount_groups(black_box(balances), MIN_AMOUNT_CLUSTER))
+        });
+        group.bench_with_input(BenchmarkId::new("calculate_stats", size), &balances.len(), |b, count| {
+            b.iter(|| calculate_stats(black_box(*count), Some(black_box(count / 2))))
+        });
+    }
+    group.finish();
+}
+
+fn bench_diff(c: &mut Criterion) {
+    let mut group = c.benchmark_group("diff");
+    group.sample_size(10);
+    for &size in SIZES {
+        // 1% of holders replaced between polls
+        let previous: HashSet<Pubkey> = (0..size).map(|_| Pubkey::new_unique()).collect();
+        let mut current = previous.clone();
+        let exiting: Vec<Pubkey> = current.iter().take(size / 100).copied().collect();
+        for holder in exiting {
+            current.remove(&holder);
+            current.insert(Pubkey::new_unique());
+        }
+        group.throughput(Throughput::Elements(size as u64));
+        group.bench_with_input(BenchmarkId::new("diff_holders", size), &(previous, current), |b, (previous, current)| {
+            b.iter(|| diff_holders(black_box(previous), black_box(current)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_extract, bench_distribution, bench_diff);
+criterion_main!(benches);
@@ -0,0 +1,70 @@
+//! Build info for `GET /version` and `/health`: git commit, build time, enabled features and the
+//! Solana SDK version from Cargo.lock, passed to the crate as compile-time env variables
+
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn main() {
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let root = Path::new(&manifest_dir);
+
+    // Docker builds have no .git: the commit comes in as GIT_SHA (from VCS_REF)
+    println!("cargo:rerun-if-env-changed=GIT_SHA");
+    let git_sha = env::var("GIT_SHA").ok().filter(|sha| !sha.is_empty()).or_else(|| git_head(root));
+    if let Some(sha) = git_sha {
+        println!("cargo:rustc-env=GIT_SHA={}", sha);
+    }
+
+    // SOURCE_DATE_EPOCH keeps reproducible builds reproducible
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    let timestamp = env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.parse::<u64>().ok())
+        .unwrap_or_else(|| SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()));
+    println!("cargo:rustc-env=BUILD_TIMESTAMP={}", timestamp);
+
+    let mut features: Vec<String> = env::vars()
+        .filter_map(|(key, _)| key.strip_prefix("CARGO_FEATURE_").map(|name| name.to_lowercase().replace('_', "-")))
+        .filter(|name| name != "default")
+        .collect();
+    features.sort();
+    println!("cargo:rustc-env=BUILD_FEATURES={}", features.join(","));
+
+    println!("cargo:rerun-if-changed=Cargo.lock");
+    let solana = locked_version(&root.join("Cargo.lock"), "solana-sdk").unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=SOLANA_SDK_VERSION={}", solana);
+}
+
+/// Short commit of HEAD; reruns the script when HEAD moves
+fn git_head(root: &Path) -> Option<String> {
+    let git_dir = root.join(".git");
+    if git_dir.is_dir() {
+        println!("cargo:rerun-if-changed={}", git_dir.join("HEAD").display());
+        if let Ok(head) = fs::read_to_string(git_dir.join("HEAD")) {
+            if let Some(reference) = head.trim().strip_prefix("ref: ") {
+                println!("cargo:rerun-if-changed={}", git_dir.join(reference).display());
+            }
+        }
+    }
+    let output = Command::new("git")
+        .args(["rev-parse", "--short=12", "HEAD"])
+        .current_dir(root)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let sha = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (!sha.is_empty()).then_some(sha)
+}
+
+/// Version of `package` in the lock file
+fn locked_version(lock: &Path, package: &str) -> Option<String> {
+    let lock = fs::read_to_string(lock).ok()?;
+    let name = format!("name = \"{}\"", package);
+    let mut lines = lock.lines();
+    lines.find(|line| line.trim() == name)?;
+    let version = lines.next()?.trim().strip_prefix("version = ")?;
+    Some(version.trim_matches('"').to_string())
+}
@@ -0,0 +1,233 @@
+//! Pro-rata reward allocation for the `allocate` subcommand (and `merkle --total`): a total is
+//! split by snapshot balance with optional per-wallet caps, balance / amount minimums and a
+//! choice of rounding
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use solana_program::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+
+use crate::export::sorted_balances;
+
+/// How fractional shares become whole raw units
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Rounding {
+    /// Round down, then give the leftover units to the largest fractional parts (adds up to
+    /// exactly the total)
+    #[default]
+    LargestRemainder,
+    /// Round down; the leftover stays undistributed
+    Down,
+    /// Round half up; the sum may differ from the total by up to one unit per wallet
+    Nearest,
+}
+
+/// Parameters of a pro-rata split, in raw token units
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AllocationRules {
+    pub total: u64,
+    pub rounding: Rounding,
+    /// Most a single wallet receives; the excess is shared by the other wallets
+    pub cap: Option<u64>,
+    /// Wallets holding less than this are not eligible
+    pub min_balance: u64,
+    /// Wallets whose share would be less than this are dropped and their share redistributed
+    pub min_amount: u64,
+}
+
+impl AllocationRules {
+    pub fn new(total: u64) -> Self {
+        Self {
+            total,
+            rounding: Rounding::default(),
+            cap: None,
+            min_balance: 1,
+            min_amount: 1,
+        }
+    }
+
+    pub fn with_rounding(mut self, rounding: Rounding) -> Self {
+        self.rounding = rounding;
+        self
+    }
+
+    pub fn with_cap(mut self, cap: Option<u64>) -> Self {
+        self.cap = cap;
+        self
+    }
+
+    pub fn with_min_balance(mut self, min_balance: u64) -> Self {
+        self.min_balance = min_balance.max(1);
+        self
+    }
+
+    pub fn with_min_amount(mut self, min_amount: u64) -> Self {
+        self.min_amount = min_amount.max(1);
+        self
+    }
+}
+
+/// Split `rules.total` between the eligible wallets in proportion to their balances, largest
+/// balance first
+pub fn pro_rata(balances: HashMap<Pubkey, u64>, rules: &AllocationRules) -> Vec<(Pubkey, u64)> {
+    let mut eligible: Vec<(Pubkey, u64)> = sorted_balances(balances)
+        .into_iter()
+        .filter(|(_, balance)| *balance >= rules.min_balance)
+        .collect();
+    loop {
+        let amounts = split(&eligible, rules);
+        if amounts.iter().all(|(_, amount)| *amount >= rules.min_amount) {
+            return amounts;
+        }
+        // Drop the wallets below the minimum and split again: their share goes to the others
+        let kept: Vec<Pubkey> = amounts
+            .iter()
+            .filter(|(_, amount)| *amount >= rules.min_amount)
+            .map(|(wallet, _)| *wallet)
+            .collect();
+        eligible.retain(|(wallet, _)| kept.contains(wallet));
+    }
+}
+
+/// One pass of the split over `holders` (sorted, largest first)
+fn split(holders: &[(Pubkey, u64)], rules: &AllocationRules) -> Vec<(Pubkey, u64)> {
+    let mut total = u128::from(rules.total);
+    let mut sum: u128 = holders.iter().map(|(_, balance)| u128::from(*balance)).sum();
+    let mut amounts: Vec<(Pubkey, u64)> = Vec::with_capacity(holders.len());
+
+    // Wallets whose share exceeds the cap get the cap; the rest is re-split among the others.
+    // Shares only grow as capped wallets leave, so checking from the largest balance is enough.
+    let mut capped = 0;
+    if let Some(cap) = rules.cap {
+        for (wallet, balance) in holders {
+            if sum == 0 || u128::from(*balance) * total <= u128::from(cap) * sum {
+                break;
+            }
+            amounts.push((*wallet, cap));
+            total -= u128::from(cap).min(total);
+            sum -= u128::from(*balance);
+            capped += 1;
+        }
+    }
+    if sum == 0 {
+        return amounts;
+    }
+
+    let rest = &holders[capped..];
+    let mut remainders = Vec::with_capacity(rest.len());
+    let mut given: u128 = 0;
+    for (i, (wallet, balance)) in rest.iter().enumerate() {
+        let share = total * u128::from(*balance);
+        let (whole, remainder) = (share / sum, share % sum);
+        let amount = match rules.rounding {
+            Rounding::Nearest if remainder * 2 >= sum => whole + 1,
+            _ => whole,
+        };
+        given += amount;
+        amounts.push((*wallet, amount as u64));
+        remainders.push((remainder, capped + i));
+    }
+    if rules.rounding == Rounding::LargestRemainder {
+        // Stable sort: equal remainders go to the larger balance first
+        remainders.sort_by_key(|(remainder, _)| std::cmp::Reverse(*remainder));
+        let left = total.saturating_sub(given) as usize;
+        for (_, i) in remainders.into_iter().take(left) {
+            amounts[i].1 += 1;
+        }
+    }
+    amounts
+}
+
+/// Write allocations as `wallet,amount` CSV (raw units) for a distribution script
+pub fn write_allocation_csv(allocations: &[(Pubkey, u64)], mut out: impl Write) -> std::io::Result<()> {
+    writeln!(out, "wallet,amount")?;
+    for (wallet, amount) in allocations {
+        writeln!(out, "{},{}", wallet, amount)?;
+    }
+    out.flush()
+}
+
+/// Write the CSV to `output` (or stdout) and print a summary to stderr
+pub fn write_allocation(allocations: &[(Pubkey, u64)], total: u64, output: Option<&Path>) -> Result<()> {
+    match output {
+        Some(path) => {
+            let file = std::fs::File::create(path)
+                .with_context(|| format!("Failed to create allocation file {}", path.display()))?;
+            write_allocation_csv(allocations, std::io::BufWriter::new(file))
+                .with_context(|| format!("Failed to write allocation file {}", path.display()))?;
+        }
+        None => write_allocation_csv(allocations, std::io::stdout().lock())?,
+    }
+    let distributed: u128 = allocations.iter().map(|(_, amount)| u128::from(*amount)).sum();
+    eprintln!(
+        "{} wallets, {} of {} raw units allocated ({} left over)",
+        allocations.len(),
+        distributed,
+        total,
+        i128::from(total) - distributed as i128
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wallets(balances: &[u64]) -> (Vec<Pubkey>, HashMap<Pubkey, u64>) {
+        let keys: Vec<Pubkey> = balances.iter().map(|_| Pubkey::new_unique()).collect();
+        let map = keys.iter().zip(balances).map(|(k, b)| (*k, *b)).collect();
+        (keys, map)
+    }
+
+    fn amounts(allocations: &[(Pubkey, u64)]) -> Vec<u64> {
+        allocations.iter().map(|(_, amount)| *amount).collect()
+    }
+
+    #[test]
+    fn test_rounding() {
+        let (_, balances) = wallets(&[1, 1, 1, 0]);
+        let exact = pro_rata(balances.clone(), &AllocationRules::new(100));
+        assert_eq!(exact.len(), 3);
+        let mut sorted = amounts(&exact);
+        sorted.sort_unstable();
+        assert_eq!(sorted, vec![33, 33, 34]);
+
+        let down = pro_rata(balances.clone(), &AllocationRules::new(100).with_rounding(Rounding::Down));
+        assert_eq!(amounts(&down), vec![33, 33, 33]);
+
+        let nearest = pro_rata(balances, &AllocationRules::new(200).with_rounding(Rounding::Nearest));
+        assert_eq!(amounts(&nearest), vec![67, 67, 67]);
+
+        let (_, balances) = wallets(&[600, 300, 100]);
+        assert_eq!(amounts(&pro_rata(balances, &AllocationRules::new(10))), vec![6, 3, 1]);
+    }
+
+    #[test]
+    fn test_cap_and_minimums() {
+        // The whale would get 900; capped at 500, the other 400 go to the rest by balance
+        let (keys, balances) = wallets(&[900, 60, 40]);
+        let capped = pro_rata(balances.clone(), &AllocationRules::new(1_000).with_cap(Some(500)));
+        assert_eq!(capped, vec![(keys[0], 500), (keys[1], 300), (keys[2], 200)]);
+
+        // Capping everyone leaves the rest undistributed
+        let all_capped = pro_rata(balances.clone(), &AllocationRules::new(1_000).with_cap(Some(100)));
+        assert_eq!(amounts(&all_capped), vec![100, 100, 100]);
+
+        let rich_only = pro_rata(balances.clone(), &AllocationRules::new(1_000).with_min_balance(50));
+        assert_eq!(rich_only, vec![(keys[0], 938), (keys[1], 62)]);
+
+        // 40/1000 of 100 is 4, below the minimum: dropped and re-split between the other two
+        let min_amount = pro_rata(balances, &AllocationRules::new(100).with_min_amount(5));
+        assert_eq!(min_amount, vec![(keys[0], 94), (keys[1], 6)]);
+    }
+
+    #[test]
+    fn test_allocation_csv() {
+        let (keys, _) = wallets(&[1]);
+        let mut out = Vec::new();
+        write_allocation_csv(&[(keys[0], 42)], &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), format!("wallet,amount\n{},42\n", keys[0]));
+    }
+}
@@ -1,21 +1,57 @@
 use anyhow::{Context, Result};
 use axum::{
-    extract::Path,
-    http::StatusCode,
-    response::Json,
-    routing::get,
-    Router,
+    body::Body,
+    extract::{Path, Query, Request},
+    http::{
+        header::{HeaderName, ACCEPT, AUTHORIZATION, CONTENT_DISPOSITION, CONTENT_TYPE, RETRY_AFTER},
+        HeaderMap, StatusCode,
+    },
+    middleware::{self, Next},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Json, Response,
+    },
+    routing::{get, post},
+    Extension, Router,
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use solana_program::pubkey::Pubkey;
-use crate::rpc_client::SolanaRpcClient;
-use crate::token_monitor::extract_holders;
-use std::collections::HashMap;
+use crate::api_keys::{ApiClient, ApiKeys, KeyUsage, Rejection};
+use crate::build_info::BuildInfo;
+use crate::churn::{diff_holders, ChurnStats, ChurnTracker, CHURN_RETENTION_SECS};
+use crate::dashboard;
+use crate::diagnostics::Diagnostic;
+use crate::events::{EventBus, HolderEvent};
+use crate::export::{sorted_balances, ExportChunks, ExportFormat};
+use crate::forecast::{forecast, parse_horizon, Forecast, ForecastMethod};
+use crate::health::{HealthResponse, MonitorHealth};
+use crate::history::{Annotation, CountBucket, CountHistory, TierHistory, TierSeries};
+use crate::leaderboard::{rank, Leaderboard, LeaderboardWindow, RankBy};
+use crate::ledger::{Cohort, HolderLedger, LedgerSummary};
+use crate::memory::{self, MemoryUsage};
+use crate::metadata::CountMetadata;
+use crate::owners::{breakdown_owners, OwnerBreakdown};
+use crate::protocols::{attribute_deposits, DepositAttribution, ProtocolConfig};
+use crate::profile::{profile_holders, ProfileBreakdown};
+use crate::prometheus::{metric_families, render_families, MetricFamily, OPENMETRICS_CONTENT_TYPE, TEXT_CONTENT_TYPE};
+use crate::publisher::PublishedEvent;
+use crate::quality::{analyze_holders, sample_wallets, HolderQuality};
+use crate::rpc_client::{AccountSnapshot, Fallback, SolanaRpcClient};
+use crate::rpc_stats::MethodStats;
+use crate::sinks::{Sink, SinkFuture};
+use crate::summary::{RecentCounts, StatsSummary, TokenSummary};
+use crate::token_monitor::{extract_balances, MetricsSummary};
+use crate::watch::{WalletWatch, WatchedWallet};
+use futures::stream::{self, Stream};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::convert::Infallible;
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{RwLock, Semaphore};
 use tokio::time::{interval, Duration};
-use tracing::{error, info, warn};
+use tracing::{debug, error, info, warn};
 
 /// Cache entry for holder count
 #[derive(Debug, Clone)]
@@ -26,34 +62,348 @@ pub struct HolderCacheEntry {
     mint: Pubkey,
     request_count: u64,  // Количество запросов для этого токена
     first_seen: u64,      // Когда токен был впервые запрошен
+    first_count: usize,   // Количество держателей при первом запросе
+    slot: Option<u64>,    // Слот, на котором прочитаны аккаунты (если RPC его сообщает)
+    block_time: Option<u64>,  // Время блока этого слота
+    degraded: Option<Fallback>,  // Запасной источник, если getProgramAccounts недоступен
+    missing_supply_percent: Option<f64>,  // Доля supply, не найденная в аккаунтах (ответ RPC обрезан)
+    token_accounts: Option<usize>,  // Токен-аккаунты, из которых посчитаны держатели (у владельца их может быть несколько)
+    metadata: Option<Arc<CountMetadata>>,  // Как получено число: источник, фильтры, commitment, слот
 }
 
+/// How a holder set was read: slot and its block time when the RPC reports them, the
+/// fallback backend when getProgramAccounts was unavailable, the truncation check result, the
+/// token account count and the full count metadata
+#[derive(Debug, Clone, Default)]
+struct FetchInfo {
+    slot: Option<u64>,
+    block_time: Option<u64>,
+    degraded: Option<Fallback>,
+    missing_supply_percent: Option<f64>,
+    token_accounts: Option<usize>,
+    metadata: Option<Arc<CountMetadata>>,
+}
+
+impl HolderCacheEntry {
+    /// Holders at the last refresh
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// API requests for this mint so far
+    pub fn request_count(&self) -> u64 {
+        self.request_count
+    }
+}
+
+/// Holder set and derived analytics for a cached mint
+/// Kept apart from `HolderCacheEntry` so entries stay cheap to clone
+#[derive(Debug, Default)]
+pub struct MintAnalytics {
+    holders: HashSet<Pubkey>,
+    last_updated: Option<u64>,
+    churn: ChurnTracker,
+    /// In-memory ledger of every wallet seen since the mint entered the cache
+    ledger: HolderLedger,
+    /// Last holder quality report per requested sample size (computed on demand)
+    quality: HashMap<usize, HolderQuality>,
+    /// Last holder profile breakdown per requested sample size (computed on demand)
+    profiles: HashMap<usize, ProfileBreakdown>,
+    /// Last owner program breakdown (computed on demand)
+    owners: Option<OwnerBreakdown>,
+    /// Last protocol deposit attribution (computed on demand)
+    deposits: Option<DepositAttribution>,
+    /// Hourly counts of the last day (growth and sparkline in `GET /stats/summary`)
+    recent: RecentCounts,
+    /// Hourly counts since the mint entered the cache, for `GET /holders/:mint/history` and
+    /// `GET /leaderboard` when the monitor saved no history file for it
+    history: CountHistory,
+}
+
+/// How long a holder quality report is served before it is recomputed
+const QUALITY_CACHE_SECS: u64 = 3600;
+/// Upper bound for `?sample=` (each sampled wallet costs ~2 RPC calls)
+const MAX_QUALITY_SAMPLE: usize = 200;
+/// Upper bound for profile `?sample=` (each sampled wallet costs up to 11 RPC calls)
+const MAX_PROFILE_SAMPLE: usize = 100;
+/// Upper bound for owner breakdown `?sample=` / `?full=true` (one RPC call per 100 owners)
+const MAX_OWNER_SAMPLE: usize = 50_000;
+/// Upper bound for deposit attribution `?top=` (one RPC call per 100 holders)
+const MAX_DEPOSIT_TOP: usize = 10_000;
+
+impl MintAnalytics {
+    /// Record a fresh holder set, diffing it against the previous one
+    fn record(&mut self, holders: HashSet<Pubkey>, now: u64) {
+        if let Some(since) = self.last_updated {
+            let diff = diff_holders(&self.holders, &holders);
+            self.churn.record(since, now, &diff, self.holders.len());
+        } else {
+            self.ledger.created_at = now;
+        }
+        self.ledger.observe(&holders, now);
+        self.recent.record(now, holders.len());
+        self.history.record(holders.len(), now, None);
+        self.holders = holders;
+        self.last_updated = Some(now);
+    }
+
+    /// Approximate memory held by the holder set and history
+    fn memory_bytes(&self) -> usize {
+        memory::holder_set_bytes(self.holders.len()) + self.history_bytes()
+    }
+
+    fn history_bytes(&self) -> usize {
+        memory::ledger_bytes(&self.ledger)
+            + self.churn.memory_bytes()
+            + self.recent.memory_bytes()
+            + self.history.buckets.len() * std::mem::size_of::<CountBucket>()
+    }
+}
+
+/// Record a fresh holder set for a mint unless that would push analytics over the memory limit
+/// Over the limit the mint's analytics are dropped: its count is still cached and served,
+/// but churn/lifetime/quality answer 404 (count-only)
+async fn record_analytics(
+    analytics: &RwLock<HashMap<String, MintAnalytics>>,
+    mint_str: &str,
+    holders: HashSet<Pubkey>,
+    now: u64,
+    max_memory_bytes: Option<usize>,
+) {
+    let mut analytics_write = analytics.write().await;
+    if let Some(limit) = max_memory_bytes {
+        let others: usize = analytics_write
+            .iter()
+            .filter(|(mint, _)| mint.as_str() != mint_str)
+            .map(|(_, a)| a.memory_bytes())
+            .sum();
+        let ledger_wallets = analytics_write
+            .get(mint_str)
+            .map_or(0, |a| a.ledger.wallets.len())
+            .max(holders.len());
+        let projected = others
+            + memory::holder_set_bytes(holders.len())
+            + ledger_wallets * memory::LEDGER_ENTRY_BYTES;
+        if projected > limit {
+            if analytics_write.remove(mint_str).is_some() {
+                warn!(
+                    "Analytics for {} dropped: {} would exceed the memory limit ({}), count-only from now on",
+                    mint_str,
+                    memory::format_mb(projected),
+                    memory::format_mb(limit)
+                );
+            } else {
+                debug!("Skipping analytics for {}: memory limit reached", mint_str);
+            }
+            return;
+        }
+    }
+    analytics_write
+        .entry(mint_str.to_string())
+        .or_default()
+        .record(holders, now);
+}
+
+/// Mints fetched at the same time by the background refresh (`--cache-refresh-concurrency`)
+pub const DEFAULT_REFRESH_CONCURRENCY: usize = 2;
+
+/// Cached mints ordered by last refresh, oldest first
+fn stalest_first(cache: &HashMap<String, HolderCacheEntry>) -> Vec<String> {
+    let mut mints: Vec<(&String, u64)> = cache.iter().map(|(mint, entry)| (mint, entry.timestamp)).collect();
+    mints.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(b.0)));
+    mints.into_iter().map(|(mint, _)| mint.clone()).collect()
+}
+
+/// Re-fetch a cached mint and store the new count, keeping its request statistics
+async fn refresh_cached(
+    cache: &RwLock<HashMap<String, HolderCacheEntry>>,
+    analytics: &RwLock<HashMap<String, MintAnalytics>>,
+    rpc_client: &SolanaRpcClient,
+    mint_str: &str,
+    timeout: Duration,
+    max_memory_bytes: Option<usize>,
+) -> Result<HolderCacheEntry> {
+    let (holders, fetch_info) = match HolderCache::fetch_holders(rpc_client, mint_str, timeout).await {
+        Ok(fetched) => fetched,
+        Err(e) => {
+            // Keep serving the last value, flagged as stale
+            if let Some(entry) = cache.write().await.get_mut(mint_str) {
+                entry.degraded = Some(Fallback::Cached);
+            }
+            return Err(e);
+        }
+    };
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    store_holders(cache, analytics, mint_str, holders, now, fetch_info, max_memory_bytes).await
+}
+
+/// Store a fresh holder set, keeping the request statistics of an existing entry
+async fn store_holders(
+    cache: &RwLock<HashMap<String, HolderCacheEntry>>,
+    analytics: &RwLock<HashMap<String, MintAnalytics>>,
+    mint_str: &str,
+    holders: HashSet<Pubkey>,
+    now: u64,
+    fetch_info: FetchInfo,
+    max_memory_bytes: Option<usize>,
+) -> Result<HolderCacheEntry> {
+    let count = holders.len();
+    let mint = Pubkey::from_str(mint_str).context("Invalid mint address")?;
+
+    // Сохраняем существующие данные если есть
+    let (request_count, first_seen, first_count) = {
+        let cache_read = cache.read().await;
+        if let Some(existing) = cache_read.get(mint_str) {
+            (existing.request_count, existing.first_seen, existing.first_count)
+        } else {
+            (0, now, count)
+        }
+    };
+
+    let entry = HolderCacheEntry {
+        count,
+        timestamp: now,
+        mint,
+        request_count,
+        first_seen,
+        first_count,
+        slot: fetch_info.slot,
+        block_time: fetch_info.block_time,
+        degraded: fetch_info.degraded,
+        missing_supply_percent: fetch_info.missing_supply_percent,
+        token_accounts: fetch_info.token_accounts,
+        metadata: fetch_info.metadata,
+    };
+
+    cache.write().await.insert(mint_str.to_string(), entry.clone());
+    record_analytics(analytics, mint_str, holders, now, max_memory_bytes).await;
+    Ok(entry)
+}
+
+/// Session metrics of the mints polled by the monitoring loop, keyed by mint
+pub type MonitorMetrics = Arc<RwLock<HashMap<String, MetricsSummary>>>;
+
 /// Cache for holder counts with automatic refresh
-/// Limited to 2 tokens maximum - oldest token is removed when adding a third
+/// Limited to `max_tokens` entries (2 by default) - oldest token is removed when the limit is hit.
+/// Mints polled by the monitoring loop are published into the cache and don't count toward the limit
 pub struct HolderCache {
     cache: Arc<RwLock<HashMap<String, HolderCacheEntry>>>,
+    analytics: Arc<RwLock<HashMap<String, MintAnalytics>>>,
     rpc_client: Arc<SolanaRpcClient>,
     refresh_interval: Duration,
+    refresh_concurrency: usize,  // Сколько токенов фоновое обновление запрашивает одновременно
     max_tokens: usize,  // Максимальное количество токенов в кэше
+    monitored: Arc<HashSet<String>>,  // Mint, которые опрашивает монитор: их данные публикует он, фоновое обновление их пропускает
     api_timeout: Duration,  // Таймаут для API запросов (короче чем RPC timeout)
+    ledger_dir: Option<PathBuf>,  // Каталог с ledger-файлами монитора (для когорт)
+    max_memory_bytes: Option<usize>,  // Лимит памяти для аналитики (--max-memory-mb)
+    monitor_metrics: MonitorMetrics,  // Метрики монитора по каждому mint (GET /monitor/:mint/metrics)
+    events: Option<EventBus>,  // Шина событий монитора для GET /events
+    api_keys: ApiKeys,  // Ключи API: лимиты, разрешенные mint и учет запросов по каждому ключу
+    require_api_key: bool,  // Все эндпоинты (кроме /health) только с ключом
+    protocols: Arc<ProtocolConfig>,  // Хранилища протоколов и receipt-токены из [protocols]
+    watch: Option<Arc<WalletWatch>>,  // Отслеживаемые кошельки (POST /watch/:mint/:wallet, GET /watch)
 }
 
 impl HolderCache {
-    pub fn new(rpc_client: Arc<SolanaRpcClient>, refresh_interval_secs: u64) -> Self {
+    pub fn new(rpc_client: Arc<SolanaRpcClient>, refresh_interval_secs: u64, max_tokens: usize) -> Self {
         Self {
             cache: Arc::new(RwLock::new(HashMap::new())),
+            analytics: Arc::new(RwLock::new(HashMap::new())),
             rpc_client,
             refresh_interval: Duration::from_secs(refresh_interval_secs),
-            max_tokens: 2,  // Ограничение: максимум 2 токена
+            refresh_concurrency: DEFAULT_REFRESH_CONCURRENCY,
+            max_tokens,
+            monitored: Arc::default(),
             api_timeout: Duration::from_secs(90),  // API таймаут: 30 секунд (быстрее чем RPC timeout)
+            ledger_dir: None,
+            max_memory_bytes: None,
+            monitor_metrics: MonitorMetrics::default(),
+            events: None,
+            api_keys: ApiKeys::default(),
+            require_api_key: false,
+            protocols: Arc::default(),
+            watch: None,
         }
     }
 
+    /// Keys accepted by authenticated endpoints (`Authorization: Bearer <key>` or `X-API-Key`)
+    /// Without keys those endpoints answer 403
+    pub fn with_api_keys(mut self, api_keys: ApiKeys) -> Self {
+        self.api_keys = api_keys;
+        self
+    }
+
+    /// Reject requests without a valid key on every endpoint except `/health`
+    pub fn with_required_api_key(mut self, required: bool) -> Self {
+        self.require_api_key = required;
+        self
+    }
+
+    /// Mints polled by the monitoring loop, which publishes their holders with [`Self::publish`]
+    /// The refresh task skips them and they are never evicted, so each mint is fetched once per interval
+    pub fn with_monitored_mints(mut self, mints: impl IntoIterator<Item = String>) -> Self {
+        self.monitored = Arc::new(mints.into_iter().collect());
+        self
+    }
+
+    /// Protocol vault authorities and receipt mints used by `GET /holders/:mint/deposits`
+    pub fn with_protocols(mut self, protocols: ProtocolConfig) -> Self {
+        self.protocols = Arc::new(protocols);
+        self
+    }
+
+    /// Mints refreshed at the same time by the background refresh task
+    pub fn with_refresh_concurrency(mut self, concurrency: usize) -> Self {
+        self.refresh_concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Serve per-mint metrics of the monitoring loop, which keeps `metrics` up to date
+    pub fn with_monitor_metrics(mut self, metrics: MonitorMetrics) -> Self {
+        self.monitor_metrics = metrics;
+        self
+    }
+
+    /// Stream the monitoring loop's events to `GET /events` subscribers
+    pub fn with_event_bus(mut self, bus: EventBus) -> Self {
+        self.events = Some(bus);
+        self
+    }
+
+    /// Serve and extend the monitor's watched wallets (`GET /watch`, `POST /watch/:mint/:wallet`)
+    pub fn with_wallet_watch(mut self, watch: Arc<WalletWatch>) -> Self {
+        self.watch = Some(watch);
+        self
+    }
+
+    /// Stop keeping holder sets / ledgers for new mints once analytics would exceed this budget
+    pub fn with_memory_limit(mut self, max_memory_bytes: Option<usize>) -> Self {
+        self.max_memory_bytes = max_memory_bytes;
+        self
+    }
+
+    /// Serve cohort data from ledgers written by the monitor (`--ledger-dir`)
+    pub fn with_ledger_dir(mut self, ledger_dir: Option<PathBuf>) -> Self {
+        self.ledger_dir = ledger_dir;
+        self
+    }
+
     /// Start background task to refresh cache
+    /// At most `refresh_concurrency` mints are fetched at once, the stalest first; a mint whose
+    /// previous refresh is still queued or running is skipped, so slow cycles don't pile up
     pub fn start_refresh_task(&self) {
         let cache = self.cache.clone();
+        let analytics = self.analytics.clone();
         let rpc_client = self.rpc_client.clone();
         let interval_duration = self.refresh_interval;
+        let max_memory_bytes = self.max_memory_bytes;
+        let monitored = self.monitored.clone();
+        let permits = Arc::new(Semaphore::new(self.refresh_concurrency));
+        let in_flight: Arc<std::sync::Mutex<HashSet<String>>> = Arc::default();
 
         tokio::spawn(async move {
             let mut refresh_timer = interval(interval_duration);
@@ -61,61 +411,46 @@ impl HolderCache {
             loop {
                 refresh_timer.tick().await;
 
-                // Collect all mints that need refresh
-                let mints_to_refresh: Vec<String> = {
-                    let cache_read = cache.read().await;
-                    cache_read.keys().cloned().collect()
-                };
-
-                // Refresh each mint
-                for mint_str in &mints_to_refresh {
-                    // Use longer timeout for background refresh (no user waiting)
-                    let refresh_timeout = Duration::from_secs(90);
-                    match Self::fetch_holder_count(&rpc_client, mint_str, refresh_timeout).await {
-                        Ok(count) => {
-                            let mint = match Pubkey::from_str(mint_str) {
-                                Ok(m) => m,
-                                Err(_) => continue,
-                            };
-
-                            let now = std::time::SystemTime::now()
-                                .duration_since(std::time::UNIX_EPOCH)
-                                .unwrap()
-                                .as_secs();
-                            
-                            // Сохраняем существующие данные если есть
-                            let (request_count, first_seen) = {
-                                let cache_read = cache.read().await;
-                                if let Some(existing) = cache_read.get(mint_str) {
-                                    (existing.request_count, existing.first_seen)
-                                } else {
-                                    (0, now)
+                let mut mints_to_refresh = stalest_first(&*cache.read().await);
+                mints_to_refresh.retain(|mint| !monitored.contains(mint));
+                let mut skipped = 0;
+                for mint_str in mints_to_refresh {
+                    if !in_flight.lock().unwrap().insert(mint_str.clone()) {
+                        skipped += 1;
+                        continue;
+                    }
+                    let (cache, analytics, rpc_client) = (cache.clone(), analytics.clone(), rpc_client.clone());
+                    let (permits, in_flight) = (permits.clone(), in_flight.clone());
+                    // The semaphore is fair: mints start in staleness order
+                    tokio::spawn(async move {
+                        if let Ok(_permit) = permits.acquire().await {
+                            // Use longer timeout for background refresh (no user waiting)
+                            let refresh_timeout = Duration::from_secs(90);
+                            match refresh_cached(&cache, &analytics, &rpc_client, &mint_str, refresh_timeout, max_memory_bytes).await {
+                                Ok(entry) => {
+                                    info!("Refreshed cache for mint {}: {} holders", mint_str, entry.count);
                                 }
-                            };
-
-                            let entry = HolderCacheEntry {
-                                count,
-                                timestamp: now,
-                                mint,
-                                request_count,
-                                first_seen,
-                            };
-
-                            let mut cache_write = cache.write().await;
-                            cache_write.insert(mint_str.clone(), entry);
-                            info!("Refreshed cache for mint {}: {} holders", mint_str, count);
-                        }
-                        Err(e) => {
-                            error!("Failed to refresh cache for {}: {}", mint_str, e);
+                                Err(e) => {
+                                    error!("Failed to refresh cache for {}: {}", mint_str, e);
+                                }
+                            }
                         }
-                    }
+                        in_flight.lock().unwrap().remove(&mint_str);
+                    });
+                }
+                if skipped > 0 {
+                    warn!(
+                        "Cache refresh is behind: {} mint(s) still refreshing from the previous cycle were skipped",
+                        skipped
+                    );
                 }
             }
         });
     }
 
     /// Get holder count from cache or fetch if not cached
-    pub async fn get_holder_count(&self, mint_str: &str) -> Result<HolderCacheEntry> {
+    /// The returned flag is true when the value was served from cache
+    pub async fn get_holder_count(&self, mint_str: &str) -> Result<(HolderCacheEntry, bool)> {
         let now = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
@@ -128,21 +463,22 @@ impl HolderCache {
                 // Увеличиваем счетчик запросов
                 entry.request_count += 1;
                 info!("Cache hit for {} (request #{}), returning cached data", mint_str, entry.request_count);
-                return Ok(entry.clone());
+                return Ok((entry.clone(), true));
             }
         }
 
         // Not in cache, fetch it
         info!("Cache miss for {}, fetching from RPC...", mint_str);
         let fetch_start = std::time::Instant::now();
-        let count = match Self::fetch_holder_count(&self.rpc_client, mint_str, self.api_timeout).await {
-            Ok(count) => count,
+        let (holders, fetch_info) = match Self::fetch_holders(&self.rpc_client, mint_str, self.api_timeout).await {
+            Ok(fetched) => fetched,
             Err(e) => {
                 let elapsed = fetch_start.elapsed();
                 warn!("Failed to fetch holders for {} after {:.2}s: {}", mint_str, elapsed.as_secs_f64(), e);
                 return Err(e);
             }
         };
+        let count = holders.len();
         let fetch_elapsed = fetch_start.elapsed();
         info!("Fetched holders for {} in {:.2}s: {} holders", mint_str, fetch_elapsed.as_secs_f64(), count);
         let mint = Pubkey::from_str(mint_str)
@@ -154,22 +490,36 @@ impl HolderCache {
             mint,
             request_count: 1,  // Первый запрос
             first_seen: now,   // Впервые запрошен сейчас
+            first_count: count,
+            slot: fetch_info.slot,
+            block_time: fetch_info.block_time,
+            degraded: fetch_info.degraded,
+            missing_supply_percent: fetch_info.missing_supply_percent,
+            token_accounts: fetch_info.token_accounts,
+            metadata: fetch_info.metadata,
         };
 
-        // Store in cache (with limit of 2 tokens)
+        // Store in cache (with limit of max_tokens)
         {
             let mut cache_write = self.cache.write().await;
             
             // Если кэш полон и добавляется новый токен, удаляем самый старый
-            if cache_write.len() >= self.max_tokens && !cache_write.contains_key(mint_str) {
+            // (токены монитора не считаются и не удаляются)
+            let requested = cache_write.keys().filter(|mint| !self.monitored.contains(*mint)).count();
+            if requested >= self.max_tokens
+                && !cache_write.contains_key(mint_str)
+                && !self.monitored.contains(mint_str)
+            {
                 // Находим токен с самым старым timestamp (первый добавленный)
                 let oldest_mint = cache_write
                     .iter()
+                    .filter(|(mint, _)| !self.monitored.contains(*mint))
                     .min_by_key(|(_, entry)| entry.timestamp)
                     .map(|(mint, _)| mint.clone());
                 
                 if let Some(old_mint) = oldest_mint {
                     cache_write.remove(&old_mint);
+                    self.analytics.write().await.remove(&old_mint);
                     info!("Removed oldest token {} from cache (limit: {} tokens)", old_mint, self.max_tokens);
                 }
             }
@@ -177,8 +527,42 @@ impl HolderCache {
             cache_write.insert(mint_str.to_string(), entry.clone());
             info!("Added {} to cache (total tracked tokens: {}/{})", mint_str, cache_write.len(), self.max_tokens);
         }
+        record_analytics(&self.analytics, mint_str, holders, now, self.max_memory_bytes).await;
+
+        Ok((entry, false))
+    }
+
+    /// Refresh a mint right away: cached mints are re-fetched (keeping request statistics),
+    /// others are fetched and added like a cache miss
+    pub async fn refresh_now(&self, mint_str: &str) -> Result<HolderCacheEntry> {
+        let cached = self.cache.read().await.contains_key(mint_str);
+        if cached {
+            info!("Refresh requested for {}", mint_str);
+            refresh_cached(
+                &self.cache,
+                &self.analytics,
+                &self.rpc_client,
+                mint_str,
+                self.api_timeout,
+                self.max_memory_bytes,
+            )
+            .await
+        } else {
+            self.get_holder_count(mint_str).await.map(|(entry, _)| entry)
+        }
+    }
+
+    /// Client of an endpoint that always needs a key (set by the `authenticate` middleware)
+    fn authorize(&self, client: Option<Extension<ApiClient>>) -> Result<ApiClient, StatusCode> {
+        if self.api_keys.is_empty() {
+            return Err(StatusCode::FORBIDDEN);
+        }
+        client.map(|Extension(client)| client).ok_or(StatusCode::UNAUTHORIZED)
+    }
 
-        Ok(entry)
+    /// Per-key usage for `GET /admin/usage`
+    pub fn api_key_usage(&self) -> Vec<KeyUsage> {
+        self.api_keys.usage()
     }
 
     /// Get list of all tracked tokens with statistics
@@ -186,12 +570,23 @@ impl HolderCache {
         let cache_read = self.cache.read().await;
         cache_read
             .iter()
-            .map(|(mint, entry)| TokenStats {
-                mint: mint.clone(),
-                holders: entry.count,
-                last_updated: entry.timestamp,
-                request_count: entry.request_count,
-                first_seen: entry.first_seen,
+            .map(|(mint, entry)| {
+                let growth = entry.count as i64 - entry.first_count as i64;
+                let growth_percent = if entry.first_count > 0 {
+                    (growth as f64 / entry.first_count as f64) * 100.0
+                } else {
+                    0.0
+                };
+                TokenStats {
+                    mint: mint.clone(),
+                    holders: entry.count,
+                    last_updated: entry.timestamp,
+                    request_count: entry.request_count,
+                    first_seen: entry.first_seen,
+                    holders_at_first_seen: entry.first_count,
+                    growth,
+                    growth_percent,
+                }
             })
             .collect()
     }
@@ -201,29 +596,318 @@ impl HolderCache {
         let cache_read = self.cache.read().await;
         let total_tokens = cache_read.len();
         let total_requests: u64 = cache_read.values().map(|e| e.request_count).sum();
-        
+
+        let analytics_read = self.analytics.read().await;
+        let holder_sets: usize = analytics_read
+            .values()
+            .map(|a| memory::holder_set_bytes(a.holders.len()))
+            .sum();
+        let history: usize = analytics_read.values().map(|a| a.history_bytes()).sum();
+
         CacheStats {
             total_tracked_tokens: total_tokens,
             total_requests,
             cache_size_bytes: std::mem::size_of_val(&*cache_read) as u64,
+            memory: MemoryUsage::new(holder_sets, history),
+            max_memory_bytes: self.max_memory_bytes,
+        }
+    }
+
+    /// Overview of the tracked tokens `allows_mint` accepts: unique wallets across them, 24h
+    /// growth and sparklines
+    pub async fn get_summary(&self, allows_mint: impl Fn(&str) -> bool) -> StatsSummary {
+        let cache_read = self.cache.read().await;
+        let analytics_read = self.analytics.read().await;
+        let tokens = cache_read
+            .iter()
+            .filter(|(mint, _)| allows_mint(mint))
+            .map(|(mint, entry)| {
+                let analytics = analytics_read.get(mint);
+                let token = TokenSummary::new(
+                    mint.clone(),
+                    entry.count,
+                    entry.timestamp,
+                    analytics.map(|a| &a.recent),
+                );
+                (token, analytics.map(|a| &a.holders))
+            })
+            .collect();
+        StatsSummary::build(tokens)
+    }
+
+    /// Get churn over 1h and 24h windows for a cached mint
+    /// Returns None when the mint is not tracked
+    pub async fn get_churn(&self, mint_str: &str) -> Option<ChurnResponse> {
+        let analytics_read = self.analytics.read().await;
+        let analytics = analytics_read.get(mint_str)?;
+        let last_updated = analytics.last_updated?;
+
+        Some(ChurnResponse {
+            mint: mint_str.to_string(),
+            holders: analytics.holders.len(),
+            last_updated,
+            windows: [3600, CHURN_RETENTION_SECS]
+                .into_iter()
+                .map(|window| analytics.churn.stats(last_updated, window))
+                .collect(),
+        })
+    }
+
+    /// Unique-holders-ever and first-time vs returning holders since the mint entered the cache
+    pub async fn get_lifetime(&self, mint_str: &str) -> Option<LifetimeResponse> {
+        let analytics_read = self.analytics.read().await;
+        let analytics = analytics_read.get(mint_str)?;
+
+        Some(LifetimeResponse {
+            mint: mint_str.to_string(),
+            tracked_since: analytics.ledger.created_at,
+            last_updated: analytics.last_updated?,
+            summary: analytics.ledger.summary(),
+        })
+    }
+
+    /// Cohort retention for a mint from its ledger file
+    /// Returns None when no ledger directory is configured or the mint has no ledger
+    pub async fn get_cohorts(&self, mint_str: &str, period_secs: u64) -> Result<Option<Vec<Cohort>>> {
+        let Some(dir) = &self.ledger_dir else {
+            return Ok(None);
+        };
+        let path = HolderLedger::path_for(dir, mint_str);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        // Ledger files can be large: parse off the async runtime
+        let ledger = tokio::task::spawn_blocking(move || HolderLedger::load(&path))
+            .await
+            .context("Ledger loading task failed")??;
+        Ok(Some(ledger.cohorts(period_secs)))
+    }
+
+    /// History files the monitor saved for a mint in `--ledger-dir` (counts, tiers), each None
+    /// when missing
+    async fn load_history_files(&self, mint_str: &str) -> Result<(Option<CountHistory>, Option<TierHistory>)> {
+        let Some(dir) = self.ledger_dir.clone() else {
+            return Ok((None, None));
+        };
+        let mint = mint_str.to_string();
+        // History files grow by an entry per hour: parse off the async runtime
+        tokio::task::spawn_blocking(move || -> Result<_> {
+            let counts = if CountHistory::path_for(&dir, &mint).exists() {
+                Some(CountHistory::load_or_new(&dir, &mint)?)
+            } else {
+                None
+            };
+            let tiers = if TierHistory::path_for(&dir, &mint).exists() {
+                Some(TierHistory::load_or_new(&dir, &mint)?)
+            } else {
+                None
+            };
+            Ok((counts, tiers))
+        })
+        .await
+        .context("History loading task failed")?
+    }
+
+    /// Hourly count history of a mint: the monitor's file in `--ledger-dir` when it saved one, else
+    /// the counts the cache recorded since the mint entered it
+    async fn get_count_history(&self, mint_str: &str) -> Result<Option<CountHistory>> {
+        if let (Some(history), _) = self.load_history_files(mint_str).await? {
+            return Ok(Some(history));
+        }
+        Ok(self.analytics.read().await.get(mint_str).map(|analytics| analytics.history.clone()))
+    }
+
+    /// Hourly count history with its annotations and per-tier series: the monitor's files in
+    /// `--ledger-dir` when it saved them, else the counts the cache recorded since the mint
+    /// entered it; None when the mint has neither
+    pub async fn get_history(&self, mint_str: &str, since: u64) -> Result<Option<HistoryResponse>> {
+        let (stored, tiers) = self.load_history_files(mint_str).await?;
+        let history = match stored {
+            Some(history) => Some(history),
+            None => self.get_count_history(mint_str).await?,
+        };
+        if history.is_none() && tiers.is_none() {
+            return Ok(None);
+        }
+        let history = history.unwrap_or_else(|| CountHistory::new(mint_str));
+        let tiers = tiers.map_or_else(Vec::new, |tiers| tiers.series(since));
+        Ok(Some(HistoryResponse {
+            mint: mint_str.to_string(),
+            points: history.buckets.into_iter().filter(|bucket| bucket.start >= since).collect(),
+            annotations: history.annotations.into_iter().filter(|annotation| annotation.bucket >= since).collect(),
+            tiers,
+        }))
+    }
+
+    /// Holder quality report for a tracked mint, recomputed at most once per `QUALITY_CACHE_SECS`
+    /// for each sample size; returns None when the mint is not tracked
+    pub async fn get_quality(&self, mint_str: &str, sample_size: usize) -> Result<Option<HolderQuality>> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        {
+            let analytics_read = self.analytics.read().await;
+            let Some(analytics) = analytics_read.get(mint_str) else {
+                return Ok(None);
+            };
+            if let Some(quality) = analytics.quality.get(&sample_size) {
+                if now.saturating_sub(quality.computed_at) < QUALITY_CACHE_SECS {
+                    return Ok(Some(quality.clone()));
+                }
+            }
+        }
+
+        // Balances aren't kept in the cache: fetch the accounts once more for this report
+        let snapshot = Self::fetch_accounts(&self.rpc_client, mint_str, self.api_timeout).await?;
+        let quality = analyze_holders(&self.rpc_client, &snapshot.accounts, sample_size, now).await;
+
+        if let Some(analytics) = self.analytics.write().await.get_mut(mint_str) {
+            analytics.quality.retain(|_, cached| now.saturating_sub(cached.computed_at) < QUALITY_CACHE_SECS);
+            analytics.quality.insert(sample_size, quality.clone());
+        }
+        Ok(Some(quality))
+    }
+
+    /// Holder profile breakdown (DEX traders, NFT collectors, stakers) for a tracked mint
+    /// Cached per sample size like the quality report; returns None when the mint is not tracked
+    pub async fn get_profiles(&self, mint_str: &str, sample_size: usize) -> Option<ProfileBreakdown> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let (holders, sample) = {
+            let analytics_read = self.analytics.read().await;
+            let analytics = analytics_read.get(mint_str)?;
+            if let Some(profiles) = analytics.profiles.get(&sample_size) {
+                if now.saturating_sub(profiles.computed_at) < QUALITY_CACHE_SECS {
+                    return Some(profiles.clone());
+                }
+            }
+            (analytics.holders.len(), sample_wallets(analytics.holders.iter(), sample_size))
+        };
+
+        let breakdown = profile_holders(&self.rpc_client, holders, &sample, now).await;
+        if let Some(analytics) = self.analytics.write().await.get_mut(mint_str) {
+            analytics.profiles.retain(|_, cached| now.saturating_sub(cached.computed_at) < QUALITY_CACHE_SECS);
+            analytics.profiles.insert(sample_size, breakdown.clone());
+        }
+        Some(breakdown)
+    }
+
+    /// Owner program breakdown (wallets vs program-owned accounts) for a tracked mint
+    /// A cached breakdown is reused for `QUALITY_CACHE_SECS` if it examined at least as many
+    /// owners as requested; returns None when the mint is not tracked
+    pub async fn get_owner_breakdown(&self, mint_str: &str, sample_size: usize) -> Result<Option<OwnerBreakdown>> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let (holders, sample) = {
+            let analytics_read = self.analytics.read().await;
+            let Some(analytics) = analytics_read.get(mint_str) else {
+                return Ok(None);
+            };
+            let wanted = sample_size.min(analytics.holders.len());
+            if let Some(owners) = &analytics.owners {
+                if now.saturating_sub(owners.computed_at) < QUALITY_CACHE_SECS && owners.examined_owners >= wanted {
+                    return Ok(Some(owners.clone()));
+                }
+            }
+            (analytics.holders.len(), sample_wallets(analytics.holders.iter(), wanted))
+        };
+
+        let breakdown = breakdown_owners(&self.rpc_client, holders, &sample, now).await?;
+        if let Some(analytics) = self.analytics.write().await.get_mut(mint_str) {
+            analytics.owners = Some(breakdown.clone());
+        }
+        Ok(Some(breakdown))
+    }
+
+    /// Holders with and without protocol deposits for a tracked mint
+    /// Cached like the owner breakdown; returns None when the mint is not tracked
+    pub async fn get_deposits(&self, mint_str: &str, top: usize) -> Result<Option<DepositAttribution>> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        {
+            let analytics_read = self.analytics.read().await;
+            let Some(analytics) = analytics_read.get(mint_str) else {
+                return Ok(None);
+            };
+            let wanted = top.min(analytics.holders.len());
+            if let Some(deposits) = &analytics.deposits {
+                if now.saturating_sub(deposits.computed_at) < QUALITY_CACHE_SECS && deposits.examined_owners >= wanted {
+                    return Ok(Some(deposits.clone()));
+                }
+            }
         }
+
+        // Balances aren't kept in the cache: fetch the accounts once more to find the largest holders
+        let mint = Pubkey::from_str(mint_str).context("Invalid mint address")?;
+        let snapshot = Self::fetch_accounts(&self.rpc_client, mint_str, self.api_timeout).await?;
+        let balances = extract_balances(&snapshot.accounts);
+        let deposits = attribute_deposits(&self.rpc_client, &mint, &balances, top, &self.protocols, now).await?;
+
+        if let Some(analytics) = self.analytics.write().await.get_mut(mint_str) {
+            analytics.deposits = Some(deposits.clone());
+        }
+        Ok(Some(deposits))
+    }
+
+    /// Total balance per holder of a tracked mint, fetched from RPC (balances aren't cached)
+    /// None when the mint isn't tracked
+    pub async fn get_balances(&self, mint_str: &str) -> Result<Option<HashMap<Pubkey, u64>>> {
+        if !self.cache.read().await.contains_key(mint_str) {
+            return Ok(None);
+        }
+        let snapshot = Self::fetch_accounts(&self.rpc_client, mint_str, self.api_timeout).await?;
+        Ok(Some(extract_balances(&snapshot.accounts)))
     }
 
-    /// Fetch holder count from RPC with timeout
-    async fn fetch_holder_count(
+    /// Fetch unique holders from RPC with timeout, with the slot they were read at
+    async fn fetch_holders(
         rpc_client: &SolanaRpcClient,
         mint_str: &str,
         api_timeout: Duration,
-    ) -> Result<usize> {
+    ) -> Result<(HashSet<Pubkey>, FetchInfo)> {
+        let snapshot = Self::fetch_accounts(rpc_client, mint_str, api_timeout).await?;
+        let (holders, zero_balance_owners) = rpc_client.holders_of(&snapshot).context("Failed to extract holders")?;
+        let mint = Pubkey::from_str(mint_str).context("Invalid mint address")?;
+        let mut metadata = rpc_client.count_metadata(&mint, &snapshot);
+        metadata.zero_balance_owners = Some(zero_balance_owners);
+        let block_time = match snapshot.slot {
+            Some(slot) => rpc_client.get_block_time(slot).await,
+            None => None,
+        };
+        let fetch_info = FetchInfo {
+            slot: snapshot.slot,
+            block_time,
+            degraded: snapshot.degraded,
+            missing_supply_percent: snapshot.missing_supply_percent,
+            token_accounts: Some(snapshot.accounts.len()),
+            metadata: Some(Arc::new(metadata)),
+        };
+        Ok((holders, fetch_info))
+    }
+
+    /// Fetch token accounts of a mint from RPC with timeout
+    async fn fetch_accounts(
+        rpc_client: &SolanaRpcClient,
+        mint_str: &str,
+        api_timeout: Duration,
+    ) -> Result<AccountSnapshot> {
         let mint = Pubkey::from_str(mint_str)
             .context("Invalid mint address")?;
         let fetch_result = tokio::time::timeout(
             api_timeout,
-            rpc_client.get_token_accounts_by_mint(&mint)
+            rpc_client.get_token_accounts_snapshot(&mint)
         ).await;
 
         let accounts = match fetch_result {
-            Ok(Ok(accounts)) => accounts,
+            Ok(Ok(snapshot)) => snapshot,
             Ok(Err(e)) => {
                 return Err(e).context("Failed to fetch token accounts");
             }
@@ -235,63 +919,941 @@ impl HolderCache {
             }
         };
 
-        let holders = extract_holders(&accounts)
-            .context("Failed to extract holders")?;
+        Ok(accounts)
+    }
+}
+
+/// Stores every observation of the monitoring loop, so the API serves monitored mints
+/// without fetching them again, and annotates their history with the alerts that fired
+impl Sink for HolderCache {
+    fn name(&self) -> &str {
+        "api-cache"
+    }
 
-        Ok(holders.len())
+    fn handle<'a>(&'a self, event: &'a HolderEvent) -> SinkFuture<'a> {
+        Box::pin(async move {
+            let observation = match event {
+                HolderEvent::Observation(observation) => observation,
+                HolderEvent::Alert(alert) => {
+                    if let Some(analytics) = self.analytics.write().await.get_mut(&alert.mint.to_string()) {
+                        analytics.history.annotate(alert.rule, &alert.message, &alert.stats);
+                    }
+                    return Ok(());
+                }
+                _ => return Ok(()),
+            };
+            let mint_str = observation.mint.to_string();
+            let holders = HashSet::clone(&observation.holders);
+            let stats = &observation.stats;
+            let entry = store_holders(
+                &self.cache,
+                &self.analytics,
+                &mint_str,
+                holders,
+                stats.timestamp,
+                FetchInfo {
+                    slot: stats.slot,
+                    block_time: stats.block_time,
+                    degraded: stats.degraded,
+                    missing_supply_percent: stats.missing_supply_percent,
+                    token_accounts: stats.token_accounts,
+                    metadata: Some(observation.metadata.clone()),
+                },
+                self.max_memory_bytes,
+            )
+            .await?;
+            debug!("Published monitor result for {}: {} holders", mint_str, entry.count);
+            Ok(())
+        })
     }
 }
 
 /// API response structure
-#[derive(serde::Serialize)]
-struct HolderResponse {
-    mint: String,
-    holders: usize,
-    timestamp: u64,
-    cached: bool,
+#[derive(Debug, Serialize)]
+pub struct HolderResponse {
+    pub mint: String,
+    /// Unique owners with a balance
+    pub holders: usize,
+    /// True when served from cache, false when fetched from RPC for this request
+    pub cached: bool,
+    /// Seconds since the value was fetched from RPC
+    pub age_seconds: u64,
+    /// Unix timestamp of the RPC fetch that produced the value
+    pub refreshed_at: u64,
+    /// Same as `refreshed_at`, kept for existing clients
+    pub timestamp: u64,
+    /// Slot the holder set was read at, when the RPC reports it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slot: Option<u64>,
+    /// Block time of `slot` (unix seconds)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_time: Option<u64>,
+    /// Set when the count didn't come from getProgramAccounts: `das`, `largest_accounts`
+    /// (a lower bound) or `cached` (the last refresh failed)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub degraded: Option<Fallback>,
+    /// Share of the supply missing from the fetched accounts, when the RPC response looks truncated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub missing_supply_percent: Option<f64>,
+    /// Token accounts the holders were counted from (an owner may have several)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_accounts: Option<usize>,
+    /// How the count was produced (source, filters, commitment, dust threshold, slot)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<CountMetadata>,
+}
+
+impl HolderResponse {
+    pub fn new(mint: String, holders: usize, refreshed_at: u64, cached: bool, now: u64) -> Self {
+        Self {
+            mint,
+            holders,
+            cached,
+            age_seconds: now.saturating_sub(refreshed_at),
+            refreshed_at,
+            timestamp: refreshed_at,
+            slot: None,
+            block_time: None,
+            degraded: None,
+            missing_supply_percent: None,
+            token_accounts: None,
+            metadata: None,
+        }
+    }
+
+    /// On-chain position and data quality of the cached value
+    pub fn with_fetch_info(mut self, entry: &HolderCacheEntry) -> Self {
+        self.slot = entry.slot;
+        self.block_time = entry.block_time;
+        self.degraded = entry.degraded;
+        self.missing_supply_percent = entry.missing_supply_percent;
+        self.token_accounts = entry.token_accounts;
+        self.metadata = entry.metadata.as_deref().cloned();
+        self
+    }
 }
 
 /// Get holder count endpoint
 async fn get_holders(
     Path(mint_str): Path<String>,
     axum::extract::State(cache): axum::extract::State<Arc<HolderCache>>,
-) -> Result<Json<HolderResponse>, StatusCode> {
+) -> Result<Json<HolderResponse>, Response> {
     // Validate mint address format
     if Pubkey::from_str(&mint_str).is_err() {
-        return Err(StatusCode::BAD_REQUEST);
+        return Err(StatusCode::BAD_REQUEST.into_response());
     }
 
     match cache.get_holder_count(&mint_str).await {
-        Ok(entry) => {
-            // Проверяем, был ли это кэш или новый запрос
-            let was_cached = entry.request_count > 1;
-            Ok(Json(HolderResponse {
-                mint: mint_str,
-                holders: entry.count,
-                timestamp: entry.timestamp,
-                cached: was_cached,
-            }))
+        Ok((entry, cached)) => {
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs();
+            Ok(Json(HolderResponse::new(mint_str, entry.count, entry.timestamp, cached, now).with_fetch_info(&entry)))
         },
         Err(e) => {
             error!("Error getting holder count for {}: {}", mint_str, e);
-            // Return more specific error for timeout
-            let error_msg = format!("{}", e);
-            if error_msg.contains("timed out") {
-                return Err(StatusCode::GATEWAY_TIMEOUT);
+            Err(fetch_error_response(&cache, &e))
+        }
+    }
+}
+
+/// HTTP status for a failed RPC fetch
+fn fetch_error_status(e: &anyhow::Error) -> StatusCode {
+    if format!("{}", e).contains("timed out") {
+        StatusCode::GATEWAY_TIMEOUT
+    } else {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
+
+/// Body of a failed RPC fetch: the error and the endpoint's diagnostics, whose
+/// `suggested_action` tells wrappers what to propose to the user
+#[derive(Debug, Serialize)]
+pub struct FetchErrorResponse {
+    pub error: String,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+fn fetch_error_response(cache: &HolderCache, e: &anyhow::Error) -> Response {
+    let body = FetchErrorResponse { error: format!("{:#}", e), diagnostics: cache.rpc_client.diagnostics() };
+    (fetch_error_status(e), Json(body)).into_response()
+}
+
+/// API key from `Authorization: Bearer <key>` or `X-API-Key: <key>`
+pub fn api_key_from_headers(headers: &HeaderMap) -> Option<&str> {
+    if let Some(value) = headers.get(AUTHORIZATION).and_then(|v| v.to_str().ok()) {
+        return value.strip_prefix("Bearer ").map(str::trim);
+    }
+    headers.get("x-api-key").and_then(|v| v.to_str().ok()).map(str::trim)
+}
+
+/// Resolve the request's API key, count it against the key's rate limit and allowed mints
+/// and pass the `ApiClient` on to the handler
+/// Requests without a key pass unless `--require-api-key` is set; endpoints that always
+/// need a key check for the client themselves
+async fn authenticate(
+    axum::extract::State(cache): axum::extract::State<Arc<HolderCache>>,
+    params: Option<Path<HashMap<String, String>>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    if cache.api_keys.is_empty() && !cache.require_api_key {
+        return next.run(request).await;
+    }
+    let key = api_key_from_headers(request.headers()).map(str::to_string);
+    let client = match key {
+        Some(key) => match cache.api_keys.identify(&key) {
+            Some(client) => client.clone(),
+            None => return StatusCode::UNAUTHORIZED.into_response(),
+        },
+        None if cache.require_api_key => return StatusCode::UNAUTHORIZED.into_response(),
+        None => return next.run(request).await,
+    };
+
+    let mint = params.as_ref().and_then(|Path(params)| params.get("mint"));
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    match cache.api_keys.check(&client, mint.map(String::as_str), now) {
+        Ok(()) => {}
+        Err(Rejection::MintNotAllowed) => return StatusCode::FORBIDDEN.into_response(),
+        Err(Rejection::RateLimited { retry_after_secs }) => {
+            debug!("API key '{}' is over its rate limit", client.name);
+            return (
+                StatusCode::TOO_MANY_REQUESTS,
+                [(RETRY_AFTER, retry_after_secs.to_string())],
+            )
+                .into_response();
+        }
+    }
+    request.extensions_mut().insert(client);
+    next.run(request).await
+}
+
+/// Query for `POST /refresh/:mint`
+#[derive(Debug, Default, Deserialize)]
+pub struct RefreshQuery {
+    /// Respond 202 right away and POST the result here when the refresh is done
+    pub webhook_url: Option<String>,
+}
+
+/// Body POSTed to `webhook_url` after `POST /refresh/:mint`
+#[derive(Debug, Serialize)]
+pub struct RefreshCallback {
+    pub mint: String,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub holders: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refreshed_at: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Refresh a token now instead of waiting for the cache TTL (requires an API key)
+/// Without `webhook_url` the response is the fresh `HolderResponse`; with it, 202 is returned
+/// immediately and the `RefreshCallback` is POSTed to the webhook when the fetch completes
+async fn post_refresh(
+    Path(mint_str): Path<String>,
+    Query(query): Query<RefreshQuery>,
+    client: Option<Extension<ApiClient>>,
+    axum::extract::State(cache): axum::extract::State<Arc<HolderCache>>,
+) -> Result<Response, StatusCode> {
+    cache.authorize(client)?;
+    if Pubkey::from_str(&mint_str).is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let Some(webhook_url) = query.webhook_url else {
+        return match cache.refresh_now(&mint_str).await {
+            Ok(entry) => {
+                let now = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs();
+                let response = HolderResponse::new(mint_str, entry.count, entry.timestamp, false, now).with_fetch_info(&entry);
+                Ok(Json(response).into_response())
             }
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e) => {
+                error!("Error refreshing {}: {}", mint_str, e);
+                Ok(fetch_error_response(&cache, &e))
+            }
+        };
+    };
+
+    let Ok(url) = reqwest::Url::parse(&webhook_url) else {
+        return Err(StatusCode::BAD_REQUEST);
+    };
+    let Some((host, address)) = public_webhook_address(&url).await else {
+        warn!("Rejected refresh webhook {}: not a public http(s) address", webhook_url);
+        return Err(StatusCode::BAD_REQUEST);
+    };
+    let response = serde_json::json!({ "mint": &mint_str, "status": "accepted" });
+    tokio::spawn(async move {
+        let callback = match cache.refresh_now(&mint_str).await {
+            Ok(entry) => RefreshCallback {
+                mint: mint_str.clone(),
+                ok: true,
+                holders: Some(entry.count),
+                refreshed_at: Some(entry.timestamp),
+                error: None,
+            },
+            Err(e) => RefreshCallback {
+                mint: mint_str.clone(),
+                ok: false,
+                holders: None,
+                refreshed_at: None,
+                error: Some(e.to_string()),
+            },
+        };
+        // Pinned to the checked address, and no redirects: the webhook cannot bounce to an internal host
+        let http_client = reqwest::Client::builder()
+            .resolve(&host, address)
+            .redirect(reqwest::redirect::Policy::none())
+            .timeout(Duration::from_secs(10))
+            .build();
+        let sent = match http_client {
+            Ok(http_client) => http_client
+                .post(url)
+                .json(&callback)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status()),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = sent {
+            warn!("Failed to deliver refresh webhook for {}: {}", mint_str, e);
+        }
+    });
+    Ok((StatusCode::ACCEPTED, Json(response)).into_response())
+}
+
+/// Address a `?webhook_url=` callback is delivered to: an http(s) URL whose host resolves only to
+/// public addresses, so the API cannot be used to reach loopback, link-local or private networks
+async fn public_webhook_address(url: &reqwest::Url) -> Option<(String, SocketAddr)> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+    let host = url.host_str()?.trim_start_matches('[').trim_end_matches(']').to_string();
+    let port = url.port_or_known_default()?;
+    let addresses: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), port)).await.ok()?.collect();
+    if !addresses.iter().all(|address| is_public_ip(address.ip())) {
+        return None;
+    }
+    addresses.first().map(|address| (host, *address))
+}
+
+/// Globally routable address: not loopback, private, link-local, CGNAT, unspecified or broadcast
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || a == 0
+                || (a == 100 && (b & 0xc0) == 64))
         }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_ip(IpAddr::V4(ip)),
+            None => {
+                let first = ip.segments()[0];
+                !(ip.is_loopback() || ip.is_unspecified() || (first & 0xfe00) == 0xfc00 || (first & 0xffc0) == 0xfe80)
+            }
+        },
     }
 }
 
-/// Health check endpoint
-async fn health_check() -> Json<serde_json::Value> {
-    Json(serde_json::json!({
-        "status": "ok",
-        "service": "solana-holder-bot-api"
+/// Churn response for `GET /holders/:mint/churn`
+#[derive(Debug, Serialize)]
+pub struct ChurnResponse {
+    pub mint: String,
+    pub holders: usize,
+    pub last_updated: u64,
+    /// Rolling windows: 1h and 24h
+    pub windows: Vec<ChurnStats>,
+}
+
+/// Get holder churn endpoint
+/// Churn needs at least two refreshes of the mint to be non-empty
+async fn get_holder_churn(
+    Path(mint_str): Path<String>,
+    axum::extract::State(cache): axum::extract::State<Arc<HolderCache>>,
+) -> Result<Json<ChurnResponse>, StatusCode> {
+    if Pubkey::from_str(&mint_str).is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    cache
+        .get_churn(&mint_str)
+        .await
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+/// Monitor metrics response for `GET /monitor/:mint/metrics`
+#[derive(Debug, Serialize)]
+pub struct MonitorMetricsResponse {
+    pub mint: String,
+    #[serde(flatten)]
+    pub metrics: MetricsSummary,
+}
+
+/// Get session metrics (min/max/avg/alerts) of a mint polled by the monitor
+/// Mints passed as MINT_ADDRESS only; API cache mints return 404
+async fn get_monitor_metrics(
+    Path(mint_str): Path<String>,
+    axum::extract::State(cache): axum::extract::State<Arc<HolderCache>>,
+) -> Result<Json<MonitorMetricsResponse>, StatusCode> {
+    if Pubkey::from_str(&mint_str).is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let metrics = cache.monitor_metrics.read().await;
+    let summary = metrics.get(&mint_str).ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(MonitorMetricsResponse {
+        mint: mint_str,
+        metrics: summary.clone(),
     }))
 }
 
+/// Monitor metrics of the mints the client may query, ordered by mint
+async fn visible_monitor_metrics(cache: &HolderCache, client: Option<&ApiClient>) -> Vec<MonitorMetricsResponse> {
+    let metrics = cache.monitor_metrics.read().await;
+    let mut monitored: Vec<MonitorMetricsResponse> = metrics
+        .iter()
+        .filter(|(mint, _)| client.is_none_or(|client| client.allows_mint(mint)))
+        .map(|(mint, summary)| MonitorMetricsResponse {
+            mint: mint.clone(),
+            metrics: summary.clone(),
+        })
+        .collect();
+    monitored.sort_by(|a, b| a.mint.cmp(&b.mint));
+    monitored
+}
+
+/// Query for `GET /leaderboard`
+#[derive(Debug, Deserialize)]
+pub struct LeaderboardQuery {
+    #[serde(default)]
+    pub window: LeaderboardWindow,
+    #[serde(default)]
+    pub by: RankBy,
+    #[serde(default = "default_leaderboard_limit")]
+    pub limit: usize,
+}
+
+fn default_leaderboard_limit() -> usize {
+    50
+}
+
+const MAX_LEADERBOARD_LIMIT: usize = 1000;
+
+/// Tracked mints (cached by the API or polled by the monitor) ranked by holder growth over the
+/// window (`GET /leaderboard`); the count at the window start comes from the hourly history
+async fn get_leaderboard(
+    Query(query): Query<LeaderboardQuery>,
+    client: Option<Extension<ApiClient>>,
+    axum::extract::State(cache): axum::extract::State<Arc<HolderCache>>,
+) -> Result<Json<Leaderboard>, StatusCode> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let since = now.saturating_sub(query.window.secs());
+    let client = client.as_ref().map(|Extension(client)| client);
+
+    // Latest count of every visible mint; the monitor's own metrics win for the mints it polls
+    let mut current: BTreeMap<String, Option<usize>> = cache
+        .cache
+        .read()
+        .await
+        .iter()
+        .filter(|(mint, _)| client.is_none_or(|client| client.allows_mint(mint)))
+        .map(|(mint, entry)| (mint.clone(), Some(entry.count)))
+        .collect();
+    for monitored in visible_monitor_metrics(&cache, client).await {
+        let cached = current.get(&monitored.mint).copied().flatten();
+        current.insert(monitored.mint, monitored.metrics.last_holders.or(cached));
+    }
+
+    let mut counts = Vec::new();
+    for (mint, holders) in current {
+        let history = cache.get_count_history(&mint).await.map_err(|e| {
+            error!("Error loading count history for {}: {}", mint, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+        let history = history.unwrap_or_else(|| CountHistory::new(&mint));
+        match holders.or_else(|| history.holders_at(now)) {
+            Some(holders) => counts.push((mint, holders, history.holders_at(since))),
+            None => counts.push((mint, 0, None)),
+        }
+    }
+    Ok(Json(rank(query.window, query.by, since, counts, query.limit.min(MAX_LEADERBOARD_LIMIT))))
+}
+
+/// Metrics of every mint polled by the monitor (`GET /monitor`)
+async fn get_monitored(
+    client: Option<Extension<ApiClient>>,
+    axum::extract::State(cache): axum::extract::State<Arc<HolderCache>>,
+) -> Json<Vec<MonitorMetricsResponse>> {
+    Json(visible_monitor_metrics(&cache, client.as_ref().map(|Extension(client)| client)).await)
+}
+
+/// Monitor metrics for Prometheus (`GET /metrics`); OpenMetrics when the scraper accepts
+/// `application/openmetrics-text`
+async fn get_metrics(
+    headers: HeaderMap,
+    client: Option<Extension<ApiClient>>,
+    axum::extract::State(cache): axum::extract::State<Arc<HolderCache>>,
+) -> Response {
+    let monitored = visible_monitor_metrics(&cache, client.as_ref().map(|Extension(client)| client)).await;
+    let families = metric_families(monitored.iter().map(|m| (m.mint.as_str(), &m.metrics)));
+    let openmetrics = headers
+        .get(ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|accept| accept.contains("application/openmetrics-text"));
+    let content_type = if openmetrics { OPENMETRICS_CONTENT_TYPE } else { TEXT_CONTENT_TYPE };
+    ([(CONTENT_TYPE, content_type)], render_families(&families, openmetrics)).into_response()
+}
+
+/// Response for `GET /metrics.json`
+#[derive(Debug, Serialize)]
+pub struct MetricsJsonResponse {
+    pub metrics: Vec<MetricFamily>,
+}
+
+/// The values of `GET /metrics` as JSON, for consumers without an exposition format parser
+async fn get_metrics_json(
+    client: Option<Extension<ApiClient>>,
+    axum::extract::State(cache): axum::extract::State<Arc<HolderCache>>,
+) -> Json<MetricsJsonResponse> {
+    let monitored = visible_monitor_metrics(&cache, client.as_ref().map(|Extension(client)| client)).await;
+    Json(MetricsJsonResponse { metrics: metric_families(monitored.iter().map(|m| (m.mint.as_str(), &m.metrics))) })
+}
+
+/// Live monitor events as server-sent events (`GET /events`)
+/// Each event is named after its type (`observation`, `alert`, `error`, `wallet_entered`,
+/// `wallet_exited`, `wallet_tier_changed`) and carries the same
+/// JSON as the message broker events; only mints the key may query are streamed
+async fn get_events(
+    client: Option<Extension<ApiClient>>,
+    axum::extract::State(cache): axum::extract::State<Arc<HolderCache>>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, StatusCode> {
+    let bus = cache.events.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    let client = client.map(|Extension(client)| client);
+    let subscription = bus.subscribe("sse");
+
+    let events = stream::unfold((subscription, client), |(mut subscription, client)| async move {
+        loop {
+            let event = subscription.next().await?;
+            let mint = event.mint().to_string();
+            if client.as_ref().is_some_and(|client| !client.allows_mint(&mint)) {
+                continue;
+            }
+            let published = PublishedEvent::from(&event);
+            let Ok(data) = serde_json::to_string(&published) else {
+                continue;
+            };
+            let sse = Event::default().event(published.kind()).data(data);
+            return Some((Ok(sse), (subscription, client)));
+        }
+    });
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+/// Query for `GET /holders/:mint/history`
+#[derive(Debug, Deserialize)]
+pub struct HistoryQuery {
+    #[serde(default = "default_history_days")]
+    pub days: u64,
+}
+
+fn default_history_days() -> u64 {
+    7
+}
+
+/// Response for `GET /holders/:mint/history`
+#[derive(Debug, Serialize)]
+pub struct HistoryResponse {
+    pub mint: String,
+    /// Hourly min/max, oldest first
+    pub points: Vec<CountBucket>,
+    /// Alerts fired in the range, linked to their point by `bucket`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub annotations: Vec<Annotation>,
+    /// Hourly holders per balance tier, one series per tier
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tiers: Vec<TierSeries>,
+}
+
+/// Hourly holder count history of a monitored mint (dashboard sparklines), with per-tier series
+async fn get_holder_history(
+    Path(mint_str): Path<String>,
+    Query(query): Query<HistoryQuery>,
+    axum::extract::State(cache): axum::extract::State<Arc<HolderCache>>,
+) -> Result<Json<HistoryResponse>, StatusCode> {
+    if Pubkey::from_str(&mint_str).is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let since = now.saturating_sub(query.days.max(1).saturating_mul(86_400));
+    match cache.get_history(&mint_str, since).await {
+        Ok(Some(history)) => Ok(Json(history)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            error!("Error loading count history for {}: {}", mint_str, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Query for `GET /holders/:mint/forecast`
+#[derive(Debug, Deserialize)]
+pub struct ForecastQuery {
+    /// How far ahead, e.g. `24h` or `7d`
+    #[serde(default = "default_forecast_horizon")]
+    pub horizon: String,
+    /// History the model is fitted on
+    #[serde(default = "default_forecast_days")]
+    pub days: u64,
+    #[serde(default)]
+    pub method: ForecastMethod,
+    /// Holder count to estimate the arrival of (e.g. the next milestone)
+    pub target: Option<f64>,
+}
+
+fn default_forecast_horizon() -> String {
+    "24h".to_string()
+}
+
+fn default_forecast_days() -> u64 {
+    14
+}
+
+/// When the forecast reaches `?target=`
+#[derive(Debug, Serialize)]
+pub struct TargetEstimate {
+    pub holders: f64,
+    /// Expected time of reaching it; None beyond the horizon
+    pub reached_at: Option<u64>,
+}
+
+/// Response for `GET /holders/:mint/forecast`
+#[derive(Debug, Serialize)]
+pub struct ForecastResponse {
+    pub mint: String,
+    #[serde(flatten)]
+    pub forecast: Forecast,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<TargetEstimate>,
+}
+
+/// Holder count forecast from the hourly history (needs the same history as `GET /holders/:mint/history`)
+/// 422 while the history is too short to fit a trend
+async fn get_holder_forecast(
+    Path(mint_str): Path<String>,
+    Query(query): Query<ForecastQuery>,
+    axum::extract::State(cache): axum::extract::State<Arc<HolderCache>>,
+) -> Result<Json<ForecastResponse>, StatusCode> {
+    if Pubkey::from_str(&mint_str).is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let horizon_secs = parse_horizon(&query.horizon).map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let since = now.saturating_sub(query.days.max(1).saturating_mul(86_400));
+    let points = match cache.get_history(&mint_str, since).await {
+        Ok(Some(history)) if !history.points.is_empty() => history.points,
+        Ok(_) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            error!("Error loading count history for {}: {}", mint_str, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+    let forecast = forecast(&points, query.method, horizon_secs).ok_or(StatusCode::UNPROCESSABLE_ENTITY)?;
+    let target = query.target.map(|holders| TargetEstimate {
+        holders,
+        reached_at: forecast.reaches(holders).map(|point| point.at),
+    });
+    Ok(Json(ForecastResponse { mint: mint_str, forecast, target }))
+}
+
+/// Query for `GET /holders/:mint/quality`
+#[derive(Debug, Deserialize)]
+pub struct QualityQuery {
+    /// Wallets checked for shared funders / creation slots
+    #[serde(default = "default_quality_sample")]
+    pub sample: usize,
+}
+
+fn default_quality_sample() -> usize {
+    50
+}
+
+/// Get holder quality endpoint: raw count next to estimated organic holders
+/// Only for tracked mints; the report is cached for an hour
+async fn get_holder_quality(
+    Path(mint_str): Path<String>,
+    Query(query): Query<QualityQuery>,
+    axum::extract::State(cache): axum::extract::State<Arc<HolderCache>>,
+) -> Result<Json<HolderQuality>, StatusCode> {
+    if Pubkey::from_str(&mint_str).is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    match cache.get_quality(&mint_str, query.sample.min(MAX_QUALITY_SAMPLE)).await {
+        Ok(Some(quality)) => Ok(Json(quality)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            error!("Error computing holder quality for {}: {}", mint_str, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Query for `GET /holders/:mint/profiles`
+#[derive(Debug, Deserialize)]
+pub struct ProfilesQuery {
+    /// Wallets whose recent transactions are inspected
+    #[serde(default = "default_profile_sample")]
+    pub sample: usize,
+}
+
+fn default_profile_sample() -> usize {
+    20
+}
+
+/// Get holder profile breakdown endpoint (program interaction personas)
+/// Only for tracked mints; the breakdown is cached for an hour
+async fn get_holder_profiles(
+    Path(mint_str): Path<String>,
+    Query(query): Query<ProfilesQuery>,
+    axum::extract::State(cache): axum::extract::State<Arc<HolderCache>>,
+) -> Result<Json<ProfileBreakdown>, StatusCode> {
+    if Pubkey::from_str(&mint_str).is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    cache
+        .get_profiles(&mint_str, query.sample.min(MAX_PROFILE_SAMPLE))
+        .await
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+/// Query for `GET /holders/:mint/breakdown`
+#[derive(Debug, Deserialize)]
+pub struct BreakdownQuery {
+    /// Holders whose owner accounts are looked up
+    #[serde(default = "default_breakdown_sample")]
+    pub sample: usize,
+    /// Look up every holder (up to `MAX_OWNER_SAMPLE`)
+    #[serde(default)]
+    pub full: bool,
+}
+
+fn default_breakdown_sample() -> usize {
+    1000
+}
+
+/// Get owner program breakdown endpoint: wallets vs multisigs / vaults / protocol accounts
+/// Only for tracked mints; the breakdown is cached for an hour
+async fn get_holder_breakdown(
+    Path(mint_str): Path<String>,
+    Query(query): Query<BreakdownQuery>,
+    axum::extract::State(cache): axum::extract::State<Arc<HolderCache>>,
+) -> Result<Json<OwnerBreakdown>, StatusCode> {
+    if Pubkey::from_str(&mint_str).is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let sample = if query.full { MAX_OWNER_SAMPLE } else { query.sample.min(MAX_OWNER_SAMPLE) };
+    match cache.get_owner_breakdown(&mint_str, sample).await {
+        Ok(Some(breakdown)) => Ok(Json(breakdown)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            error!("Error computing owner breakdown for {}: {}", mint_str, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Query for `GET /holders/:mint/deposits`
+#[derive(Debug, Deserialize)]
+pub struct DepositsQuery {
+    /// Largest holders checked for protocol vaults
+    #[serde(default = "default_deposits_top")]
+    pub top: usize,
+}
+
+fn default_deposits_top() -> usize {
+    1000
+}
+
+/// Get protocol deposit attribution endpoint: holders with and without lending / staking / farm deposits
+/// Only for tracked mints; the attribution is cached for an hour
+async fn get_holder_deposits(
+    Path(mint_str): Path<String>,
+    Query(query): Query<DepositsQuery>,
+    axum::extract::State(cache): axum::extract::State<Arc<HolderCache>>,
+) -> Result<Json<DepositAttribution>, StatusCode> {
+    if Pubkey::from_str(&mint_str).is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    match cache.get_deposits(&mint_str, query.top.min(MAX_DEPOSIT_TOP)).await {
+        Ok(Some(deposits)) => Ok(Json(deposits)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            error!("Error attributing protocol deposits for {}: {}", mint_str, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Query parameters for `GET /holders/:mint/export`
+#[derive(Debug, Deserialize)]
+pub struct ExportQuery {
+    #[serde(default)]
+    pub format: ExportFormat,
+    /// Send a `.gz` file instead of plain text
+    #[serde(default)]
+    pub gzip: bool,
+}
+
+/// Download every holder of a tracked mint with its total raw balance (and UI amount when the
+/// mint account can be read), largest first
+/// The body is streamed in chunks of `EXPORT_CHUNK_ROWS` rows
+async fn get_holder_export(
+    Path(mint_str): Path<String>,
+    Query(query): Query<ExportQuery>,
+    axum::extract::State(cache): axum::extract::State<Arc<HolderCache>>,
+) -> Result<Response, StatusCode> {
+    let Ok(mint) = Pubkey::from_str(&mint_str) else {
+        return Err(StatusCode::BAD_REQUEST);
+    };
+
+    let balances = match cache.get_balances(&mint_str).await {
+        Ok(Some(balances)) => balances,
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            error!("Error exporting holders of {}: {}", mint_str, e);
+            return Ok(fetch_error_response(&cache, &e));
+        }
+    };
+    info!("Exporting {} holders of {} as {}", balances.len(), mint_str, query.format.extension());
+
+    let mut filename = format!("{}-holders.{}", mint_str, query.format.extension());
+    let content_type = if query.gzip {
+        filename.push_str(".gz");
+        "application/gzip"
+    } else {
+        query.format.content_type()
+    };
+    let mut chunks = ExportChunks::new(sorted_balances(balances), query.format, query.gzip);
+    match cache.rpc_client.get_mint_info(&mint).await {
+        Ok(mint_info) => {
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs();
+            chunks = chunks.with_ui_amounts(mint_info, now as i64);
+        }
+        Err(e) => warn!("Exporting raw amounts only for {}: {:#}", mint_str, e),
+    }
+    Ok((
+        [
+            (CONTENT_TYPE, content_type.to_string()),
+            (CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+        ],
+        Body::from_stream(stream::iter(chunks)),
+    )
+        .into_response())
+}
+
+/// Response for `GET /holders/:mint/lifetime`
+#[derive(Debug, Serialize)]
+pub struct LifetimeResponse {
+    pub mint: String,
+    pub tracked_since: u64,
+    pub last_updated: u64,
+    #[serde(flatten)]
+    pub summary: LedgerSummary,
+}
+
+/// Get unique-holders-ever and returning-holder counts
+async fn get_holder_lifetime(
+    Path(mint_str): Path<String>,
+    axum::extract::State(cache): axum::extract::State<Arc<HolderCache>>,
+) -> Result<Json<LifetimeResponse>, StatusCode> {
+    if Pubkey::from_str(&mint_str).is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    cache
+        .get_lifetime(&mint_str)
+        .await
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+/// Query parameters for `GET /holders/:mint/cohorts`
+#[derive(Debug, Deserialize)]
+pub struct CohortsQuery {
+    #[serde(default = "default_period_days")]
+    pub period_days: u64,
+}
+
+fn default_period_days() -> u64 {
+    7
+}
+
+/// Get cohort retention endpoint (needs the monitor's `--ledger-dir`)
+async fn get_holder_cohorts(
+    Path(mint_str): Path<String>,
+    Query(query): Query<CohortsQuery>,
+    axum::extract::State(cache): axum::extract::State<Arc<HolderCache>>,
+) -> Result<Json<Vec<Cohort>>, StatusCode> {
+    if Pubkey::from_str(&mint_str).is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    match cache.get_cohorts(&mint_str, query.period_days.max(1).saturating_mul(86_400)).await {
+        Ok(Some(cohorts)) => Ok(Json(cohorts)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            error!("Error computing cohorts for {}: {}", mint_str, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Health check endpoint: liveness plus uptime, version and the last successful RPC call and
+/// monitor poll, so monitoring can alert on staleness
+async fn health_check(axum::extract::State(cache): axum::extract::State<Arc<HolderCache>>) -> Json<HealthResponse> {
+    let metrics = cache.monitor_metrics.read().await;
+    let monitor = (!metrics.is_empty()).then(|| MonitorHealth::from_metrics(metrics.values()));
+    Json(HealthResponse::new(cache.rpc_client.rpc_health(), monitor))
+}
+
+/// Version, git commit, build time, features and solana-sdk version of the running binary
+async fn get_version() -> Json<BuildInfo> {
+    Json(BuildInfo::current())
+}
+
 /// Statistics for a tracked token
 #[derive(Debug, Clone, Serialize)]
 pub struct TokenStats {
@@ -300,6 +1862,62 @@ pub struct TokenStats {
     pub last_updated: u64,
     pub request_count: u64,
     pub first_seen: u64,
+    pub holders_at_first_seen: usize,
+    pub growth: i64,
+    pub growth_percent: f64,
+}
+
+/// Sort key for `GET /tokens`
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenSort {
+    #[default]
+    Holders,
+    LastUpdated,
+}
+
+/// Sort direction for `GET /tokens`
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+/// Query parameters for `GET /tokens?sort=&order=&limit=&offset=`
+#[derive(Debug, Default, Deserialize)]
+pub struct TokensQuery {
+    #[serde(default)]
+    pub sort: TokenSort,
+    #[serde(default)]
+    pub order: SortOrder,
+    pub limit: Option<usize>,
+    #[serde(default)]
+    pub offset: usize,
+}
+
+/// Default and maximum page size for `GET /tokens`
+const DEFAULT_TOKENS_LIMIT: usize = 100;
+const MAX_TOKENS_LIMIT: usize = 1000;
+
+/// Sort tracked tokens and cut out the requested page
+/// Ties are broken by mint so pages are stable between requests
+pub fn sort_and_paginate(mut tokens: Vec<TokenStats>, query: &TokensQuery) -> Vec<TokenStats> {
+    tokens.sort_by(|a, b| {
+        let ordering = match query.sort {
+            TokenSort::Holders => a.holders.cmp(&b.holders),
+            TokenSort::LastUpdated => a.last_updated.cmp(&b.last_updated),
+        };
+        let ordering = match query.order {
+            SortOrder::Asc => ordering,
+            SortOrder::Desc => ordering.reverse(),
+        };
+        ordering.then_with(|| a.mint.cmp(&b.mint))
+    });
+
+    let limit = query.limit.unwrap_or(DEFAULT_TOKENS_LIMIT).min(MAX_TOKENS_LIMIT);
+    tokens.into_iter().skip(query.offset).take(limit).collect()
 }
 
 /// Cache statistics
@@ -308,14 +1926,29 @@ pub struct CacheStats {
     pub total_tracked_tokens: usize,
     pub total_requests: u64,
     pub cache_size_bytes: u64,
+    /// Approximate memory of fetched accounts, holder sets and history
+    pub memory: MemoryUsage,
+    pub max_memory_bytes: Option<usize>,
 }
 
-/// Get list of all tracked tokens
+/// Get list of all tracked tokens (sorted, paginated)
+/// Total number of tracked tokens is returned in the `X-Total-Count` header
+/// Keys limited to some mints only see those
 async fn get_tracked_tokens(
+    Query(query): Query<TokensQuery>,
+    client: Option<Extension<ApiClient>>,
     axum::extract::State(cache): axum::extract::State<Arc<HolderCache>>,
-) -> Json<Vec<TokenStats>> {
-    let tokens = cache.get_tracked_tokens().await;
-    Json(tokens)
+) -> ([(HeaderName, String); 1], Json<Vec<TokenStats>>) {
+    let mut tokens = cache.get_tracked_tokens().await;
+    if let Some(Extension(client)) = client {
+        tokens.retain(|token| client.allows_mint(&token.mint));
+    }
+    let total = tokens.len();
+    let page = sort_and_paginate(tokens, &query);
+    (
+        [(HeaderName::from_static("x-total-count"), total.to_string())],
+        Json(page),
+    )
 }
 
 /// Get cache statistics
@@ -326,15 +1959,131 @@ async fn get_cache_stats(
     Json(stats)
 }
 
+/// Overview across tracked tokens; keys limited to some mints only see those
+async fn get_stats_summary(
+    client: Option<Extension<ApiClient>>,
+    axum::extract::State(cache): axum::extract::State<Arc<HolderCache>>,
+) -> Json<StatsSummary> {
+    let summary = cache
+        .get_summary(|mint| client.as_ref().is_none_or(|Extension(client)| client.allows_mint(mint)))
+        .await;
+    Json(summary)
+}
+
+/// Calls, error rate and latency per JSON-RPC method since startup, most called first
+async fn get_rpc_stats(
+    axum::extract::State(cache): axum::extract::State<Arc<HolderCache>>,
+) -> Json<Vec<MethodStats>> {
+    Json(cache.rpc_client.rpc_stats())
+}
+
+/// Watch a wallet on a monitored mint (requires an API key): its balance shows up in `GET /watch`
+/// after the next poll, later entries, exits and tier changes as `wallet_entered` /
+/// `wallet_exited` / `wallet_tier_changed` events
+async fn post_watch(
+    Path((mint_str, wallet_str)): Path<(String, String)>,
+    client: Option<Extension<ApiClient>>,
+    axum::extract::State(cache): axum::extract::State<Arc<HolderCache>>,
+) -> Result<(StatusCode, Json<WatchedWallet>), StatusCode> {
+    cache.authorize(client)?;
+    let watch = cache.watch.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    let (Ok(mint), Ok(wallet)) = (Pubkey::from_str(&mint_str), Pubkey::from_str(&wallet_str)) else {
+        return Err(StatusCode::BAD_REQUEST);
+    };
+    // Only the monitoring loop updates watched wallets
+    if !cache.monitored.contains(&mint_str) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    info!("Watching wallet {} on {}", wallet, mint);
+    Ok((StatusCode::CREATED, Json(watch.register(&mint, &wallet, now))))
+}
+
+/// Query for `GET /watch`
+#[derive(Debug, Default, Deserialize)]
+pub struct WatchQuery {
+    /// Only wallets watched on this mint
+    pub mint: Option<String>,
+}
+
+/// Watched wallets with their balance and membership at the last poll
+async fn get_watch(
+    Query(query): Query<WatchQuery>,
+    client: Option<Extension<ApiClient>>,
+    axum::extract::State(cache): axum::extract::State<Arc<HolderCache>>,
+) -> Result<Json<Vec<WatchedWallet>>, StatusCode> {
+    let watch = cache.watch.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    let mint = match &query.mint {
+        Some(mint) => Some(Pubkey::from_str(mint).map_err(|_| StatusCode::BAD_REQUEST)?),
+        None => None,
+    };
+    let mut watched = watch.list(mint.as_ref());
+    if let Some(Extension(client)) = &client {
+        watched.retain(|entry| client.allows_mint(&entry.mint));
+    }
+    Ok(Json(watched))
+}
+
+/// Current problems with the RPC endpoint, each with a `suggested_action`
+async fn get_diagnostics(
+    axum::extract::State(cache): axum::extract::State<Arc<HolderCache>>,
+) -> Json<Vec<Diagnostic>> {
+    Json(cache.rpc_client.diagnostics())
+}
+
+/// Per-key request counters (requires an admin key)
+async fn get_admin_usage(
+    client: Option<Extension<ApiClient>>,
+    axum::extract::State(cache): axum::extract::State<Arc<HolderCache>>,
+) -> Result<Json<Vec<KeyUsage>>, StatusCode> {
+    if !cache.authorize(client)?.admin {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    Ok(Json(cache.api_key_usage()))
+}
+
 /// Create API router
+/// Every route except `/health`, `/version` and the dashboard goes through the `authenticate` middleware
 pub fn create_api_router(cache: Arc<HolderCache>) -> Router {
     Router::new()
         .route("/holders/:mint", get(get_holders))
-        .route("/health", get(health_check))
+        .route("/holders/:mint/churn", get(get_holder_churn))
+        .route("/holders/:mint/cohorts", get(get_holder_cohorts))
+        .route("/holders/:mint/lifetime", get(get_holder_lifetime))
+        .route("/holders/:mint/quality", get(get_holder_quality))
+        .route("/holders/:mint/profiles", get(get_holder_profiles))
+        .route("/holders/:mint/breakdown", get(get_holder_breakdown))
+        .route("/holders/:mint/deposits", get(get_holder_deposits))
+        .route("/holders/:mint/history", get(get_holder_history))
+        .route("/holders/:mint/forecast", get(get_holder_forecast))
+        .route("/holders/:mint/export", get(get_holder_export))
+        .route("/monitor", get(get_monitored))
+        .route("/leaderboard", get(get_leaderboard))
+        .route("/metrics", get(get_metrics))
+        .route("/metrics.json", get(get_metrics_json))
+        .route("/events", get(get_events))
+        .route("/monitor/:mint/metrics", get(get_monitor_metrics))
+        .route("/refresh/:mint", post(post_refresh))
         .route("/tokens", get(get_tracked_tokens))
         .route("/stats", get(get_cache_stats))
+        .route("/stats/summary", get(get_stats_summary))
+        .route("/admin/usage", get(get_admin_usage))
+        .route("/rpc-stats", get(get_rpc_stats))
+        .route("/diagnostics", get(get_diagnostics))
+        .route("/watch", get(get_watch))
+        .route("/watch/:mint/:wallet", post(post_watch))
+        .route_layer(middleware::from_fn_with_state(cache.clone(), authenticate))
+        .route("/health", get(health_check))
+        .route("/version", get(get_version))
+        .route("/", get(dashboard::index))
+        .route("/assets/*path", get(dashboard::asset))
         .with_state(cache)
         .layer(tower_http::cors::CorsLayer::permissive())
+        // gzip/br negotiated via Accept-Encoding; large JSON payloads shrink several times
+        .layer(tower_http::compression::CompressionLayer::new())
 }
 
 /// Start API server
@@ -351,9 +2100,31 @@ pub async fn start_api_server(
     info!("API server started on http://0.0.0.0:{}", port);
     info!("Endpoints:");
     info!("  GET /holders/:mint - Get holder count for token");
-    info!("  GET /health - Health check");
-    info!("  GET /tokens - Get list of all tracked tokens");
+    info!("  GET /holders/:mint/churn - Holders entered/exited over 1h and 24h");
+    info!("  GET /holders/:mint/cohorts?period_days=7 - Cohort retention (needs --ledger-dir)");
+    info!("  GET /holders/:mint/lifetime - Unique holders ever, first-time vs returning");
+    info!("  GET /holders/:mint/quality?sample=50 - Estimated organic holders (sybil heuristics)");
+    info!("  GET /holders/:mint/profiles?sample=20 - DEX traders / NFT collectors / stakers breakdown");
+    info!("  GET /holders/:mint/breakdown?sample=1000 - Wallets vs program-owned holders");
+    info!("  GET /holders/:mint/deposits?top=1000 - Holders with and without protocol deposits");
+    info!("  GET /holders/:mint/history?days=7 - Hourly holder counts and tiers (saved with --ledger-dir)");
+    info!("  GET /holders/:mint/forecast?horizon=24h&method=linear|holt&target= - Estimated holder growth with 95% bounds");
+    info!("  GET /holders/:mint/export?format=csv|json&gzip=true - Download holders with balances");
+    info!("  GET /monitor - Metrics of all monitored mints");
+    info!("  GET /metrics - Monitor metrics in Prometheus / OpenMetrics text format");
+    info!("  GET /metrics.json - The same metrics as JSON");
+    info!("  GET /monitor/:mint/metrics - Min/max/avg/alerts of a monitored mint");
+    info!("  POST /refresh/:mint?webhook_url= - Refresh a token now (requires --api-key)");
+    info!("  GET /health - Health check with uptime, version and last successful RPC call / poll");
+    info!("  GET /version - Version, git commit, build time, features, solana-sdk version");
+    info!("  GET / - Dashboard");
+    info!("  GET /tokens?sort=holders|last_updated&order=asc|desc&limit=&offset= - Get list of all tracked tokens");
     info!("  GET /stats - Get cache statistics");
+    info!("  GET /rpc-stats - Calls, errors and latency per RPC method");
+    info!("  GET /watch?mint= - Watched wallets with balance and membership");
+    info!("  POST /watch/:mint/:wallet - Watch a wallet on a monitored mint (requires --api-key)");
+    info!("  GET /diagnostics - RPC problems with a suggested_action (switch_provider, increase_timeout)");
+    info!("  GET /admin/usage - Requests per API key (requires an admin key)");
 
     axum::serve(listener, app)
         .await
@@ -362,3 +2133,93 @@ pub async fn start_api_server(
     Ok(())
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(mint: &str, holders: usize, last_updated: u64) -> TokenStats {
+        TokenStats {
+            mint: mint.to_string(),
+            holders,
+            last_updated,
+            request_count: 1,
+            first_seen: 0,
+            holders_at_first_seen: holders,
+            growth: 0,
+            growth_percent: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_holder_response_age() {
+        let response = HolderResponse::new("mint".to_string(), 5, 100, true, 130);
+        assert!(response.cached);
+        assert_eq!(response.age_seconds, 30);
+        assert_eq!(response.refreshed_at, 100);
+        assert_eq!(response.timestamp, 100);
+
+        // Clock skew must not underflow
+        let response = HolderResponse::new("mint".to_string(), 5, 200, false, 130);
+        assert_eq!(response.age_seconds, 0);
+    }
+
+    #[test]
+    fn test_stalest_first() {
+        let entry = |timestamp| HolderCacheEntry {
+            count: 1,
+            timestamp,
+            mint: Pubkey::new_unique(),
+            request_count: 0,
+            first_seen: 0,
+            first_count: 1,
+            slot: None,
+            block_time: None,
+            degraded: None,
+            missing_supply_percent: None,
+            token_accounts: None,
+            metadata: None,
+        };
+        let cache = HashMap::from([
+            ("a".to_string(), entry(300)),
+            ("b".to_string(), entry(100)),
+            ("c".to_string(), entry(200)),
+            ("d".to_string(), entry(100)),
+        ]);
+        assert_eq!(stalest_first(&cache), vec!["b", "d", "c", "a"]);
+    }
+
+    #[test]
+    fn test_sort_and_paginate() {
+        let tokens = vec![token("a", 10, 3), token("b", 30, 1), token("c", 20, 2)];
+
+        let query = TokensQuery::default();
+        let page = sort_and_paginate(tokens.clone(), &query);
+        let mints: Vec<_> = page.iter().map(|t| t.mint.as_str()).collect();
+        assert_eq!(mints, vec!["b", "c", "a"]);
+
+        let query = TokensQuery {
+            sort: TokenSort::LastUpdated,
+            order: SortOrder::Asc,
+            limit: Some(1),
+            offset: 1,
+        };
+        let page = sort_and_paginate(tokens, &query);
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].mint, "c");
+    }
+
+    #[test]
+    fn test_is_public_ip() {
+        let internal = [
+            "127.0.0.1", "10.1.2.3", "172.16.0.1", "192.168.1.1", "169.254.169.254", "100.64.0.1", "0.0.0.0",
+            "::1", "fe80::1", "fd00::1", "::ffff:127.0.0.1",
+        ];
+        for ip in internal {
+            assert!(!is_public_ip(ip.parse().unwrap()), "{}", ip);
+        }
+        for ip in ["1.1.1.1", "8.8.8.8", "2606:4700::1111"] {
+            assert!(is_public_ip(ip.parse().unwrap()), "{}", ip);
+        }
+    }
+}
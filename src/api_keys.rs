@@ -0,0 +1,253 @@
+//! API keys for downstream teams: per-key rate limits, allowed mints and usage accounting
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use std::sync::Mutex;
+
+/// Length of the fixed rate-limit window
+const RATE_WINDOW_SECS: u64 = 60;
+
+/// `[api_keys.<name>]` config section
+///
+/// ```toml
+/// [api_keys.analytics]
+/// key = "..."
+/// requests_per_minute = 60
+/// mints = ["EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"]
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiKeyConfig {
+    pub key: String,
+    /// Unlimited when unset
+    #[serde(default)]
+    pub requests_per_minute: Option<u32>,
+    /// Mints the key may query; every mint when unset
+    #[serde(default)]
+    pub mints: Option<Vec<String>>,
+    /// May read `GET /admin/usage`
+    #[serde(default)]
+    pub admin: bool,
+}
+
+/// Identity behind an API key, handed to handlers as a request extension
+#[derive(Debug, Clone)]
+pub struct ApiClient {
+    pub name: String,
+    pub admin: bool,
+    pub requests_per_minute: Option<u32>,
+    pub mints: Option<HashSet<String>>,
+}
+
+impl ApiClient {
+    pub fn allows_mint(&self, mint: &str) -> bool {
+        self.mints.as_ref().is_none_or(|mints| mints.contains(mint))
+    }
+}
+
+/// Why a request with a known key was refused
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rejection {
+    MintNotAllowed,
+    RateLimited { retry_after_secs: u64 },
+}
+
+/// Usage counters of one key (`GET /admin/usage`)
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct KeyUsage {
+    pub name: String,
+    pub requests: u64,
+    pub rate_limited: u64,
+    pub mint_denied: u64,
+    pub last_used: Option<u64>,
+    pub requests_per_minute: Option<u32>,
+    /// Requests in the current rate-limit window
+    pub window_requests: u32,
+    #[serde(skip)]
+    window_start: u64,
+}
+
+/// All accepted keys with their usage
+#[derive(Debug, Default)]
+pub struct ApiKeys {
+    keys: Vec<(String, ApiClient)>,
+    usage: Mutex<HashMap<String, KeyUsage>>,
+}
+
+impl ApiKeys {
+    /// `--api-key` keys are unrestricted admin keys named `cli-1`, `cli-2`, …;
+    /// config keys are named after their section
+    pub fn new(cli_keys: &[String], configured: &HashMap<String, ApiKeyConfig>) -> Result<Self> {
+        let mut keys = Vec::new();
+        for (i, key) in cli_keys.iter().enumerate() {
+            let client = ApiClient {
+                name: format!("cli-{}", i + 1),
+                admin: true,
+                requests_per_minute: None,
+                mints: None,
+            };
+            keys.push((key.clone(), client));
+        }
+        for (name, config) in configured {
+            if let Some(mints) = &config.mints {
+                for mint in mints {
+                    Pubkey::from_str(mint)
+                        .map_err(|_| anyhow::anyhow!("API key '{}': invalid mint '{}'", name, mint))?;
+                }
+            }
+            if config.requests_per_minute == Some(0) {
+                return Err(anyhow::anyhow!("API key '{}': requests_per_minute must be at least 1", name));
+            }
+            let client = ApiClient {
+                name: name.clone(),
+                admin: config.admin,
+                requests_per_minute: config.requests_per_minute,
+                mints: config.mints.as_ref().map(|mints| mints.iter().cloned().collect()),
+            };
+            keys.push((config.key.clone(), client));
+        }
+
+        let mut seen = HashSet::new();
+        for (key, client) in &keys {
+            if key.trim().is_empty() {
+                return Err(anyhow::anyhow!("API key '{}' is empty", client.name));
+            }
+            if !seen.insert(key.as_str()) {
+                return Err(anyhow::anyhow!("API key '{}' is used by another key entry", client.name));
+            }
+        }
+
+        let usage = keys
+            .iter()
+            .map(|(_, client)| {
+                let usage = KeyUsage {
+                    name: client.name.clone(),
+                    requests_per_minute: client.requests_per_minute,
+                    ..KeyUsage::default()
+                };
+                (client.name.clone(), usage)
+            })
+            .collect();
+        Ok(Self {
+            keys,
+            usage: Mutex::new(usage),
+        })
+    }
+
+    /// Unrestricted keys only (`--api-key`)
+    pub fn from_keys(keys: Vec<String>) -> Result<Self> {
+        Self::new(&keys, &HashMap::new())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub fn identify(&self, key: &str) -> Option<&ApiClient> {
+        self.keys
+            .iter()
+            .find(|(expected, _)| constant_time_eq(expected, key))
+            .map(|(_, client)| client)
+    }
+
+    /// Count one request of `client` for `mint` (the path's mint, if any)
+    /// Refused requests are counted separately and don't use up the rate limit
+    pub fn check(&self, client: &ApiClient, mint: Option<&str>, now: u64) -> Result<(), Rejection> {
+        let mut usage = self.usage.lock().unwrap();
+        let usage = usage.entry(client.name.clone()).or_default();
+
+        if mint.is_some_and(|mint| !client.allows_mint(mint)) {
+            usage.mint_denied += 1;
+            return Err(Rejection::MintNotAllowed);
+        }
+
+        if now >= usage.window_start + RATE_WINDOW_SECS {
+            usage.window_start = now - now % RATE_WINDOW_SECS;
+            usage.window_requests = 0;
+        }
+        if let Some(limit) = client.requests_per_minute {
+            if usage.window_requests >= limit {
+                usage.rate_limited += 1;
+                return Err(Rejection::RateLimited {
+                    retry_after_secs: usage.window_start + RATE_WINDOW_SECS - now,
+                });
+            }
+        }
+        usage.window_requests += 1;
+        usage.requests += 1;
+        usage.last_used = Some(now);
+        Ok(())
+    }
+
+    /// Usage of every key, sorted by name
+    pub fn usage(&self) -> Vec<KeyUsage> {
+        let mut usage: Vec<KeyUsage> = self.usage.lock().unwrap().values().cloned().collect();
+        usage.sort_by(|a, b| a.name.cmp(&b.name));
+        usage
+    }
+}
+
+/// Compare keys without leaking the matching prefix length through timing
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len() && a.bytes().zip(b.bytes()).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys() -> ApiKeys {
+        let mint = Pubkey::new_unique().to_string();
+        let configured = HashMap::from([(
+            "team".to_string(),
+            ApiKeyConfig {
+                key: "team-key".to_string(),
+                requests_per_minute: Some(2),
+                mints: Some(vec![mint]),
+                admin: false,
+            },
+        )]);
+        ApiKeys::new(&["root-key".to_string()], &configured).unwrap()
+    }
+
+    #[test]
+    fn test_rate_limit_window() {
+        let keys = keys();
+        let team = keys.identify("team-key").unwrap().clone();
+        let mint = team.mints.as_ref().unwrap().iter().next().unwrap().clone();
+
+        assert_eq!(keys.check(&team, Some(&mint), 120), Ok(()));
+        assert_eq!(keys.check(&team, None, 130), Ok(()));
+        assert_eq!(
+            keys.check(&team, None, 150),
+            Err(Rejection::RateLimited { retry_after_secs: 30 })
+        );
+        // Next window
+        assert_eq!(keys.check(&team, None, 180), Ok(()));
+
+        let usage = keys.usage();
+        assert_eq!(usage[1].name, "team");
+        assert_eq!(usage[1].requests, 3);
+        assert_eq!(usage[1].rate_limited, 1);
+        assert_eq!(usage[1].last_used, Some(180));
+    }
+
+    #[test]
+    fn test_allowed_mints() {
+        let keys = keys();
+        assert!(keys.identify("wrong").is_none());
+        let root = keys.identify("root-key").unwrap().clone();
+        let team = keys.identify("team-key").unwrap().clone();
+        let other = Pubkey::new_unique().to_string();
+
+        assert!(root.admin && !team.admin);
+        assert_eq!(keys.check(&root, Some(&other), 0), Ok(()));
+        assert_eq!(keys.check(&team, Some(&other), 0), Err(Rejection::MintNotAllowed));
+        assert_eq!(keys.usage()[1].mint_denied, 1);
+
+        assert!(ApiKeys::from_keys(vec!["a".to_string(), "a".to_string()]).is_err());
+    }
+}
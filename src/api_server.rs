@@ -23,6 +23,15 @@ use crate::{extract_holders, SolanaRpcClient};
 struct CachedResult {
     count: usize,
     timestamp: Instant,
+    refreshed_at: u64,
+}
+
+/// Current unix time in seconds
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
 }
 
 /// Cache entry with TTL
@@ -54,11 +63,18 @@ pub struct ApiState {
 }
 
 /// Response structure for holder count API
+/// Same shape as `api::HolderResponse`
 #[derive(Serialize, Deserialize)]
 pub struct HolderCountResponse {
     pub mint: String,
     pub holders: usize,
+    /// True when served from cache, false when fetched from RPC for this request
     pub cached: bool,
+    /// Seconds since the value was fetched from RPC
+    pub age_seconds: u64,
+    /// Unix timestamp of the RPC fetch that produced the value
+    pub refreshed_at: u64,
+    /// Same as `refreshed_at`, kept for existing clients
     pub timestamp: u64,
 }
 
@@ -83,7 +99,9 @@ impl ApiState {
                         mint: mint.to_string(),
                         holders: entry.result.count,
                         cached: true,
-                        timestamp: entry.result.timestamp.elapsed().as_secs(),
+                        age_seconds: entry.result.timestamp.elapsed().as_secs(),
+                        refreshed_at: entry.result.refreshed_at,
+                        timestamp: entry.result.refreshed_at,
                     });
                 }
             }
@@ -92,6 +110,7 @@ impl ApiState {
         // Cache miss or expired - fetch from RPC
         info!("Cache miss for mint: {}, fetching from RPC...", mint);
         let count = self.fetch_holder_count(mint).await?;
+        let refreshed_at = unix_now();
 
         // Update cache
         {
@@ -102,6 +121,7 @@ impl ApiState {
                     result: CachedResult {
                         count,
                         timestamp: Instant::now(),
+                        refreshed_at,
                     },
                     ttl: self.cache_ttl,
                 },
@@ -112,7 +132,9 @@ impl ApiState {
             mint: mint.to_string(),
             holders: count,
             cached: false,
-            timestamp: 0,
+            age_seconds: 0,
+            refreshed_at,
+            timestamp: refreshed_at,
         })
     }
 
@@ -152,6 +174,7 @@ impl ApiState {
                                     result: CachedResult {
                                         count,
                                         timestamp: Instant::now(),
+                                        refreshed_at: unix_now(),
                                     },
                                     ttl: state.cache_ttl,
                                 },
@@ -205,6 +228,8 @@ pub fn create_router(state: ApiState) -> Router {
         .route("/holders/:mint", get(get_holders_handler))
         .route("/health", get(health_handler))
         .layer(tower_http::cors::CorsLayer::permissive())
+        // gzip/br negotiated via Accept-Encoding; large JSON payloads shrink several times
+        .layer(tower_http::compression::CompressionLayer::new())
         .with_state(state)
 }
 
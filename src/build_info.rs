@@ -0,0 +1,63 @@
+//! What the binary was built from (`GET /version`), set by `build.rs`
+
+use serde::Serialize;
+
+/// Crate version the binary was built from
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+/// Git commit, when the build knows it (a checkout or `GIT_SHA`)
+pub const GIT_COMMIT: Option<&str> = option_env!("GIT_SHA");
+/// Unix time of the build (`SOURCE_DATE_EPOCH` when set)
+pub const BUILD_TIMESTAMP: &str = env!("BUILD_TIMESTAMP");
+/// Comma-separated cargo features
+pub const FEATURES: &str = env!("BUILD_FEATURES");
+/// solana-sdk version from Cargo.lock
+pub const SOLANA_SDK_VERSION: &str = env!("SOLANA_SDK_VERSION");
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BuildInfo {
+    pub version: &'static str,
+    pub git_commit: Option<&'static str>,
+    pub build_timestamp: Option<u64>,
+    pub features: Vec<&'static str>,
+    pub solana_sdk_version: &'static str,
+}
+
+impl BuildInfo {
+    pub fn current() -> Self {
+        Self {
+            version: VERSION,
+            git_commit: GIT_COMMIT,
+            build_timestamp: BUILD_TIMESTAMP.parse().ok(),
+            features: FEATURES.split(',').filter(|feature| !feature.is_empty()).collect(),
+            solana_sdk_version: SOLANA_SDK_VERSION,
+        }
+    }
+
+    /// One line for `--version` style logs
+    pub fn describe(&self) -> String {
+        let mut line = format!("v{}", self.version);
+        if let Some(commit) = self.git_commit {
+            line.push_str(&format!(" ({})", commit));
+        }
+        line.push_str(&format!(", solana-sdk {}", self.solana_sdk_version));
+        if !self.features.is_empty() {
+            line.push_str(&format!(", features: {}", self.features.join(", ")));
+        }
+        line
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_current() {
+        let info = BuildInfo::current();
+        assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+        assert!(info.build_timestamp.is_some());
+        assert!(info.describe().starts_with(&format!("v{}", info.version)));
+        #[cfg(feature = "test-util")]
+        assert!(info.features.contains(&"test-util"));
+    }
+}
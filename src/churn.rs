@@ -0,0 +1,167 @@
+use serde::Serialize;
+use solana_program::pubkey::Pubkey;
+use std::collections::{HashSet, VecDeque};
+
+/// How long churn samples are kept (largest window that can be queried)
+pub const CHURN_RETENTION_SECS: u64 = 24 * 3600;
+
+/// Difference between two consecutive holder sets
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HolderDiff {
+    pub entered: Vec<Pubkey>,
+    pub exited: Vec<Pubkey>,
+}
+
+/// Compute which holders appeared and which disappeared between two polls
+pub fn diff_holders(previous: &HashSet<Pubkey>, current: &HashSet<Pubkey>) -> HolderDiff {
+    HolderDiff {
+        entered: current.difference(previous).copied().collect(),
+        exited: previous.difference(current).copied().collect(),
+    }
+}
+
+/// One poll-to-poll transition
+#[derive(Debug, Clone)]
+struct ChurnSample {
+    /// Timestamp of the previous observation
+    since: u64,
+    /// Timestamp of this observation
+    timestamp: u64,
+    entered: usize,
+    exited: usize,
+    /// Holder count at the previous observation
+    holders_before: usize,
+}
+
+/// Churn over a rolling window
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct ChurnStats {
+    pub window_secs: u64,
+    pub entered: usize,
+    pub exited: usize,
+    pub entered_per_hour: f64,
+    pub exited_per_hour: f64,
+    /// Share of holders at the start of the window that are still holding.
+    /// Approximation: a wallet that entered and left within the window counts as an exit
+    pub retention_percent: f64,
+}
+
+/// Rolling churn tracker fed with holder-set diffs
+#[derive(Debug, Clone, Default)]
+pub struct ChurnTracker {
+    samples: VecDeque<ChurnSample>,
+}
+
+impl ChurnTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record transition from the previous observation to the current one
+    pub fn record(&mut self, since: u64, timestamp: u64, diff: &HolderDiff, holders_before: usize) {
+        self.samples.push_back(ChurnSample {
+            since,
+            timestamp,
+            entered: diff.entered.len(),
+            exited: diff.exited.len(),
+            holders_before,
+        });
+
+        let cutoff = timestamp.saturating_sub(CHURN_RETENTION_SECS);
+        while self.samples.front().is_some_and(|s| s.since < cutoff) {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Approximate memory held by the samples
+    pub fn memory_bytes(&self) -> usize {
+        self.samples.len() * std::mem::size_of::<ChurnSample>()
+    }
+
+    /// Churn over the last `window_secs` seconds ending at `now`
+    pub fn stats(&self, now: u64, window_secs: u64) -> ChurnStats {
+        let cutoff = now.saturating_sub(window_secs);
+        let in_window: Vec<&ChurnSample> = self
+            .samples
+            .iter()
+            .filter(|s| s.since >= cutoff && s.timestamp <= now)
+            .collect();
+
+        let Some(first) = in_window.first() else {
+            return ChurnStats {
+                window_secs,
+                retention_percent: 100.0,
+                ..Default::default()
+            };
+        };
+
+        let entered: usize = in_window.iter().map(|s| s.entered).sum();
+        let exited: usize = in_window.iter().map(|s| s.exited).sum();
+        let span_secs = now.saturating_sub(first.since).max(1) as f64;
+        let per_hour = |n: usize| n as f64 * 3600.0 / span_secs;
+
+        let retention_percent = if first.holders_before > 0 {
+            let kept = first.holders_before.saturating_sub(exited);
+            kept as f64 / first.holders_before as f64 * 100.0
+        } else {
+            100.0
+        };
+
+        ChurnStats {
+            window_secs,
+            entered,
+            exited,
+            entered_per_hour: per_hour(entered),
+            exited_per_hour: per_hour(exited),
+            retention_percent,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_holders() {
+        let a = Pubkey::new_unique();
+        let b = Pubkey::new_unique();
+        let c = Pubkey::new_unique();
+        let previous: HashSet<_> = [a, b].into_iter().collect();
+        let current: HashSet<_> = [b, c].into_iter().collect();
+
+        let diff = diff_holders(&previous, &current);
+        assert_eq!(diff.entered, vec![c]);
+        assert_eq!(diff.exited, vec![a]);
+    }
+
+    #[test]
+    fn test_churn_window() {
+        let mut tracker = ChurnTracker::new();
+        let diff = |entered, exited| HolderDiff {
+            entered: vec![Pubkey::default(); entered],
+            exited: vec![Pubkey::default(); exited],
+        };
+
+        tracker.record(0, 1800, &diff(10, 2), 100);
+        tracker.record(1800, 3600, &diff(6, 3), 108);
+
+        let stats = tracker.stats(3600, 3600);
+        assert_eq!(stats.entered, 16);
+        assert_eq!(stats.exited, 5);
+        assert!((stats.entered_per_hour - 16.0).abs() < 1e-9);
+        assert!((stats.retention_percent - 95.0).abs() < 1e-9);
+
+        // Only the second transition fits into a 30 minute window
+        let stats = tracker.stats(3600, 1800);
+        assert_eq!(stats.entered, 6);
+        assert_eq!(stats.exited, 3);
+    }
+
+    #[test]
+    fn test_churn_empty() {
+        let stats = ChurnTracker::new().stats(100, 3600);
+        assert_eq!(stats.entered, 0);
+        assert_eq!(stats.retention_percent, 100.0);
+    }
+}
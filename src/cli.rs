@@ -1,55 +1,829 @@
-use clap::Parser;
+use clap::{parser::ValueSource, ArgGroup, ArgMatches, Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
 use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
 use std::str::FromStr;
+use std::time::Duration;
+
+use crate::allocation::{AllocationRules, Rounding};
+use crate::cluster::Cluster;
+use crate::http_tuning::HttpVersion;
+use crate::i18n::Language;
+use crate::launch::parse_launch_time;
+use crate::locale::{parse_timezone, Locale, NumberLocale};
+use crate::logging::LogRotation;
+use crate::merkle::{Allocation, MerkleLayout};
+use crate::output::OutputFormat;
+use crate::redact::redact_url;
+use crate::scheduler::{Priority, Stagger};
+use crate::truncation::TruncationMode;
 
 /// Solana Token Holder Monitoring Bot
 /// Monitors token holder count changes in real-time
+/// Every option except `-v` can also be set through a `SOLBOT_<OPTION>` environment variable
+/// (e.g. `SOLBOT_RPC_URL`); list options take comma-separated values there
 #[derive(Parser, Debug)]
-#[command(name = "solana-holder-bot")]
+#[command(name = "solana-holder-bot", version)]
 #[command(about = "Monitor Solana token holders in real-time", long_about = None)]
+#[command(subcommand_negates_reqs = true)]
+#[command(subcommand_precedence_over_arg = true)]
 pub struct Cli {
-    /// Token mint address to monitor
-    #[arg(value_name = "MINT_ADDRESS")]
-    pub mint_address: String,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Token mint address(es) to monitor
+    #[arg(value_name = "MINT_ADDRESS", required = true, env = "SOLBOT_MINT_ADDRESS", value_delimiter = ',')]
+    pub mint_addresses: Vec<String>,
+
+    /// RPC endpoint URL [default: public endpoint of --cluster, mainnet if not set]
+    #[arg(long = "rpc-url", env = "SOLBOT_RPC_URL")]
+    pub rpc_url: Option<String>,
+
+    /// Read the RPC URL (which often embeds an API key) from a file instead
+    #[arg(long = "rpc-url-file", env = "SOLBOT_RPC_URL_FILE", value_name = "FILE", conflicts_with = "rpc_url")]
+    pub rpc_url_file: Option<PathBuf>,
+
+    /// Expected cluster: picks the default RPC URL and checks the RPC's genesis hash at startup
+    #[arg(long = "cluster", env = "SOLBOT_CLUSTER", value_enum)]
+    pub cluster: Option<Cluster>,
 
-    /// RPC endpoint URL
-    #[arg(long = "rpc-url", default_value = "https://api.mainnet-beta.solana.com")]
-    pub rpc_url: String,
+    /// Token program that owns the accounts (override for forks or custom deployments)
+    #[arg(
+        long = "token-program",
+        env = "SOLBOT_TOKEN_PROGRAM",
+        value_name = "PUBKEY",
+        default_value = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
+    )]
+    pub token_program: String,
+
+    /// TOML config file: named notifier channels and alert escalation chains
+    #[arg(long = "config", env = "SOLBOT_CONFIG", value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Print the effective settings (flags, SOLBOT_* variables, defaults) without secrets and exit
+    #[arg(long = "print-config")]
+    pub print_config: bool,
+
+    /// Print the man page (roff) and exit, e.g. `solana-holder-bot --man > solana-holder-bot.1`
+    #[arg(long = "man", exclusive = true)]
+    pub man: bool,
 
     /// Polling interval in seconds
-    #[arg(long = "interval", default_value = "30")]
+    #[arg(long = "interval", env = "SOLBOT_INTERVAL", default_value = "30")]
     pub interval: u64,
 
+    /// Maximum number of mints fetched at the same time (polls are spread over the interval)
+    #[arg(long = "max-concurrent-fetches", env = "SOLBOT_MAX_CONCURRENT_FETCHES", default_value = "2", value_name = "N")]
+    pub max_concurrent_fetches: usize,
+
+    /// Fetch order when several mints are due: MINT=high|normal|low (repeatable)
+    #[arg(long = "priority", env = "SOLBOT_PRIORITY", value_name = "MINT=PRIORITY", value_delimiter = ',')]
+    pub priorities: Vec<String>,
+
+    /// Spread the polls of mints sharing an interval over its first SECONDS; 0 polls them together
+    /// [default: the whole interval]
+    #[arg(long = "stagger-window-secs", env = "SOLBOT_STAGGER_WINDOW_SECS", value_name = "SECONDS")]
+    pub stagger_window_secs: Option<u64>,
+
+    /// Count poll offsets from wall-clock multiples of the interval (:00, :30, ...) instead of the start
+    #[arg(long = "align-polls", env = "SOLBOT_ALIGN_POLLS")]
+    pub align_polls: bool,
+
+    /// Poll every mint every --launch-interval-secs for --launch-duration-mins after --launch-at,
+    /// then back off to the normal intervals (fresh token launches)
+    #[arg(long = "launch-mode", env = "SOLBOT_LAUNCH_MODE")]
+    pub launch_mode: bool,
+
+    /// Launch time: unix seconds or RFC 3339, e.g. 2024-05-01T14:00:00Z [default: startup]
+    #[arg(long = "launch-at", env = "SOLBOT_LAUNCH_AT", value_name = "TIME", value_parser = parse_launch_time, requires = "launch_mode")]
+    pub launch_at: Option<u64>,
+
+    /// Poll interval during the launch window
+    #[arg(long = "launch-interval-secs", env = "SOLBOT_LAUNCH_INTERVAL_SECS", default_value = "5", value_name = "SECONDS")]
+    pub launch_interval_secs: u64,
+
+    /// Length of the launch window
+    #[arg(long = "launch-duration-mins", env = "SOLBOT_LAUNCH_DURATION_MINS", default_value = "30", value_name = "MINUTES")]
+    pub launch_duration_mins: u64,
+
+    /// Poll once, print metrics and exit (for cron / one-shot usage)
+    #[arg(long = "once", env = "SOLBOT_ONCE")]
+    pub once: bool,
+
+    /// Backtest alert rules: replay recorded observations (JSONL) instead of polling RPC
+    #[arg(long = "replay", env = "SOLBOT_REPLAY", value_name = "FILE")]
+    pub replay: Option<PathBuf>,
+
+    /// Replay speed multiplier (e.g. 60 = one recorded minute per second); 0 = no delay
+    #[arg(long = "replay-speed", env = "SOLBOT_REPLAY_SPEED", default_value = "0", requires = "replay")]
+    pub replay_speed: f64,
+
+    /// Per-cycle console output format
+    #[arg(long = "output", env = "SOLBOT_OUTPUT", value_enum, default_value = "plain")]
+    pub output: OutputFormat,
+
+    /// Disable colored output (also honored via NO_COLOR env)
+    #[arg(long = "no-color", env = "SOLBOT_NO_COLOR")]
+    pub no_color: bool,
+
+    /// Timezone for displayed times (IANA name, e.g. Europe/Berlin); default UTC
+    #[arg(long = "timezone", env = "SOLBOT_TIMEZONE", value_name = "TZ", value_parser = parse_timezone)]
+    pub timezone: Option<chrono_tz::Tz>,
+
+    /// Number format for the status line and notifications (thousands/decimal separators)
+    #[arg(long = "number-format", env = "SOLBOT_NUMBER_FORMAT", value_enum, default_value = "raw")]
+    pub number_format: NumberLocale,
+
+    /// Language of alert and notification texts
+    #[arg(long = "language", env = "SOLBOT_LANGUAGE", value_enum, default_value = "en")]
+    pub language: Language,
+
+    /// Quiet mode: only alerts and warnings, no per-cycle status lines
+    #[arg(short = 'q', long = "quiet", env = "SOLBOT_QUIET", conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Increase log verbosity (-v debug, -vv trace); overrides RUST_LOG
+    #[arg(short = 'v', long = "verbose", action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
     /// Enable JSON logging output
-    #[arg(long = "json-log")]
+    #[arg(long = "json-log", env = "SOLBOT_JSON_LOG")]
     pub json_log: bool,
 
+    /// Also write logs to this file (in addition to stdout)
+    #[arg(long = "log-file", env = "SOLBOT_LOG_FILE", value_name = "PATH")]
+    pub log_file: Option<PathBuf>,
+
+    /// Log file rotation policy
+    #[arg(long = "log-rotation", env = "SOLBOT_LOG_ROTATION", value_enum, default_value = "daily")]
+    pub log_rotation: LogRotation,
+
+    /// Maximum log file size in MB before rotation (with --log-rotation size)
+    #[arg(long = "log-max-size-mb", env = "SOLBOT_LOG_MAX_SIZE_MB", default_value = "100")]
+    pub log_max_size_mb: u64,
+
+    /// Number of rotated log files to keep
+    #[arg(long = "log-max-files", env = "SOLBOT_LOG_MAX_FILES", default_value = "7")]
+    pub log_max_files: usize,
+
     /// Maximum number of RPC retries
-    #[arg(long = "max-retries", default_value = "3")]
+    #[arg(long = "max-retries", env = "SOLBOT_MAX_RETRIES", default_value = "3")]
     pub max_retries: u32,
 
+    /// Retries allowed per --retry-budget-window across monitoring, API refresh and API requests;
+    /// alerts once per window when exhausted [default: unlimited]
+    #[arg(long = "retry-budget", env = "SOLBOT_RETRY_BUDGET", value_name = "N")]
+    pub retry_budget: Option<u32>,
+
+    /// Window of --retry-budget in seconds
+    #[arg(
+        long = "retry-budget-window",
+        env = "SOLBOT_RETRY_BUDGET_WINDOW",
+        default_value = "60",
+        value_name = "SECONDS",
+        requires = "retry_budget"
+    )]
+    pub retry_budget_window: u64,
+
     /// RPC request timeout in seconds
-    #[arg(long = "timeout", default_value = "30")]
+    #[arg(long = "timeout", env = "SOLBOT_TIMEOUT", default_value = "30")]
     pub timeout: u64,
 
+    /// Idle connections to the RPC kept open per host [default: unlimited]
+    #[arg(long = "rpc-pool-max-idle", env = "SOLBOT_RPC_POOL_MAX_IDLE", value_name = "N")]
+    pub rpc_pool_max_idle: Option<usize>,
+
+    /// TCP keepalive interval of RPC connections [default: off]
+    #[arg(long = "rpc-tcp-keepalive-secs", env = "SOLBOT_RPC_TCP_KEEPALIVE_SECS", value_name = "SECONDS")]
+    pub rpc_tcp_keepalive_secs: Option<u64>,
+
+    /// HTTP version of RPC connections
+    #[arg(long = "rpc-http-version", env = "SOLBOT_RPC_HTTP_VERSION", value_enum, default_value = "auto")]
+    pub rpc_http_version: HttpVersion,
+
+    /// Timeout of a single HTTP request to the RPC [default: 30]
+    #[arg(long = "rpc-request-timeout-secs", env = "SOLBOT_RPC_REQUEST_TIMEOUT_SECS", value_name = "SECONDS")]
+    pub rpc_request_timeout_secs: Option<u64>,
+
+    /// Log method, sizes and duration of every JSON-RPC call (target `rpc_wire`, debug level)
+    #[arg(long = "log-rpc-requests", env = "SOLBOT_LOG_RPC_REQUESTS")]
+    pub log_rpc_requests: bool,
+
+    /// User-Agent of RPC requests [default: solana-holder-bot/<version>]
+    #[arg(long = "rpc-user-agent", env = "SOLBOT_RPC_USER_AGENT", value_name = "UA")]
+    pub rpc_user_agent: Option<String>,
+
+    /// Don't check GitHub releases for a newer version at startup (the check only logs)
+    #[arg(long = "no-update-check", env = "SOLBOT_NO_UPDATE_CHECK")]
+    pub no_update_check: bool,
+
+    /// Compare fetched balances with getTokenSupply to catch responses the RPC truncated:
+    /// off | flag (keep the count, mark it) | error (fail and retry the fetch)
+    #[arg(long = "truncation-check", env = "SOLBOT_TRUNCATION_CHECK", value_enum, default_value = "flag")]
+    pub truncation_check: TruncationMode,
+
+    /// Share of the supply (percent) that may be missing before truncation is suspected
+    #[arg(long = "truncation-tolerance-percent", env = "SOLBOT_TRUNCATION_TOLERANCE_PERCENT", default_value = "1.0", value_name = "PERCENT")]
+    pub truncation_tolerance_percent: f64,
+
+    /// Count wallets whose token accounts all hold a zero balance as holders too ("wallets ever
+    /// touched"); they are reported separately as zero_balance_owners either way
+    #[arg(long = "include-zero-balance", env = "SOLBOT_INCLUDE_ZERO_BALANCE")]
+    pub include_zero_balance: bool,
+
     /// Enable API server
-    #[arg(long = "api")]
+    #[arg(long = "api", env = "SOLBOT_API")]
     pub api_server: bool,
 
     /// API server port
-    #[arg(long = "api-port", default_value = "56789")]
+    #[arg(long = "api-port", env = "SOLBOT_API_PORT", default_value = "56789")]
     pub api_port: u16,
 
+    /// API key for authenticated endpoints (POST /refresh/:mint); repeat for several keys
+    /// Unrestricted; per-team keys with limits go into `[api_keys.*]` of the config file
+    #[arg(long = "api-key", env = "SOLBOT_API_KEY", value_name = "KEY", value_delimiter = ',')]
+    pub api_keys: Vec<String>,
+
+    /// Read more API keys from a file, one per line
+    #[arg(long = "api-key-file", env = "SOLBOT_API_KEY_FILE", value_name = "FILE")]
+    pub api_key_file: Option<PathBuf>,
+
+    /// Reject API requests without a valid key (except /health)
+    #[arg(long = "require-api-key", env = "SOLBOT_REQUIRE_API_KEY")]
+    pub require_api_key: bool,
+
     /// Cache TTL in seconds for API
-    #[arg(long = "cache-ttl", default_value = "30")]
+    #[arg(long = "cache-ttl", env = "SOLBOT_CACHE_TTL", default_value = "30")]
     pub cache_ttl: u64,
+
+    /// Maximum number of tokens kept in the API cache
+    #[arg(long = "cache-max-tokens", env = "SOLBOT_CACHE_MAX_TOKENS", default_value = "2")]
+    pub cache_max_tokens: usize,
+
+    /// Cached tokens refreshed at the same time; the least recently refreshed go first
+    #[arg(long = "cache-refresh-concurrency", env = "SOLBOT_CACHE_REFRESH_CONCURRENCY", default_value = "2", value_name = "N")]
+    pub cache_refresh_concurrency: usize,
+
+    /// Directory for per-mint holder ledgers (first/last seen of every wallet)
+    #[arg(long = "ledger-dir", env = "SOLBOT_LEDGER_DIR", value_name = "DIR")]
+    pub ledger_dir: Option<PathBuf>,
+
+    /// Memory budget: above it, holder sets/ledger are dropped and only owner + amount
+    /// are fetched (count-only mode: no churn, ledger or quality report)
+    #[arg(long = "max-memory-mb", env = "SOLBOT_MAX_MEMORY_MB", value_name = "MB")]
+    pub max_memory_mb: Option<usize>,
+
+    /// Wallets sampled hourly for sybil heuristics (funder, creation slot); 0 disables
+    /// the holder quality report
+    #[arg(long = "quality-sample", env = "SOLBOT_QUALITY_SAMPLE", default_value = "0", value_name = "N")]
+    pub quality_sample: usize,
+
+    /// Kafka bootstrap servers for event publishing (requires `kafka` feature)
+    #[arg(long = "kafka-brokers", env = "SOLBOT_KAFKA_BROKERS", value_name = "HOST:PORT,...")]
+    pub kafka_brokers: Option<String>,
+
+    /// Kafka topic for holder events
+    #[arg(long = "kafka-topic", env = "SOLBOT_KAFKA_TOPIC", default_value = "solana-holder-events")]
+    pub kafka_topic: String,
+
+    /// NATS server URL for JetStream event publishing (requires `nats` feature)
+    #[arg(long = "nats-url", env = "SOLBOT_NATS_URL", value_name = "URL")]
+    pub nats_url: Option<String>,
+
+    /// NATS JetStream subject for holder events
+    #[arg(long = "nats-subject", env = "SOLBOT_NATS_SUBJECT", default_value = "solana.holders.events")]
+    pub nats_subject: String,
+
+    /// Upload a full holder snapshot of every mint to this S3 bucket on a schedule
+    /// (requires `s3` feature; credentials from AWS_ACCESS_KEY_ID / AWS_SECRET_ACCESS_KEY)
+    #[arg(long = "s3-bucket", env = "SOLBOT_S3_BUCKET", value_name = "BUCKET")]
+    pub s3_bucket: Option<String>,
+
+    /// Endpoint of an S3-compatible store (MinIO, Cloudflare R2, ...); AWS when unset
+    #[arg(long = "s3-endpoint", env = "SOLBOT_S3_ENDPOINT", value_name = "URL")]
+    pub s3_endpoint: Option<String>,
+
+    /// Bucket region
+    #[arg(long = "s3-region", env = "SOLBOT_S3_REGION", default_value = "us-east-1")]
+    pub s3_region: String,
+
+    /// Key prefix of the snapshots (`<prefix><mint>/<UTC time>.csv.gz`)
+    #[arg(long = "s3-prefix", env = "SOLBOT_S3_PREFIX", default_value = "holder-snapshots/")]
+    pub s3_prefix: String,
+
+    /// Hours between holder snapshots (the first is taken at startup)
+    #[arg(long = "snapshot-interval-hours", env = "SOLBOT_SNAPSHOT_INTERVAL_HOURS", default_value = "24", value_name = "HOURS")]
+    pub snapshot_interval_hours: u64,
+
+    /// Delete snapshots older than this many days; 0 keeps them forever
+    #[arg(long = "snapshot-retention-days", env = "SOLBOT_SNAPSHOT_RETENTION_DAYS", default_value = "90", value_name = "DAYS")]
+    pub snapshot_retention_days: u64,
+
+    /// POST a JSON event (location, holder count, SHA-256) here after every holder snapshot
+    #[arg(long = "snapshot-webhook-url", env = "SOLBOT_SNAPSHOT_WEBHOOK_URL", value_name = "URL", requires = "s3_bucket")]
+    pub snapshot_webhook_url: Option<String>,
+
+    /// Wallet watched on every monitored mint: balance in `GET /watch`, wallet_entered /
+    /// wallet_exited events (repeatable)
+    #[arg(long = "watch-wallet", env = "SOLBOT_WATCH_WALLETS", value_delimiter = ',', value_name = "WALLET")]
+    pub watch_wallets: Vec<String>,
+
+    /// Endpoint receiving wallet_entered / wallet_exited events of the --watch-wallet wallets
+    #[arg(long = "watch-webhook-url", env = "SOLBOT_WATCH_WEBHOOK_URL", value_name = "URL")]
+    pub watch_webhook_url: Option<String>,
+
+    /// Sign every holder snapshot with this keypair file (`<key>.sig` next to `<key>.sha256`)
+    #[arg(long = "snapshot-signing-keypair", env = "SOLBOT_SNAPSHOT_SIGNING_KEYPAIR", value_name = "FILE", requires = "s3_bucket")]
+    pub snapshot_signing_keypair: Option<PathBuf>,
+
+    /// Telegram bot token for alerts and the shutdown report (with --telegram-chat-id)
+    #[arg(long = "telegram-bot-token", env = "SOLBOT_TELEGRAM_BOT_TOKEN", value_name = "TOKEN", requires = "telegram_chat_id")]
+    pub telegram_bot_token: Option<String>,
+
+    /// Read the Telegram bot token from a file instead
+    #[arg(
+        long = "telegram-token-file",
+        env = "SOLBOT_TELEGRAM_TOKEN_FILE",
+        value_name = "FILE",
+        conflicts_with = "telegram_bot_token",
+        requires = "telegram_chat_id"
+    )]
+    pub telegram_token_file: Option<PathBuf>,
+
+    /// Telegram chat ID that receives notifications
+    #[arg(long = "telegram-chat-id", env = "SOLBOT_TELEGRAM_CHAT_ID", value_name = "ID")]
+    pub telegram_chat_id: Option<String>,
+
+    /// Discord webhook URL for alerts and the shutdown report
+    #[arg(long = "discord-webhook", env = "SOLBOT_DISCORD_WEBHOOK", value_name = "URL")]
+    pub discord_webhook: Option<String>,
+
+    /// Read the Discord webhook URL from a file instead
+    #[arg(long = "discord-webhook-file", env = "SOLBOT_DISCORD_WEBHOOK_FILE", value_name = "FILE", conflicts_with = "discord_webhook")]
+    pub discord_webhook_file: Option<PathBuf>,
+
+    /// Slack incoming webhook URL for alerts and the shutdown report
+    #[arg(long = "slack-webhook", env = "SOLBOT_SLACK_WEBHOOK", value_name = "URL")]
+    pub slack_webhook: Option<String>,
+
+    /// Read the Slack webhook URL from a file instead
+    #[arg(long = "slack-webhook-file", env = "SOLBOT_SLACK_WEBHOOK_FILE", value_name = "FILE", conflicts_with = "slack_webhook")]
+    pub slack_webhook_file: Option<PathBuf>,
+
+    /// Generic webhook: alerts and the shutdown report are POSTed as JSON
+    #[arg(long = "webhook-url", env = "SOLBOT_WEBHOOK_URL", value_name = "URL")]
+    pub webhook_url: Option<String>,
+
+    /// Read the generic webhook URL from a file instead
+    #[arg(long = "webhook-url-file", env = "SOLBOT_WEBHOOK_URL_FILE", value_name = "FILE", conflicts_with = "webhook_url")]
+    pub webhook_url_file: Option<PathBuf>,
+
+    /// Evaluate alert rules as usual but only log what would be sent to each notifier channel
+    #[arg(long = "dry-run-alerts", env = "SOLBOT_DRY_RUN_ALERTS")]
+    pub dry_run_alerts: bool,
+
+    /// Send a "still alive" heartbeat (holders, last poll age) every N seconds to the
+    /// notifier channels, or to --heartbeat-url if set
+    #[arg(long = "heartbeat-interval", env = "SOLBOT_HEARTBEAT_INTERVAL", value_name = "SECONDS")]
+    pub heartbeat_interval: Option<u64>,
+
+    /// Webhook that receives heartbeats instead of the notifier channels (e.g. a dead man's switch)
+    #[arg(long = "heartbeat-url", env = "SOLBOT_HEARTBEAT_URL", value_name = "URL", requires = "heartbeat_interval")]
+    pub heartbeat_url: Option<String>,
+
+    /// Announce when holders first cross these counts (e.g. 1000,10000)
+    #[arg(long = "milestones", env = "SOLBOT_MILESTONES", value_name = "N,...", value_delimiter = ',')]
+    pub milestones: Vec<usize>,
+
+    /// Also announce every multiple of N holders (e.g. 5000)
+    #[arg(long = "milestone-step", env = "SOLBOT_MILESTONE_STEP", value_name = "N")]
+    pub milestone_step: Option<usize>,
+
+    /// Alert when holders break the all-time high / low (history is persisted in --ledger-dir,
+    /// otherwise kept for the session)
+    #[arg(long = "ath-alerts", env = "SOLBOT_ATH_ALERTS")]
+    pub ath_alerts: bool,
+
+    /// Compare against the high / low of the last N days instead of all history
+    #[arg(long = "ath-lookback-days", env = "SOLBOT_ATH_LOOKBACK_DAYS", value_name = "DAYS", requires = "ath_alerts")]
+    pub ath_lookback_days: Option<u64>,
+
+    /// Alert when one delegate (approval) may transfer this share of the supply or more; 0 disables
+    #[arg(long = "delegation-alert-percent", env = "SOLBOT_DELEGATION_ALERT_PERCENT", default_value = "10.0", value_name = "PERCENT")]
+    pub delegation_alert_percent: f64,
+
+    /// With several mints: also report the community size, the holders of all of them with
+    /// wallets holding more than one counted once
+    #[arg(long = "community-size", env = "SOLBOT_COMMUNITY_SIZE")]
+    pub community_size: bool,
+
+    /// Before a drop alert, re-read the holders at finalized commitment and alert only if the
+    /// drop holds there (filters out forks and un-finalized state)
+    #[arg(long = "await-finality", env = "SOLBOT_AWAIT_FINALITY")]
+    pub await_finality: bool,
+
+    /// Alert when a mint has no successful poll for this many intervals; 0 disables
+    #[arg(long = "stall-factor", env = "SOLBOT_STALL_FACTOR", default_value = "5", value_name = "K")]
+    pub stall_factor: u32,
+
+    /// Exit with code 3 when polling stalls (lets systemd / Docker restart the bot)
+    #[arg(long = "exit-on-stall", env = "SOLBOT_EXIT_ON_STALL")]
+    pub exit_on_stall: bool,
+
+    /// Alert (operational) when the p95 account fetch duration of the RPC exceeds this many seconds
+    #[arg(long = "latency-slo-secs", env = "SOLBOT_LATENCY_SLO_SECS", value_name = "SECONDS")]
+    pub latency_slo_secs: Option<f64>,
+
+    /// Consecutive windows over the latency SLO before alerting
+    #[arg(long = "latency-slo-windows", env = "SOLBOT_LATENCY_SLO_WINDOWS", default_value = "3", value_name = "N", requires = "latency_slo_secs")]
+    pub latency_slo_windows: u32,
+
+    /// Length of a latency SLO window
+    #[arg(long = "latency-slo-window-secs", env = "SOLBOT_LATENCY_SLO_WINDOW_SECS", default_value = "300", value_name = "SECONDS", requires = "latency_slo_secs")]
+    pub latency_slo_window_secs: u64,
+
+    /// Send latency SLO alerts to these `[channels.<name>]` from --config instead of every channel
+    #[arg(long = "latency-slo-channel", env = "SOLBOT_LATENCY_SLO_CHANNEL", value_name = "NAME", value_delimiter = ',', requires = "latency_slo_secs")]
+    pub latency_slo_channels: Vec<String>,
+
+    /// Prometheus Pushgateway URL; final metrics are pushed on exit
+    #[arg(long = "pushgateway-url", env = "SOLBOT_PUSHGATEWAY_URL", value_name = "URL")]
+    pub pushgateway_url: Option<String>,
+
+    /// Job name used when pushing to the Pushgateway
+    #[arg(long = "pushgateway-job", env = "SOLBOT_PUSHGATEWAY_JOB", default_value = "solana_holder_bot")]
+    pub pushgateway_job: String,
+}
+
+/// Offline tools that run instead of the monitoring loop
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Split a reward pro-rata between the wallets of a holder snapshot and write a
+    /// `wallet,amount` CSV for a distribution script
+    Allocate(AllocateArgs),
+    /// Cohort retention from a holder ledger (requires --ledger-dir history)
+    Cohorts(CohortsArgs),
+    /// Print a shell completion script, e.g. `solana-holder-bot completions bash > /etc/bash_completion.d/solana-holder-bot`
+    Completions(CompletionsArgs),
+    /// Check the environment: settings and --config, DNS, RPC and getProgramAccounts support,
+    /// cluster, clock skew, the mints and the alert channel endpoints (nothing is sent)
+    Doctor(DoctorArgs),
+    /// Print Prometheus alerting rules (YAML) matching the alert flags given before the subcommand
+    GenAlertRules(GenAlertRulesArgs),
+    /// Interactive setup: asks for the RPC provider, mints, alert channels and thresholds and
+    /// writes a validated `SOLBOT_*` environment file
+    Init(InitArgs),
+    /// Build an airdrop Merkle tree (root + per-wallet proofs) from a holder snapshot
+    /// exported with GET /holders/:mint/export
+    Merkle(MerkleArgs),
+    /// Compare the holder count with Solscan / Birdeye and explain the difference
+    /// (uses --rpc-url and --token-program given before the subcommand)
+    Verify(VerifyArgs),
+    /// Send a synthetic alert to the notifier channels (flags before the subcommand and
+    /// --config) to check credentials and message formatting
+    TestAlert(TestAlertArgs),
+    /// Check a snapshot or airdrop file against its `.sha256` checksum and `.sig` signature
+    VerifySnapshot(VerifySnapshotArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct TestAlertArgs {
+    /// Only this channel: `telegram`, `discord`, `slack`, `webhook` or a `[channels.<name>]` from
+    /// the config (repeatable; all channels by default)
+    #[arg(long = "channel", value_name = "NAME")]
+    pub channels: Vec<String>,
+
+    /// Mint shown in the alert (its `symbols` entry is used if configured)
+    #[arg(long = "mint", value_name = "MINT_ADDRESS")]
+    pub mint: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct VerifySnapshotArgs {
+    /// Snapshot, allocation or Merkle file (the sidecars are looked up next to it)
+    #[arg(value_name = "FILE")]
+    pub file: PathBuf,
+
+    /// Require a signature by this public key
+    #[arg(long = "signer", value_name = "PUBKEY")]
+    pub signer: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct VerifyArgs {
+    /// Token mint address
+    #[arg(value_name = "MINT_ADDRESS")]
+    pub mint_address: String,
+
+    /// Solscan Pro API key
+    #[arg(long = "solscan-api-key", env = "SOLBOT_SOLSCAN_API_KEY", value_name = "KEY")]
+    pub solscan_api_key: Option<String>,
+
+    /// Birdeye API key
+    #[arg(long = "birdeye-api-key", env = "SOLBOT_BIRDEYE_API_KEY", value_name = "KEY")]
+    pub birdeye_api_key: Option<String>,
+
+    /// Print the report as JSON
+    #[arg(long = "json")]
+    pub json: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct AllocateArgs {
+    /// Holder snapshot: `owner,amount` CSV or JSON from the export endpoint (may be gzipped)
+    #[arg(long = "snapshot", value_name = "FILE")]
+    pub snapshot: PathBuf,
+
+    /// Reward to split, in raw token units
+    #[arg(long = "total", value_name = "RAW_AMOUNT")]
+    pub total: u64,
+
+    /// How fractional shares are rounded
+    #[arg(long = "rounding", value_enum, default_value = "largest-remainder")]
+    pub rounding: Rounding,
+
+    /// Most a single wallet receives (raw units); the excess goes to the other wallets
+    #[arg(long = "cap", value_name = "RAW_AMOUNT")]
+    pub cap: Option<u64>,
+
+    /// Only wallets holding at least this raw balance are eligible
+    #[arg(long = "min-balance", value_name = "RAW_AMOUNT", default_value = "1")]
+    pub min_balance: u64,
+
+    /// Drop wallets whose share is below this (raw units) and give it to the others
+    #[arg(long = "min-amount", value_name = "RAW_AMOUNT", default_value = "1")]
+    pub min_amount: u64,
+
+    /// Write the CSV to a file instead of stdout (with a `.sha256` checksum next to it)
+    #[arg(long = "output", short = 'o', value_name = "FILE")]
+    pub output: Option<PathBuf>,
+
+    /// Also write a `.sig` Ed25519 signature of the output with this keypair file
+    #[arg(long = "signing-keypair", value_name = "FILE", requires = "output")]
+    pub signing_keypair: Option<PathBuf>,
+}
+
+impl AllocateArgs {
+    pub fn rules(&self) -> AllocationRules {
+        AllocationRules::new(self.total)
+            .with_rounding(self.rounding)
+            .with_cap(self.cap)
+            .with_min_balance(self.min_balance)
+            .with_min_amount(self.min_amount)
+    }
+}
+
+#[derive(Args, Debug)]
+#[command(group(ArgGroup::new("allocation").required(true).args(["total", "fixed"])))]
+pub struct MerkleArgs {
+    /// Holder snapshot: `owner,amount` CSV or JSON from the export endpoint (may be gzipped)
+    #[arg(long = "snapshot", value_name = "FILE")]
+    pub snapshot: PathBuf,
+
+    /// Split this many raw token units pro-rata to the snapshot balances
+    #[arg(long = "total", value_name = "RAW_AMOUNT")]
+    pub total: Option<u64>,
+
+    /// Give every wallet this many raw token units
+    #[arg(long = "fixed", value_name = "RAW_AMOUNT")]
+    pub fixed: Option<u64>,
+
+    /// Leaf and node hashing of the claim program
+    #[arg(long = "layout", value_enum, default_value = "saber")]
+    pub layout: MerkleLayout,
+
+    /// Write the tree JSON to a file instead of stdout (with a `.sha256` checksum next to it)
+    #[arg(long = "output", short = 'o', value_name = "FILE")]
+    pub output: Option<PathBuf>,
+
+    /// Also write a `.sig` Ed25519 signature of the output with this keypair file
+    #[arg(long = "signing-keypair", value_name = "FILE", requires = "output")]
+    pub signing_keypair: Option<PathBuf>,
+}
+
+impl MerkleArgs {
+    pub fn allocation(&self) -> Allocation {
+        match (self.total, self.fixed) {
+            (Some(total), _) => Allocation::ProRata { total },
+            (None, Some(amount)) => Allocation::Fixed { amount },
+            (None, None) => unreachable!("clap requires --total or --fixed"),
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct CompletionsArgs {
+    /// bash, zsh, fish, elvish or powershell
+    #[arg(value_enum)]
+    pub shell: Shell,
+}
+
+/// Write the completion script for `shell` covering every flag and subcommand
+pub fn write_completions(shell: Shell, out: &mut dyn Write) {
+    let mut command = Cli::command();
+    let name = command.get_name().to_string();
+    clap_complete::generate(shell, &mut command, name, out);
+}
+
+/// `--man` output: the man page of the binary with every flag and subcommand
+pub fn write_man_page(out: &mut dyn Write) -> std::io::Result<()> {
+    clap_mangen::Man::new(Cli::command()).render(out)
+}
+
+#[derive(Args, Debug)]
+pub struct DoctorArgs {
+    /// Print the report as JSON
+    #[arg(long = "json")]
+    pub json: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct GenAlertRulesArgs {
+    /// Only match series with this `job` label
+    #[arg(long = "job", value_name = "NAME")]
+    pub job: Option<String>,
+
+    /// Write the rules to a file instead of stdout
+    #[arg(long = "output", short = 'o', value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct InitArgs {
+    /// Environment file to write
+    #[arg(long = "output", short = 'o', value_name = "FILE", default_value = "solana-holder-bot.env")]
+    pub output: PathBuf,
+
+    /// Overwrite the file if it exists
+    #[arg(long = "force")]
+    pub force: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct CohortsArgs {
+    /// Token mint address
+    #[arg(value_name = "MINT_ADDRESS")]
+    pub mint_address: String,
+
+    /// Directory with holder ledgers written by the monitor
+    #[arg(long = "ledger-dir", value_name = "DIR")]
+    pub ledger_dir: PathBuf,
+
+    /// Cohort period length in days
+    #[arg(long = "period-days", default_value = "7")]
+    pub period_days: u64,
+
+    /// Print cohorts as JSON
+    #[arg(long = "json")]
+    pub json: bool,
+}
+
+/// Options whose values `--print-config` never shows
+const SECRET_ARGS: &[&str] = &[
+    "api_keys",
+    "telegram_bot_token",
+    "discord_webhook",
+    "slack_webhook",
+    "webhook_url",
+];
+
+/// URL options shown with credentials masked (`redact_url`)
+const URL_ARGS: &[&str] = &["rpc_url", "heartbeat_url", "pushgateway_url", "nats_url"];
+
+/// `--print-config` output: one `name = value (source)` line per option
+pub fn render_effective_config(matches: &ArgMatches) -> String {
+    let command = Cli::command();
+    let mut out = String::new();
+    for arg in command.get_arguments() {
+        let id = arg.get_id().as_str();
+        if matches!(id, "help" | "version" | "print_config" | "man") {
+            continue;
+        }
+        let name = arg.get_long().unwrap_or(id);
+        let values: Vec<String> = matches
+            .get_raw(id)
+            .map(|values| values.map(|value| value.to_string_lossy().into_owned()).collect())
+            .unwrap_or_default();
+        let value = if values.is_empty() {
+            "-".to_string()
+        } else if SECRET_ARGS.contains(&id) {
+            "<redacted>".to_string()
+        } else if URL_ARGS.contains(&id) {
+            values.iter().map(|url| redact_url(url)).collect::<Vec<_>>().join(",")
+        } else {
+            values.join(",")
+        };
+        let source = match matches.value_source(id) {
+            Some(ValueSource::CommandLine) => "command line".to_string(),
+            Some(ValueSource::EnvVariable) => arg
+                .get_env()
+                .map_or("env".to_string(), |env| env.to_string_lossy().into_owned()),
+            Some(ValueSource::DefaultValue) => "default".to_string(),
+            _ => "unset".to_string(),
+        };
+        out.push_str(&format!("{} = {} ({})\n", name, value, source));
+    }
+    out
 }
 
 impl Cli {
-    /// Parse and validate mint address
-    pub fn parse_mint(&self) -> anyhow::Result<Pubkey> {
-        Pubkey::from_str(&self.mint_address)
-            .map_err(|e| anyhow::anyhow!("Invalid mint address '{}': {}", self.mint_address, e))
+    /// Parse arguments, keeping the raw matches for `--print-config`
+    pub fn parse_with_matches() -> (Self, ArgMatches) {
+        let matches = Self::command().get_matches();
+        match <Self as clap::FromArgMatches>::from_arg_matches(&matches) {
+            Ok(cli) => (cli, matches),
+            Err(e) => e.exit(),
+        }
+    }
+
+    /// Display settings from `--timezone`, `--number-format` and `--language`
+    pub fn locale(&self) -> Locale {
+        Locale {
+            timezone: self.timezone,
+            numbers: self.number_format,
+            language: self.language,
+        }
+    }
+
+    /// Parse and validate mint addresses (duplicates are dropped)
+    pub fn parse_mints(&self) -> anyhow::Result<Vec<Pubkey>> {
+        if self.mint_addresses.is_empty() {
+            return Err(anyhow::anyhow!("MINT_ADDRESS is required"));
+        }
+        let mut mints = Vec::with_capacity(self.mint_addresses.len());
+        for mint_address in &self.mint_addresses {
+            let mint = parse_pubkey(mint_address)?;
+            if !mints.contains(&mint) {
+                mints.push(mint);
+            }
+        }
+        Ok(mints)
+    }
+
+    /// Placement of the mints' polls in the interval
+    pub fn stagger(&self) -> Stagger {
+        Stagger {
+            window: self.stagger_window_secs.map(Duration::from_secs),
+            align: self.align_polls,
+        }
+    }
+
+    /// Parse `--priority MINT=PRIORITY` entries; mints without one are `normal`
+    pub fn parse_priorities(&self) -> anyhow::Result<HashMap<Pubkey, Priority>> {
+        let mut priorities = HashMap::new();
+        for spec in &self.priorities {
+            let (mint, priority) = spec
+                .split_once('=')
+                .ok_or_else(|| anyhow::anyhow!("Invalid priority '{}': expected MINT=PRIORITY", spec))?;
+            let priority = Priority::from_str(priority, true)
+                .map_err(|_| anyhow::anyhow!("Invalid priority '{}': use high, normal or low", priority))?;
+            priorities.insert(parse_pubkey(mint)?, priority);
+        }
+        Ok(priorities)
+    }
+
+    /// RPC URL to use: `--rpc-url`, or the default endpoint of `--cluster` (mainnet if neither is set)
+    pub fn rpc_url(&self) -> String {
+        match &self.rpc_url {
+            Some(url) => url.clone(),
+            None => self.cluster.unwrap_or(Cluster::Mainnet).default_rpc_url().to_string(),
+        }
+    }
+
+    /// Replace `--*-file` options with the secrets they point to (e.g. Docker / Kubernetes secrets)
+    /// Files are read once at startup; surrounding whitespace is trimmed
+    pub fn load_secret_files(&mut self) -> anyhow::Result<()> {
+        let targets = [
+            (&self.rpc_url_file, &mut self.rpc_url),
+            (&self.telegram_token_file, &mut self.telegram_bot_token),
+            (&self.discord_webhook_file, &mut self.discord_webhook),
+            (&self.slack_webhook_file, &mut self.slack_webhook),
+            (&self.webhook_url_file, &mut self.webhook_url),
+        ];
+        for (path, target) in targets {
+            if let Some(path) = path {
+                *target = Some(read_secret_file(path)?);
+            }
+        }
+        if let Some(path) = &self.api_key_file {
+            let keys = read_secret_file(path)?;
+            self.api_keys.extend(
+                keys.lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(str::to_string),
+            );
+        }
+        Ok(())
+    }
+
+    /// Parse token program ID
+    pub fn parse_token_program(&self) -> anyhow::Result<Pubkey> {
+        Pubkey::from_str(&self.token_program).map_err(|e| {
+            anyhow::anyhow!("Invalid token program '{}': {}", self.token_program, e)
+        })
     }
 
     /// Validate CLI arguments
@@ -57,10 +831,143 @@ impl Cli {
         if self.interval == 0 {
             return Err(anyhow::anyhow!("Interval must be greater than 0"));
         }
+        if self.launch_interval_secs == 0 || self.launch_duration_mins == 0 {
+            return Err(anyhow::anyhow!("Launch interval and duration must be greater than 0"));
+        }
+        if self.stagger_window_secs.is_some_and(|window| window > self.interval) {
+            return Err(anyhow::anyhow!("Stagger window must not exceed the interval"));
+        }
         if self.max_retries == 0 {
             return Err(anyhow::anyhow!("Max retries must be greater than 0"));
         }
+        if self.log_rotation == LogRotation::Size && self.log_max_size_mb == 0 {
+            return Err(anyhow::anyhow!("Log max size must be greater than 0"));
+        }
+        if self.replay_speed.is_nan() || self.replay_speed < 0.0 {
+            return Err(anyhow::anyhow!("Replay speed must be 0 or greater"));
+        }
+        if self.heartbeat_interval == Some(0) {
+            return Err(anyhow::anyhow!("Heartbeat interval must be greater than 0"));
+        }
+        if self.retry_budget_window == 0 {
+            return Err(anyhow::anyhow!("Retry budget window must be greater than 0"));
+        }
+        if self.latency_slo_secs.is_some_and(|slo| slo.is_nan() || slo <= 0.0) {
+            return Err(anyhow::anyhow!("Latency SLO must be greater than 0"));
+        }
+        if self.rpc_request_timeout_secs == Some(0) {
+            return Err(anyhow::anyhow!("RPC request timeout must be greater than 0"));
+        }
+        if let Some(user_agent) = &self.rpc_user_agent {
+            if user_agent.trim().is_empty() || reqwest::header::HeaderValue::from_str(user_agent).is_err() {
+                return Err(anyhow::anyhow!("RPC User-Agent must be a non-empty printable header value"));
+            }
+        }
+        if self.max_concurrent_fetches == 0 {
+            return Err(anyhow::anyhow!("Max concurrent fetches must be greater than 0"));
+        }
+        if !(0.0..100.0).contains(&self.truncation_tolerance_percent) {
+            return Err(anyhow::anyhow!("Truncation tolerance must be between 0 and 100 percent"));
+        }
+        if self.cache_max_tokens == 0 {
+            return Err(anyhow::anyhow!("Cache max tokens must be greater than 0"));
+        }
+        if self.cache_refresh_concurrency == 0 {
+            return Err(anyhow::anyhow!("Cache refresh concurrency must be greater than 0"));
+        }
+        if self.telegram_chat_id.is_some() && self.telegram_bot_token.is_none() {
+            return Err(anyhow::anyhow!(
+                "--telegram-chat-id needs --telegram-bot-token or --telegram-token-file"
+            ));
+        }
         Ok(())
     }
 }
 
+fn read_secret_file(path: &std::path::Path) -> anyhow::Result<String> {
+    let value = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read secret file {}: {}", path.display(), e))?;
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(anyhow::anyhow!("Secret file {} is empty", path.display()));
+    }
+    Ok(value.to_string())
+}
+
+fn parse_pubkey(mint_address: &str) -> anyhow::Result<Pubkey> {
+    Pubkey::from_str(mint_address)
+        .map_err(|e| anyhow::anyhow!("Invalid mint address '{}': {}", mint_address, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_effective_config_hides_secrets() {
+        let matches = Cli::command()
+            .try_get_matches_from([
+                "solana-holder-bot",
+                "MINT",
+                "--rpc-url",
+                "https://rpc.example.com/?api-key=S3CRET",
+                "--interval",
+                "15",
+            ])
+            .unwrap();
+        let config = render_effective_config(&matches);
+        assert!(config.contains("interval = 15 (command line)"));
+        assert!(config.contains("rpc-url = https://rpc.example.com/?api-key=*** (command line)"));
+        assert!(config.contains("cache-ttl = 30 (default)"));
+        assert!(!config.contains("S3CRET"));
+        assert!(!config.contains("print-config"));
+    }
+
+    #[test]
+    fn test_load_secret_files() {
+        let dir = std::env::temp_dir().join(format!("holder-bot-secrets-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("token"), "123:ABC\n").unwrap();
+        std::fs::write(dir.join("keys"), "# team keys\nfirst\n\nsecond\n").unwrap();
+
+        let mut cli = Cli::try_parse_from([
+            "solana-holder-bot".to_string(),
+            "MINT".to_string(),
+            "--telegram-chat-id".to_string(),
+            "42".to_string(),
+            "--telegram-token-file".to_string(),
+            dir.join("token").display().to_string(),
+            "--api-key".to_string(),
+            "cli".to_string(),
+            "--api-key-file".to_string(),
+            dir.join("keys").display().to_string(),
+        ])
+        .unwrap();
+        cli.load_secret_files().unwrap();
+        assert_eq!(cli.telegram_bot_token.as_deref(), Some("123:ABC"));
+        assert_eq!(cli.api_keys, vec!["cli", "first", "second"]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_completions_and_man_page() {
+        // Neither needs a mint address
+        let cli = Cli::try_parse_from(["solana-holder-bot", "completions", "zsh"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Completions(CompletionsArgs { shell: Shell::Zsh }))));
+        assert!(Cli::try_parse_from(["solana-holder-bot", "--man"]).unwrap().man);
+        assert!(Cli::try_parse_from(["solana-holder-bot", "--man", "--interval", "5"]).is_err());
+
+        let mut script = Vec::new();
+        write_completions(Shell::Bash, &mut script);
+        let script = String::from_utf8(script).unwrap();
+        assert!(script.contains("--rpc-url"));
+        assert!(script.contains("verify-snapshot"));
+
+        let mut page = Vec::new();
+        write_man_page(&mut page).unwrap();
+        let page = String::from_utf8(page).unwrap();
+        assert!(page.contains(".TH solana-holder-bot 1"));
+        assert!(page.contains("rpc\\-url"));
+    }
+}
@@ -0,0 +1,79 @@
+use clap::ValueEnum;
+use solana_sdk::hash::Hash;
+
+/// Solana cluster the bot is expected to talk to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Cluster {
+    #[value(alias = "mainnet-beta")]
+    Mainnet,
+    Devnet,
+    Testnet,
+    /// Local test validator (genesis differs per instance, so it is not checked)
+    Localnet,
+}
+
+impl Cluster {
+    /// Public RPC endpoint used when `--rpc-url` is not given
+    pub fn default_rpc_url(self) -> &'static str {
+        match self {
+            Cluster::Mainnet => "https://api.mainnet-beta.solana.com",
+            Cluster::Devnet => "https://api.devnet.solana.com",
+            Cluster::Testnet => "https://api.testnet.solana.com",
+            Cluster::Localnet => "http://127.0.0.1:8899",
+        }
+    }
+
+    /// Genesis hash of the cluster (None for localnet)
+    pub fn genesis_hash(self) -> Option<&'static str> {
+        match self {
+            Cluster::Mainnet => Some("5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d"),
+            Cluster::Devnet => Some("EtWTRABZaYq6iMfeYKouRu166VU2xqa1wcaWoxPkrZBG"),
+            Cluster::Testnet => Some("4uhcVJyU9pJkvQyS88uRDiswHXSCkY3zQawwpjk2NsNY"),
+            Cluster::Localnet => None,
+        }
+    }
+
+    /// Identify a public cluster by genesis hash
+    pub fn from_genesis_hash(hash: &Hash) -> Option<Self> {
+        let hash = hash.to_string();
+        [Cluster::Mainnet, Cluster::Devnet, Cluster::Testnet]
+            .into_iter()
+            .find(|cluster| cluster.genesis_hash() == Some(hash.as_str()))
+    }
+
+    /// Check that an RPC's genesis hash belongs to this cluster
+    pub fn verify_genesis(self, hash: &Hash) -> anyhow::Result<()> {
+        match self.genesis_hash() {
+            Some(expected) if hash.to_string() != expected => {
+                let actual = Cluster::from_genesis_hash(hash)
+                    .map(|cluster| format!("{:?}", cluster).to_lowercase())
+                    .unwrap_or_else(|| "an unknown cluster".to_string());
+                Err(anyhow::anyhow!(
+                    "RPC serves {} (genesis {}), expected {:?} (genesis {})",
+                    actual,
+                    hash,
+                    self,
+                    expected
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_verify_genesis() {
+        let devnet = Hash::from_str(Cluster::Devnet.genesis_hash().unwrap()).unwrap();
+
+        assert!(Cluster::Devnet.verify_genesis(&devnet).is_ok());
+        assert!(Cluster::Localnet.verify_genesis(&devnet).is_ok());
+        let err = Cluster::Mainnet.verify_genesis(&devnet).unwrap_err();
+        assert!(err.to_string().contains("RPC serves devnet"));
+        assert_eq!(Cluster::from_genesis_hash(&devnet), Some(Cluster::Devnet));
+    }
+}
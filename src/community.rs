@@ -0,0 +1,147 @@
+//! Community size (`--community-size`): the union of the holders of every monitored mint, for
+//! projects that track several tokens (token, LP token, staked derivative) and want wallets
+//! holding more than one of them counted once
+
+use serde::Serialize;
+use solana_program::pubkey::Pubkey;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use crate::events::HolderEvent;
+use crate::output::ConsoleOutput;
+use crate::sinks::{Sink, SinkFuture};
+
+/// Wallets across a set of tokens
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct CommunitySize {
+    pub tokens: usize,
+    /// Wallets holding at least one of the tokens, counted once
+    pub wallets: usize,
+    /// Wallets holding two or more of the tokens
+    pub multi_token_wallets: usize,
+    /// Sum of the per-token holder counts
+    pub holders_total: usize,
+}
+
+impl CommunitySize {
+    pub fn compute<'a>(sets: impl IntoIterator<Item = &'a HashSet<Pubkey>>) -> Self {
+        let mut tokens_held: HashMap<&Pubkey, usize> = HashMap::new();
+        let mut size = Self::default();
+        for holders in sets {
+            size.tokens += 1;
+            size.holders_total += holders.len();
+            for wallet in holders {
+                *tokens_held.entry(wallet).or_default() += 1;
+            }
+        }
+        size.wallets = tokens_held.len();
+        size.multi_token_wallets = tokens_held.values().filter(|held| **held > 1).count();
+        size
+    }
+}
+
+/// Latest holder set of each mint
+type HolderSets = HashMap<Pubkey, Arc<HashSet<Pubkey>>>;
+
+/// Keeps the latest holder set of every monitored mint and prints the community size whenever
+/// it changes, once each mint has been polled
+pub struct CommunityTracker {
+    mints: usize,
+    console: ConsoleOutput,
+    latest: Mutex<(HolderSets, Option<CommunitySize>)>,
+}
+
+impl CommunityTracker {
+    pub fn new(mints: usize, console: ConsoleOutput) -> Self {
+        Self {
+            mints,
+            console,
+            latest: Mutex::default(),
+        }
+    }
+
+    /// Record a mint's holders; returns the community size when it changed
+    pub fn observe(&self, mint: Pubkey, holders: Arc<HashSet<Pubkey>>) -> Option<CommunitySize> {
+        let mut latest = self.latest.lock().expect("community lock poisoned");
+        let (sets, last) = &mut *latest;
+        sets.insert(mint, holders);
+        if sets.len() < self.mints {
+            return None;
+        }
+        let size = CommunitySize::compute(sets.values().map(|holders| holders.as_ref()));
+        if *last == Some(size) {
+            return None;
+        }
+        *last = Some(size);
+        Some(size)
+    }
+
+    /// Last computed community size
+    pub fn current(&self) -> Option<CommunitySize> {
+        self.latest.lock().expect("community lock poisoned").1
+    }
+}
+
+impl Sink for CommunityTracker {
+    fn name(&self) -> &str {
+        "community"
+    }
+
+    fn handle<'a>(&'a self, event: &'a HolderEvent) -> SinkFuture<'a> {
+        if let HolderEvent::Observation(observation) = event {
+            // Partial reads (largest accounts only) would shrink the union
+            let partial = observation.stats.degraded.is_some_and(|fallback| fallback.is_partial());
+            if !partial {
+                if let Some(size) = self.observe(observation.mint, observation.holders.clone()) {
+                    if !self.console.quiet {
+                        println!("{}", format_community(&size, &self.console));
+                    }
+                }
+            }
+        }
+        Box::pin(async { Ok(()) })
+    }
+}
+
+/// `Community: 15,980 unique wallets across 3 tokens | 1,254 hold 2+ | 16,234 holdings`
+pub fn format_community(size: &CommunitySize, console: &ConsoleOutput) -> String {
+    let locale = &console.locale;
+    format!(
+        "Community: {} unique wallets across {} tokens | {} hold 2+ | {} holdings",
+        locale.format_count(size.wallets),
+        size.tokens,
+        locale.format_count(size.multi_token_wallets),
+        locale.format_count(size.holders_total)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::OutputFormat;
+
+    #[test]
+    fn test_community_size() {
+        let (a, b, c) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let token: HashSet<Pubkey> = [a, b].into_iter().collect();
+        let lp: HashSet<Pubkey> = [b, c].into_iter().collect();
+        let staked: HashSet<Pubkey> = [b].into_iter().collect();
+        let size = CommunitySize::compute([&token, &lp, &staked]);
+        assert_eq!(size, CommunitySize { tokens: 3, wallets: 3, multi_token_wallets: 1, holders_total: 5 });
+    }
+
+    #[test]
+    fn test_tracker_waits_for_every_mint() {
+        let tracker = CommunityTracker::new(2, ConsoleOutput::new(OutputFormat::Plain, true, true));
+        let (token, lp) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let wallet = Pubkey::new_unique();
+        let holders = Arc::new([wallet].into_iter().collect::<HashSet<_>>());
+
+        assert_eq!(tracker.observe(token, holders.clone()), None);
+        let size = tracker.observe(lp, holders.clone()).unwrap();
+        assert_eq!((size.wallets, size.multi_token_wallets), (1, 1));
+        // Unchanged: nothing new to report
+        assert_eq!(tracker.observe(lp, holders), None);
+        assert_eq!(tracker.current(), Some(size));
+    }
+}
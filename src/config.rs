@@ -0,0 +1,215 @@
+//! Optional TOML config file (`--config`) for settings that don't fit on the command line
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::api_keys::ApiKeyConfig;
+use crate::discovery::DiscoveryConfig;
+use crate::notifier::{Channel, NotificationClass};
+use crate::protocols::ProtocolConfig;
+use crate::quiet_hours::QuietHours;
+use crate::scheduler::PollingConfig;
+use crate::sinks::SinkConfig;
+use crate::templates::TemplateConfig;
+use crate::tiers::{validate_tiers, TierConfig};
+use crate::token_monitor::AlertRule;
+
+/// One step of an escalation chain
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct EscalationStep {
+    /// Notify when the rule has fired this many polls in a row (1 = first occurrence)
+    pub after_cycles: u32,
+    /// Channel names: `telegram`, `discord`, `slack`, `webhook` (from CLI flags) or `[channels.*]` entries
+    pub channels: Vec<String>,
+}
+
+/// Channels per notification class; a class without an entry goes to every CLI channel
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RoutingConfig {
+    /// Holder growth / drop, milestones, ATH / ATL and their quiet-hours digests
+    #[serde(default)]
+    pub market: Option<Vec<String>>,
+    /// Problems with the bot or its RPC (failures, stalls, retry budget, latency SLO) and heartbeats
+    #[serde(default)]
+    pub operational: Option<Vec<String>>,
+}
+
+impl RoutingConfig {
+    pub fn channels(&self, class: NotificationClass) -> Option<&[String]> {
+        match class {
+            NotificationClass::Market => self.market.as_deref(),
+            NotificationClass::Operational => self.operational.as_deref(),
+        }
+    }
+}
+
+/// Config file contents
+///
+/// ```toml
+/// [channels.oncall]
+/// type = "pagerduty"
+/// routing_key = "..."
+///
+/// [[escalation.drop]]
+/// after_cycles = 1
+/// channels = ["slack"]
+///
+/// [[escalation.drop]]
+/// after_cycles = 3
+/// channels = ["oncall"]
+///
+/// [routing]
+/// operational = ["oncall"]
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Named notifier channels, only used where referenced (e.g. by escalation steps)
+    #[serde(default)]
+    pub channels: HashMap<String, Channel>,
+    /// Per-rule escalation chains; rules without one notify every CLI channel
+    #[serde(default)]
+    pub escalation: HashMap<AlertRule, Vec<EscalationStep>>,
+    /// Notification text templates
+    #[serde(default)]
+    pub templates: TemplateConfig,
+    /// Token symbols by mint, shown in notifications (`{{ symbol }}` in templates)
+    #[serde(default)]
+    pub symbols: HashMap<String, String>,
+    /// Times when non-critical alerts are collected into a digest instead of sent
+    #[serde(default)]
+    pub quiet_hours: Option<QuietHours>,
+    /// API keys of downstream teams with their rate limits and allowed mints
+    #[serde(default)]
+    pub api_keys: HashMap<String, ApiKeyConfig>,
+    /// Extra event destinations, e.g. external-process plugins
+    #[serde(default)]
+    pub sinks: HashMap<String, SinkConfig>,
+    /// Protocol vault authorities and receipt mints for `GET /holders/:mint/deposits`
+    #[serde(default)]
+    pub protocols: ProtocolConfig,
+    /// Market vs operational notifications to different channels
+    #[serde(default)]
+    pub routing: RoutingConfig,
+    /// Balance tiers per mint (`[[tiers.<MINT>]]`), reported as holder counts per tier
+    #[serde(default)]
+    pub tiers: HashMap<String, Vec<TierConfig>>,
+    /// Primary mints on a short poll interval, background mints on a long one
+    #[serde(default)]
+    pub polling: PollingConfig,
+    /// Fresh launches added to the monitored mints automatically
+    #[serde(default)]
+    pub discovery: Option<DiscoveryConfig>,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self> {
+        let data = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+        let mut config: Config = toml::from_str(&data)
+            .with_context(|| format!("Failed to parse config file {}", path.display()))?;
+        for steps in config.escalation.values_mut() {
+            steps.sort_by_key(|step| step.after_cycles);
+        }
+        Ok(config)
+    }
+
+    /// Check that escalation steps are well-formed and they and the routing only reference known
+    /// channels
+    pub fn validate(&self, cli_channels: &[&str]) -> Result<()> {
+        self.protocols.validate()?;
+        validate_tiers(&self.tiers)?;
+        self.polling.validate()?;
+        if let Some(discovery) = &self.discovery {
+            discovery.validate()?;
+        }
+        for name in self.channels.keys() {
+            if cli_channels.contains(&name.as_str()) {
+                return Err(anyhow::anyhow!(
+                    "Channel '{}' in config clashes with the channel configured by CLI flags",
+                    name
+                ));
+            }
+        }
+        for channel in self.channels.values() {
+            channel.check_supported()?;
+        }
+        for (rule, steps) in &self.escalation {
+            for step in steps {
+                if step.after_cycles == 0 {
+                    return Err(anyhow::anyhow!("Escalation for {:?}: after_cycles must be at least 1", rule));
+                }
+                for name in &step.channels {
+                    if !self.channels.contains_key(name) && !cli_channels.contains(&name.as_str()) {
+                        return Err(anyhow::anyhow!(
+                            "Escalation for {:?} uses unknown channel '{}'",
+                            rule,
+                            name
+                        ));
+                    }
+                }
+            }
+        }
+        for (class, names) in [
+            ("market", &self.routing.market),
+            ("operational", &self.routing.operational),
+        ] {
+            for name in names.iter().flatten() {
+                if !self.channels.contains_key(name) && !cli_channels.contains(&name.as_str()) {
+                    return Err(anyhow::anyhow!("Routing of {} notifications uses unknown channel '{}'", class, name));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+        [channels.oncall]
+        type = "pagerduty"
+        routing_key = "R0UT1NG"
+
+        [[escalation.drop]]
+        after_cycles = 3
+        channels = ["oncall"]
+
+        [[escalation.drop]]
+        after_cycles = 1
+        channels = ["slack"]
+    "#;
+
+    #[test]
+    fn test_parse_and_validate() {
+        let dir = std::env::temp_dir().join(format!("holder-bot-config-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.toml");
+        fs::write(&path, CONFIG).unwrap();
+
+        let config = Config::load(&path).unwrap();
+        let steps = &config.escalation[&AlertRule::Drop];
+        assert_eq!(steps[0].after_cycles, 1);
+        assert_eq!(steps[1].channels, vec!["oncall".to_string()]);
+        assert!(matches!(config.channels["oncall"], Channel::PagerDuty { .. }));
+
+        assert!(config.validate(&["slack"]).is_ok());
+        // "slack" is only known when set via --slack-webhook
+        assert!(config.validate(&[]).is_err());
+        assert!(config.validate(&["slack", "oncall"]).is_err());
+
+        let routed: Config = toml::from_str("[routing]\noperational = [\"pager\"]").unwrap();
+        assert_eq!(routed.routing.channels(NotificationClass::Operational), Some(&["pager".to_string()][..]));
+        assert_eq!(routed.routing.channels(NotificationClass::Market), None);
+        assert!(routed.validate(&[]).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
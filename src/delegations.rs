@@ -0,0 +1,221 @@
+//! Delegations and close authorities of the mint's token accounts: how many holder accounts
+//! approved a delegate, and how much of the supply one delegate could move. A single address
+//! able to transfer a large share of the supply is alerted on (`--delegation-alert-percent`)
+
+use serde::Serialize;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::account::Account;
+use std::collections::{HashMap, HashSet};
+
+use crate::i18n::fill;
+use crate::locale::Locale;
+use crate::token_monitor::{AlertRule, TokenAccountLayout};
+
+/// Delegates listed in the stats, largest first
+const MAX_LISTED_DELEGATES: usize = 10;
+
+/// What one delegate may transfer
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct DelegateShare {
+    pub delegate: String,
+    /// Holder accounts that approved it
+    pub accounts: usize,
+    /// Raw amount it may transfer (approved amounts capped at the balances)
+    pub amount: u64,
+    pub supply_percent: f64,
+}
+
+/// Approvals and close authorities over the holder accounts (accounts with a balance)
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct DelegationStats {
+    /// Holder accounts with an active approval
+    pub delegated_accounts: usize,
+    /// Raw amount all delegates together may transfer
+    pub delegated_amount: u64,
+    pub delegated_percent: f64,
+    /// Holder accounts whose close authority was handed to another address
+    pub close_authority_accounts: usize,
+    /// Largest delegates by amount
+    pub top_delegates: Vec<DelegateShare>,
+}
+
+fn percent_of(amount: u64, supply: u64) -> f64 {
+    if supply == 0 {
+        return 0.0;
+    }
+    (amount as f64 / supply as f64 * 10_000.0).round() / 100.0
+}
+
+/// Delegation stats of a snapshot; None when the accounts were fetched without the delegate
+/// fields (count-only mode) or rebuilt by a fallback that doesn't report them
+pub fn delegation_stats(accounts: &[(Pubkey, Account)]) -> Option<DelegationStats> {
+    let mut stats = DelegationStats::default();
+    let mut supply: u64 = 0;
+    let mut parsed = 0;
+    let mut delegates: HashMap<Pubkey, (usize, u64)> = HashMap::new();
+    for (_, account) in accounts {
+        let Some(layout) = TokenAccountLayout::from_data(&account.data) else {
+            continue;
+        };
+        parsed += 1;
+        let amount = layout.amount();
+        supply = supply.saturating_add(amount);
+        if amount == 0 {
+            continue;
+        }
+        if let Some(delegate) = layout.delegate() {
+            let delegated = layout.delegated_amount().min(amount);
+            if delegated > 0 {
+                stats.delegated_accounts += 1;
+                stats.delegated_amount = stats.delegated_amount.saturating_add(delegated);
+                let entry = delegates.entry(delegate).or_default();
+                entry.0 += 1;
+                entry.1 = entry.1.saturating_add(delegated);
+            }
+        }
+        if layout.close_authority().is_some_and(|authority| authority != layout.owner()) {
+            stats.close_authority_accounts += 1;
+        }
+    }
+    if parsed == 0 && !accounts.is_empty() {
+        return None;
+    }
+
+    stats.delegated_percent = percent_of(stats.delegated_amount, supply);
+    let mut top: Vec<(Pubkey, usize, u64)> =
+        delegates.into_iter().map(|(delegate, (accounts, amount))| (delegate, accounts, amount)).collect();
+    top.sort_by(|a, b| b.2.cmp(&a.2).then(a.0.cmp(&b.0)));
+    top.truncate(MAX_LISTED_DELEGATES);
+    stats.top_delegates = top
+        .into_iter()
+        .map(|(delegate, accounts, amount)| DelegateShare {
+            delegate: delegate.to_string(),
+            accounts,
+            amount,
+            supply_percent: percent_of(amount, supply),
+        })
+        .collect();
+    Some(stats)
+}
+
+/// Delegates at or above the alert threshold; each fires once until it drops below again
+#[derive(Debug, Default)]
+pub struct DelegationAlerts {
+    /// Share of the supply (percent); 0 disables the alert
+    threshold_percent: f64,
+    alerted: HashSet<String>,
+}
+
+impl DelegationAlerts {
+    pub fn new(threshold_percent: f64) -> Self {
+        Self { threshold_percent, alerted: HashSet::new() }
+    }
+
+    /// Delegates that reached the threshold since the previous poll
+    pub fn observe<'a>(&mut self, stats: &'a DelegationStats) -> Vec<&'a DelegateShare> {
+        if self.threshold_percent <= 0.0 {
+            return Vec::new();
+        }
+        let above: Vec<&DelegateShare> =
+            stats.top_delegates.iter().filter(|share| share.supply_percent >= self.threshold_percent).collect();
+        let newly: Vec<&DelegateShare> =
+            above.iter().copied().filter(|share| !self.alerted.contains(&share.delegate)).collect();
+        self.alerted = above.iter().map(|share| share.delegate.clone()).collect();
+        newly
+    }
+}
+
+/// Delegation alert with its message per delegate newly above the threshold
+pub fn check_delegations(
+    stats: &DelegationStats,
+    alerts: &mut DelegationAlerts,
+    locale: &Locale,
+) -> Vec<(AlertRule, String)> {
+    alerts
+        .observe(stats)
+        .into_iter()
+        .map(|share| {
+            let message = fill(
+                locale.messages().delegation_alert,
+                &[
+                    ("percent", &format!("{}%", locale.format_float(share.supply_percent, 1))),
+                    ("delegate", &share.delegate),
+                    ("accounts", &locale.format_count(share.accounts)),
+                ],
+            );
+            (AlertRule::Delegation, message)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::token_monitor::encode_token_account;
+
+    fn account(
+        owner: &Pubkey,
+        amount: u64,
+        delegate: Option<(&Pubkey, u64)>,
+        close_authority: Option<&Pubkey>,
+    ) -> (Pubkey, Account) {
+        let mut data = encode_token_account(&Pubkey::new_unique(), owner, amount);
+        if let Some((delegate, delegated)) = delegate {
+            data[72..76].copy_from_slice(&1u32.to_le_bytes());
+            data[76..108].copy_from_slice(delegate.as_ref());
+            data[121..129].copy_from_slice(&delegated.to_le_bytes());
+        }
+        if let Some(authority) = close_authority {
+            data[129..133].copy_from_slice(&1u32.to_le_bytes());
+            data[133..165].copy_from_slice(authority.as_ref());
+        }
+        (Pubkey::new_unique(), Account { data, ..Account::default() })
+    }
+
+    #[test]
+    fn test_delegation_stats() {
+        let (pool, bot, owner) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let accounts = vec![
+            account(&owner, 500, Some((&pool, 1_000)), None),
+            account(&Pubkey::new_unique(), 200, Some((&pool, 100)), Some(&bot)),
+            account(&Pubkey::new_unique(), 200, Some((&bot, 50)), None),
+            account(&owner, 100, None, Some(&owner)),
+            // Empty accounts don't count even with a leftover approval
+            account(&Pubkey::new_unique(), 0, Some((&bot, 10)), None),
+        ];
+
+        let stats = delegation_stats(&accounts).unwrap();
+        assert_eq!(stats.delegated_accounts, 3);
+        assert_eq!(stats.delegated_amount, 650);
+        assert_eq!(stats.delegated_percent, 65.0);
+        assert_eq!(stats.close_authority_accounts, 1);
+        assert_eq!(
+            stats.top_delegates[0],
+            DelegateShare { delegate: pool.to_string(), accounts: 2, amount: 600, supply_percent: 60.0 }
+        );
+        assert_eq!(stats.top_delegates[1].amount, 50);
+
+        let slim = vec![(Pubkey::new_unique(), Account { data: vec![0; 40], ..Account::default() })];
+        assert_eq!(delegation_stats(&slim), None);
+    }
+
+    #[test]
+    fn test_alert_fires_once_per_delegate() {
+        let share = |delegate: &str, supply_percent| DelegateShare {
+            delegate: delegate.to_string(),
+            accounts: 1,
+            amount: 0,
+            supply_percent,
+        };
+        let stats = |shares: Vec<DelegateShare>| DelegationStats { top_delegates: shares, ..DelegationStats::default() };
+        let mut alerts = DelegationAlerts::new(10.0);
+
+        assert_eq!(alerts.observe(&stats(vec![share("a", 12.0), share("b", 3.0)])).len(), 1);
+        assert!(alerts.observe(&stats(vec![share("a", 15.0)])).is_empty());
+        assert!(alerts.observe(&stats(vec![share("a", 5.0)])).is_empty());
+        assert_eq!(alerts.observe(&stats(vec![share("a", 11.0)]))[0].delegate, "a");
+
+        let mut disabled = DelegationAlerts::default();
+        assert!(disabled.observe(&stats(vec![share("a", 90.0)])).is_empty());
+    }
+}
@@ -0,0 +1,135 @@
+//! Provider diagnostics: chronic timeouts and unsupported methods found in the per-method RPC
+//! statistics, each with a machine-readable `suggested_action` so wrappers can prompt the user
+//! (logged once by the monitor, returned by `GET /diagnostics` and in API fetch errors)
+
+use serde::Serialize;
+
+use crate::rpc_stats::MethodStats;
+
+/// Calls of a method before its timeout rate is judged
+const MIN_CALLS: u64 = 5;
+/// Timeout share above which the endpoint is too slow for the method
+const SWITCH_PROVIDER_TIMEOUT_PERCENT: f64 = 50.0;
+/// Timeout share above which a longer timeout may be enough
+const INCREASE_TIMEOUT_PERCENT: f64 = 20.0;
+
+/// Providers known to serve getProgramAccounts and DAS for token programs
+pub const RECOMMENDED_PROVIDERS: &[&str] = &["Helius", "QuickNode", "Alchemy", "Triton"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosticCode {
+    /// A large share of a method's calls time out
+    ChronicTimeouts,
+    /// The RPC does not serve a method the bot relies on
+    MethodUnsupported,
+}
+
+impl DiagnosticCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ChronicTimeouts => "chronic_timeouts",
+            Self::MethodUnsupported => "method_unsupported",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SuggestedAction {
+    /// Use another RPC provider (see `providers`)
+    SwitchProvider,
+    /// Raise `--timeout` / `--rpc-request-timeout-secs`
+    IncreaseTimeout,
+}
+
+impl SuggestedAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::SwitchProvider => "switch_provider",
+            Self::IncreaseTimeout => "increase_timeout",
+        }
+    }
+}
+
+/// One problem with the RPC endpoint
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Diagnostic {
+    pub code: DiagnosticCode,
+    pub method: String,
+    pub message: String,
+    pub suggested_action: SuggestedAction,
+    /// Suggested alternatives with `switch_provider`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub providers: Vec<&'static str>,
+}
+
+impl Diagnostic {
+    /// Identity used to report each problem once
+    pub fn key(&self) -> String {
+        format!("{}:{}", self.code.as_str(), self.method)
+    }
+}
+
+/// Problems visible in the statistics, unsupported methods first
+pub fn diagnose(stats: &[MethodStats]) -> Vec<Diagnostic> {
+    let unsupported = stats.iter().filter(|m| m.unsupported).map(|m| Diagnostic {
+        code: DiagnosticCode::MethodUnsupported,
+        method: m.method.clone(),
+        message: format!("The RPC does not support {} for this bot's queries", m.method),
+        suggested_action: SuggestedAction::SwitchProvider,
+        providers: RECOMMENDED_PROVIDERS.to_vec(),
+    });
+    let timeouts = stats
+        .iter()
+        .filter(|m| !m.unsupported && m.calls >= MIN_CALLS && m.timeout_rate_percent >= INCREASE_TIMEOUT_PERCENT)
+        .map(|m| {
+            let switch = m.timeout_rate_percent >= SWITCH_PROVIDER_TIMEOUT_PERCENT;
+            Diagnostic {
+                code: DiagnosticCode::ChronicTimeouts,
+                method: m.method.clone(),
+                message: format!(
+                    "{}% of {} calls ({} of {}) timed out",
+                    m.timeout_rate_percent, m.method, m.timeouts, m.calls
+                ),
+                suggested_action: if switch { SuggestedAction::SwitchProvider } else { SuggestedAction::IncreaseTimeout },
+                providers: if switch { RECOMMENDED_PROVIDERS.to_vec() } else { Vec::new() },
+            }
+        });
+    unsupported.chain(timeouts).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc_stats::{CallOutcome, RpcStats};
+    use std::time::Duration;
+
+    #[test]
+    fn test_diagnose() {
+        let stats = RpcStats::default();
+        for i in 0..10 {
+            let outcome = if i < 6 { CallOutcome::TimedOut } else { CallOutcome::Ok };
+            stats.record("getProgramAccounts", Duration::from_secs(1), outcome);
+            let outcome = if i < 3 { CallOutcome::TimedOut } else { CallOutcome::Ok };
+            stats.record("getTokenSupply", Duration::from_secs(1), outcome);
+        }
+        stats.record("getTokenAccounts", Duration::from_secs(1), CallOutcome::Unsupported);
+        stats.record("getSlot", Duration::from_secs(1), CallOutcome::TimedOut);
+
+        let diagnostics = diagnose(&stats.snapshot());
+        assert_eq!(diagnostics.len(), 3);
+        assert_eq!(diagnostics[0].code, DiagnosticCode::MethodUnsupported);
+        assert_eq!(diagnostics[0].method, "getTokenAccounts");
+        let gpa = diagnostics.iter().find(|d| d.method == "getProgramAccounts").unwrap();
+        assert_eq!(gpa.suggested_action, SuggestedAction::SwitchProvider);
+        let supply = diagnostics.iter().find(|d| d.method == "getTokenSupply").unwrap();
+        assert_eq!(supply.suggested_action, SuggestedAction::IncreaseTimeout);
+        // Too few calls to judge getSlot
+        assert!(diagnostics.iter().all(|d| d.method != "getSlot"));
+
+        let json = serde_json::to_value(supply).unwrap();
+        assert_eq!(json["suggested_action"], "increase_timeout");
+        assert!(json.get("providers").is_none());
+    }
+}
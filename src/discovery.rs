@@ -0,0 +1,274 @@
+//! Auto-discovery of fresh launches (`[discovery]` in the config file): pump.fun token creations and
+//! Raydium pool initializations seen over the RPC websocket (`logsSubscribe`) add their mint to the
+//! monitored set when it passes the filter
+
+use anyhow::{Context, Result};
+use futures::stream::{select_all, StreamExt};
+use serde::Deserialize;
+use solana_client::nonblocking::pubsub_client::PubsubClient;
+use solana_client::rpc_config::{RpcTransactionLogsConfig, RpcTransactionLogsFilter};
+use solana_program::pubkey::Pubkey;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::signature::Signature;
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+
+use crate::redact::redact_url;
+use crate::rpc_client::SolanaRpcClient;
+
+/// Quote sides of new pools, never tracked: wrapped SOL, USDC, USDT
+pub const QUOTE_MINTS: [&str; 3] = [
+    "So11111111111111111111111111111111111111112",
+    "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+    "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB",
+];
+
+const DEFAULT_MAX_MINTS: usize = 20;
+const RECONNECT_MIN: Duration = Duration::from_secs(5);
+const RECONNECT_MAX: Duration = Duration::from_secs(60);
+
+/// Where launches are watched
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiscoverySource {
+    /// pump.fun bonding-curve token creations
+    PumpFun,
+    /// Raydium AMM v4 pool initializations
+    Raydium,
+}
+
+impl DiscoverySource {
+    pub fn program_id(&self) -> &'static str {
+        match self {
+            Self::PumpFun => "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P",
+            Self::Raydium => "675kPX9MHTjS2zt1qfr1NYHXzeSNHhxBpM5ng1qB9Ppa",
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::PumpFun => "pump_fun",
+            Self::Raydium => "raydium",
+        }
+    }
+
+    /// Whether a transaction's logs are a launch (other instructions of the program are trades)
+    pub fn is_launch(&self, logs: &[String]) -> bool {
+        match self {
+            Self::PumpFun => logs.iter().any(|line| line == "Program log: Instruction: Create"),
+            Self::Raydium => logs.iter().any(|line| line.starts_with("Program log: initialize2")),
+        }
+    }
+}
+
+fn default_max_mints() -> usize {
+    DEFAULT_MAX_MINTS
+}
+
+/// `[discovery]` of the config file
+///
+/// ```toml
+/// [discovery]
+/// sources = ["pump_fun", "raydium"]
+/// mint_suffix = "pump"
+/// max_mints = 10
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DiscoveryConfig {
+    pub sources: Vec<DiscoverySource>,
+    /// Websocket endpoint [default: the RPC URL with ws:// / wss://]
+    pub ws_url: Option<String>,
+    /// Only mints whose address starts / ends with this (pump.fun mints end in `pump`)
+    pub mint_prefix: Option<String>,
+    pub mint_suffix: Option<String>,
+    /// Never tracked
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Discovered mints tracked at most; discovery stops at the cap
+    #[serde(default = "default_max_mints")]
+    pub max_mints: usize,
+}
+
+impl DiscoveryConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.sources.is_empty() {
+            return Err(anyhow::anyhow!("Discovery: sources must not be empty"));
+        }
+        if self.max_mints == 0 {
+            return Err(anyhow::anyhow!("Discovery: max_mints must be greater than 0"));
+        }
+        for mint in &self.exclude {
+            Pubkey::from_str(mint).map_err(|e| anyhow::anyhow!("Discovery: invalid excluded mint '{}': {}", mint, e))?;
+        }
+        Ok(())
+    }
+
+    /// `ws_url`, or the RPC URL with its scheme switched to websocket
+    pub fn websocket_url(&self, rpc_url: &str) -> String {
+        if let Some(url) = &self.ws_url {
+            return url.clone();
+        }
+        if let Some(rest) = rpc_url.strip_prefix("https://") {
+            format!("wss://{}", rest)
+        } else if let Some(rest) = rpc_url.strip_prefix("http://") {
+            format!("ws://{}", rest)
+        } else {
+            rpc_url.to_string()
+        }
+    }
+
+    /// Not a quote mint, not excluded, matching the prefix / suffix
+    pub fn accepts(&self, mint: &Pubkey) -> bool {
+        let address = mint.to_string();
+        !QUOTE_MINTS.contains(&address.as_str())
+            && !self.exclude.contains(&address)
+            && self.mint_prefix.as_deref().is_none_or(|prefix| address.starts_with(prefix))
+            && self.mint_suffix.as_deref().is_none_or(|suffix| address.ends_with(suffix))
+    }
+}
+
+/// A mint found by discovery
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveredMint {
+    pub mint: Pubkey,
+    pub source: DiscoverySource,
+    pub signature: String,
+}
+
+/// Watch the sources in the background; mints already in `known` are skipped. The channel closes
+/// once `max_mints` were discovered
+pub fn spawn_discovery(
+    config: DiscoveryConfig,
+    rpc_url: String,
+    rpc_client: Arc<SolanaRpcClient>,
+    known: Vec<Pubkey>,
+) -> mpsc::Receiver<DiscoveredMint> {
+    let (sender, receiver) = mpsc::channel(config.max_mints);
+    tokio::spawn(async move {
+        let ws_url = config.websocket_url(&rpc_url);
+        let mut seen: HashSet<Pubkey> = known.into_iter().collect();
+        let mut discovered = 0;
+        let mut backoff = RECONNECT_MIN;
+        while discovered < config.max_mints && !sender.is_closed() {
+            match watch(&config, &ws_url, &rpc_client, &sender, &mut seen, &mut discovered).await {
+                Ok(()) => backoff = RECONNECT_MIN,
+                Err(e) => warn!(
+                    "Discovery via {} failed: {:#}; reconnecting in {}s",
+                    redact_url(&ws_url),
+                    e,
+                    backoff.as_secs()
+                ),
+            }
+            if discovered < config.max_mints && !sender.is_closed() {
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(RECONNECT_MAX);
+            }
+        }
+        info!("Discovery stopped after {} new mint(s)", discovered);
+    });
+    receiver
+}
+
+/// One websocket session; Ok when the subscriptions end or the cap is reached
+async fn watch(
+    config: &DiscoveryConfig,
+    ws_url: &str,
+    rpc_client: &SolanaRpcClient,
+    sender: &mpsc::Sender<DiscoveredMint>,
+    seen: &mut HashSet<Pubkey>,
+    discovered: &mut usize,
+) -> Result<()> {
+    let client = PubsubClient::new(ws_url).await.context("Failed to connect")?;
+    let mut streams = Vec::new();
+    for source in &config.sources {
+        let filter = RpcTransactionLogsFilter::Mentions(vec![source.program_id().to_string()]);
+        let logs_config = RpcTransactionLogsConfig { commitment: Some(CommitmentConfig::confirmed()) };
+        let (stream, _unsubscribe) = client
+            .logs_subscribe(filter, logs_config)
+            .await
+            .with_context(|| format!("logsSubscribe for {} failed", source.as_str()))?;
+        let source = *source;
+        streams.push(stream.map(move |response| (source, response.value)).boxed());
+    }
+    let sources: Vec<&str> = config.sources.iter().map(|source| source.as_str()).collect();
+    info!("Discovering launches on {} via {}", sources.join(", "), redact_url(ws_url));
+
+    let mut logs = select_all(streams);
+    while let Some((source, response)) = logs.next().await {
+        if response.err.is_some() || !source.is_launch(&response.logs) {
+            continue;
+        }
+        let Ok(signature) = Signature::from_str(&response.signature) else {
+            continue;
+        };
+        let mints = match rpc_client.get_transaction_token_mints(&signature).await {
+            Ok(mints) => mints,
+            Err(e) => {
+                debug!("Discovery: skipping {}: {:#}", signature, e);
+                continue;
+            }
+        };
+        for mint in mints.into_iter().filter(|mint| config.accepts(mint)) {
+            if !seen.insert(mint) {
+                continue;
+            }
+            let found = DiscoveredMint { mint, source, signature: response.signature.clone() };
+            if sender.send(found).await.is_err() {
+                return Ok(());
+            }
+            *discovered += 1;
+            if *discovered >= config.max_mints {
+                return Ok(());
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> DiscoveryConfig {
+        toml::from_str("sources = [\"pump_fun\", \"raydium\"]\nmint_suffix = \"pump\"").unwrap()
+    }
+
+    #[test]
+    fn test_config_and_filter() {
+        let config = config();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.max_mints, DEFAULT_MAX_MINTS);
+        assert_eq!(config.websocket_url("https://rpc.example.com/?api-key=k"), "wss://rpc.example.com/?api-key=k");
+        assert_eq!(config.websocket_url("http://127.0.0.1:8899"), "ws://127.0.0.1:8899");
+
+        let pump = Pubkey::from_str("9BB6NFEcjBCtnNLFko2FqVQBq8HHM13kCyYcdQbgpump").unwrap();
+        assert!(config.accepts(&pump));
+        assert!(!config.accepts(&Pubkey::from_str(QUOTE_MINTS[0]).unwrap()));
+        assert!(!config.accepts(&Pubkey::new_unique()));
+        let excluded = DiscoveryConfig { exclude: vec![pump.to_string()], ..config };
+        assert!(!excluded.accepts(&pump));
+
+        let empty = DiscoveryConfig { sources: Vec::new(), ..excluded };
+        assert!(empty.validate().is_err());
+    }
+
+    #[test]
+    fn test_launch_logs() {
+        let logs = |lines: &[&str]| lines.iter().map(|line| line.to_string()).collect::<Vec<_>>();
+        let create = logs(&[
+            "Program 6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P invoke [1]",
+            "Program log: Instruction: Create",
+        ]);
+        let buy = logs(&["Program log: Instruction: Buy"]);
+        assert!(DiscoverySource::PumpFun.is_launch(&create));
+        assert!(!DiscoverySource::PumpFun.is_launch(&buy));
+        let init = logs(&["Program log: initialize2: InitializeInstruction2 { nonce: 254, open_time: 0 }"]);
+        assert!(DiscoverySource::Raydium.is_launch(&init));
+        assert!(!DiscoverySource::Raydium.is_launch(&create));
+    }
+}
@@ -0,0 +1,427 @@
+//! Environment diagnostics (`solana-holder-bot doctor`): settings and config file, DNS, RPC
+//! reachability and getProgramAccounts support, cluster, clock skew, the mints and the notifier
+//! endpoints, as one pass / warn / fail report. Nothing is sent to the alert channels
+
+use anyhow::Result;
+use serde::Serialize;
+use solana_sdk::account::Account;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::cli::Cli;
+use crate::cluster::Cluster;
+use crate::config::Config;
+use crate::extensions::MintInfo;
+use crate::http_tuning::{HttpTuning, DEFAULT_USER_AGENT};
+use crate::notifier::{Channel, Notifier};
+use crate::redact::redact_url;
+use crate::rpc_client::{ProgramAccountsSupport, SolanaRpcClient, TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID};
+
+/// Clock skew against the cluster's block time above which timestamps look off
+const CLOCK_SKEW_WARN_SECS: u64 = 15;
+/// Clock skew above which launch windows, quiet hours and history buckets go wrong
+const CLOCK_SKEW_FAIL_SECS: u64 = 120;
+/// Recent slots tried for a block time (the newest ones may not have one yet)
+const BLOCK_TIME_SLOTS: u64 = 5;
+const CHANNEL_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+impl CheckStatus {
+    fn icon(&self) -> &'static str {
+        match self {
+            Self::Pass => "✅",
+            Self::Warn => "⚠️ ",
+            Self::Fail => "❌",
+        }
+    }
+}
+
+/// One line of the report
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Check {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DoctorReport {
+    pub checks: Vec<Check>,
+}
+
+impl DoctorReport {
+    fn add(&mut self, name: impl Into<String>, status: CheckStatus, detail: impl Into<String>) {
+        self.checks.push(Check { name: name.into(), status, detail: detail.into() });
+    }
+
+    fn pass(&mut self, name: impl Into<String>, detail: impl Into<String>) {
+        self.add(name, CheckStatus::Pass, detail);
+    }
+
+    fn warn(&mut self, name: impl Into<String>, detail: impl Into<String>) {
+        self.add(name, CheckStatus::Warn, detail);
+    }
+
+    fn fail(&mut self, name: impl Into<String>, detail: impl Into<String>) {
+        self.add(name, CheckStatus::Fail, detail);
+    }
+
+    pub fn count(&self, status: CheckStatus) -> usize {
+        self.checks.iter().filter(|check| check.status == status).count()
+    }
+
+    pub fn check(&self, name: &str) -> Option<&Check> {
+        self.checks.iter().find(|check| check.name == name)
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for check in &self.checks {
+            out.push_str(&format!("{} {}: {}\n", check.status.icon(), check.name, check.detail));
+        }
+        out.push_str(&format!(
+            "\n{} passed, {} warning(s), {} failed\n",
+            self.count(CheckStatus::Pass),
+            self.count(CheckStatus::Warn),
+            self.count(CheckStatus::Fail)
+        ));
+        out
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Local clock minus the cluster's block time
+pub fn classify_skew(skew_secs: i64) -> CheckStatus {
+    match skew_secs.unsigned_abs() {
+        secs if secs > CLOCK_SKEW_FAIL_SECS => CheckStatus::Fail,
+        secs if secs > CLOCK_SKEW_WARN_SECS => CheckStatus::Warn,
+        _ => CheckStatus::Pass,
+    }
+}
+
+/// How a channel's endpoint is checked without sending a message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChannelProbe {
+    /// GET that must succeed: validates the credentials (Telegram `getMe`, Discord webhook info)
+    Verify(String),
+    /// HEAD where any HTTP answer counts: the endpoint is reachable
+    Reach(String),
+    /// Not HTTP (SMTP): only the host is resolved
+    ResolveOnly(String),
+}
+
+impl ChannelProbe {
+    pub fn of(channel: &Channel) -> Self {
+        match channel {
+            Channel::Telegram { bot_token, .. } => Self::Verify(format!("https://api.telegram.org/bot{}/getMe", bot_token)),
+            Channel::Discord { webhook_url } => Self::Verify(webhook_url.clone()),
+            Channel::Slack { webhook_url } => Self::Reach(webhook_url.clone()),
+            Channel::Webhook { url } => Self::Reach(url.clone()),
+            Channel::PagerDuty { .. } => Self::Reach("https://events.pagerduty.com/v2/enqueue".to_string()),
+            Channel::Email { smtp_url, .. } => Self::ResolveOnly(smtp_url.clone()),
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        match self {
+            Self::Verify(url) | Self::Reach(url) | Self::ResolveOnly(url) => url,
+        }
+    }
+}
+
+/// `(host, port)` of a URL for the DNS check; port 0 when the scheme has no default
+pub fn host_of(url: &str) -> Option<(String, u16)> {
+    let parsed = reqwest::Url::parse(url).ok()?;
+    let host = parsed.host_str()?.trim_start_matches('[').trim_end_matches(']').to_string();
+    Some((host, parsed.port_or_known_default().unwrap_or(0)))
+}
+
+/// Run every check; later checks are skipped when what they depend on failed
+pub async fn diagnose(cli: &mut Cli) -> DoctorReport {
+    let mut report = DoctorReport::default();
+
+    // Settings: the same validation as at startup, reported instead of aborting
+    match cli.load_secret_files().and_then(|_| cli.validate()) {
+        Ok(()) => report.pass("settings", "flags and SOLBOT_* variables are valid"),
+        Err(e) => report.fail("settings", format!("{:#}", e)),
+    }
+    let mints = if cli.mint_addresses.is_empty() {
+        report.warn("mints", "no MINT_ADDRESS given (SOLBOT_MINT_ADDRESS or before the subcommand): mint checks skipped");
+        Vec::new()
+    } else {
+        match cli.parse_mints() {
+            Ok(mints) => mints,
+            Err(e) => {
+                report.fail("mints", format!("{:#}", e));
+                Vec::new()
+            }
+        }
+    };
+    let token_program = match cli.parse_token_program() {
+        Ok(program) => Some(program),
+        Err(e) => {
+            report.fail("token program", format!("{:#}", e));
+            None
+        }
+    };
+
+    let mut channels: Vec<(String, Channel)> =
+        Notifier::cli_channels(cli).into_iter().map(|channel| (channel.name().to_string(), channel)).collect();
+    match &cli.config {
+        Some(path) => match Config::load(path).and_then(|config| Notifier::from_cli(cli, &config).map(|_| config)) {
+            Ok(config) => {
+                report.pass("config file", format!("{} is valid", path.display()));
+                let mut named: Vec<(String, Channel)> = config.channels.into_iter().collect();
+                named.sort_by(|a, b| a.0.cmp(&b.0));
+                channels.extend(named);
+            }
+            Err(e) => report.fail("config file", format!("{:#}", e)),
+        },
+        None => report.pass("config file", "none (--config not set)"),
+    }
+
+    // DNS of every endpoint, before the connections that depend on it
+    let rpc_url = cli.rpc_url();
+    let probes: Vec<(String, ChannelProbe)> =
+        channels.iter().map(|(name, channel)| (name.clone(), ChannelProbe::of(channel))).collect();
+    let hosts: BTreeMap<String, u16> = std::iter::once(rpc_url.as_str())
+        .chain(probes.iter().map(|(_, probe)| probe.url()))
+        .filter_map(host_of)
+        .collect();
+    let mut unresolved = BTreeSet::new();
+    for (host, port) in &hosts {
+        match tokio::net::lookup_host((host.as_str(), *port)).await {
+            Ok(mut addresses) => match addresses.next() {
+                Some(address) => report.pass(format!("dns {}", host), address.ip().to_string()),
+                None => {
+                    unresolved.insert(host.clone());
+                    report.fail(format!("dns {}", host), "no addresses");
+                }
+            },
+            Err(e) => {
+                unresolved.insert(host.clone());
+                report.fail(format!("dns {}", host), e.to_string());
+            }
+        }
+    }
+    let resolves = |url: &str| host_of(url).is_none_or(|(host, _)| !unresolved.contains(&host));
+
+    if let Some(token_program) = token_program.filter(|_| resolves(&rpc_url)) {
+        check_rpc(cli, &rpc_url, token_program, &mints, &mut report).await;
+    }
+
+    if probes.is_empty() {
+        report.warn("alert channels", "none configured: alerts are only logged");
+    }
+    for (name, probe) in probes.iter().filter(|(_, probe)| resolves(probe.url())) {
+        check_channel(name, probe, &mut report).await;
+    }
+    report
+}
+
+/// Reachability, getProgramAccounts, cluster, clock skew and the mint accounts
+async fn check_rpc(cli: &Cli, rpc_url: &str, token_program: Pubkey, mints: &[Pubkey], report: &mut DoctorReport) {
+    let rpc_client = match SolanaRpcClient::new(rpc_url.to_string(), cli.max_retries, cli.timeout)
+        .with_token_program(token_program)
+        .with_http_tuning(&HttpTuning::from_cli(cli))
+    {
+        Ok(client) => client,
+        Err(e) => return report.fail("rpc", format!("{:#}", e)),
+    };
+    let capabilities = match rpc_client.probe_capabilities().await {
+        Ok(capabilities) => capabilities,
+        Err(e) => return report.fail("rpc", format!("{} unreachable: {:#}", redact_url(rpc_url), e)),
+    };
+    report.pass("rpc", format!("{} answers in {}ms", redact_url(rpc_url), capabilities.latency_ms));
+    match &capabilities.program_accounts {
+        ProgramAccountsSupport::Supported => report.pass(
+            "getProgramAccounts",
+            format!("supported ({}ms)", capabilities.program_accounts_latency_ms.unwrap_or_default()),
+        ),
+        ProgramAccountsSupport::Rejected { .. } => report.warn(
+            "getProgramAccounts",
+            "rejected for the token program: degraded mode (DAS or the largest accounts only); use a private RPC for exact counts",
+        ),
+        ProgramAccountsSupport::TimedOut => report.fail(
+            "getProgramAccounts",
+            format!("no answer within {}s: use a faster RPC or raise --timeout", cli.timeout),
+        ),
+        ProgramAccountsSupport::Failed { error } => report.fail("getProgramAccounts", error.clone()),
+    }
+
+    match (rpc_client.get_genesis_hash().await, cli.cluster) {
+        (Ok(genesis), Some(cluster)) => match cluster.verify_genesis(&genesis) {
+            Ok(()) => report.pass("cluster", format!("{:?} as expected", cluster)),
+            Err(e) => report.fail("cluster", format!("{:#}", e)),
+        },
+        (Ok(genesis), None) => match Cluster::from_genesis_hash(&genesis) {
+            Some(cluster) => report.pass("cluster", format!("{:?}", cluster)),
+            None => report.warn("cluster", format!("unknown (genesis {})", genesis)),
+        },
+        (Err(e), _) => report.warn("cluster", format!("{:#}", e)),
+    }
+
+    match cluster_time(&rpc_client).await {
+        Some(block_time) => {
+            let skew = unix_now() as i64 - block_time as i64;
+            let detail = format!("local clock is {}s {} the cluster", skew.unsigned_abs(), if skew >= 0 { "ahead of" } else { "behind" });
+            match classify_skew(skew) {
+                CheckStatus::Pass => report.pass("clock", detail),
+                CheckStatus::Warn => report.warn("clock", detail),
+                CheckStatus::Fail => report.fail("clock", format!("{}: sync it with NTP (e.g. timedatectl set-ntp true)", detail)),
+            }
+        }
+        None => report.warn("clock", "no recent block time from the RPC"),
+    }
+
+    if mints.is_empty() {
+        return;
+    }
+    match rpc_client.get_multiple_accounts(mints, true).await {
+        Ok(accounts) => {
+            for (mint, account) in mints.iter().zip(accounts) {
+                check_mint(mint, account.as_ref(), &token_program, report);
+            }
+        }
+        Err(e) => report.fail("mints", format!("{:#}", e)),
+    }
+}
+
+/// Block time of the newest slot that has one
+async fn cluster_time(rpc_client: &SolanaRpcClient) -> Option<u64> {
+    let slot = rpc_client.get_slot().await.ok()?;
+    for slot in (slot.saturating_sub(BLOCK_TIME_SLOTS)..=slot).rev() {
+        if let Some(time) = rpc_client.get_block_time(slot).await {
+            return Some(time);
+        }
+    }
+    None
+}
+
+/// The mint exists on this cluster and belongs to `--token-program`
+pub fn check_mint(mint: &Pubkey, account: Option<&Account>, token_program: &Pubkey, report: &mut DoctorReport) {
+    let name = format!("mint {}", mint);
+    let Some(account) = account else {
+        return report.fail(name, "not found on this cluster (wrong --rpc-url / --cluster?)");
+    };
+    if account.owner != *token_program {
+        let other = if token_program.to_string() == TOKEN_PROGRAM_ID { TOKEN_2022_PROGRAM_ID } else { TOKEN_PROGRAM_ID };
+        if account.owner.to_string() == other {
+            return report.fail(name, format!("owned by {}: run with --token-program {}", other, other));
+        }
+        return report.fail(name, format!("owned by {}, not a token mint", account.owner));
+    }
+    match MintInfo::parse(&account.data) {
+        Some(info) => report.pass(name, format!("token mint, {} decimals", info.decimals)),
+        None => report.fail(name, "not a mint account"),
+    }
+}
+
+/// Reach a notifier endpoint without posting to it
+async fn check_channel(name: &str, probe: &ChannelProbe, report: &mut DoctorReport) {
+    let check = format!("channel {}", name);
+    let url = match probe {
+        ChannelProbe::ResolveOnly(_) => return report.pass(check, "host resolves (use test-alert to check delivery)"),
+        ChannelProbe::Verify(url) | ChannelProbe::Reach(url) => url,
+    };
+    let client = match reqwest::Client::builder().timeout(CHANNEL_TIMEOUT).user_agent(DEFAULT_USER_AGENT).build() {
+        Ok(client) => client,
+        Err(e) => return report.fail(check, e.to_string()),
+    };
+    let request = match probe {
+        ChannelProbe::Verify(_) => client.get(url),
+        _ => client.head(url),
+    };
+    // Errors carry the URL, which holds the bot token / webhook secret
+    let host = host_of(url).map(|(host, _)| host).unwrap_or_default();
+    match request.send().await {
+        Ok(response) => {
+            let status = response.status();
+            match probe {
+                ChannelProbe::Verify(_) if !status.is_success() => {
+                    report.fail(check, format!("{} answered HTTP {}: token or webhook rejected", host, status.as_u16()))
+                }
+                ChannelProbe::Verify(_) => report.pass(check, format!("{} accepts the credentials", host)),
+                _ => report.pass(check, format!("{} reachable (HTTP {})", host, status.as_u16())),
+            }
+        }
+        Err(e) if e.is_timeout() => report.fail(check, format!("{} timed out after {}s", host, CHANNEL_TIMEOUT.as_secs())),
+        Err(e) if e.is_connect() => report.fail(check, format!("cannot connect to {} (firewall or proxy?)", host)),
+        Err(_) => report.fail(check, format!("request to {} failed", host)),
+    }
+}
+
+/// `solana-holder-bot doctor`: print the report; an error when any check failed
+pub async fn run(mut cli: Cli, json: bool) -> Result<()> {
+    let report = diagnose(&mut cli).await;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print!("{}", report.render());
+    }
+    match report.count(CheckStatus::Fail) {
+        0 => Ok(()),
+        failed => Err(anyhow::anyhow!("{} check(s) failed", failed)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_probes_and_hosts() {
+        let telegram = Channel::Telegram { bot_token: "1:abc".to_string(), chat_id: "-1".to_string() };
+        assert_eq!(ChannelProbe::of(&telegram), ChannelProbe::Verify("https://api.telegram.org/bot1:abc/getMe".to_string()));
+        let slack = Channel::Slack { webhook_url: "https://hooks.slack.com/services/T/B/x".to_string() };
+        assert!(matches!(ChannelProbe::of(&slack), ChannelProbe::Reach(_)));
+        let email = Channel::Email {
+            smtp_url: "smtps://bot:pw@smtp.example.com:465".to_string(),
+            from: "bot@example.com".to_string(),
+            to: Vec::new(),
+        };
+        assert_eq!(host_of(ChannelProbe::of(&email).url()), Some(("smtp.example.com".to_string(), 465)));
+        assert_eq!(host_of("https://mainnet.helius-rpc.com/?api-key=k"), Some(("mainnet.helius-rpc.com".to_string(), 443)));
+        assert_eq!(host_of("not a url"), None);
+    }
+
+    #[test]
+    fn test_clock_skew() {
+        assert_eq!(classify_skew(3), CheckStatus::Pass);
+        assert_eq!(classify_skew(-30), CheckStatus::Warn);
+        assert_eq!(classify_skew(600), CheckStatus::Fail);
+    }
+
+    #[test]
+    fn test_mint_checks() {
+        let mint = Pubkey::new_unique();
+        let spl = Pubkey::from_str(TOKEN_PROGRAM_ID).unwrap();
+        let token_2022 = Pubkey::from_str(TOKEN_2022_PROGRAM_ID).unwrap();
+        let account = |owner: Pubkey| Account { data: vec![0; 82], owner, ..Account::default() };
+        let mut report = DoctorReport::default();
+
+        check_mint(&mint, Some(&account(spl)), &spl, &mut report);
+        check_mint(&mint, Some(&account(token_2022)), &spl, &mut report);
+        check_mint(&mint, None, &spl, &mut report);
+        let statuses: Vec<CheckStatus> = report.checks.iter().map(|check| check.status).collect();
+        assert_eq!(statuses, vec![CheckStatus::Pass, CheckStatus::Fail, CheckStatus::Fail]);
+        assert!(report.checks[1].detail.contains("--token-program TokenzQd"));
+
+        assert!(report.render().ends_with("1 passed, 0 warning(s), 2 failed\n"));
+    }
+}
@@ -0,0 +1,228 @@
+//! Internal event bus: the monitoring loop publishes each poll's result once and every consumer
+//! (console, API cache, notifiers, message brokers, `GET /events`) subscribes to it
+
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::error::{RecvError, TryRecvError};
+use tokio::sync::{broadcast, watch};
+use tracing::warn;
+
+use crate::churn::ChurnStats;
+use crate::metadata::CountMetadata;
+use crate::token_monitor::{AlertRule, HolderStats};
+use crate::watch::{WatchChange, WatchEvent};
+
+/// Events buffered for the slowest subscriber before it starts missing events
+pub const EVENT_BUS_CAPACITY: usize = 256;
+
+/// Result of one successful poll
+#[derive(Debug, Clone)]
+pub struct Observation {
+    pub mint: Pubkey,
+    pub stats: HolderStats,
+    pub previous_count: Option<usize>,
+    /// Time the poll took (fetch + extraction)
+    pub elapsed: Duration,
+    /// Churn over the last hour, once a previous holder set exists
+    pub churn: Option<ChurnStats>,
+    pub holders: Arc<HashSet<Pubkey>>,
+    /// How the count was produced
+    pub metadata: Arc<CountMetadata>,
+}
+
+/// Alert fired by a poll, published right after the poll's observation
+#[derive(Debug, Clone)]
+pub struct AlertEvent {
+    pub mint: Pubkey,
+    pub rule: AlertRule,
+    pub message: String,
+    pub stats: HolderStats,
+    pub previous_count: Option<usize>,
+    /// Consecutive polls the rule has fired, for escalation chains
+    pub streak: u32,
+}
+
+#[derive(Debug, Clone)]
+pub enum HolderEvent {
+    Observation(Observation),
+    Alert(AlertEvent),
+    /// Failed poll
+    Error {
+        mint: Pubkey,
+        message: String,
+        timestamp: u64,
+    },
+    /// A watched wallet entered or left the holders, or changed tier
+    Watch(WatchEvent),
+}
+
+impl HolderEvent {
+    /// Every value of [`Self::kind`]
+    pub const KINDS: [&'static str; 6] =
+        ["observation", "alert", "error", "wallet_entered", "wallet_exited", "wallet_tier_changed"];
+
+    /// Event type, as in the `type` field of published events
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Observation(_) => "observation",
+            Self::Alert(_) => "alert",
+            Self::Error { .. } => "error",
+            Self::Watch(watch) => match watch.change {
+                WatchChange::Entered => "wallet_entered",
+                WatchChange::Exited => "wallet_exited",
+                WatchChange::TierChanged => "wallet_tier_changed",
+            },
+        }
+    }
+
+    pub fn mint(&self) -> &Pubkey {
+        match self {
+            Self::Observation(observation) => &observation.mint,
+            Self::Alert(alert) => &alert.mint,
+            Self::Error { mint, .. } => mint,
+            Self::Watch(watch) => &watch.mint,
+        }
+    }
+}
+
+/// Broadcast channel of [`HolderEvent`]s; clones publish into the same channel
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<HolderEvent>,
+    closed: Arc<watch::Sender<bool>>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(EVENT_BUS_CAPACITY)
+    }
+}
+
+impl EventBus {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self {
+            sender,
+            closed: Arc::new(watch::Sender::new(false)),
+        }
+    }
+
+    /// Deliver `event` to every current subscriber; without subscribers it is dropped
+    pub fn publish(&self, event: HolderEvent) {
+        let _ = self.sender.send(event);
+    }
+
+    /// Receive every event published from now on; `name` identifies the subscriber in logs
+    pub fn subscribe(&self, name: &str) -> Subscription {
+        Subscription {
+            name: name.to_string(),
+            receiver: self.sender.subscribe(),
+            closed: self.closed.subscribe(),
+            closing: false,
+        }
+    }
+
+    pub fn subscriber_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+
+    /// End every subscription once it has received the events already published
+    pub fn close(&self) {
+        self.closed.send_replace(true);
+    }
+}
+
+/// One subscriber's view of the bus
+#[derive(Debug)]
+pub struct Subscription {
+    name: String,
+    receiver: broadcast::Receiver<HolderEvent>,
+    closed: watch::Receiver<bool>,
+    closing: bool,
+}
+
+impl Subscription {
+    /// Next event, or None once the bus is closed and drained
+    /// A subscriber that falls more than the bus capacity behind skips the oldest events
+    pub async fn next(&mut self) -> Option<HolderEvent> {
+        loop {
+            let result = if self.closing {
+                match self.receiver.try_recv() {
+                    Ok(event) => Ok(event),
+                    Err(TryRecvError::Lagged(missed)) => Err(RecvError::Lagged(missed)),
+                    Err(_) => return None,
+                }
+            } else {
+                tokio::select! {
+                    biased;
+                    result = self.receiver.recv() => result,
+                    _ = self.closed.wait_for(|closed| *closed) => {
+                        self.closing = true;
+                        continue;
+                    }
+                }
+            };
+            match result {
+                Ok(event) => return Some(event),
+                Err(RecvError::Lagged(missed)) => {
+                    warn!("Event subscriber '{}' fell behind and missed {} event(s)", self.name, missed);
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(mint: Pubkey, timestamp: u64) -> HolderEvent {
+        HolderEvent::Error {
+            mint,
+            message: "rpc error".to_string(),
+            timestamp,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_close_drains_published_events() {
+        let bus = EventBus::new(8);
+        let mint = Pubkey::new_unique();
+        let mut first = bus.subscribe("first");
+        let mut second = bus.subscribe("second");
+        assert_eq!(bus.subscriber_count(), 2);
+
+        bus.publish(error(mint, 1));
+        bus.publish(error(mint, 2));
+        bus.close();
+
+        for subscription in [&mut first, &mut second] {
+            for expected in [1, 2] {
+                match subscription.next().await {
+                    Some(HolderEvent::Error { timestamp, .. }) => assert_eq!(timestamp, expected),
+                    other => panic!("unexpected event: {:?}", other),
+                }
+            }
+            assert!(subscription.next().await.is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lagging_subscriber_skips_oldest() {
+        let bus = EventBus::new(2);
+        let mut subscription = bus.subscribe("slow");
+        for timestamp in 0..5 {
+            bus.publish(error(Pubkey::new_unique(), timestamp));
+        }
+        bus.close();
+
+        let mut received = Vec::new();
+        while let Some(HolderEvent::Error { timestamp, .. }) = subscription.next().await {
+            received.push(timestamp);
+        }
+        assert_eq!(received, vec![3, 4]);
+    }
+}
@@ -0,0 +1,302 @@
+//! Holder / balance list export for `GET /holders/:mint/export`: CSV or JSON rows are encoded a
+//! chunk at a time (optionally gzipped), so a download never holds the whole file in memory.
+//! Exported files are read back as snapshots by the `merkle` and `allocate` subcommands.
+//! Amounts are raw; when the mint account is known every row also carries its UI amount (whole
+//! tokens with accrued interest), which snapshot readers ignore.
+
+use anyhow::{Context, Result};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use solana_program::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::extensions::MintInfo;
+
+/// Holder rows encoded per body chunk
+pub const EXPORT_CHUNK_ROWS: usize = 1000;
+
+/// File format of the export
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// `owner,amount` (or `owner,amount,ui_amount`) with a header line
+    #[default]
+    Csv,
+    /// Array of `{"owner", "amount"}` objects (with `ui_amount` when known)
+    Json,
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Json => "application/json",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Json => "json",
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonRow {
+    owner: String,
+    amount: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ui_amount: Option<f64>,
+}
+
+/// Holders with their total raw balance, largest first (ties by address for a stable order)
+pub fn sorted_balances(balances: HashMap<Pubkey, u64>) -> Vec<(Pubkey, u64)> {
+    let mut rows: Vec<(Pubkey, u64)> = balances.into_iter().collect();
+    rows.sort_unstable_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    rows
+}
+
+/// Body chunks of an export: the header, `EXPORT_CHUNK_ROWS` rows at a time, then the footer
+pub struct ExportChunks {
+    rows: std::vec::IntoIter<(Pubkey, u64)>,
+    format: ExportFormat,
+    /// Mint and time the UI amounts are computed for
+    ui_amounts: Option<(MintInfo, i64)>,
+    gzip: Option<GzEncoder<Vec<u8>>>,
+    written: usize,
+    finished: bool,
+}
+
+impl ExportChunks {
+    pub fn new(rows: Vec<(Pubkey, u64)>, format: ExportFormat, gzip: bool) -> Self {
+        Self {
+            rows: rows.into_iter(),
+            format,
+            ui_amounts: None,
+            gzip: gzip.then(|| GzEncoder::new(Vec::new(), Compression::default())),
+            written: 0,
+            finished: false,
+        }
+    }
+
+    /// Add the UI amount of every row at `now` (unix seconds)
+    pub fn with_ui_amounts(mut self, mint: MintInfo, now: i64) -> Self {
+        self.ui_amounts = Some((mint, now));
+        self
+    }
+
+    /// Plain text of the next chunk; None once the footer was produced
+    fn next_plain(&mut self) -> Option<Vec<u8>> {
+        if self.finished {
+            return None;
+        }
+        let mut out = Vec::new();
+        if self.written == 0 {
+            match self.format {
+                ExportFormat::Csv if self.ui_amounts.is_some() => out.extend_from_slice(b"owner,amount,ui_amount\n"),
+                ExportFormat::Csv => out.extend_from_slice(b"owner,amount\n"),
+                ExportFormat::Json => out.push(b'['),
+            }
+        }
+        for (owner, amount) in self.rows.by_ref().take(EXPORT_CHUNK_ROWS) {
+            let ui_amount = self.ui_amounts.map(|(mint, now)| mint.ui_amount(amount, now));
+            match self.format {
+                ExportFormat::Csv => {
+                    let line = match ui_amount {
+                        Some(ui_amount) => format!("{},{},{}\n", owner, amount, ui_amount),
+                        None => format!("{},{}\n", owner, amount),
+                    };
+                    out.extend_from_slice(line.as_bytes());
+                }
+                ExportFormat::Json => {
+                    if self.written > 0 {
+                        out.push(b',');
+                    }
+                    let row = JsonRow { owner: owner.to_string(), amount, ui_amount };
+                    serde_json::to_writer(&mut out, &row).expect("row serializes");
+                }
+            }
+            self.written += 1;
+        }
+        if self.rows.as_slice().is_empty() {
+            if self.format == ExportFormat::Json {
+                out.push(b']');
+            }
+            self.finished = true;
+        }
+        Some(out)
+    }
+}
+
+impl Iterator for ExportChunks {
+    type Item = std::io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let plain = self.next_plain()?;
+        let Some(encoder) = &mut self.gzip else {
+            return Some(Ok(plain));
+        };
+        if let Err(e) = encoder.write_all(&plain) {
+            return Some(Err(e));
+        }
+        if !self.finished {
+            return Some(Ok(std::mem::take(encoder.get_mut())));
+        }
+        // Last chunk: the rest of the compressed stream and the gzip trailer
+        let encoder = self.gzip.take().expect("encoder present");
+        Some(encoder.finish())
+    }
+}
+
+/// Wallet balances of a snapshot written by `GET /holders/:mint/export` (CSV or JSON, optionally
+/// gzipped)
+pub fn load_snapshot(path: &Path) -> Result<HashMap<Pubkey, u64>> {
+    let raw = std::fs::read(path).with_context(|| format!("Failed to read snapshot {}", path.display()))?;
+    let text = if raw.starts_with(&[0x1f, 0x8b]) {
+        let mut text = String::new();
+        GzDecoder::new(&raw[..])
+            .read_to_string(&mut text)
+            .with_context(|| format!("Failed to decompress snapshot {}", path.display()))?;
+        text
+    } else {
+        String::from_utf8(raw).with_context(|| format!("Snapshot {} is not UTF-8", path.display()))?
+    };
+    parse_snapshot(&text).with_context(|| format!("Failed to parse snapshot {}", path.display()))
+}
+
+#[derive(Deserialize)]
+struct SnapshotRow {
+    owner: String,
+    amount: u64,
+}
+
+/// Parse `owner,amount` CSV or a JSON array of `{"owner", "amount"}`; repeated owners are summed
+/// and UI amounts are ignored
+pub fn parse_snapshot(text: &str) -> Result<HashMap<Pubkey, u64>> {
+    let rows: Vec<(String, u64)> = if text.trim_start().starts_with('[') {
+        serde_json::from_str::<Vec<SnapshotRow>>(text)?
+            .into_iter()
+            .map(|row| (row.owner, row.amount))
+            .collect()
+    } else {
+        let mut rows = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || (i == 0 && line.starts_with("owner")) {
+                continue;
+            }
+            let mut columns = line.split(',');
+            let (Some(owner), Some(amount)) = (columns.next(), columns.next()) else {
+                anyhow::bail!("Line {}: expected owner,amount", i + 1);
+            };
+            let amount = amount
+                .trim()
+                .parse()
+                .with_context(|| format!("Line {}: invalid amount '{}'", i + 1, amount))?;
+            rows.push((owner.trim().to_string(), amount));
+        }
+        rows
+    };
+
+    let mut balances: HashMap<Pubkey, u64> = HashMap::new();
+    for (owner, amount) in rows {
+        let owner = Pubkey::from_str(&owner).map_err(|e| anyhow::anyhow!("Invalid owner '{}': {}", owner, e))?;
+        let balance = balances.entry(owner).or_default();
+        *balance = balance.saturating_add(amount);
+    }
+    Ok(balances)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+
+    fn export(rows: Vec<(Pubkey, u64)>, format: ExportFormat, gzip: bool) -> Vec<u8> {
+        ExportChunks::new(rows, format, gzip)
+            .collect::<std::io::Result<Vec<_>>>()
+            .unwrap()
+            .concat()
+    }
+
+    fn rows(n: usize) -> Vec<(Pubkey, u64)> {
+        sorted_balances((0..n).map(|i| (Pubkey::new_unique(), i as u64 + 1)).collect())
+    }
+
+    #[test]
+    fn test_csv_chunks() {
+        let rows = rows(EXPORT_CHUNK_ROWS + 5);
+        let chunks: Vec<Vec<u8>> = ExportChunks::new(rows.clone(), ExportFormat::Csv, false)
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(chunks.len(), 2);
+
+        let csv = String::from_utf8(chunks.concat()).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), rows.len() + 1);
+        assert_eq!(lines[0], "owner,amount");
+        // Largest first
+        assert_eq!(lines[1], format!("{},{}", rows[0].0, EXPORT_CHUNK_ROWS + 5));
+    }
+
+    #[test]
+    fn test_json_and_gzip() {
+        let rows = rows(3);
+        let json: serde_json::Value = serde_json::from_slice(&export(rows.clone(), ExportFormat::Json, false)).unwrap();
+        assert_eq!(json.as_array().unwrap().len(), 3);
+        assert_eq!(json[0]["owner"], rows[0].0.to_string());
+        assert_eq!(json[0]["amount"], 3);
+
+        let empty: serde_json::Value = serde_json::from_slice(&export(Vec::new(), ExportFormat::Json, false)).unwrap();
+        assert_eq!(empty, serde_json::json!([]));
+
+        let gzipped = export(rows.clone(), ExportFormat::Csv, true);
+        let mut csv = String::new();
+        GzDecoder::new(&gzipped[..]).read_to_string(&mut csv).unwrap();
+        assert_eq!(csv.as_bytes(), export(rows, ExportFormat::Csv, false));
+    }
+
+    #[test]
+    fn test_parse_snapshot() {
+        let a = Pubkey::new_unique();
+        let b = Pubkey::new_unique();
+        let csv = format!("owner,amount\n{},10\n{},5\n{},1\n", a, b, a);
+        let balances = parse_snapshot(&csv).unwrap();
+        assert_eq!(balances[&a], 11);
+        assert_eq!(balances[&b], 5);
+
+        let json = format!(r#"[{{"owner":"{}","amount":7}}]"#, b);
+        assert_eq!(parse_snapshot(&json).unwrap()[&b], 7);
+
+        assert!(parse_snapshot("owner,amount\nnot-a-key,1\n").is_err());
+        assert!(parse_snapshot(&format!("owner,amount\n{}\n", a)).is_err());
+    }
+
+    #[test]
+    fn test_ui_amounts() {
+        let rows = rows(2);
+        let mint = MintInfo { decimals: 1, transfer_fee: None, interest: None };
+        let export = |format| {
+            ExportChunks::new(rows.clone(), format, false)
+                .with_ui_amounts(mint, 0)
+                .collect::<std::io::Result<Vec<_>>>()
+                .unwrap()
+                .concat()
+        };
+
+        let csv = String::from_utf8(export(ExportFormat::Csv)).unwrap();
+        assert_eq!(csv.lines().next(), Some("owner,amount,ui_amount"));
+        assert_eq!(csv.lines().nth(1), Some(format!("{},2,0.2", rows[0].0).as_str()));
+        assert_eq!(parse_snapshot(&csv).unwrap()[&rows[0].0], 2);
+
+        let json: serde_json::Value = serde_json::from_slice(&export(ExportFormat::Json)).unwrap();
+        assert_eq!(json[1]["ui_amount"], 0.1);
+        assert_eq!(parse_snapshot(&json.to_string()).unwrap()[&rows[1].0], 1);
+    }
+}
@@ -0,0 +1,202 @@
+//! Token-2022 extension TLVs: transfer fees withheld in token accounts and on the mint (part of
+//! the supply but of no holder's balance) and interest-bearing mints, whose UI amount grows with
+//! time while the raw amount stays the same
+
+use serde::Serialize;
+
+use crate::token_monitor::TOKEN_ACCOUNT_LEN;
+
+/// Byte after the 165-byte base layout telling a mint from a token account (Token-2022 accounts
+/// with extensions only; mints are padded up to it)
+pub const ACCOUNT_TYPE_OFFSET: usize = TOKEN_ACCOUNT_LEN;
+pub const ACCOUNT_TYPE_MINT: u8 = 1;
+pub const ACCOUNT_TYPE_ACCOUNT: u8 = 2;
+
+/// Offset of `decimals` in a mint account
+const MINT_DECIMALS_OFFSET: usize = 44;
+
+const TRANSFER_FEE_CONFIG: u16 = 1;
+const TRANSFER_FEE_AMOUNT: u16 = 2;
+const INTEREST_BEARING_CONFIG: u16 = 10;
+
+/// Year length the interest-bearing extension compounds over
+const SECONDS_PER_YEAR: f64 = 60.0 * 60.0 * 24.0 * 365.24;
+
+/// Extension entries (type, value) after the account type byte; stops at the first malformed entry
+fn tlv_entries(data: &[u8]) -> impl Iterator<Item = (u16, &[u8])> {
+    let mut rest = data.get(ACCOUNT_TYPE_OFFSET + 1..).unwrap_or_default();
+    std::iter::from_fn(move || {
+        let header = rest.get(..4)?;
+        let kind = u16::from_le_bytes([header[0], header[1]]);
+        let len = usize::from(u16::from_le_bytes([header[2], header[3]]));
+        // Uninitialized (zeroed) space at the end of the account
+        if kind == 0 && len == 0 {
+            return None;
+        }
+        let value = rest.get(4..4 + len)?;
+        rest = &rest[4 + len..];
+        Some((kind, value))
+    })
+}
+
+fn extension(data: &[u8], kind: u16) -> Option<&[u8]> {
+    tlv_entries(data).find(|(k, _)| *k == kind).map(|(_, value)| value)
+}
+
+fn u64_at(value: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(value.get(offset..offset + 8)?.try_into().ok()?))
+}
+
+fn i64_at(value: &[u8], offset: usize) -> Option<i64> {
+    Some(i64::from_le_bytes(value.get(offset..offset + 8)?.try_into().ok()?))
+}
+
+fn i16_at(value: &[u8], offset: usize) -> Option<i16> {
+    Some(i16::from_le_bytes(value.get(offset..offset + 2)?.try_into().ok()?))
+}
+
+/// Whether account data is a token account: the base layout, or a longer Token-2022 account
+/// marked as one (mints and multisigs are not)
+pub fn is_token_account(data: &[u8]) -> bool {
+    match data.len() {
+        TOKEN_ACCOUNT_LEN => true,
+        len if len > TOKEN_ACCOUNT_LEN => data[ACCOUNT_TYPE_OFFSET] == ACCOUNT_TYPE_ACCOUNT,
+        _ => false,
+    }
+}
+
+/// Transfer fees withheld in a token account (0 without the TransferFeeAmount extension)
+pub fn withheld_amount(data: &[u8]) -> u64 {
+    extension(data, TRANSFER_FEE_AMOUNT)
+        .and_then(|value| u64_at(value, 0))
+        .unwrap_or(0)
+}
+
+/// Current transfer fee of a mint and the fees harvested to it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct TransferFee {
+    pub basis_points: u16,
+    pub maximum_fee: u64,
+    /// Fees withdrawn from token accounts into the mint, not yet claimed
+    pub withheld_amount: u64,
+}
+
+/// Interest-bearing mint parameters (rates in basis points per year)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct InterestRate {
+    pub initialization_timestamp: i64,
+    pub pre_update_average_rate: i16,
+    pub last_update_timestamp: i64,
+    pub current_rate: i16,
+}
+
+impl InterestRate {
+    /// Accrued growth factor at `now` (UI amount = raw amount × factor / 10^decimals)
+    pub fn growth(&self, now: i64) -> f64 {
+        let exp = |rate: i16, seconds: i64| (f64::from(rate) * seconds as f64 / SECONDS_PER_YEAR / 10_000.0).exp();
+        exp(self.pre_update_average_rate, self.last_update_timestamp - self.initialization_timestamp)
+            * exp(self.current_rate, now - self.last_update_timestamp)
+    }
+}
+
+/// Mint fields that change how raw balances read
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct MintInfo {
+    pub decimals: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transfer_fee: Option<TransferFee>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interest: Option<InterestRate>,
+}
+
+impl MintInfo {
+    /// Parse mint account data (SPL Token or Token-2022); None if it is too short
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let decimals = *data.get(MINT_DECIMALS_OFFSET)?;
+        let is_extended = data.get(ACCOUNT_TYPE_OFFSET) == Some(&ACCOUNT_TYPE_MINT);
+        if !is_extended {
+            return Some(Self { decimals, transfer_fee: None, interest: None });
+        }
+        // TransferFeeConfig: two authorities, withheld amount, older and newer fee (epoch, max, bps)
+        let transfer_fee = extension(data, TRANSFER_FEE_CONFIG).and_then(|value| {
+            Some(TransferFee {
+                withheld_amount: u64_at(value, 64)?,
+                maximum_fee: u64_at(value, 98)?,
+                basis_points: u16::from_le_bytes(value.get(106..108)?.try_into().ok()?),
+            })
+        });
+        // InterestBearingConfig: rate authority, then timestamps and rates
+        let interest = extension(data, INTEREST_BEARING_CONFIG).and_then(|value| {
+            Some(InterestRate {
+                initialization_timestamp: i64_at(value, 32)?,
+                pre_update_average_rate: i16_at(value, 40)?,
+                last_update_timestamp: i64_at(value, 42)?,
+                current_rate: i16_at(value, 50)?,
+            })
+        });
+        Some(Self { decimals, transfer_fee, interest })
+    }
+
+    /// Raw amount in whole tokens, with interest accrued up to `now` (unix seconds)
+    pub fn ui_amount(&self, raw: u64, now: i64) -> f64 {
+        let growth = self.interest.map_or(1.0, |interest| interest.growth(now));
+        raw as f64 * growth / 10f64.powi(i32::from(self.decimals))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_extensions(base: &[u8], account_type: u8, extensions: &[(u16, Vec<u8>)]) -> Vec<u8> {
+        let mut data = base.to_vec();
+        data.resize(ACCOUNT_TYPE_OFFSET, 0);
+        data.push(account_type);
+        for (kind, value) in extensions {
+            data.extend_from_slice(&kind.to_le_bytes());
+            data.extend_from_slice(&(value.len() as u16).to_le_bytes());
+            data.extend_from_slice(value);
+        }
+        data
+    }
+
+    #[test]
+    fn test_token_account_extensions() {
+        let plain = vec![0u8; TOKEN_ACCOUNT_LEN];
+        assert!(is_token_account(&plain));
+        assert_eq!(withheld_amount(&plain), 0);
+
+        // ImmutableOwner (empty) before TransferFeeAmount
+        let account = with_extensions(&plain, ACCOUNT_TYPE_ACCOUNT, &[(7, Vec::new()), (2, 250u64.to_le_bytes().to_vec())]);
+        assert!(is_token_account(&account));
+        assert_eq!(withheld_amount(&account), 250);
+
+        let mint = with_extensions(&[0u8; 82], ACCOUNT_TYPE_MINT, &[]);
+        assert!(!is_token_account(&mint));
+        assert!(!is_token_account(&plain[..100]));
+    }
+
+    #[test]
+    fn test_mint_info() {
+        let mut base = vec![0u8; 82];
+        base[MINT_DECIMALS_OFFSET] = 6;
+        assert_eq!(MintInfo::parse(&base).unwrap(), MintInfo { decimals: 6, transfer_fee: None, interest: None });
+
+        let mut fee = vec![0u8; 108];
+        fee[64..72].copy_from_slice(&1_000u64.to_le_bytes());
+        fee[98..106].copy_from_slice(&5_000u64.to_le_bytes());
+        fee[106..108].copy_from_slice(&50u16.to_le_bytes());
+        let mut interest = vec![0u8; 52];
+        interest[50..52].copy_from_slice(&500i16.to_le_bytes());
+        let mint = MintInfo::parse(&with_extensions(&base, ACCOUNT_TYPE_MINT, &[(1, fee), (10, interest)])).unwrap();
+        assert_eq!(
+            mint.transfer_fee,
+            Some(TransferFee { basis_points: 50, maximum_fee: 5_000, withheld_amount: 1_000 })
+        );
+
+        // 5% a year, continuously compounded since timestamp 0
+        let year = SECONDS_PER_YEAR as i64;
+        assert!((mint.ui_amount(1_000_000, 0) - 1.0).abs() < 1e-9);
+        assert!((mint.ui_amount(1_000_000, year) - 0.05f64.exp()).abs() < 1e-6);
+    }
+}
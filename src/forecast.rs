@@ -0,0 +1,251 @@
+//! Holder growth forecast (`GET /holders/:mint/forecast`) over the hourly count history: a linear
+//! trend or Holt's double exponential smoothing, with 95% bounds. An estimate for planning
+//! milestone announcements, not a prediction of market behavior
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::history::CountBucket;
+
+/// History points needed before a forecast is made
+pub const MIN_POINTS: usize = 6;
+/// Longest horizon served
+pub const MAX_HORIZON_SECS: u64 = 30 * 86_400;
+
+const HOUR_SECS: u64 = 3600;
+/// z-score of the 95% bounds
+const Z_95: f64 = 1.96;
+/// Holt smoothing of the level and the trend
+const HOLT_ALPHA: f64 = 0.5;
+const HOLT_BETA: f64 = 0.3;
+
+pub const DISCLAIMER: &str =
+    "Estimate extrapolated from past holder counts; launches, listings and market moves are not foreseen";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ForecastMethod {
+    /// Least-squares line through the history
+    #[default]
+    Linear,
+    /// Double exponential smoothing: follows recent changes in the trend
+    Holt,
+}
+
+/// Parse a horizon like `24h`, `7d` or `90m` (plain numbers are hours)
+pub fn parse_horizon(value: &str) -> Result<u64> {
+    let value = value.trim();
+    let (number, unit) = match value.char_indices().last() {
+        Some((i, 'm')) => (&value[..i], 60),
+        Some((i, 'h')) => (&value[..i], HOUR_SECS),
+        Some((i, 'd')) => (&value[..i], 86_400),
+        _ => (value, HOUR_SECS),
+    };
+    let number: u64 = number.parse().map_err(|_| anyhow::anyhow!("Invalid horizon '{}': use e.g. 24h or 7d", value))?;
+    let secs = number.saturating_mul(unit);
+    if secs == 0 || secs > MAX_HORIZON_SECS {
+        return Err(anyhow::anyhow!("Horizon must be between 1m and {}d", MAX_HORIZON_SECS / 86_400));
+    }
+    Ok(secs)
+}
+
+/// Expected holders at one time with the 95% range
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ForecastPoint {
+    pub at: u64,
+    pub holders: f64,
+    pub lower: f64,
+    pub upper: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Forecast {
+    pub method: ForecastMethod,
+    /// Always true: the values are extrapolated, not observed
+    pub estimate: bool,
+    pub disclaimer: &'static str,
+    /// History points the model was fitted on
+    pub history_points: usize,
+    /// Last observed hour (start of the bucket)
+    pub last_observed_at: u64,
+    pub last_observed_holders: f64,
+    /// Fitted trend in holders per day
+    pub trend_per_day: f64,
+    /// Hourly points up to the horizon
+    pub points: Vec<ForecastPoint>,
+}
+
+impl Forecast {
+    /// First forecast point whose expected count reaches `target` (either direction)
+    pub fn reaches(&self, target: f64) -> Option<&ForecastPoint> {
+        let rising = target >= self.last_observed_holders;
+        self.points
+            .iter()
+            .find(|point| if rising { point.holders >= target } else { point.holders <= target })
+    }
+}
+
+/// Observations as (hours since the first point, holders): the midpoint of each hour's min / max
+fn series(buckets: &[CountBucket]) -> Vec<(f64, f64)> {
+    let Some(first) = buckets.first() else {
+        return Vec::new();
+    };
+    buckets
+        .iter()
+        .map(|bucket| {
+            let x = (bucket.start - first.start) as f64 / HOUR_SECS as f64;
+            (x, (bucket.min + bucket.max) as f64 / 2.0)
+        })
+        .collect()
+}
+
+/// Least-squares line: (intercept, slope per hour, residual std, mean x, sum of squared x deviations)
+fn fit_linear(points: &[(f64, f64)]) -> (f64, f64, f64, f64, f64) {
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let sxx: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    let sxy: f64 = points.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+    let slope = if sxx > 0.0 { sxy / sxx } else { 0.0 };
+    let intercept = mean_y - slope * mean_x;
+    let sse: f64 = points.iter().map(|(x, y)| (y - intercept - slope * x).powi(2)).sum();
+    let std = (sse / (n - 2.0).max(1.0)).sqrt();
+    (intercept, slope, std, mean_x, sxx)
+}
+
+/// Holt level / trend after the last point and the std of the one-step-ahead errors
+/// Gaps between buckets advance the level by the trend for every missing hour
+fn fit_holt(points: &[(f64, f64)]) -> (f64, f64, f64) {
+    let (mut level, mut trend) = (points[0].1, points[1].1 - points[0].1);
+    let mut errors = Vec::with_capacity(points.len());
+    for window in points.windows(2) {
+        let (previous_x, (x, y)) = (window[0].0, window[1]);
+        let dt = (x - previous_x).max(1.0);
+        let expected = level + trend * dt;
+        errors.push(y - expected);
+        let previous_level = level;
+        level = HOLT_ALPHA * y + (1.0 - HOLT_ALPHA) * expected;
+        trend = HOLT_BETA * (level - previous_level) / dt + (1.0 - HOLT_BETA) * trend;
+    }
+    let std = (errors.iter().map(|e| e * e).sum::<f64>() / errors.len() as f64).sqrt();
+    (level, trend, std)
+}
+
+fn round1(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+/// Forecast `horizon_secs` past the last bucket; None with fewer than `MIN_POINTS` buckets
+pub fn forecast(buckets: &[CountBucket], method: ForecastMethod, horizon_secs: u64) -> Option<Forecast> {
+    if buckets.len() < MIN_POINTS {
+        return None;
+    }
+    let points = series(buckets);
+    let last_x = points.last()?.0;
+    let last = buckets.last()?;
+    let steps = horizon_secs.div_ceil(HOUR_SECS);
+
+    let (expected, trend_per_hour): (Vec<(f64, f64)>, f64) = match method {
+        ForecastMethod::Linear => {
+            let (intercept, slope, std, mean_x, sxx) = fit_linear(&points);
+            let n = points.len() as f64;
+            let expected = (1..=steps)
+                .map(|step| {
+                    let x = last_x + step as f64;
+                    let spread = Z_95 * std * (1.0 + 1.0 / n + (x - mean_x).powi(2) / sxx.max(1.0)).sqrt();
+                    (intercept + slope * x, spread)
+                })
+                .collect();
+            (expected, slope)
+        }
+        ForecastMethod::Holt => {
+            let (level, trend, std) = fit_holt(&points);
+            let expected = (1..=steps)
+                .map(|step| {
+                    let h = step as f64;
+                    (level + trend * h, Z_95 * std * h.sqrt())
+                })
+                .collect();
+            (expected, trend)
+        }
+    };
+
+    Some(Forecast {
+        method,
+        estimate: true,
+        disclaimer: DISCLAIMER,
+        history_points: points.len(),
+        last_observed_at: last.start,
+        last_observed_holders: points.last()?.1,
+        trend_per_day: round1(trend_per_hour * 24.0),
+        points: expected
+            .into_iter()
+            .enumerate()
+            .map(|(i, (holders, spread))| ForecastPoint {
+                at: last.start + (i as u64 + 1) * HOUR_SECS,
+                holders: round1(holders.max(0.0)),
+                lower: round1((holders - spread).max(0.0)),
+                upper: round1(holders + spread),
+            })
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buckets(counts: &[usize]) -> Vec<CountBucket> {
+        counts
+            .iter()
+            .enumerate()
+            .map(|(i, &count)| {
+                let start = i as u64 * HOUR_SECS;
+                CountBucket { start, min: count, min_at: start, max: count, max_at: start, min_slot: None, max_slot: None, token_accounts: None }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_horizon() {
+        assert_eq!(parse_horizon("24h").unwrap(), 86_400);
+        assert_eq!(parse_horizon("7d").unwrap(), 7 * 86_400);
+        assert_eq!(parse_horizon("90m").unwrap(), 5400);
+        assert_eq!(parse_horizon("12").unwrap(), 12 * HOUR_SECS);
+        assert!(parse_horizon("0h").is_err());
+        assert!(parse_horizon("31d").is_err());
+        assert!(parse_horizon("soon").is_err());
+    }
+
+    #[test]
+    fn test_linear_trend() {
+        let history = buckets(&[100, 110, 120, 130, 140, 150, 160, 170]);
+        let forecast = forecast(&history, ForecastMethod::Linear, 3 * HOUR_SECS).unwrap();
+        assert!(forecast.estimate);
+        assert_eq!(forecast.trend_per_day, 240.0);
+        assert_eq!(forecast.points.len(), 3);
+        assert_eq!(forecast.points[2].at, 10 * HOUR_SECS);
+        assert_eq!(forecast.points[2].holders, 200.0);
+        // A perfect line leaves no uncertainty
+        assert_eq!((forecast.points[2].lower, forecast.points[2].upper), (200.0, 200.0));
+        assert_eq!(forecast.reaches(185.0).unwrap().at, 9 * HOUR_SECS);
+        assert!(forecast.reaches(1000.0).is_none());
+    }
+
+    #[test]
+    fn test_holt_bounds_widen() {
+        let history = buckets(&[100, 104, 103, 110, 112, 111, 118, 121, 119, 126]);
+        let forecast = forecast(&history, ForecastMethod::Holt, 6 * HOUR_SECS).unwrap();
+        assert!(forecast.trend_per_day > 0.0);
+        let first = forecast.points[0];
+        let last = forecast.points[5];
+        assert!(first.lower < first.holders && first.holders < first.upper);
+        assert!(last.upper - last.lower > first.upper - first.lower);
+        assert!(last.holders > first.holders);
+    }
+
+    #[test]
+    fn test_needs_history() {
+        assert!(forecast(&buckets(&[1, 2, 3]), ForecastMethod::Linear, HOUR_SECS).is_none());
+    }
+}
@@ -0,0 +1,106 @@
+//! `GET /health` payload: liveness plus what monitoring needs to alert on staleness (uptime,
+//! version, last successful RPC call and poll, consecutive failures)
+
+use serde::Serialize;
+use std::sync::OnceLock;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use crate::build_info::{GIT_COMMIT, VERSION};
+use crate::rpc_stats::RpcHealth;
+use crate::token_monitor::MetricsSummary;
+
+static STARTED: OnceLock<(Instant, u64)> = OnceLock::new();
+
+/// Remember the process start; call first thing in `main` (later calls keep the first time)
+pub fn mark_started() {
+    started();
+}
+
+fn started() -> (Instant, u64) {
+    *STARTED.get_or_init(|| {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        (Instant::now(), now)
+    })
+}
+
+/// Polls of the monitored mints, summed up so the public endpoint doesn't list mints
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MonitorHealth {
+    pub mints: usize,
+    /// Last success of the stalest mint; None while any mint has never been polled successfully
+    pub last_success_at: Option<u64>,
+    /// Highest count of consecutive failed polls among the mints
+    pub consecutive_failures: u32,
+}
+
+impl MonitorHealth {
+    pub fn from_metrics<'a>(metrics: impl IntoIterator<Item = &'a MetricsSummary>) -> Self {
+        let mut health = Self { mints: 0, last_success_at: None, consecutive_failures: 0 };
+        let mut never_succeeded = false;
+        for summary in metrics {
+            health.mints += 1;
+            health.consecutive_failures = health.consecutive_failures.max(summary.consecutive_failures);
+            match summary.last_success_at {
+                Some(at) => health.last_success_at = Some(health.last_success_at.map_or(at, |last| last.min(at))),
+                None => never_succeeded = true,
+            }
+        }
+        if never_succeeded {
+            health.last_success_at = None;
+        }
+        health
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthResponse {
+    pub status: &'static str,
+    pub service: &'static str,
+    pub version: &'static str,
+    pub git_commit: Option<&'static str>,
+    pub started_at: u64,
+    pub uptime_secs: u64,
+    pub rpc: RpcHealth,
+    /// Absent when the API runs without the monitor
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub monitor: Option<MonitorHealth>,
+}
+
+impl HealthResponse {
+    pub fn new(rpc: RpcHealth, monitor: Option<MonitorHealth>) -> Self {
+        let (instant, started_at) = started();
+        Self {
+            status: "ok",
+            service: "solana-holder-bot-api",
+            version: VERSION,
+            git_commit: GIT_COMMIT,
+            started_at,
+            uptime_secs: instant.elapsed().as_secs(),
+            rpc,
+            monitor,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_monitor_health() {
+        let summary = |last_success_at, consecutive_failures| MetricsSummary {
+            last_success_at,
+            consecutive_failures,
+            ..MetricsSummary::default()
+        };
+        let health = MonitorHealth::from_metrics(&[summary(Some(200), 0), summary(Some(100), 3)]);
+        assert_eq!(health, MonitorHealth { mints: 2, last_success_at: Some(100), consecutive_failures: 3 });
+        let health = MonitorHealth::from_metrics(&[summary(Some(200), 0), summary(None, 1)]);
+        assert_eq!(health.last_success_at, None);
+
+        let response = serde_json::to_value(HealthResponse::new(RpcHealth::default(), None)).unwrap();
+        assert_eq!(response["status"], "ok");
+        assert_eq!(response["version"], VERSION);
+        assert!(response.get("monitor").is_none());
+    }
+}
@@ -0,0 +1,485 @@
+//! Holder count history (hourly min/max) for all-time high / low alerts, with the alerts fired
+//! along the way as annotations (chart markers explaining spikes)
+//! Persisted next to the holder ledger as `<ledger-dir>/<mint>.history.json`; holders per balance
+//! tier go to `<ledger-dir>/<mint>.tiers.json` in the same hourly buckets
+//! Points are placed at the block time of the slot they were read at when the RPC reports it,
+//! so the history follows on-chain time rather than the local clock
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::i18n::fill;
+use crate::ledger::save_json_atomically;
+use crate::locale::Locale;
+use crate::tiers::TierCount;
+use crate::token_monitor::{AlertRule, HolderStats};
+
+/// Bucket length; keeps a year of history at ~9k entries regardless of poll interval
+const BUCKET_SECS: u64 = 3600;
+/// Annotations kept; the oldest are dropped first
+const MAX_ANNOTATIONS: usize = 5_000;
+
+const SECS_PER_DAY: f64 = 86_400.0;
+
+/// Lowest and highest count seen in one hour
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CountBucket {
+    pub start: u64,
+    pub min: usize,
+    /// When `min` was first reached
+    pub min_at: u64,
+    pub max: usize,
+    /// When `max` was first reached
+    pub max_at: u64,
+    /// Slot `min` was read at
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_slot: Option<u64>,
+    /// Slot `max` was read at
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_slot: Option<u64>,
+    /// Token accounts at the latest poll of the hour (`min` / `max` count unique owners)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_accounts: Option<usize>,
+}
+
+/// Record count and when it was set
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Record {
+    pub holders: usize,
+    pub at: u64,
+}
+
+impl Record {
+    pub fn days_ago(&self, now: u64) -> f64 {
+        now.saturating_sub(self.at) as f64 / SECS_PER_DAY
+    }
+}
+
+/// An alert that fired at a point of the history
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Annotation {
+    /// Start of the bucket the alert belongs to
+    pub bucket: u64,
+    pub at: u64,
+    pub rule: AlertRule,
+    pub holders: usize,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slot: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CountHistory {
+    pub mint: String,
+    pub buckets: Vec<CountBucket>,
+    /// Alerts in the order they fired
+    #[serde(default)]
+    pub annotations: Vec<Annotation>,
+}
+
+impl CountHistory {
+    pub fn new(mint: &str) -> Self {
+        Self {
+            mint: mint.to_string(),
+            buckets: Vec::new(),
+            annotations: Vec::new(),
+        }
+    }
+
+    pub fn path_for(dir: &Path, mint: &str) -> PathBuf {
+        dir.join(format!("{}.history.json", mint))
+    }
+
+    /// Load history from file, or start a new one if the file doesn't exist
+    pub fn load_or_new(dir: &Path, mint: &str) -> Result<Self> {
+        let path = Self::path_for(dir, mint);
+        if !path.exists() {
+            return Ok(Self::new(mint));
+        }
+        let data = fs::read(&path)
+            .with_context(|| format!("Failed to read count history {}", path.display()))?;
+        serde_json::from_slice(&data)
+            .with_context(|| format!("Invalid count history file {}", path.display()))
+    }
+
+    /// Save atomically, see [`save_json_atomically`]
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create ledger directory {}", dir.display()))?;
+        save_json_atomically(&Self::path_for(dir, &self.mint), self, "count history")
+    }
+
+    pub fn record(&mut self, holders: usize, now: u64, slot: Option<u64>) {
+        let start = now - now % BUCKET_SECS;
+        match self.buckets.last_mut() {
+            Some(bucket) if bucket.start == start => {
+                if holders < bucket.min {
+                    bucket.min = holders;
+                    bucket.min_at = now;
+                    bucket.min_slot = slot;
+                }
+                if holders > bucket.max {
+                    bucket.max = holders;
+                    bucket.max_at = now;
+                    bucket.max_slot = slot;
+                }
+            }
+            _ => self.buckets.push(CountBucket {
+                start,
+                min: holders,
+                min_at: now,
+                max: holders,
+                max_at: now,
+                min_slot: slot,
+                max_slot: slot,
+                token_accounts: None,
+            }),
+        }
+    }
+
+    /// Record the count and token accounts of a poll at its on-chain time
+    pub fn observe(&mut self, stats: &HolderStats) {
+        self.record(stats.count, stats.observed_at(), stats.slot);
+        if let Some(accounts) = stats.token_accounts {
+            self.record_token_accounts(accounts);
+        }
+    }
+
+    /// Token accounts seen by the poll just recorded
+    pub fn record_token_accounts(&mut self, accounts: usize) {
+        if let Some(bucket) = self.buckets.last_mut() {
+            bucket.token_accounts = Some(accounts);
+        }
+    }
+
+    /// Attach a fired alert to the point of `stats`
+    pub fn annotate(&mut self, rule: AlertRule, message: &str, stats: &HolderStats) {
+        let at = stats.observed_at();
+        self.annotations.push(Annotation {
+            bucket: at - at % BUCKET_SECS,
+            at,
+            rule,
+            holders: stats.count,
+            message: message.to_string(),
+            slot: stats.slot,
+        });
+        if self.annotations.len() > MAX_ANNOTATIONS {
+            self.annotations.drain(..self.annotations.len() - MAX_ANNOTATIONS);
+        }
+    }
+
+    /// Highest count since `since` (earliest occurrence on ties)
+    pub fn high(&self, since: u64) -> Option<Record> {
+        self.recent(since)
+            .map(|b| Record { holders: b.max, at: b.max_at })
+            .reduce(|best, r| if r.holders > best.holders { r } else { best })
+    }
+
+    /// Lowest count since `since` (earliest occurrence on ties)
+    pub fn low(&self, since: u64) -> Option<Record> {
+        self.recent(since)
+            .map(|b| Record { holders: b.min, at: b.min_at })
+            .reduce(|best, r| if r.holders < best.holders { r } else { best })
+    }
+
+    /// Count known at `at`: of the min / max in the last bucket starting by then, the later one
+    /// reached by `at` (the earlier one when neither was); None when the history starts later
+    pub fn holders_at(&self, at: u64) -> Option<usize> {
+        let bucket = self.buckets.iter().rev().find(|b| b.start <= at)?;
+        let mut points = [(bucket.min_at, bucket.min), (bucket.max_at, bucket.max)];
+        points.sort();
+        let (_, holders) = points.iter().rev().find(|(reached, _)| *reached <= at).unwrap_or(&points[0]);
+        Some(*holders)
+    }
+
+    /// Buckets overlapping `[since, ..)`
+    fn recent(&self, since: u64) -> impl Iterator<Item = &CountBucket> {
+        self.buckets
+            .iter()
+            .filter(move |b| b.start + BUCKET_SECS > since)
+    }
+}
+
+/// Holders per tier at the last poll of one hour
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TierBucket {
+    pub start: u64,
+    /// When the counts were read
+    pub at: u64,
+    /// Highest tier first
+    pub counts: Vec<TierCount>,
+}
+
+/// One point of a tier's series
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct TierPoint {
+    pub start: u64,
+    pub holders: usize,
+}
+
+/// Hourly holder counts of one tier, oldest first
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TierSeries {
+    pub tier: String,
+    pub points: Vec<TierPoint>,
+}
+
+/// Holders per balance tier over time, to tell growth among small holders from large ones
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TierHistory {
+    pub mint: String,
+    pub buckets: Vec<TierBucket>,
+}
+
+impl TierHistory {
+    pub fn new(mint: &str) -> Self {
+        Self {
+            mint: mint.to_string(),
+            buckets: Vec::new(),
+        }
+    }
+
+    pub fn path_for(dir: &Path, mint: &str) -> PathBuf {
+        dir.join(format!("{}.tiers.json", mint))
+    }
+
+    /// Load tier history from file, or start a new one if the file doesn't exist
+    pub fn load_or_new(dir: &Path, mint: &str) -> Result<Self> {
+        let path = Self::path_for(dir, mint);
+        if !path.exists() {
+            return Ok(Self::new(mint));
+        }
+        let data = fs::read(&path)
+            .with_context(|| format!("Failed to read tier history {}", path.display()))?;
+        serde_json::from_slice(&data)
+            .with_context(|| format!("Invalid tier history file {}", path.display()))
+    }
+
+    /// Save atomically, see [`save_json_atomically`]
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create ledger directory {}", dir.display()))?;
+        save_json_atomically(&Self::path_for(dir, &self.mint), self, "tier history")
+    }
+
+    /// Keep the latest counts of each hour
+    pub fn record(&mut self, counts: &[TierCount], now: u64) {
+        let bucket = TierBucket { start: now - now % BUCKET_SECS, at: now, counts: counts.to_vec() };
+        match self.buckets.last_mut() {
+            Some(last) if last.start == bucket.start => *last = bucket,
+            _ => self.buckets.push(bucket),
+        }
+    }
+
+    /// One series per tier for buckets starting at `since`, in the order of the latest buckets
+    /// (highest tier first); tiers renamed or removed from `[tiers]` keep their old points
+    pub fn series(&self, since: u64) -> Vec<TierSeries> {
+        let mut series: Vec<TierSeries> = Vec::new();
+        for bucket in self.buckets.iter().rev().filter(|bucket| bucket.start >= since) {
+            for count in &bucket.counts {
+                let point = TierPoint { start: bucket.start, holders: count.holders };
+                match series.iter_mut().find(|series| series.tier == count.tier) {
+                    Some(series) => series.points.push(point),
+                    None => series.push(TierSeries { tier: count.tier.clone(), points: vec![point] }),
+                }
+            }
+        }
+        for series in &mut series {
+            series.points.reverse();
+        }
+        series
+    }
+}
+
+/// Alert when the count breaks the high / low of the lookback window (`None` = all history)
+/// Only the poll that breaks the record fires, not every following poll that extends it;
+/// the first poll after startup never fires since the previous count is unknown.
+/// Records the count in `history` afterwards.
+pub fn check_records(
+    stats: &HolderStats,
+    previous_count: Option<usize>,
+    history: &mut CountHistory,
+    lookback_secs: Option<u64>,
+    locale: &Locale,
+) -> Vec<(AlertRule, String)> {
+    let now = stats.observed_at();
+    let since = lookback_secs.map_or(0, |lookback| now.saturating_sub(lookback));
+    let messages = locale.messages();
+    let mut fired = Vec::new();
+
+    if let Some(previous) = previous_count {
+        let broken = [
+            (history.high(since), AlertRule::Ath, messages.ath_alert),
+            (history.low(since), AlertRule::Atl, messages.atl_alert),
+        ];
+        for (record, rule, template) in broken {
+            let Some(record) = record else { continue };
+            let is_new = match rule {
+                AlertRule::Ath => stats.count > record.holders && previous < record.holders,
+                _ => stats.count < record.holders && previous > record.holders,
+            };
+            if is_new {
+                let message = fill(
+                    template,
+                    &[
+                        ("holders", &locale.format_count(stats.count)),
+                        ("previous", &locale.format_count(record.holders)),
+                        ("days", &locale.format_float(record.days_ago(now), 1)),
+                    ],
+                );
+                fired.push((rule, message));
+            }
+        }
+    }
+
+    history.observe(stats);
+    fired
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(count: usize, timestamp: u64) -> HolderStats {
+        HolderStats {
+            count,
+            timestamp,
+            change: 0,
+            change_percent: 0.0,
+            slot: None,
+            block_time: None,
+            degraded: None,
+            missing_supply_percent: None,
+            token_accounts: None,
+        }
+    }
+
+    #[test]
+    fn test_buckets_and_records() {
+        let mut history = CountHistory::new("M");
+        history.record(100, 10, None);
+        history.record(120, 20, None);
+        history.record(90, 30, None);
+        history.record(110, BUCKET_SECS + 5, None);
+        history.record_token_accounts(130);
+
+        assert_eq!(history.buckets.len(), 2);
+        assert_eq!(history.buckets[0].token_accounts, None);
+        assert_eq!(history.buckets[1].token_accounts, Some(130));
+        assert_eq!(history.high(0), Some(Record { holders: 120, at: 20 }));
+        assert_eq!(history.low(0), Some(Record { holders: 90, at: 30 }));
+        assert_eq!(history.high(BUCKET_SECS).unwrap().holders, 110);
+
+        assert_eq!(history.holders_at(25), Some(120));
+        assert_eq!(history.holders_at(BUCKET_SECS - 1), Some(90));
+        assert_eq!(history.holders_at(BUCKET_SECS + 100), Some(110));
+        assert_eq!(CountHistory::new("M").holders_at(100), None);
+    }
+
+    #[test]
+    fn test_ath_fires_on_breakout_only() {
+        let day = 86_400;
+        let mut history = CountHistory::new("M");
+        let locale = Locale::default();
+        let mut messages = Vec::new();
+        let mut check = |count, previous, at| {
+            let fired = check_records(&stats(count, at), previous, &mut history, None, &locale);
+            messages.extend(fired.iter().map(|(_, message)| message.clone()));
+            fired.into_iter().map(|(rule, _)| rule).collect::<Vec<_>>()
+        };
+
+        assert!(check(100, None, 0).is_empty());
+        assert!(check(200, Some(100), 3600).is_empty());
+        assert!(check(150, Some(200), 7200).is_empty());
+        // Breaks the 200 set 10 days earlier
+        assert_eq!(check(210, Some(150), 3600 + 10 * day), vec![AlertRule::Ath]);
+        // Still climbing: no repeat
+        assert!(check(220, Some(210), 7200 + 10 * day).is_empty());
+        assert_eq!(check(90, Some(220), 10800 + 10 * day), vec![AlertRule::Atl]);
+
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0], "🏔 NEW ATH: 210 holders | previous high 200 set 10.0 days ago");
+    }
+
+    #[test]
+    fn test_block_time_places_points() {
+        let mut history = CountHistory::new("M");
+        let mut observed = stats(100, 2 * BUCKET_SECS);
+        observed.slot = Some(42);
+        observed.block_time = Some(BUCKET_SECS + 10);
+        check_records(&observed, None, &mut history, None, &Locale::default());
+
+        assert_eq!(history.buckets[0].start, BUCKET_SECS);
+        assert_eq!(history.buckets[0].max_at, BUCKET_SECS + 10);
+        assert_eq!(history.buckets[0].max_slot, Some(42));
+    }
+
+    #[test]
+    fn test_annotations() {
+        let mut history = CountHistory::new("M");
+        let mut observed = stats(150, BUCKET_SECS + 30);
+        observed.slot = Some(7);
+        history.annotate(AlertRule::Growth, "growth", &observed);
+        assert_eq!(
+            history.annotations[0],
+            Annotation {
+                bucket: BUCKET_SECS,
+                at: BUCKET_SECS + 30,
+                rule: AlertRule::Growth,
+                holders: 150,
+                message: "growth".to_string(),
+                slot: Some(7),
+            }
+        );
+        for i in 0..MAX_ANNOTATIONS {
+            history.annotate(AlertRule::Drop, &i.to_string(), &stats(100, 2 * BUCKET_SECS));
+        }
+        assert_eq!(history.annotations.len(), MAX_ANNOTATIONS);
+        assert_eq!(history.annotations[0].message, "0");
+
+        // Files written before annotations existed still load
+        let old: CountHistory = serde_json::from_str(r#"{"mint":"M","buckets":[]}"#).unwrap();
+        assert!(old.annotations.is_empty());
+    }
+
+    #[test]
+    fn test_tier_series() {
+        let counts = |gold, silver| {
+            vec![
+                TierCount { tier: "gold".to_string(), holders: gold },
+                TierCount { tier: "silver".to_string(), holders: silver },
+            ]
+        };
+        let mut history = TierHistory::new("M");
+        history.record(&counts(1, 10), 10);
+        // The last poll of the hour wins
+        history.record(&counts(2, 12), 20);
+        history.record(&counts(3, 15), BUCKET_SECS + 5);
+        history.record(&[TierCount { tier: "silver".to_string(), holders: 20 }], 2 * BUCKET_SECS);
+        assert_eq!(history.buckets.len(), 3);
+
+        let series = history.series(0);
+        assert_eq!(series[0].tier, "silver");
+        let silver: Vec<(u64, usize)> = series[0].points.iter().map(|p| (p.start, p.holders)).collect();
+        assert_eq!(silver, vec![(0, 12), (BUCKET_SECS, 15), (2 * BUCKET_SECS, 20)]);
+        assert_eq!(series[1].points.len(), 2);
+        assert_eq!(history.series(BUCKET_SECS)[1].points, vec![TierPoint { start: BUCKET_SECS, holders: 3 }]);
+    }
+
+    #[test]
+    fn test_lookback_window() {
+        let day = 86_400;
+        let mut history = CountHistory::new("M");
+        let locale = Locale::default();
+
+        history.record(500, 0, None);
+        history.record(120, 40 * day, None);
+        // 500 is older than the 30-day window
+        let fired = check_records(&stats(150, 41 * day), Some(100), &mut history, Some(30 * day), &locale);
+        assert!(matches!(fired[..], [(AlertRule::Ath, _)]));
+        let fired = check_records(&stats(600, 42 * day), Some(90), &mut history, None, &locale);
+        assert!(matches!(fired[..], [(AlertRule::Ath, _)]));
+    }
+}
@@ -0,0 +1,116 @@
+//! Tuning of the HTTP client behind the RPC connection: solana-client's defaults (no keepalive,
+//! unbounded idle pool, 30s per request, no User-Agent) don't suit every provider
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use solana_client::client_error::reqwest;
+use std::time::Duration;
+
+use crate::cli::Cli;
+
+/// User-Agent of RPC requests unless `--rpc-user-agent` overrides it
+pub const DEFAULT_USER_AGENT: &str = concat!("solana-holder-bot/", env!("CARGO_PKG_VERSION"));
+
+/// HTTP version used to talk to the RPC
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum HttpVersion {
+    /// The client's default
+    #[default]
+    Auto,
+    /// HTTP/1.1 only (some load balancers mishandle HTTP/2 streams)
+    Http1,
+}
+
+/// Client settings; every `None` keeps the solana-client default (the User-Agent falls back to
+/// `DEFAULT_USER_AGENT`)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HttpTuning {
+    /// Idle connections kept per host
+    pub pool_max_idle_per_host: Option<usize>,
+    pub tcp_keepalive: Option<Duration>,
+    pub version: HttpVersion,
+    /// Timeout of a single HTTP request (retries are governed by `--timeout`)
+    pub request_timeout: Option<Duration>,
+    /// Log every JSON-RPC call at debug level (see `rpc_logging`)
+    pub log_requests: bool,
+    /// Sent on every request; providers rate-limit or allowlist by it
+    pub user_agent: Option<String>,
+}
+
+impl HttpTuning {
+    /// Timeout solana-client applies to each request
+    pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+    pub fn from_cli(cli: &Cli) -> Self {
+        Self {
+            pool_max_idle_per_host: cli.rpc_pool_max_idle,
+            tcp_keepalive: cli.rpc_tcp_keepalive_secs.map(Duration::from_secs),
+            version: cli.rpc_http_version,
+            request_timeout: cli.rpc_request_timeout_secs.map(Duration::from_secs),
+            log_requests: cli.log_rpc_requests,
+            user_agent: cli.rpc_user_agent.clone(),
+        }
+    }
+
+    pub fn user_agent(&self) -> &str {
+        self.user_agent.as_deref().unwrap_or(DEFAULT_USER_AGENT)
+    }
+
+    pub fn build_client(&self) -> Result<reqwest::Client> {
+        let mut builder = reqwest::Client::builder()
+            .timeout(self.request_timeout.unwrap_or(Self::DEFAULT_REQUEST_TIMEOUT))
+            .tcp_keepalive(self.tcp_keepalive)
+            .user_agent(self.user_agent());
+        if let Some(idle) = self.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(idle);
+        }
+        builder = match self.version {
+            HttpVersion::Auto => builder,
+            HttpVersion::Http1 => builder.http1_only(),
+        };
+        builder.build().context("Failed to build the RPC HTTP client")
+    }
+
+    /// One-line description for the startup log
+    pub fn describe(&self) -> String {
+        let mut parts = vec![format!("{:?}", self.version).to_lowercase()];
+        if let Some(idle) = self.pool_max_idle_per_host {
+            parts.push(format!("pool {} idle/host", idle));
+        }
+        if let Some(keepalive) = self.tcp_keepalive {
+            parts.push(format!("keepalive {}s", keepalive.as_secs()));
+        }
+        parts.push(format!(
+            "request timeout {}s",
+            self.request_timeout.unwrap_or(Self::DEFAULT_REQUEST_TIMEOUT).as_secs()
+        ));
+        parts.push(format!("user-agent {}", self.user_agent()));
+        if self.log_requests {
+            parts.push("request logging".to_string());
+        }
+        parts.join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tuning() {
+        assert_eq!(HttpTuning::default().user_agent(), DEFAULT_USER_AGENT);
+        let tuning = HttpTuning {
+            pool_max_idle_per_host: Some(4),
+            tcp_keepalive: Some(Duration::from_secs(30)),
+            version: HttpVersion::Http1,
+            request_timeout: None,
+            log_requests: false,
+            user_agent: Some("acme-bot/1.0".to_string()),
+        };
+        assert_eq!(
+            tuning.describe(),
+            "http1, pool 4 idle/host, keepalive 30s, request timeout 30s, user-agent acme-bot/1.0"
+        );
+        tuning.build_client().unwrap();
+    }
+}
@@ -0,0 +1,184 @@
+//! Message catalog for alert and notification texts (`--language`)
+
+use clap::ValueEnum;
+
+/// Language of alert and notification texts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Language {
+    #[default]
+    En,
+    Ru,
+}
+
+impl Language {
+    pub fn messages(self) -> &'static Messages {
+        match self {
+            Self::En => &EN,
+            Self::Ru => &RU,
+        }
+    }
+}
+
+/// Translated texts; `{name}` placeholders are filled by `fill`
+pub struct Messages {
+    /// `{change}`, `{percent}`, `{previous}`, `{holders}`
+    pub growth_alert: &'static str,
+    /// `{change}`, `{percent}`, `{previous}`, `{holders}`
+    pub drop_alert: &'static str,
+    /// `{milestone}`, `{holders}`
+    pub milestone_alert: &'static str,
+    /// `{next}`
+    pub milestone_next: &'static str,
+    /// `{holders}`, `{previous}`, `{days}`
+    pub ath_alert: &'static str,
+    /// `{holders}`, `{previous}`, `{days}`
+    pub atl_alert: &'static str,
+    /// `{percent}`, `{delegate}`, `{accounts}`
+    pub delegation_alert: &'static str,
+    /// `{count}`
+    pub digest_title: &'static str,
+    /// `{polls}`
+    pub persisting: &'static str,
+    pub heartbeat_title: &'static str,
+    /// `{label}`, `{holders}`, `{ago}`
+    pub heartbeat_line: &'static str,
+    /// `{label}`
+    pub heartbeat_no_poll: &'static str,
+    /// `{mint}`
+    pub report_title: &'static str,
+    pub report_total_polls: &'static str,
+    pub report_min_holders: &'static str,
+    pub report_max_holders: &'static str,
+    pub report_average_holders: &'static str,
+    pub report_alerts: &'static str,
+    /// `{count}`
+    pub report_earlier_alerts: &'static str,
+    pub polling_recovered: &'static str,
+    /// `{secs}`, `{factor}`, `{interval}`
+    pub polling_stalled: &'static str,
+    /// `{denied}`, `{budget}`, `{window}`
+    pub retry_budget_exhausted: &'static str,
+    pub test_alert_title: &'static str,
+    /// `{p95}`, `{slo}`, `{windows}`, `{window}`, `{endpoint}`
+    pub latency_slo_breached: &'static str,
+    /// `{p95}`, `{slo}`, `{endpoint}`
+    pub latency_slo_recovered: &'static str,
+}
+
+const EN: Messages = Messages {
+    growth_alert: "🚀 SIGNIFICANT GROWTH: +{change} holders ({percent}) | {previous} -> {holders}",
+    drop_alert: "⚠️ SIGNIFICANT DROP: {change} holders ({percent}) | {previous} -> {holders}",
+    milestone_alert: "🎉 MILESTONE: {milestone} holders reached | now {holders}",
+    milestone_next: " | next: {next}",
+    ath_alert: "🏔 NEW ATH: {holders} holders | previous high {previous} set {days} days ago",
+    atl_alert: "🕳 NEW ATL: {holders} holders | previous low {previous} set {days} days ago",
+    delegation_alert: "🔑 DELEGATION: {percent} of supply may be moved by {delegate} (approved by {accounts} accounts)",
+    digest_title: "🌙 {count} alerts during quiet hours",
+    persisting: " (persisting for {polls} polls)",
+    heartbeat_title: "💓 Still alive",
+    heartbeat_line: "{label}: {holders} holders, last poll {ago}s ago",
+    heartbeat_no_poll: "{label}: no successful poll yet",
+    report_title: "📊 Final metrics for {mint}",
+    report_total_polls: "Total polls",
+    report_min_holders: "Min holders",
+    report_max_holders: "Max holders",
+    report_average_holders: "Average holders",
+    report_alerts: "🚨 Alerts triggered",
+    report_earlier_alerts: "… {count} earlier",
+    polling_recovered: "✅ Polling recovered",
+    polling_stalled: "🛑 No successful poll for {secs}s (--stall-factor {factor} x {interval}s interval)",
+    retry_budget_exhausted: "⛽ RPC retry budget exhausted: {denied} retries skipped (--retry-budget {budget} per {window}s)",
+    test_alert_title: "🧪 Test alert from solana-holder-bot, no action needed",
+    latency_slo_breached: "🐢 RPC fetch p95 {p95}s above the {slo}s SLO for {windows} windows of {window}s ({endpoint})",
+    latency_slo_recovered: "✅ RPC fetch p95 back under the {slo}s SLO: {p95}s ({endpoint})",
+};
+
+const RU: Messages = Messages {
+    growth_alert: "🚀 РЕЗКИЙ РОСТ: +{change} держателей ({percent}) | {previous} -> {holders}",
+    drop_alert: "⚠️ РЕЗКОЕ ПАДЕНИЕ: {change} держателей ({percent}) | {previous} -> {holders}",
+    milestone_alert: "🎉 РУБЕЖ: {milestone} держателей | сейчас {holders}",
+    milestone_next: " | следующий: {next}",
+    ath_alert: "🏔 НОВЫЙ МАКСИМУМ: {holders} держателей | прошлый максимум {previous} был {days} дн. назад",
+    atl_alert: "🕳 НОВЫЙ МИНИМУМ: {holders} держателей | прошлый минимум {previous} был {days} дн. назад",
+    delegation_alert: "🔑 ДЕЛЕГИРОВАНИЕ: {delegate} может перевести {percent} supply (разрешение от аккаунтов: {accounts})",
+    digest_title: "🌙 Алертов за тихие часы: {count}",
+    persisting: " (опросов подряд: {polls})",
+    heartbeat_title: "💓 Бот работает",
+    heartbeat_line: "{label}: держателей {holders}, последний опрос {ago} с назад",
+    heartbeat_no_poll: "{label}: успешных опросов еще не было",
+    report_title: "📊 Итоговые метрики для {mint}",
+    report_total_polls: "Всего опросов",
+    report_min_holders: "Минимум держателей",
+    report_max_holders: "Максимум держателей",
+    report_average_holders: "Среднее число держателей",
+    report_alerts: "🚨 Сработало алертов",
+    report_earlier_alerts: "… еще {count} ранее",
+    polling_recovered: "✅ Опросы восстановились",
+    polling_stalled: "🛑 Нет успешных опросов {secs} с (--stall-factor {factor} x интервал {interval} с)",
+    retry_budget_exhausted: "⛽ Бюджет повторов RPC исчерпан: пропущено повторов {denied} (--retry-budget {budget} за {window} с)",
+    test_alert_title: "🧪 Тестовый алерт от solana-holder-bot, ничего делать не нужно",
+    latency_slo_breached: "🐢 p95 запроса к RPC {p95} с выше SLO {slo} с уже {windows} окна по {window} с ({endpoint})",
+    latency_slo_recovered: "✅ p95 запроса к RPC снова в пределах SLO {slo} с: {p95} с ({endpoint})",
+};
+
+/// Replace `{name}` placeholders in a catalog template
+pub fn fill(template: &str, args: &[(&str, &str)]) -> String {
+    let mut text = template.to_string();
+    for (name, value) in args {
+        text = text.replace(&format!("{{{}}}", name), value);
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fill() {
+        let args = [("change", "500"), ("percent", "+50,0%"), ("previous", "1000"), ("holders", "1500")];
+        assert_eq!(
+            fill(Language::Ru.messages().growth_alert, &args),
+            "🚀 РЕЗКИЙ РОСТ: +500 держателей (+50,0%) | 1000 -> 1500"
+        );
+        assert_eq!(
+            fill(Language::En.messages().growth_alert, &args),
+            "🚀 SIGNIFICANT GROWTH: +500 holders (+50,0%) | 1000 -> 1500"
+        );
+    }
+
+    #[test]
+    fn test_catalogs_use_same_placeholders() {
+        fn placeholders(text: &str) -> Vec<&str> {
+            let mut names: Vec<&str> = text
+                .split('{')
+                .skip(1)
+                .filter_map(|part| part.split_once('}').map(|(name, _)| name))
+                .collect();
+            names.sort_unstable();
+            names
+        }
+        let pairs = [
+            (EN.growth_alert, RU.growth_alert),
+            (EN.drop_alert, RU.drop_alert),
+            (EN.milestone_alert, RU.milestone_alert),
+            (EN.milestone_next, RU.milestone_next),
+            (EN.ath_alert, RU.ath_alert),
+            (EN.atl_alert, RU.atl_alert),
+            (EN.delegation_alert, RU.delegation_alert),
+            (EN.digest_title, RU.digest_title),
+            (EN.persisting, RU.persisting),
+            (EN.heartbeat_line, RU.heartbeat_line),
+            (EN.heartbeat_no_poll, RU.heartbeat_no_poll),
+            (EN.report_title, RU.report_title),
+            (EN.report_earlier_alerts, RU.report_earlier_alerts),
+            (EN.polling_stalled, RU.polling_stalled),
+            (EN.retry_budget_exhausted, RU.retry_budget_exhausted),
+            (EN.latency_slo_breached, RU.latency_slo_breached),
+            (EN.latency_slo_recovered, RU.latency_slo_recovered),
+        ];
+        for (en, ru) in pairs {
+            assert_eq!(placeholders(en), placeholders(ru), "{}", en);
+        }
+    }
+}
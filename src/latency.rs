@@ -0,0 +1,157 @@
+//! Fetch latency: recent durations per mint (percentiles in `Metrics`) and the p95 SLO per RPC
+//! endpoint (`--latency-slo-secs`), which raises an operational alert once p95 stays above it for
+//! `--latency-slo-windows` consecutive windows
+
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+/// Fetch durations kept per mint for the percentiles
+const RECENT_SAMPLES: usize = 256;
+
+/// Nearest-rank percentile (`q` in 0..=1) of unsorted samples
+pub fn percentile(samples: &[f64], q: f64) -> Option<f64> {
+    if samples.is_empty() {
+        return None;
+    }
+    let mut sorted = samples.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let rank = (q * sorted.len() as f64).ceil().max(1.0) as usize;
+    Some(sorted[rank.min(sorted.len()) - 1])
+}
+
+/// Most recent fetch durations in seconds
+#[derive(Debug, Clone, Default)]
+pub struct LatencySamples {
+    samples: VecDeque<f64>,
+}
+
+impl LatencySamples {
+    pub fn record(&mut self, duration: Duration) {
+        if self.samples.len() == RECENT_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(duration.as_secs_f64());
+    }
+
+    pub fn percentile(&self, q: f64) -> Option<f64> {
+        percentile(&self.samples.iter().copied().collect::<Vec<_>>(), q)
+    }
+}
+
+/// Change of an endpoint's SLO state
+#[derive(Debug, Clone, PartialEq)]
+pub enum SloEvent {
+    /// p95 above the SLO for the required number of consecutive windows
+    Breached { endpoint: String, p95_secs: f64, windows: u32 },
+    /// First window back under the SLO after a breach
+    Recovered { endpoint: String, p95_secs: f64 },
+}
+
+#[derive(Debug, Default)]
+struct EndpointWindow {
+    started_at: u64,
+    samples: Vec<f64>,
+    /// Consecutive windows over the SLO
+    streak: u32,
+    breached: bool,
+}
+
+/// p95 fetch latency per endpoint over fixed windows, compared with the SLO
+#[derive(Debug)]
+pub struct LatencySlo {
+    slo_secs: f64,
+    windows: u32,
+    window_secs: u64,
+    endpoints: HashMap<String, EndpointWindow>,
+}
+
+impl LatencySlo {
+    pub fn new(slo_secs: f64, windows: u32, window_secs: u64) -> Self {
+        Self {
+            slo_secs,
+            windows: windows.max(1),
+            window_secs: window_secs.max(1),
+            endpoints: HashMap::new(),
+        }
+    }
+
+    pub fn slo_secs(&self) -> f64 {
+        self.slo_secs
+    }
+
+    pub fn window_secs(&self) -> u64 {
+        self.window_secs
+    }
+
+    /// Record a fetch; a window is evaluated by the first sample after it ends
+    pub fn record(&mut self, endpoint: &str, now: u64, duration: Duration) -> Option<SloEvent> {
+        let window = self.endpoints.entry(endpoint.to_string()).or_insert_with(|| EndpointWindow {
+            started_at: now,
+            ..Default::default()
+        });
+        let mut event = None;
+        if now >= window.started_at + self.window_secs {
+            let p95 = percentile(&window.samples, 0.95);
+            match p95 {
+                Some(p95) if p95 > self.slo_secs => {
+                    window.streak += 1;
+                    if window.streak == self.windows && !window.breached {
+                        window.breached = true;
+                        event = Some(SloEvent::Breached { endpoint: endpoint.to_string(), p95_secs: p95, windows: self.windows });
+                    }
+                }
+                Some(p95) => {
+                    window.streak = 0;
+                    if window.breached {
+                        window.breached = false;
+                        event = Some(SloEvent::Recovered { endpoint: endpoint.to_string(), p95_secs: p95 });
+                    }
+                }
+                None => {}
+            }
+            window.started_at = now;
+            window.samples.clear();
+        }
+        window.samples.push(duration.as_secs_f64());
+        event
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentiles() {
+        let samples: Vec<f64> = (1..=100).map(f64::from).collect();
+        assert_eq!(percentile(&samples, 0.5), Some(50.0));
+        assert_eq!(percentile(&samples, 0.95), Some(95.0));
+        assert_eq!(percentile(&[], 0.95), None);
+
+        let mut recent = LatencySamples::default();
+        for secs in 0..300 {
+            recent.record(Duration::from_secs(secs));
+        }
+        // Only the last 256 are kept
+        assert_eq!(recent.percentile(0.0), Some(44.0));
+        assert_eq!(recent.percentile(1.0), Some(299.0));
+    }
+
+    #[test]
+    fn test_slo_windows() {
+        let mut slo = LatencySlo::new(2.0, 2, 60);
+        let slow = Duration::from_secs(5);
+        let fast = Duration::from_millis(500);
+        assert_eq!(slo.record("rpc", 0, slow), None);
+        // First window over the SLO: not yet
+        assert_eq!(slo.record("rpc", 60, slow), None);
+        let breached = slo.record("rpc", 120, fast);
+        assert_eq!(breached, Some(SloEvent::Breached { endpoint: "rpc".to_string(), p95_secs: 5.0, windows: 2 }));
+        // Reported once per breach
+        assert_eq!(slo.record("rpc", 125, slow), None);
+        assert_eq!(slo.record("rpc", 180, fast), None);
+        assert_eq!(slo.record("rpc", 240, fast), Some(SloEvent::Recovered { endpoint: "rpc".to_string(), p95_secs: 0.5 }));
+        // Endpoints are tracked separately
+        assert_eq!(slo.record("backup", 240, slow), None);
+    }
+}
@@ -0,0 +1,80 @@
+//! Launch mode (`--launch-mode`): every mint is polled every few seconds for the first minutes
+//! after a token launch, then the scheduler falls back to the normal intervals on its own
+
+use chrono::DateTime;
+use std::time::{Duration, Instant};
+
+use crate::cli::Cli;
+
+/// Parse `--launch-at`: unix seconds or an RFC 3339 time
+pub fn parse_launch_time(value: &str) -> Result<u64, String> {
+    if let Ok(secs) = value.trim().parse::<u64>() {
+        return Ok(secs);
+    }
+    let time = DateTime::parse_from_rfc3339(value.trim())
+        .map_err(|_| format!("Invalid launch time '{}': use unix seconds or e.g. 2024-05-01T14:00:00Z", value))?;
+    u64::try_from(time.timestamp()).map_err(|_| format!("Launch time '{}' is before 1970", value))
+}
+
+/// Rapid polling between `start` and `end` (unix seconds)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LaunchWindow {
+    pub start: u64,
+    pub end: u64,
+    pub interval: Duration,
+}
+
+impl LaunchWindow {
+    /// None without `--launch-mode`; the launch defaults to `unix_now`
+    pub fn from_cli(cli: &Cli, unix_now: u64) -> Option<Self> {
+        if !cli.launch_mode {
+            return None;
+        }
+        let start = cli.launch_at.unwrap_or(unix_now);
+        Some(Self {
+            start,
+            end: start.saturating_add(cli.launch_duration_mins.saturating_mul(60)),
+            interval: Duration::from_secs(cli.launch_interval_secs),
+        })
+    }
+
+    pub fn is_over(&self, unix_now: u64) -> bool {
+        unix_now >= self.end
+    }
+
+    /// The window on the monotonic clock, given the wall clock `unix_now` at `now`
+    pub fn instants(&self, now: Instant, unix_now: u64) -> (Instant, Instant) {
+        let at = |unix: u64| {
+            if unix >= unix_now {
+                now + Duration::from_secs(unix - unix_now)
+            } else {
+                now.checked_sub(Duration::from_secs(unix_now - unix)).unwrap_or(now)
+            }
+        };
+        (at(self.start), at(self.end))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_launch_time() {
+        assert_eq!(parse_launch_time("1714572000"), Ok(1_714_572_000));
+        assert_eq!(parse_launch_time("2024-05-01T14:00:00Z"), Ok(1_714_572_000));
+        assert_eq!(parse_launch_time("2024-05-01T16:00:00+02:00"), Ok(1_714_572_000));
+        assert!(parse_launch_time("tomorrow").is_err());
+    }
+
+    #[test]
+    fn test_window_instants() {
+        let window = LaunchWindow { start: 1_000, end: 2_800, interval: Duration::from_secs(5) };
+        let now = Instant::now();
+        let (start, end) = window.instants(now, 1_600);
+        assert_eq!(now.duration_since(start), Duration::from_secs(600));
+        assert_eq!(end.duration_since(now), Duration::from_secs(1_200));
+        assert!(!window.is_over(2_799));
+        assert!(window.is_over(2_800));
+    }
+}
@@ -0,0 +1,135 @@
+//! Holder-growth leaderboard across the tracked mints (`GET /leaderboard`): current holders
+//! against the count history at the start of the window, for a "what's trending" view
+
+use serde::{Deserialize, Serialize};
+
+/// Leaderboard windows
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LeaderboardWindow {
+    #[serde(rename = "1h")]
+    Hour,
+    #[default]
+    #[serde(rename = "24h")]
+    Day,
+    #[serde(rename = "7d")]
+    Week,
+}
+
+impl LeaderboardWindow {
+    pub fn secs(&self) -> u64 {
+        match self {
+            Self::Hour => 3600,
+            Self::Day => 86_400,
+            Self::Week => 7 * 86_400,
+        }
+    }
+}
+
+/// Ranking order
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RankBy {
+    /// Holders gained
+    #[default]
+    Growth,
+    /// Holders gained relative to the start of the window
+    Percent,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LeaderboardEntry {
+    pub rank: usize,
+    pub mint: String,
+    pub holders: usize,
+    /// Holders at the start of the window
+    pub holders_before: usize,
+    pub change: i64,
+    pub change_percent: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Leaderboard {
+    pub window: LeaderboardWindow,
+    pub by: RankBy,
+    pub since: u64,
+    pub entries: Vec<LeaderboardEntry>,
+    /// Tracked mints without a count for the start of the window
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unranked: Vec<String>,
+}
+
+/// Rank `(mint, holders now, holders at the window start)`, best first; ties go to the larger
+/// mint by holders, then by address
+pub fn rank(
+    window: LeaderboardWindow,
+    by: RankBy,
+    since: u64,
+    counts: impl IntoIterator<Item = (String, usize, Option<usize>)>,
+    limit: usize,
+) -> Leaderboard {
+    let mut entries = Vec::new();
+    let mut unranked = Vec::new();
+    for (mint, holders, before) in counts {
+        let Some(holders_before) = before else {
+            unranked.push(mint);
+            continue;
+        };
+        let change = holders as i64 - holders_before as i64;
+        let change_percent = if holders_before > 0 {
+            (change as f64 / holders_before as f64 * 1000.0).round() / 10.0
+        } else {
+            0.0
+        };
+        entries.push(LeaderboardEntry { rank: 0, mint, holders, holders_before, change, change_percent });
+    }
+    entries.sort_by(|a, b| {
+        let order = match by {
+            RankBy::Growth => b.change.cmp(&a.change),
+            RankBy::Percent => b.change_percent.total_cmp(&a.change_percent),
+        };
+        order.then(b.holders.cmp(&a.holders)).then(a.mint.cmp(&b.mint))
+    });
+    entries.truncate(limit);
+    for (i, entry) in entries.iter_mut().enumerate() {
+        entry.rank = i + 1;
+    }
+    unranked.sort();
+    Leaderboard { window, by, since, entries, unranked }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counts() -> Vec<(String, usize, Option<usize>)> {
+        vec![
+            ("big".to_string(), 10_500, Some(10_000)),
+            ("small".to_string(), 300, Some(100)),
+            ("falling".to_string(), 900, Some(1_000)),
+            ("new".to_string(), 50, None),
+        ]
+    }
+
+    #[test]
+    fn test_rank_by_growth_and_percent() {
+        let board = rank(LeaderboardWindow::Day, RankBy::Growth, 0, counts(), 10);
+        let order: Vec<&str> = board.entries.iter().map(|e| e.mint.as_str()).collect();
+        assert_eq!(order, vec!["big", "small", "falling"]);
+        assert_eq!(board.entries[0].change, 500);
+        assert_eq!(board.entries[0].change_percent, 5.0);
+        assert_eq!(board.entries[2].change, -100);
+        assert_eq!(board.unranked, vec!["new".to_string()]);
+
+        let board = rank(LeaderboardWindow::Day, RankBy::Percent, 0, counts(), 2);
+        let order: Vec<(usize, &str)> = board.entries.iter().map(|e| (e.rank, e.mint.as_str())).collect();
+        assert_eq!(order, vec![(1, "small"), (2, "big")]);
+        assert_eq!(board.entries[0].change_percent, 200.0);
+    }
+
+    #[test]
+    fn test_window_parse() {
+        let window: LeaderboardWindow = serde_json::from_str("\"1h\"").unwrap();
+        assert_eq!(window.secs(), 3600);
+        assert!(serde_json::from_str::<LeaderboardWindow>("\"2h\"").is_err());
+    }
+}
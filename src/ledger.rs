@@ -0,0 +1,353 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use solana_program::pubkey::Pubkey;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Write `value` as JSON to `path` atomically: a temp file is written and synced, then renamed
+/// over `path`, so a crash leaves either the old or the new file; `what` names the file in errors
+pub fn save_json_atomically<T: Serialize>(path: &Path, value: &T, what: &str) -> Result<()> {
+    let tmp = path.with_extension("json.tmp");
+    let write = || -> std::io::Result<()> {
+        let mut file = fs::File::create(&tmp)?;
+        file.write_all(&serde_json::to_vec(value)?)?;
+        file.sync_all()
+    };
+    write().with_context(|| format!("Failed to write {} {}", what, tmp.display()))?;
+    fs::rename(&tmp, path).with_context(|| format!("Failed to replace {} {}", what, path.display()))?;
+    // The rename itself is durable once the directory entry is synced
+    #[cfg(unix)]
+    if let Some(dir) = path.parent() {
+        fs::File::open(dir)
+            .and_then(|dir| dir.sync_all())
+            .with_context(|| format!("Failed to sync directory of {} {}", what, path.display()))?;
+    }
+    Ok(())
+}
+
+/// Holding history of one wallet
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WalletRecord {
+    /// First poll where the wallet held the token
+    pub first_seen: u64,
+    /// Last poll where the wallet held the token
+    pub last_seen: u64,
+    /// Whether the wallet held the token at the latest poll
+    pub holding: bool,
+    /// How many times the wallet exited and came back
+    #[serde(default)]
+    pub returns: u32,
+}
+
+/// Changes recorded by one `observe` call
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ObserveResult {
+    /// Wallets holding for the first time ever
+    pub new_wallets: usize,
+    /// Wallets that held before, exited, and are holding again
+    pub returned_wallets: usize,
+}
+
+/// Lifetime holder statistics derived from the ledger
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct LedgerSummary {
+    /// Every wallet that has ever held the token since tracking started
+    pub unique_holders_ever: usize,
+    pub current_holders: usize,
+    /// Current holders that never exited
+    pub first_time_holders: usize,
+    /// Current holders that exited at least once and came back
+    pub returning_holders: usize,
+    /// Wallets that held at some point but don't hold now
+    pub former_holders: usize,
+}
+
+/// Per-mint ledger of every wallet that has held the token
+/// Persisted as `<ledger-dir>/<mint>.json`
+#[derive(Debug, Clone, Default)]
+pub struct HolderLedger {
+    pub mint: String,
+    pub created_at: u64,
+    pub updated_at: u64,
+    pub wallets: HashMap<Pubkey, WalletRecord>,
+}
+
+/// On-disk representation (base58 keys instead of raw byte arrays)
+#[derive(Serialize, Deserialize)]
+struct LedgerFile {
+    mint: String,
+    created_at: u64,
+    updated_at: u64,
+    wallets: HashMap<String, WalletRecord>,
+}
+
+/// Retention of one cohort (wallets first seen in the same period)
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Cohort {
+    /// Start of the period the cohort first appeared in
+    pub period_start: u64,
+    pub size: usize,
+    /// `retention_percent[k]` = share of the cohort still holding k periods later
+    pub retention_percent: Vec<f64>,
+}
+
+impl HolderLedger {
+    pub fn new(mint: &Pubkey, now: u64) -> Self {
+        Self {
+            mint: mint.to_string(),
+            created_at: now,
+            updated_at: now,
+            wallets: HashMap::new(),
+        }
+    }
+
+    /// Ledger file location for a mint
+    pub fn path_for(dir: &Path, mint: &str) -> PathBuf {
+        dir.join(format!("{}.json", mint))
+    }
+
+    /// Load ledger from file, or start a new one if the file doesn't exist
+    pub fn load_or_new(dir: &Path, mint: &Pubkey, now: u64) -> Result<Self> {
+        let path = Self::path_for(dir, &mint.to_string());
+        if path.exists() {
+            Self::load(&path)
+        } else {
+            Ok(Self::new(mint, now))
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let data = fs::read(path)
+            .with_context(|| format!("Failed to read ledger {}", path.display()))?;
+        let file: LedgerFile = serde_json::from_slice(&data)
+            .with_context(|| format!("Invalid ledger file {}", path.display()))?;
+
+        let wallets = file
+            .wallets
+            .into_iter()
+            .filter_map(|(wallet, record)| Pubkey::from_str(&wallet).ok().map(|w| (w, record)))
+            .collect();
+
+        Ok(Self {
+            mint: file.mint,
+            created_at: file.created_at,
+            updated_at: file.updated_at,
+            wallets,
+        })
+    }
+
+    /// Save atomically, see [`save_json_atomically`]
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create ledger directory {}", dir.display()))?;
+        let file = LedgerFile {
+            mint: self.mint.clone(),
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            wallets: self
+                .wallets
+                .iter()
+                .map(|(wallet, record)| (wallet.to_string(), record.clone()))
+                .collect(),
+        };
+
+        save_json_atomically(&Self::path_for(dir, &self.mint), &file, "ledger")
+    }
+
+    /// Record the holder set of one poll
+    pub fn observe(&mut self, holders: &HashSet<Pubkey>, now: u64) -> ObserveResult {
+        let mut result = ObserveResult::default();
+
+        for (wallet, record) in self.wallets.iter_mut() {
+            let still_holding = holders.contains(wallet);
+            if still_holding && !record.holding {
+                record.returns += 1;
+                result.returned_wallets += 1;
+            }
+            if still_holding {
+                record.last_seen = now;
+            }
+            record.holding = still_holding;
+        }
+
+        for holder in holders {
+            self.wallets.entry(*holder).or_insert_with(|| {
+                result.new_wallets += 1;
+                WalletRecord {
+                    first_seen: now,
+                    last_seen: now,
+                    holding: true,
+                    returns: 0,
+                }
+            });
+        }
+
+        self.updated_at = now;
+        result
+    }
+
+    /// Unique-holders-ever and first-time vs returning breakdown
+    pub fn summary(&self) -> LedgerSummary {
+        let mut summary = LedgerSummary {
+            unique_holders_ever: self.wallets.len(),
+            ..Default::default()
+        };
+        for record in self.wallets.values() {
+            match (record.holding, record.returns > 0) {
+                (true, false) => summary.first_time_holders += 1,
+                (true, true) => summary.returning_holders += 1,
+                (false, _) => summary.former_holders += 1,
+            }
+        }
+        summary.current_holders = summary.first_time_holders + summary.returning_holders;
+        summary
+    }
+
+    /// Cohort retention by period (e.g. weekly)
+    ///
+    /// A wallet counts as retained in period N+k if it was seen holding in that period or
+    /// later, i.e. gaps between polls are treated as continuous holding.
+    pub fn cohorts(&self, period_secs: u64) -> Vec<Cohort> {
+        let period_secs = period_secs.max(1);
+        let origin = self.created_at;
+        let period_of = |ts: u64| (ts.saturating_sub(origin) / period_secs) as usize;
+        let current_period = period_of(self.updated_at);
+
+        let mut by_period: Vec<Vec<&WalletRecord>> = vec![Vec::new(); current_period + 1];
+        for record in self.wallets.values() {
+            let period = period_of(record.first_seen).min(current_period);
+            by_period[period].push(record);
+        }
+
+        by_period
+            .into_iter()
+            .enumerate()
+            .filter(|(_, members)| !members.is_empty())
+            .map(|(period, members)| {
+                let retention_percent = (period..=current_period)
+                    .map(|later| {
+                        let later_start = origin + later as u64 * period_secs;
+                        let retained = members.iter().filter(|r| r.last_seen >= later_start).count();
+                        retained as f64 / members.len() as f64 * 100.0
+                    })
+                    .collect();
+                Cohort {
+                    period_start: origin + period as u64 * period_secs,
+                    size: members.len(),
+                    retention_percent,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Print cohort retention table for the `cohorts` subcommand
+pub fn print_cohort_report(ledger: &HolderLedger, period_secs: u64, json: bool) -> Result<()> {
+    let cohorts = ledger.cohorts(period_secs);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&cohorts)?);
+        return Ok(());
+    }
+
+    println!("Cohort retention for {} ({} wallets ever held)", ledger.mint, ledger.wallets.len());
+    print!("{:<12} {:>8}", "COHORT", "SIZE");
+    let max_len = cohorts.iter().map(|c| c.retention_percent.len()).max().unwrap_or(0);
+    for k in 0..max_len {
+        let label = format!("+{}", k);
+        print!(" {:>7}", label);
+    }
+    println!();
+
+    for cohort in &cohorts {
+        let start = crate::token_monitor::format_timestamp(cohort.period_start);
+        print!("{:<12} {:>8}", &start[..10], cohort.size);
+        for percent in &cohort.retention_percent {
+            print!(" {:>6.1}%", percent);
+        }
+        println!();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_observe_and_cohorts() {
+        let mint = Pubkey::new_unique();
+        let a = Pubkey::new_unique();
+        let b = Pubkey::new_unique();
+        let c = Pubkey::new_unique();
+        let day = 86_400;
+
+        let mut ledger = HolderLedger::new(&mint, 0);
+        ledger.observe(&[a, b].into_iter().collect(), 0);
+        ledger.observe(&[a, c].into_iter().collect(), day);
+        ledger.observe(&[a].into_iter().collect(), 2 * day);
+
+        assert_eq!(ledger.wallets.len(), 3);
+        assert!(ledger.wallets[&a].holding);
+        assert!(!ledger.wallets[&b].holding);
+        assert_eq!(ledger.wallets[&c].first_seen, day);
+
+        let summary = ledger.summary();
+        assert_eq!(summary.unique_holders_ever, 3);
+        assert_eq!(summary.current_holders, 1);
+        assert_eq!(summary.former_holders, 2);
+
+        let cohorts = ledger.cohorts(day);
+        assert_eq!(cohorts.len(), 2);
+        // Day 0 cohort {a, b}: both at day 0, only a afterwards
+        assert_eq!(cohorts[0].size, 2);
+        assert_eq!(cohorts[0].retention_percent, vec![100.0, 50.0, 50.0]);
+        // Day 1 cohort {c}: gone on day 2
+        assert_eq!(cohorts[1].retention_percent, vec![100.0, 0.0]);
+    }
+
+    #[test]
+    fn test_returning_holders() {
+        let mint = Pubkey::new_unique();
+        let a = Pubkey::new_unique();
+        let b = Pubkey::new_unique();
+
+        let mut ledger = HolderLedger::new(&mint, 0);
+        let result = ledger.observe(&[a, b].into_iter().collect(), 0);
+        assert_eq!(result.new_wallets, 2);
+
+        ledger.observe(&[a].into_iter().collect(), 1);
+        let result = ledger.observe(&[a, b].into_iter().collect(), 2);
+        assert_eq!(result, ObserveResult { new_wallets: 0, returned_wallets: 1 });
+
+        let summary = ledger.summary();
+        assert_eq!(summary.first_time_holders, 1);
+        assert_eq!(summary.returning_holders, 1);
+        assert_eq!(summary.former_holders, 0);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = std::env::temp_dir().join(format!("solana-holder-bot-ledger-{}", std::process::id()));
+        let mint = Pubkey::new_unique();
+        let holder = Pubkey::new_unique();
+
+        let mut ledger = HolderLedger::new(&mint, 10);
+        ledger.observe(&[holder].into_iter().collect(), 20);
+        ledger.save(&dir).unwrap();
+
+        let loaded = HolderLedger::load_or_new(&dir, &mint, 99).unwrap();
+        assert_eq!(loaded.created_at, 10);
+        assert_eq!(loaded.updated_at, 20);
+        assert_eq!(loaded.wallets[&holder], ledger.wallets[&holder]);
+        // Saving again replaces the file and leaves no temp file behind
+        ledger.save(&dir).unwrap();
+        let path = HolderLedger::path_for(&dir, &mint.to_string());
+        assert!(!path.with_extension("json.tmp").exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,11 +1,72 @@
+pub mod allocation;
 pub mod api;
+pub mod api_keys;
+pub mod build_info;
+pub mod churn;
 pub mod cli;
+pub mod cluster;
+pub mod community;
+pub mod config;
+pub mod dashboard;
+pub mod delegations;
+pub mod diagnostics;
+pub mod discovery;
+pub mod doctor;
+pub mod events;
+pub mod export;
+pub mod extensions;
+pub mod forecast;
+pub mod health;
+pub mod history;
+pub mod http_tuning;
+pub mod i18n;
+pub mod latency;
+pub mod launch;
+pub mod leaderboard;
+pub mod ledger;
+pub mod locale;
+pub mod logging;
+pub mod memory;
+pub mod merkle;
+pub mod metadata;
+pub mod milestones;
+pub mod multisig;
+pub mod notifier;
+pub mod output;
+pub mod owners;
+pub mod profile;
+pub mod prometheus;
+pub mod protocols;
+pub mod publisher;
+pub mod quality;
+pub mod quiet_hours;
+pub mod redact;
+pub mod replay;
+pub mod retry_budget;
+pub mod scheduler;
 pub mod rpc_client;
+pub mod rpc_logging;
+pub mod rpc_stats;
+pub mod setup;
+pub mod signals;
+pub mod signing;
+pub mod snapshots;
+pub mod sinks;
+pub mod summary;
+pub mod systemd;
+pub mod templates;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
+pub mod tiers;
 pub mod token_monitor;
+pub mod truncation;
+pub mod update_check;
+pub mod verify;
+pub mod watch;
 
 pub use cli::Cli;
 pub use rpc_client::SolanaRpcClient;
 pub use token_monitor::{
-    check_alerts, calculate_stats, extract_holders, format_timestamp, HolderStats, Metrics,
+    check_alerts, calculate_stats, extract_balances, extract_holders, format_timestamp, HolderStats, Metrics,
 };
 
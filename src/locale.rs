@@ -0,0 +1,179 @@
+//! Timezone, number formatting and language for the status line and notifier messages
+
+use chrono::{TimeZone, Utc};
+use chrono_tz::Tz;
+use clap::ValueEnum;
+
+use crate::i18n::{Language, Messages};
+
+/// Parse an IANA timezone name (`Europe/Berlin`) for `--timezone`
+pub fn parse_timezone(name: &str) -> Result<Tz, String> {
+    name.parse::<Tz>()
+        .map_err(|_| format!("unknown timezone '{}' (expected an IANA name like Europe/Berlin)", name))
+}
+
+/// Digit grouping and decimal separator convention
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum NumberLocale {
+    /// No grouping: 1234567.8
+    #[default]
+    Raw,
+    /// 1,234,567.8
+    En,
+    /// 1.234.567,8
+    De,
+    /// 1 234 567,8 (narrow no-break space)
+    Fr,
+    /// 1 234 567,8 (no-break space)
+    Ru,
+    /// 1'234'567.8
+    Ch,
+}
+
+impl NumberLocale {
+    /// (thousands separator, decimal separator)
+    fn separators(self) -> (Option<char>, char) {
+        match self {
+            Self::Raw => (None, '.'),
+            Self::En => (Some(','), '.'),
+            Self::De => (Some('.'), ','),
+            Self::Fr => (Some('\u{202f}'), ','),
+            Self::Ru => (Some('\u{a0}'), ','),
+            Self::Ch => (Some('\''), '.'),
+        }
+    }
+}
+
+/// Output formatting settings (`--timezone`, `--number-format`, `--language`)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Locale {
+    /// None = UTC
+    pub timezone: Option<Tz>,
+    pub numbers: NumberLocale,
+    pub language: Language,
+}
+
+impl Locale {
+    /// Alert and notification texts in the configured language
+    pub fn messages(&self) -> &'static Messages {
+        self.language.messages()
+    }
+
+    /// `2023-12-11 20:00:00 UTC` or the same in the configured zone (`... CET`)
+    pub fn format_timestamp(&self, secs: u64) -> String {
+        let utc = Utc
+            .timestamp_opt(secs as i64, 0)
+            .single()
+            .unwrap_or_default();
+        match self.timezone {
+            Some(tz) => utc.with_timezone(&tz).format("%Y-%m-%d %H:%M:%S %Z").to_string(),
+            None => utc.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+        }
+    }
+
+    /// Widest `format_timestamp` output in the configured zone, for aligned columns (the zone
+    /// abbreviation may change length with daylight saving time: `CET` / `CEST`)
+    pub fn timestamp_width(&self) -> usize {
+        // Mid-winter and mid-summer
+        [1_704_067_200, 1_719_792_000]
+            .into_iter()
+            .map(|secs| self.format_timestamp(secs).chars().count())
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Integer with thousands separators (sign kept)
+    pub fn format_int(&self, value: i64) -> String {
+        let digits = value.unsigned_abs().to_string();
+        let grouped = match self.numbers.separators().0 {
+            Some(separator) => group_digits(&digits, separator),
+            None => digits,
+        };
+        if value < 0 {
+            format!("-{}", grouped)
+        } else {
+            grouped
+        }
+    }
+
+    pub fn format_count(&self, value: usize) -> String {
+        self.format_int(value as i64)
+    }
+
+    /// Number with `decimals` digits after the locale's decimal separator
+    pub fn format_float(&self, value: f64, decimals: usize) -> String {
+        let raw = format!("{:.*}", decimals, value.abs());
+        let (int_part, frac_part) = raw.split_once('.').unwrap_or((&raw, ""));
+        let (thousands, decimal) = self.numbers.separators();
+        let mut out = match thousands {
+            Some(separator) => group_digits(int_part, separator),
+            None => int_part.to_string(),
+        };
+        if !frac_part.is_empty() {
+            out.push(decimal);
+            out.push_str(frac_part);
+        }
+        if value < 0.0 && raw.chars().any(|c| c.is_ascii_digit() && c != '0') {
+            out.insert(0, '-');
+        }
+        out
+    }
+
+    /// Signed percentage with one decimal: `+50.0%`, `-20.0%`
+    pub fn format_percent(&self, value: f64) -> String {
+        let number = self.format_float(value, 1);
+        if number.starts_with('-') {
+            format!("{}%", number)
+        } else {
+            format!("+{}%", number)
+        }
+    }
+}
+
+fn group_digits(digits: &str, separator: char) -> String {
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            out.push(separator);
+        }
+        out.push(digit);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_number_formatting() {
+        let en = Locale { numbers: NumberLocale::En, ..Locale::default() };
+        let de = Locale { numbers: NumberLocale::De, ..Locale::default() };
+
+        assert_eq!(Locale::default().format_count(1234567), "1234567");
+        assert_eq!(en.format_count(1234567), "1,234,567");
+        assert_eq!(en.format_int(-1234), "-1,234");
+        assert_eq!(en.format_count(999), "999");
+        assert_eq!(de.format_float(12345.678, 1), "12.345,7");
+        assert_eq!(de.format_float(-20.0, 1), "-20,0");
+        assert_eq!(en.format_float(-0.01, 1), "0.0");
+        assert_eq!(de.format_percent(-20.0), "-20,0%");
+        assert_eq!(en.format_percent(1234.56), "+1,234.6%");
+    }
+
+    #[test]
+    fn test_timezone() {
+        let utc = Locale::default();
+        assert_eq!(utc.format_timestamp(1702324800), "2023-12-11 20:00:00 UTC");
+        assert_eq!(utc.format_timestamp(1702324800), crate::token_monitor::format_timestamp(1702324800));
+
+        let berlin = Locale {
+            timezone: Some(parse_timezone("Europe/Berlin").unwrap()),
+            ..Locale::default()
+        };
+        assert_eq!(berlin.format_timestamp(1702324800), "2023-12-11 21:00:00 CET");
+        assert_eq!(utc.timestamp_width(), 23);
+        assert_eq!(berlin.timestamp_width(), "2024-07-01 02:00:00 CEST".len());
+        assert!(parse_timezone("Mars/Olympus").is_err());
+    }
+}
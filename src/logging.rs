@@ -0,0 +1,227 @@
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::{fmt, prelude::*, EnvFilter};
+
+use crate::cli::Cli;
+
+/// Log file rotation policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LogRotation {
+    /// New file every hour (`bot.log.2024-01-15-10`)
+    Hourly,
+    /// New file every day (`bot.log.2024-01-15`)
+    Daily,
+    /// Rotate when the file exceeds `--log-max-size-mb` (`bot.log.1`, `bot.log.2`, ...)
+    Size,
+    /// Single file, never rotated
+    Never,
+}
+
+/// Initialize tracing: stdout plus optional rotating log file
+/// The returned guard must be kept alive for the whole program, otherwise buffered
+/// file output is lost on exit
+pub fn init_logging(cli: &Cli) -> Result<Option<WorkerGuard>> {
+    let stdout_layer = if cli.json_log {
+        fmt::layer().json().boxed()
+    } else {
+        fmt::layer().boxed()
+    };
+
+    let (file_layer, guard) = match &cli.log_file {
+        Some(path) => {
+            let (writer, guard) = match cli.log_rotation {
+                LogRotation::Size => tracing_appender::non_blocking(SizeRotatingWriter::new(
+                    path.clone(),
+                    cli.log_max_size_mb * 1024 * 1024,
+                    cli.log_max_files,
+                )?),
+                rotation => tracing_appender::non_blocking(time_rotating_appender(
+                    path,
+                    rotation,
+                    cli.log_max_files,
+                )?),
+            };
+            let layer = if cli.json_log {
+                fmt::layer().json().with_writer(writer).boxed()
+            } else {
+                fmt::layer().with_ansi(false).with_writer(writer).boxed()
+            };
+            (Some(layer), Some(guard))
+        }
+        None => (None, None),
+    };
+
+    let filter = match verbosity_directive(cli.quiet, cli.verbose) {
+        Some(directive) => EnvFilter::new(directive),
+        None => EnvFilter::from_default_env(),
+    };
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(stdout_layer)
+        .with(file_layer)
+        .init();
+
+    Ok(guard)
+}
+
+/// Log filter chosen by `-q` / `-v` / `-vv`
+/// None means no flag was given and `RUST_LOG` applies as before
+pub fn verbosity_directive(quiet: bool, verbose: u8) -> Option<&'static str> {
+    match (quiet, verbose) {
+        // Alerts are logged at warn level, so quiet still shows them
+        (true, _) => Some("warn"),
+        (false, 0) => None,
+        (false, 1) => Some("info,solana_holder_bot=debug"),
+        (false, _) => Some("debug,solana_holder_bot=trace"),
+    }
+}
+
+/// Time-based rotation via tracing-appender
+fn time_rotating_appender(
+    path: &Path,
+    rotation: LogRotation,
+    max_files: usize,
+) -> Result<RollingFileAppender> {
+    let dir = path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    let file_name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .context("Log file path must include a file name")?;
+
+    let rotation = match rotation {
+        LogRotation::Hourly => Rotation::HOURLY,
+        LogRotation::Daily => Rotation::DAILY,
+        LogRotation::Never | LogRotation::Size => Rotation::NEVER,
+    };
+
+    RollingFileAppender::builder()
+        .rotation(rotation)
+        .filename_prefix(file_name)
+        .max_log_files(max_files)
+        .build(dir)
+        .with_context(|| format!("Failed to open log file {}", path.display()))
+}
+
+/// Writer that rotates `bot.log` -> `bot.log.1` -> ... -> `bot.log.N` by size
+/// (tracing-appender only supports time-based rotation)
+pub struct SizeRotatingWriter {
+    path: PathBuf,
+    max_bytes: u64,
+    max_files: usize,
+    file: File,
+    written: u64,
+}
+
+impl SizeRotatingWriter {
+    pub fn new(path: PathBuf, max_bytes: u64, max_files: usize) -> Result<Self> {
+        if let Some(dir) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create log directory {}", dir.display()))?;
+        }
+        let file = Self::open(&path)
+            .with_context(|| format!("Failed to open log file {}", path.display()))?;
+        let written = file.metadata().map(|m| m.len()).unwrap_or(0);
+
+        Ok(Self {
+            path,
+            max_bytes,
+            max_files,
+            file,
+            written,
+        })
+    }
+
+    fn open(path: &Path) -> io::Result<File> {
+        OpenOptions::new().create(true).append(true).open(path)
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut name = self.path.as_os_str().to_owned();
+        name.push(format!(".{}", index));
+        PathBuf::from(name)
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+
+        if self.max_files == 0 {
+            // Nothing is kept: just start the file over
+            self.file = OpenOptions::new()
+                .create(true)
+                .write(true)
+                .truncate(true)
+                .open(&self.path)?;
+        } else {
+            // Shift bot.log.(N-1) -> bot.log.N, ..., bot.log -> bot.log.1
+            let _ = fs::remove_file(self.rotated_path(self.max_files));
+            for index in (1..self.max_files).rev() {
+                let from = self.rotated_path(index);
+                if from.exists() {
+                    fs::rename(&from, self.rotated_path(index + 1))?;
+                }
+            }
+            fs::rename(&self.path, self.rotated_path(1))?;
+            self.file = Self::open(&self.path)?;
+        }
+
+        self.written = 0;
+        Ok(())
+    }
+}
+
+impl Write for SizeRotatingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.written > 0 && self.written + buf.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        let n = self.file.write(buf)?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verbosity_directive() {
+        assert_eq!(verbosity_directive(false, 0), None);
+        assert_eq!(verbosity_directive(true, 0), Some("warn"));
+        assert_eq!(verbosity_directive(false, 1), Some("info,solana_holder_bot=debug"));
+        assert_eq!(verbosity_directive(false, 5), Some("debug,solana_holder_bot=trace"));
+    }
+
+    #[test]
+    fn test_size_rotation() {
+        let dir = std::env::temp_dir().join(format!("solana-holder-bot-log-{}", std::process::id()));
+        let path = dir.join("bot.log");
+        let mut writer = SizeRotatingWriter::new(path.clone(), 10, 2).unwrap();
+
+        writer.write_all(b"0123456789").unwrap();
+        writer.write_all(b"abcdefghij").unwrap();
+        writer.write_all(b"ABCDEFGHIJ").unwrap();
+        writer.write_all(b"last").unwrap();
+        writer.flush().unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "last");
+        assert_eq!(fs::read_to_string(dir.join("bot.log.1")).unwrap(), "ABCDEFGHIJ");
+        assert_eq!(fs::read_to_string(dir.join("bot.log.2")).unwrap(), "abcdefghij");
+        assert!(!dir.join("bot.log.3").exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
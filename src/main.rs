@@ -1,60 +1,231 @@
 use anyhow::{Context, Result};
-use clap::Parser;
 use solana_holder_bot::{
-    api::HolderCache,
-    check_alerts, calculate_stats, extract_holders, format_timestamp, Cli, Metrics,
+    allocation::{pro_rata, write_allocation},
+    api::{HolderCache, MonitorMetrics},
+    api_keys::ApiKeys,
+    build_info::BuildInfo,
+    churn::diff_holders,
+    cli::{render_effective_config, write_completions, write_man_page, Command},
+    cluster::Cluster,
+    community::{format_community, CommunityTracker},
+    config::Config,
+    delegations::{check_delegations, delegation_stats, DelegationAlerts},
+    discovery::spawn_discovery,
+    doctor,
+    events::{AlertEvent, EventBus, HolderEvent, Observation},
+    export::load_snapshot,
+    history::{check_records, CountHistory, TierHistory},
+    http_tuning::HttpTuning,
+    i18n::fill,
+    latency::{LatencySlo, SloEvent},
+    launch::LaunchWindow,
+    ledger::{print_cohort_report, HolderLedger, LedgerSummary},
+    locale::Locale,
+    logging::init_logging,
+    memory::{format_mb, holder_set_bytes, ledger_bytes, MemoryUsage},
+    merkle::{allocate, write_airdrop, MerkleAirdrop},
+    milestones::{check_milestone, MilestoneTracker},
+    notifier::{Channel, EscalationTracker, Notification, Notifier, SharedNotifier},
+    output::ConsoleOutput,
+    prometheus::AlertRuleSettings,
+    publisher::EventPublisher,
+    quality::analyze_holders,
+    redact::redact_url,
+    replay::{load_observations, replay},
+    retry_budget::RetryBudget,
+    rpc_client::{AccountSnapshot, ProgramAccountsSupport},
+    scheduler::{PollingConfig, Priority, Scheduler},
+    setup,
+    signals::ControlSignals,
+    signing::{verify_file, write_sidecars, SnapshotSigner},
+    snapshots::{spawn_snapshots, SnapshotSettings},
+    sinks::SinkRegistry,
+    systemd,
+    templates::HeartbeatMint,
+    token_monitor::{is_drop, AlertRule, MetricsSummary},
+    verify::{print_verify_report, verify_mint, ExplorerKeys},
+    tiers::TierSchedule,
+    watch::WalletWatch,
+    check_alerts, calculate_stats, extract_balances, Cli, Metrics,
     SolanaRpcClient,
 };
 use solana_sdk::pubkey::Pubkey;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use std::sync::Arc;
-use tokio::signal;
-use tokio::time::{interval, Duration};
-use tracing::{error, info, warn};
+use std::time::Instant;
+use tokio::task::JoinSet;
+use tokio::time::{interval, interval_at, sleep_until, Duration};
+use tracing::{debug, error, info, warn};
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let cli = Cli::parse();
+    solana_holder_bot::health::mark_started();
+    let (mut cli, matches) = Cli::parse_with_matches();
+    if cli.print_config {
+        print!("{}", render_effective_config(&matches));
+        return Ok(());
+    }
+    if cli.man {
+        return write_man_page(&mut std::io::stdout()).context("Failed to write man page");
+    }
+    // `doctor` reports invalid settings instead of stopping at the first one
+    if let Some(Command::Doctor(args)) = &cli.command {
+        let json = args.json;
+        return doctor::run(cli, json).await;
+    }
     
     // Validate CLI arguments
+    cli.load_secret_files().context("Failed to load secrets")?;
     cli.validate().context("Invalid CLI arguments")?;
 
-    // Initialize logging
-    if cli.json_log {
-        tracing_subscriber::fmt()
-            .json()
-            .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
-            .init();
-    } else {
-        tracing_subscriber::fmt()
-            .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
-            .init();
+    // Initialize logging (stdout + optional rotating log file)
+    // Guard flushes buffered file output on exit
+    let _log_guard = init_logging(&cli).context("Failed to initialize logging")?;
+
+    // Offline subcommands run instead of the monitoring loop
+    if let Some(command) = &cli.command {
+        return run_command(&cli, command).await;
     }
 
-    // Parse mint address
-    let mint = cli.parse_mint().context("Failed to parse mint address")?;
-    info!("Monitoring token: {}", mint);
+    info!("solana-holder-bot {}", BuildInfo::current().describe());
+    // Long-running monitors only: `--once` and replays exit before the answer matters
+    if !cli.no_update_check && !cli.once && cli.replay.is_none() {
+        solana_holder_bot::update_check::spawn();
+    }
+
+    // Parse mint addresses
+    let mut mints = cli.parse_mints().context("Failed to parse mint address")?;
+    let priorities = cli.parse_priorities().context("Failed to parse --priority")?;
+    let config = match &cli.config {
+        Some(path) => Config::load(path)?,
+        None => Config::default(),
+    };
+    for mint in &mints {
+        info!("Monitoring token: {}", mint);
+    }
+
+    // Backtest: recorded observations instead of RPC polling
+    if let Some(path) = &cli.replay {
+        let [mint] = mints.as_slice() else {
+            return Err(anyhow::anyhow!("--replay supports a single MINT_ADDRESS"));
+        };
+        let observations = load_observations(path)?;
+        let console = ConsoleOutput::new(cli.output, cli.no_color, cli.quiet).with_locale(cli.locale());
+        console.print_header();
+        let mut metrics = Metrics::new();
+        let mut milestones = MilestoneTracker::new(cli.milestones.clone(), cli.milestone_step);
+        replay(&observations, cli.replay_speed, &mint.to_string(), &console, &mut metrics, &mut milestones).await;
+        print_final_metrics(&metrics, mint);
+        return Ok(());
+    }
 
     // Initialize RPC client
-    let rpc_client = Arc::new(SolanaRpcClient::new(
-        cli.rpc_url.clone(),
-        cli.max_retries,
-        cli.timeout,
-    ));
+    let token_program = cli
+        .parse_token_program()
+        .context("Failed to parse token program")?;
+    let rpc_client = Arc::new(
+        SolanaRpcClient::new(cli.rpc_url(), cli.max_retries, cli.timeout)
+            .with_token_program(token_program)
+            .with_truncation_check(cli.truncation_check, cli.truncation_tolerance_percent)
+            .with_zero_balance_holders(cli.include_zero_balance)
+            .with_retry_budget(
+                cli.retry_budget
+                    .map(|limit| RetryBudget::new(limit, Duration::from_secs(cli.retry_budget_window))),
+            )
+            .with_http_tuning(&HttpTuning::from_cli(&cli))?,
+    );
 
-    // Health check
+    // Health check and capability probe: a misconfigured RPC fails here, not after the first interval
     info!("Performing RPC health check...");
-    rpc_client
-        .health_check()
+    let capabilities = rpc_client
+        .probe_capabilities()
         .await
         .context("RPC health check failed. Please check your RPC URL")?;
-    info!("RPC connection healthy");
+    info!("RPC connection healthy (latency {}ms)", capabilities.latency_ms);
+    match &capabilities.program_accounts {
+        ProgramAccountsSupport::Supported => info!(
+            "RPC supports getProgramAccounts with token program filters (probe {}ms)",
+            capabilities.program_accounts_latency_ms.unwrap_or_default()
+        ),
+        ProgramAccountsSupport::Rejected { .. } => warn!(
+            "RPC rejects getProgramAccounts for the token program: running in degraded mode \
+            (DAS getTokenAccounts or the largest accounts only). Use a private RPC for exact counts"
+        ),
+        ProgramAccountsSupport::TimedOut => {
+            return Err(anyhow::anyhow!(
+                "RPC did not answer a minimal getProgramAccounts query within {}s; polls would time out too. \
+                Use a faster RPC or raise --timeout",
+                cli.timeout
+            ))
+        }
+        ProgramAccountsSupport::Failed { error } => {
+            return Err(anyhow::anyhow!("RPC failed a minimal getProgramAccounts query: {}", error))
+        }
+    }
 
-    // Start API server if enabled
+    // Make sure the RPC serves the expected cluster (a devnet URL with a mainnet mint reports 0 holders)
+    match cli.cluster {
+        Some(cluster) => {
+            let genesis = rpc_client.get_genesis_hash().await?;
+            cluster
+                .verify_genesis(&genesis)
+                .context("RPC cluster mismatch. Please check --rpc-url and --cluster")?;
+            info!("RPC serves expected cluster: {:?}", cluster);
+        }
+        None => match rpc_client.get_genesis_hash().await {
+            Ok(genesis) => match Cluster::from_genesis_hash(&genesis) {
+                Some(cluster) => info!("RPC cluster: {:?}", cluster),
+                None => info!("RPC cluster: unknown (genesis {})", genesis),
+            },
+            Err(e) => warn!("Could not detect RPC cluster: {}", e),
+        },
+    }
+    systemd::notify_ready();
+
+    // Per-mint session metrics, shared with the API (GET /monitor/:mint/metrics)
+    let monitor_metrics = MonitorMetrics::default();
+    monitor_metrics
+        .write()
+        .await
+        .extend(mints.iter().map(|mint| (mint.to_string(), MetricsSummary::default())));
+
+    // Poll results, alerts and failures go through the bus to the console, API cache,
+    // notifiers, brokers and `GET /events`
+    let bus = EventBus::default();
+
+    // Watched wallets (`--watch-wallet`, `POST /watch/:mint/:wallet`), updated by every poll
+    let watched_wallets = cli
+        .watch_wallets
+        .iter()
+        .map(|wallet| Pubkey::from_str(wallet).map_err(|e| anyhow::anyhow!("Invalid --watch-wallet '{}': {}", wallet, e)))
+        .collect::<Result<Vec<_>>>()?;
+    let watch = Arc::new(WalletWatch::new(watched_wallets, cli.watch_webhook_url.clone()));
+
+    // Start API server if enabled; the monitoring loop publishes its polls into the API cache
+    let mut api_cache = None;
     if cli.api_server {
-        let cache = Arc::new(HolderCache::new(rpc_client.clone(), cli.cache_ttl));
+        let api_keys = ApiKeys::new(&cli.api_keys, &config.api_keys).context("Invalid API keys")?;
+        if cli.require_api_key && api_keys.is_empty() {
+            return Err(anyhow::anyhow!("--require-api-key needs --api-key or [api_keys.*] in the config file"));
+        }
+        let cache = Arc::new(
+            HolderCache::new(rpc_client.clone(), cli.cache_ttl, cli.cache_max_tokens)
+                .with_refresh_concurrency(cli.cache_refresh_concurrency)
+                .with_ledger_dir(cli.ledger_dir.clone())
+                .with_memory_limit(cli.max_memory_mb.map(|mb| mb * 1024 * 1024))
+                .with_monitor_metrics(monitor_metrics.clone())
+                .with_event_bus(bus.clone())
+                .with_wallet_watch(watch.clone())
+                .with_api_keys(api_keys)
+                .with_required_api_key(cli.require_api_key)
+                .with_protocols(config.protocols.clone())
+                .with_monitored_mints(mints.iter().map(|mint| mint.to_string())),
+        );
         cache.start_refresh_task();
-        
+        api_cache = Some(cache.clone());
+
         let api_port = cli.api_port;
         tokio::spawn(async move {
             if let Err(e) = solana_holder_bot::api::start_api_server(cache, api_port).await {
@@ -64,49 +235,623 @@ async fn main() -> Result<()> {
         info!("🚀 API server enabled on port {} (cache refresh: {}s)", api_port, cli.cache_ttl);
     }
 
-    // Graceful shutdown handling
-    let shutdown = Arc::new(AtomicBool::new(false));
-    let shutdown_clone = shutdown.clone();
+    // Event publishing to message brokers (Kafka / NATS)
+    let publisher = EventPublisher::from_cli(&cli)
+        .await
+        .context("Failed to initialize event publisher")?
+        .map(Arc::new);
 
-    tokio::spawn(async move {
-        match signal::ctrl_c().await {
-            Ok(()) => {
-                info!("Received shutdown signal (Ctrl+C)");
-                shutdown_clone.store(true, Ordering::SeqCst);
-            }
-            Err(err) => {
-                error!("Failed to listen for shutdown signal: {}", err);
-            }
+    // Scheduled holder snapshots to object storage (audit trail)
+    if let Some(settings) = SnapshotSettings::from_cli(&cli) {
+        if cli.once {
+            warn!("--s3-bucket is ignored with --once: snapshots run on a schedule");
+        } else {
+            spawn_snapshots(settings, rpc_client.clone(), mints.clone()).context("Failed to start holder snapshots")?;
         }
-    });
+    }
 
-    // Monitoring loop
-    let mut metrics = Metrics::new();
-    let mut previous_count: Option<usize> = None;
-    let poll_interval = Duration::from_secs(cli.interval);
-    let mut interval_timer = interval(poll_interval);
+    // Chat / webhook notifications (alerts and the shutdown report)
+    let mut notifier = Notifier::from_cli(&cli, &config)
+        .context("Failed to initialize notifier")?
+        .map(Arc::new);
+    // Swapped on SIGHUP; the alert sink reads the notifier from here
+    let shared_notifier = SharedNotifier::new(notifier.clone());
+    if cli.dry_run_alerts && notifier.is_none() {
+        warn!("--dry-run-alerts has no effect: no notifier channel is configured");
+    }
 
+    // Graceful shutdown (Ctrl+C / SIGTERM) and reload (SIGHUP) handling
+    let signals = ControlSignals::new();
+    signals.spawn_handlers();
+
+    // Monitoring loop: one state per mint, polls spread over the interval
+    let mut tiers = tier_schedules(&rpc_client, &config, &mints).await?;
+    let mut states = HashMap::new();
+    for mint in &mints {
+        let mut state = MonitorState::new(&cli, mint)?;
+        state.watch = Some(watch.clone());
+        state.set_tiers(mint, tiers.remove(mint))?;
+        states.insert(*mint, state);
+    }
+    warn_unmonitored(&config, &mints);
+    let poll_interval = Duration::from_secs(cli.interval);
+    let stagger = cli.stagger();
+    if mints.len() > 1 {
+        info!(
+            "Polls of mints sharing an interval spread over {}{}",
+            stagger.window.map_or("the whole interval".to_string(), |window| format!("{}s", window.as_secs())),
+            if stagger.align { ", aligned to the wall clock" } else { "" }
+        );
+    }
+    let mut scheduler = Scheduler::staggered(
+        mints
+            .iter()
+            .map(|mint| {
+                let (interval, class_priority) = config.polling.schedule_of(&mint.to_string(), poll_interval);
+                if interval != poll_interval {
+                    info!("Polling {} every {}s", mint, interval.as_secs());
+                }
+                (*mint, priorities.get(mint).copied().unwrap_or(class_priority), interval)
+            })
+            .collect(),
+        cli.max_concurrent_fetches,
+        Instant::now(),
+        stagger,
+        Duration::from_secs(unix_now()),
+    );
+    if let Some(launch) = LaunchWindow::from_cli(&cli, unix_now()).filter(|launch| !launch.is_over(unix_now())) {
+        let (from, until) = launch.instants(Instant::now(), unix_now());
+        info!(
+            "Launch mode: polling every {}s from {} until {}, then back to the normal intervals",
+            launch.interval.as_secs(),
+            launch.start,
+            launch.end
+        );
+        scheduler = scheduler.with_boost(launch.interval, from, until, Instant::now());
+    }
+    let mut scheduler_intervals: HashMap<Pubkey, u64> = mints
+        .iter()
+        .filter_map(|mint| scheduler.interval_of(mint).map(|interval| (*mint, interval.as_secs())))
+        .collect();
+    if cli.once {
+        scheduler.poll_now(Instant::now());
+    }
+    let mut polls = JoinSet::new();
+    let mut poll_tracker = PollTracker::default();
+    // Tiers from a reload for mints that were being polled at the time
+    let mut reloaded_tiers: HashMap<Pubkey, Option<TierSchedule>> = HashMap::new();
+    // Heartbeats go to --heartbeat-url if set, otherwise to the notifier channels
+    let mut heartbeat_notifier = match &cli.heartbeat_url {
+        Some(url) => Some(Arc::new(
+            Notifier::new(vec![Channel::Webhook { url: url.clone() }]).with_dry_run(cli.dry_run_alerts),
+        )),
+        None => notifier.clone(),
+    };
+    let mut heartbeat = heartbeat_timer(&cli, &heartbeat_notifier);
+    let mut watchdog_ping = systemd::watchdog_interval().map(interval);
+    // Stall watchdog: no successful poll of a mint for --stall-factor intervals
+    let mut stall_check = (cli.stall_factor > 0 && !cli.once).then(|| interval(poll_interval));
+    let mut stalled_exit = false;
+    // p95 fetch latency of the RPC against --latency-slo-secs
+    let mut latency_slo = cli
+        .latency_slo_secs
+        .map(|slo| LatencySlo::new(slo, cli.latency_slo_windows, cli.latency_slo_window_secs));
+    check_latency_slo_channels(&cli, notifier.as_deref())?;
+    // Quiet hours: held-back alerts are sent as a digest once the quiet range ends
+    let mut digest_check = notifier
+        .as_ref()
+        .filter(|notifier| notifier.has_quiet_hours())
+        .map(|_| interval(DIGEST_CHECK_INTERVAL));
     info!(
-        "Starting monitoring loop (interval: {}s, RPC: {})",
-        cli.interval, cli.rpc_url
+        "Starting monitoring loop ({} mint(s), interval: {}s, max concurrent fetches: {}, RPC: {})",
+        mints.len(), cli.interval, cli.max_concurrent_fetches, rpc_client.rpc_url()
     );
-    info!("Press Ctrl+C to stop and view metrics");
+    info!("Press Ctrl+C (or send SIGTERM) to stop and view metrics");
 
-    // Initial poll
-    interval_timer.tick().await;
+    let locale = cli.locale();
+    let console = ConsoleOutput::new(cli.output, cli.no_color, cli.quiet).with_locale(locale);
+    console.print_header();
+
+    // Subscribed before the first poll so no event is missed
+    let mut registry = SinkRegistry::default();
+    registry.register(console);
+    if let Some(cache) = &api_cache {
+        registry.register(cache.clone());
+    }
+    if let Some(publisher) = &publisher {
+        registry.register(publisher.clone());
+    }
+    // With --config the notifier may appear on reload even if there is none yet
+    if notifier.is_some() || cli.config.is_some() {
+        registry.register(shared_notifier.clone());
+    }
+    // Union of the holders of every monitored mint (token + LP + staked derivative, ...)
+    let community = (cli.community_size && mints.len() > 1).then(|| Arc::new(CommunityTracker::new(mints.len(), console)));
+    if let Some(community) = &community {
+        registry.register(community.clone());
+    }
+    if let Some(url) = watch.webhook_url() {
+        info!("Watched wallet changes go to {}", redact_url(url));
+    }
+    if watch.global_wallets() > 0 {
+        info!("Watching {} wallet(s) on every monitored mint", watch.global_wallets());
+    }
+    registry.register(watch.clone());
+    registry.register_configured(&config.sinks).context("Invalid [sinks] config")?;
+    debug!("Event sinks: {}", registry.names().join(", "));
+    let sinks = registry.spawn(&bus);
+
+    // Fresh launches from [discovery], added to the schedule as they appear
+    let mut discovered = match &config.discovery {
+        Some(_) if cli.once => {
+            warn!("[discovery] is ignored with --once");
+            None
+        }
+        Some(discovery) => Some(spawn_discovery(discovery.clone(), cli.rpc_url(), rpc_client.clone(), mints.clone())),
+        None => None,
+    };
 
     loop {
-        if shutdown.load(Ordering::SeqCst) {
+        if signals.is_shutdown() {
             info!("Shutdown signal received, stopping monitoring...");
             break;
         }
 
-        match monitor_holders(&rpc_client, &mint, previous_count, &mut metrics).await {
+        if signals.take_reload() {
+            // SIGHUP: dump current metrics, re-read --config and poll immediately
+            for (mint, state) in mints.iter().filter_map(|mint| Some((mint, states.get(mint)?))) {
+                print_final_metrics(&state.metrics, mint);
+                print_ledger_summary(&state.ledger.summary());
+            }
+            match &cli.config {
+                Some(path) => match reload_config(&cli, path, &rpc_client, &mints).await {
+                    Ok(mut reloaded) => {
+                        let previous = shared_notifier.replace(reloaded.notifier.clone());
+                        notifier = reloaded.notifier;
+                        match (&notifier, previous) {
+                            // Held-back alerts moved to the new notifier: send them if it has no quiet hours
+                            (Some(notifier), _) => {
+                                let notifier = notifier.clone();
+                                tokio::spawn(async move { notifier.flush_digest(unix_now()).await });
+                            }
+                            (None, Some(previous)) => {
+                                tokio::spawn(async move { previous.send_digest(unix_now()).await });
+                            }
+                            (None, None) => {}
+                        }
+                        if cli.heartbeat_url.is_none() {
+                            heartbeat_notifier = notifier.clone();
+                            if heartbeat.is_none() {
+                                heartbeat = heartbeat_timer(&cli, &heartbeat_notifier);
+                            }
+                        }
+                        digest_check = notifier
+                            .as_ref()
+                            .filter(|notifier| notifier.has_quiet_hours())
+                            .map(|_| digest_check.take().unwrap_or_else(|| interval(DIGEST_CHECK_INTERVAL)));
+
+                        // Mints being polled get their tiers when the poll returns
+                        for mint in &mints {
+                            let tiers = reloaded.tiers.remove(mint);
+                            match states.get_mut(mint) {
+                                Some(state) => {
+                                    if let Err(e) = state.set_tiers(mint, tiers) {
+                                        error!("{:#}", e);
+                                    }
+                                }
+                                None => {
+                                    reloaded_tiers.insert(*mint, tiers);
+                                }
+                            }
+                            let (interval, class_priority) = reloaded.polling.schedule_of(&mint.to_string(), poll_interval);
+                            let priority = priorities.get(mint).copied().unwrap_or(class_priority);
+                            scheduler.set_schedule(mint, priority, interval, Instant::now());
+                            scheduler_intervals.insert(*mint, interval.as_secs());
+                        }
+                        info!("Reloaded {}, polling immediately", path.display());
+                    }
+                    Err(e) => error!("Config reload failed, keeping the running config: {:#}", e),
+                },
+                None => info!("Reload requested (no --config file to re-read), polling immediately"),
+            }
+            scheduler.poll_now(Instant::now());
+        }
+
+        for mint in scheduler.take_due(Instant::now()) {
+            let Some(mut state) = states.remove(&mint) else {
+                continue;
+            };
+            let rpc_client = rpc_client.clone();
+            let bus = bus.clone();
+            polls.spawn(async move {
+                let result = monitor_holders(&rpc_client, &mint, &mut state, &bus, &console).await;
+                (mint, state, result)
+            });
+        }
+
+        // --once: every mint polled
+        if scheduler.is_empty() && polls.is_empty() {
+            break;
+        }
+
+        // Pings only while no fetch is running, so a hung RPC call trips `WatchdogSec=`
+        let idle = polls.is_empty();
+        let wakeup = scheduler.next_wakeup();
+        tokio::select! {
+            Some(joined) = polls.join_next() => {
+                let (mint, mut state, result) = joined.context("Monitoring task panicked")?;
+                if let Some(tiers) = reloaded_tiers.remove(&mint) {
+                    if let Err(e) = state.set_tiers(&mint, tiers) {
+                        error!("{:#}", e);
+                    }
+                }
+                state.metrics.record_poll(result.is_ok(), unix_now());
+                monitor_metrics.write().await.insert(mint.to_string(), state.metrics.summary());
+                if let (Some(slo), Some(fetched)) = (&mut latency_slo, state.last_fetch.take()) {
+                    if let Some(event) = slo.record(&rpc_client.rpc_url(), unix_now(), fetched) {
+                        let message = latency_slo_message(&event, slo, &locale);
+                        warn!("{}", message);
+                        let notification = Notification::operational(None, &message, unix_now());
+                        if cli.latency_slo_channels.is_empty() {
+                            send_notification(&notifier, notification);
+                        } else if let Some(notifier) = notifier.clone() {
+                            let channels = cli.latency_slo_channels.clone();
+                            tokio::spawn(async move { notifier.notify_channels(&channels, &notification).await });
+                        }
+                    }
+                }
+                states.insert(mint, state);
+                for diagnostic in rpc_client.new_diagnostics() {
+                    warn!(
+                        code = diagnostic.code.as_str(),
+                        method = %diagnostic.method,
+                        suggested_action = diagnostic.suggested_action.as_str(),
+                        "RPC diagnostic: {}",
+                        diagnostic.message
+                    );
+                }
+                if let Err(e) = &result {
+                    bus.publish(HolderEvent::Error { mint, message: format!("{:#}", e), timestamp: unix_now() });
+                }
+                if poll_tracker.record(&mint, result) {
+                    info!("Polling of {} recovered", mint);
+                    let notification = Notification::operational(Some(&mint), locale.messages().polling_recovered, unix_now());
+                    send_notification(&notifier, notification);
+                }
+                if let Some(budget) = rpc_client.retry_budget() {
+                    if let Some(denied) = budget.take_exhaustion() {
+                        let message = fill(locale.messages().retry_budget_exhausted, &[
+                            ("denied", &denied.to_string()),
+                            ("budget", &budget.limit().to_string()),
+                            ("window", &budget.window().as_secs().to_string()),
+                        ]);
+                        warn!("{}", message);
+                        send_notification(&notifier, Notification::operational(None, &message, unix_now()));
+                    }
+                }
+                if cli.once {
+                    scheduler.remove(&mint);
+                } else {
+                    scheduler.complete(&mint, Instant::now());
+                }
+                // Cycle completed (successfully or not) - the process is not hung
+                systemd::notify_watchdog();
+            }
+            _ = sleep_until(wakeup.unwrap_or_else(Instant::now).into()), if wakeup.is_some() => {}
+            // Returns early on shutdown/reload so `docker stop` doesn't wait a full interval
+            _ = signals.woken() => {}
+            _ = async { watchdog_ping.as_mut().unwrap().tick().await }, if idle && watchdog_ping.is_some() => {
+                systemd::notify_watchdog();
+            }
+            _ = async { heartbeat.as_mut().unwrap().tick().await }, if heartbeat.is_some() => {
+                if let Some(notifier) = &heartbeat_notifier {
+                    let now = unix_now();
+                    let notification = notifier.heartbeat(poll_tracker.heartbeat_mints(&mints, now), now);
+                    send_notification(&heartbeat_notifier, notification);
+                }
+            }
+            _ = async { stall_check.as_mut().unwrap().tick().await }, if stall_check.is_some() => {
+                let now = unix_now();
+                let interval_secs = |mint: &Pubkey| scheduler_intervals.get(mint).copied().unwrap_or(cli.interval);
+                let stall_after_secs = |mint: &Pubkey| interval_secs(mint) * u64::from(cli.stall_factor);
+                for (mint, since) in poll_tracker.newly_stalled(&mints, now, stall_after_secs) {
+                    let message = fill(locale.messages().polling_stalled, &[
+                        ("secs", &now.saturating_sub(since).to_string()),
+                        ("factor", &cli.stall_factor.to_string()),
+                        ("interval", &interval_secs(&mint).to_string()),
+                    ]);
+                    error!("Monitoring of {} stalled: {}", mint, message);
+                    send_notification(&notifier, Notification::operational(Some(&mint), &message, now));
+                }
+                if cli.exit_on_stall && !poll_tracker.stalled.is_empty() {
+                    stalled_exit = true;
+                    break;
+                }
+            }
+            found = async { discovered.as_mut().unwrap().recv().await }, if discovered.is_some() => {
+                let Some(found) = found else {
+                    discovered = None;
+                    continue;
+                };
+                let mut state = match MonitorState::new(&cli, &found.mint) {
+                    Ok(state) => state,
+                    Err(e) => {
+                        warn!("Discovered mint {} not monitored: {:#}", found.mint, e);
+                        continue;
+                    }
+                };
+                info!(
+                    "Discovered {} ({}, tx {}), monitoring it every {}s",
+                    found.mint,
+                    found.source.as_str(),
+                    found.signature,
+                    cli.interval
+                );
+                state.watch = Some(watch.clone());
+                monitor_metrics.write().await.insert(found.mint.to_string(), MetricsSummary::default());
+                states.insert(found.mint, state);
+                scheduler.add(found.mint, Priority::Normal, poll_interval, Instant::now());
+                scheduler_intervals.insert(found.mint, cli.interval);
+                mints.push(found.mint);
+            }
+            _ = async { digest_check.as_mut().unwrap().tick().await }, if digest_check.is_some() => {
+                if let Some(notifier) = notifier.clone() {
+                    tokio::spawn(async move { notifier.flush_digest(unix_now()).await });
+                }
+            }
+        }
+    }
+
+    if stalled_exit {
+        // Hung fetches would block shutdown; their mints get no final summary
+        polls.abort_all();
+    }
+
+    // Let in-flight fetches finish so their metrics make it into the summary
+    while let Some(joined) = polls.join_next().await {
+        let (mint, state, result) = match joined {
+            Ok(poll) => poll,
+            Err(e) if e.is_cancelled() => continue,
+            Err(e) => return Err(e).context("Monitoring task panicked"),
+        };
+        monitor_metrics.write().await.insert(mint.to_string(), state.metrics.summary());
+        states.insert(mint, state);
+        poll_tracker.record(&mint, result);
+    }
+
+    // Sinks finish the events already published (status lines, alerts) before the summary
+    bus.close();
+    for sink in sinks {
+        if let Err(e) = sink.await {
+            error!("Event sink panicked: {}", e);
+        }
+    }
+
+    systemd::notify_stopping();
+
+    // Alerts held back by quiet hours would otherwise be lost
+    if let Some(notifier) = &notifier {
+        notifier.send_digest(unix_now()).await;
+    }
+
+    for mint in &mints {
+        let Some(state) = states.remove(mint) else {
+            continue;
+        };
+
+        // In count-only mode the ledger was already saved when tracking stopped
+        if let Some(dir) = state.ledger_dir.as_ref().filter(|_| !state.count_only) {
+            if let Err(e) = state.ledger.save(dir) {
+                error!("Failed to save holder ledger: {}", e);
+            }
+        }
+
+        if let Some(dir) = &state.ledger_dir {
+            if let Err(e) = state.history.save(dir) {
+                error!("Failed to save count history: {}", e);
+            }
+        }
+        if let (Some(dir), Some(history)) = (&state.ledger_dir, &state.tier_history) {
+            if let Err(e) = history.save(dir) {
+                error!("Failed to save tier history: {}", e);
+            }
+        }
+
+        // Print final metrics
+        let metrics = state.metrics;
+        print_final_metrics(&metrics, mint);
+        print_ledger_summary(&state.ledger.summary());
+        if state.ath_alerts {
+            print_records_summary(&state.history, state.ath_lookback_secs, &locale);
+        }
+
+        // Unattended deployments get the report in chat, not only on stdout
+        if let Some(notifier) = &notifier {
+            notifier
+                .notify(&Notification::shutdown_report(mint, &metrics, &locale, unix_now()))
+                .await;
+        }
+
+        // Push final metrics so one-shot runs show up on dashboards
+        if let Some(gateway_url) = &cli.pushgateway_url {
+            let body = solana_holder_bot::prometheus::render_metrics(mint, &metrics);
+            if let Err(e) = solana_holder_bot::prometheus::push_to_gateway(
+                gateway_url,
+                &cli.pushgateway_job,
+                mint,
+                body,
+            )
+            .await
+            {
+                error!("Failed to push metrics to Pushgateway: {}", e);
+            }
+        }
+    }
+
+    // Side-by-side recap after the per-mint reports
+    if mints.len() > 1 {
+        print_mints_summary(&mints, &*monitor_metrics.read().await);
+    }
+    if let Some(size) = community.as_ref().and_then(|community| community.current()) {
+        println!("{}", format_community(&size, &console));
+    }
+
+    if cli.once && !poll_tracker.failed_mints.is_empty() {
+        return Err(anyhow::anyhow!("Monitoring cycle failed"));
+    }
+
+    if stalled_exit {
+        error!("Exiting with code {}: monitoring stalled", STALL_EXIT_CODE);
+        // process::exit skips destructors: flush the log file first
+        drop(_log_guard);
+        std::process::exit(STALL_EXIT_CODE);
+    }
+
+    Ok(())
+}
+
+/// Exit code of `--exit-on-stall`, distinct from ordinary failures (1)
+const STALL_EXIT_CODE: i32 = 3;
+
+/// Settings re-read from `--config` on SIGHUP
+struct ReloadedConfig {
+    notifier: Option<Arc<Notifier>>,
+    tiers: HashMap<Pubkey, TierSchedule>,
+    polling: PollingConfig,
+}
+
+/// Read and validate the config file again; nothing is applied unless all of it is valid.
+/// `[api_keys]`, `[protocols]`, `[sinks]` and `[discovery]` only take effect on restart.
+async fn reload_config(
+    cli: &Cli,
+    path: &Path,
+    rpc_client: &SolanaRpcClient,
+    mints: &[Pubkey],
+) -> Result<ReloadedConfig> {
+    info!("Reloading {}", path.display());
+    let config = Config::load(path)?;
+    let notifier = Notifier::from_cli(cli, &config)?.map(Arc::new);
+    check_latency_slo_channels(cli, notifier.as_deref())?;
+    let tiers = tier_schedules(rpc_client, &config, mints).await?;
+    warn_unmonitored(&config, mints);
+    Ok(ReloadedConfig { notifier, tiers, polling: config.polling })
+}
+
+/// `[tiers]` of the monitored mints, with each mint's decimals and interest read from the RPC
+async fn tier_schedules(
+    rpc_client: &SolanaRpcClient,
+    config: &Config,
+    mints: &[Pubkey],
+) -> Result<HashMap<Pubkey, TierSchedule>> {
+    let mut schedules = HashMap::new();
+    for mint in mints {
+        if let Some(tiers) = config.tiers.get(&mint.to_string()) {
+            let mint_info = rpc_client
+                .get_mint_info(mint)
+                .await
+                .with_context(|| format!("Failed to read the decimals of {} for its tiers", mint))?;
+            let names: Vec<&str> = tiers.iter().map(|tier| tier.name.as_str()).collect();
+            info!("Balance tiers of {}: {}", mint, names.join(", "));
+            schedules.insert(*mint, TierSchedule::new(tiers, mint_info));
+        }
+    }
+    Ok(schedules)
+}
+
+/// `[tiers]` and `[polling]` entries of mints that are not monitored are ignored
+fn warn_unmonitored(config: &Config, mints: &[Pubkey]) {
+    let monitored = |mint: &str| mints.iter().any(|m| m.to_string() == mint);
+    for mint in config.tiers.keys().filter(|mint| !monitored(mint)) {
+        warn!("[tiers] for {} ignored: the mint is not monitored", mint);
+    }
+    for mint in config.polling.primary.iter().chain(&config.polling.background) {
+        if !monitored(mint) {
+            warn!("[polling] entry for {} ignored: the mint is not monitored", mint);
+        }
+    }
+}
+
+/// `--latency-slo-channel` names must exist in the notifier
+fn check_latency_slo_channels(cli: &Cli, notifier: Option<&Notifier>) -> Result<()> {
+    if let Some(notifier) = notifier.filter(|_| cli.latency_slo_secs.is_some()) {
+        let names = notifier.channel_names();
+        if let Some(unknown) = cli.latency_slo_channels.iter().find(|name| !names.contains(&name.as_str())) {
+            return Err(anyhow::anyhow!("--latency-slo-channel: no channel named '{}'", unknown));
+        }
+    }
+    Ok(())
+}
+
+/// Heartbeat timer, when heartbeats are enabled and have somewhere to go
+fn heartbeat_timer(cli: &Cli, heartbeat_notifier: &Option<Arc<Notifier>>) -> Option<tokio::time::Interval> {
+    cli.heartbeat_interval
+        .filter(|_| heartbeat_notifier.is_some())
+        .map(Duration::from_secs)
+        .map(|period| interval_at((Instant::now() + period).into(), period))
+}
+
+/// Send in the background so a slow channel doesn't delay polls
+fn send_notification(notifier: &Option<Arc<Notifier>>, notification: Notification) {
+    if let Some(notifier) = notifier.clone() {
+        tokio::spawn(async move { notifier.notify(&notification).await });
+    }
+}
+
+/// Operational message of a latency SLO breach or recovery
+fn latency_slo_message(event: &SloEvent, slo: &LatencySlo, locale: &Locale) -> String {
+    let slo_secs = locale.format_float(slo.slo_secs(), 1);
+    match event {
+        SloEvent::Breached { endpoint, p95_secs, windows } => fill(locale.messages().latency_slo_breached, &[
+            ("p95", &locale.format_float(*p95_secs, 2)),
+            ("slo", &slo_secs),
+            ("windows", &windows.to_string()),
+            ("window", &slo.window_secs().to_string()),
+            ("endpoint", endpoint),
+        ]),
+        SloEvent::Recovered { endpoint, p95_secs } => fill(locale.messages().latency_slo_recovered, &[
+            ("p95", &locale.format_float(*p95_secs, 2)),
+            ("slo", &slo_secs),
+            ("endpoint", endpoint),
+        ]),
+    }
+}
+
+/// Outcome of the latest polls: `--once` exit status, heartbeat contents and stalls
+struct PollTracker {
+    started_at: u64,
+    /// Mints whose last cycle failed
+    failed_mints: HashSet<Pubkey>,
+    /// Holder count and time of the last successful poll per mint
+    last_success: HashMap<Pubkey, (usize, u64)>,
+    /// Mints reported as stalled and not recovered yet
+    stalled: HashSet<Pubkey>,
+}
+
+impl Default for PollTracker {
+    fn default() -> Self {
+        Self {
+            started_at: unix_now(),
+            failed_mints: HashSet::new(),
+            last_success: HashMap::new(),
+            stalled: HashSet::new(),
+        }
+    }
+}
+
+impl PollTracker {
+    /// Log a finished poll and remember its outcome
+    /// Returns true when the mint was stalled and has recovered
+    fn record(&mut self, mint: &Pubkey, result: Result<usize>) -> bool {
+        match result {
             Ok(count) => {
-                previous_count = Some(count);
+                self.failed_mints.remove(mint);
+                self.last_success.insert(*mint, (count, unix_now()));
+                systemd::notify_status(&format!("Holders: {} ({})", count, mint));
+                self.stalled.remove(mint)
             }
             Err(e) => {
-                error!("Error during monitoring cycle: {}", e);
+                self.failed_mints.insert(*mint);
+                error!("Error during monitoring cycle for {}: {}", mint, e);
                 // Print error chain for debugging
                 let mut source = e.source();
                 while let Some(err) = source {
@@ -114,42 +859,348 @@ async fn main() -> Result<()> {
                     source = err.source();
                 }
                 // Continue monitoring even on errors
+                false
+            }
+        }
+    }
+
+    /// Mints without a successful poll for over their `threshold_secs` that weren't reported yet,
+    /// with the time of their last success (or of startup)
+    fn newly_stalled(
+        &mut self,
+        mints: &[Pubkey],
+        now: u64,
+        threshold_secs: impl Fn(&Pubkey) -> u64,
+    ) -> Vec<(Pubkey, u64)> {
+        let mut stalled = Vec::new();
+        for mint in mints {
+            let since = self.last_success.get(mint).map_or(self.started_at, |(_, at)| *at);
+            if now.saturating_sub(since) > threshold_secs(mint) && self.stalled.insert(*mint) {
+                stalled.push((*mint, since));
             }
         }
+        stalled
+    }
 
-        // Wait for next interval
-        interval_timer.tick().await;
+    /// Latest count and poll age of every mint, for heartbeats
+    fn heartbeat_mints(&self, mints: &[Pubkey], now: u64) -> Vec<HeartbeatMint> {
+        mints
+            .iter()
+            .map(|mint| {
+                let last = self.last_success.get(mint);
+                HeartbeatMint {
+                    mint: mint.to_string(),
+                    symbol: None,
+                    holders: last.map(|(holders, _)| *holders),
+                    last_poll_secs_ago: last.map(|(_, at)| now.saturating_sub(*at)),
+                }
+            })
+            .collect()
     }
+}
 
-    // Print final metrics
-    print_final_metrics(&metrics, &mint);
+/// State carried between monitoring cycles
+#[derive(Default)]
+struct MonitorState {
+    metrics: Metrics,
+    previous_count: Option<usize>,
+    /// Holder set and timestamp of the last successful poll, for churn diffs
+    previous_holders: Option<(Arc<HashSet<Pubkey>>, u64)>,
+    /// Every wallet that held the token; persisted only when `ledger_dir` is set
+    ledger: HolderLedger,
+    ledger_dir: Option<PathBuf>,
+    ledger_saved_at: u64,
+    /// Wallets sampled for the holder quality report (0 = disabled)
+    quality_sample: usize,
+    quality_checked_at: Option<u64>,
+    max_memory_bytes: Option<usize>,
+    /// Memory limit hit: only holder counts are tracked
+    count_only: bool,
+    /// Consecutive polls per alert rule, for escalation chains
+    escalation: EscalationTracker,
+    milestones: MilestoneTracker,
+    /// Delegates already alerted on (`--delegation-alert-percent`)
+    delegation_alerts: DelegationAlerts,
+    /// Hourly count history with alert annotations; saved with --ledger-dir, in memory otherwise
+    history: CountHistory,
+    history_saved_at: u64,
+    /// Alert on new all-time highs / lows of the history (--ath-alerts)
+    ath_alerts: bool,
+    ath_lookback_secs: Option<u64>,
+    /// Confirm drops at finalized commitment before alerting
+    await_finality: bool,
+    /// Account fetch duration of the last poll, for the latency SLO
+    last_fetch: Option<Duration>,
+    /// Watched wallets, updated from every full poll
+    watch: Option<Arc<WalletWatch>>,
+    /// Balance tiers from `[tiers]`, compared with UI amounts of the mint
+    tiers: Option<TierSchedule>,
+    /// Hourly holders per tier (None unless the mint has tiers and --ledger-dir is set)
+    tier_history: Option<TierHistory>,
+    tier_history_saved_at: u64,
+}
 
+impl MonitorState {
+    fn new(cli: &Cli, mint: &Pubkey) -> Result<Self> {
+        let ledger = match &cli.ledger_dir {
+            Some(dir) => {
+                let ledger = HolderLedger::load_or_new(dir, mint, unix_now())
+                    .context("Failed to load holder ledger")?;
+                info!("Holder ledger: {} ({} wallets ever held)", dir.display(), ledger.wallets.len());
+                ledger
+            }
+            None => HolderLedger::new(mint, unix_now()),
+        };
+        let history = match &cli.ledger_dir {
+            Some(dir) => CountHistory::load_or_new(dir, &mint.to_string()).context("Failed to load count history")?,
+            None => CountHistory::new(&mint.to_string()),
+        };
+        Ok(Self {
+            ledger,
+            ledger_dir: cli.ledger_dir.clone(),
+            quality_sample: cli.quality_sample,
+            max_memory_bytes: cli.max_memory_mb.map(|mb| mb * 1024 * 1024),
+            milestones: MilestoneTracker::new(cli.milestones.clone(), cli.milestone_step),
+            delegation_alerts: DelegationAlerts::new(cli.delegation_alert_percent),
+            history,
+            ath_alerts: cli.ath_alerts,
+            ath_lookback_secs: cli.ath_lookback_days.map(|days| days * 86_400),
+            await_finality: cli.await_finality,
+            ..Self::default()
+        })
+    }
+
+    /// Balance tiers of the mint (None: no `[tiers]` entry); the tier history is loaded when
+    /// the mint first gets tiers
+    fn set_tiers(&mut self, mint: &Pubkey, tiers: Option<TierSchedule>) -> Result<()> {
+        if let (Some(_), None, Some(dir)) = (&tiers, &self.tier_history, &self.ledger_dir) {
+            self.tier_history =
+                Some(TierHistory::load_or_new(dir, &mint.to_string()).context("Failed to load tier history")?);
+        }
+        self.tiers = tiers;
+        Ok(())
+    }
+
+    /// Approximate memory held between cycles plus the latest fetch
+    fn memory_usage(&self, current_holders: usize) -> MemoryUsage {
+        let previous = self.previous_holders.as_ref().map_or(0, |(set, _)| set.len());
+        MemoryUsage::new(
+            holder_set_bytes(current_holders + previous),
+            ledger_bytes(&self.ledger) + self.metrics.churn.memory_bytes(),
+        )
+    }
+
+    /// Drop holder sets and the in-memory ledger; fetch owner + amount only from now on
+    fn enter_count_only(&mut self, rpc_client: &SolanaRpcClient) {
+        if let Some(dir) = &self.ledger_dir {
+            if let Err(e) = self.ledger.save(dir) {
+                error!("Failed to save holder ledger: {}", e);
+            }
+        }
+        let mint = Pubkey::from_str(&self.ledger.mint).unwrap_or_default();
+        self.ledger = HolderLedger::new(&mint, self.ledger.updated_at);
+        self.previous_holders = None;
+        self.count_only = true;
+        rpc_client.set_count_only(true);
+    }
+}
+
+/// How often the ledger is flushed to disk (it is also saved on shutdown)
+const LEDGER_SAVE_INTERVAL_SECS: u64 = 300;
+
+/// How often the end of quiet hours is checked
+const DIGEST_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How often the holder quality report is recomputed (it costs ~2 RPC calls per sampled wallet)
+const QUALITY_INTERVAL_SECS: u64 = 3600;
+
+/// Current unix time in seconds
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// Checksum (and signature with a keypair) next to a subcommand's output file
+fn seal_output(output: Option<&Path>, signing_keypair: Option<&Path>) -> Result<()> {
+    let Some(output) = output else {
+        return Ok(());
+    };
+    let signer = signing_keypair.map(SnapshotSigner::from_file).transpose()?;
+    write_sidecars(output, signer.as_ref())?;
+    if let Some(signer) = &signer {
+        eprintln!("Signed {} as {}", output.display(), signer.pubkey());
+    }
     Ok(())
 }
 
+/// Run an offline subcommand
+async fn run_command(cli: &Cli, command: &Command) -> Result<()> {
+    match command {
+        Command::Allocate(args) => {
+            let balances = load_snapshot(&args.snapshot)?;
+            let allocations = pro_rata(balances, &args.rules());
+            write_allocation(&allocations, args.total, args.output.as_deref())?;
+            seal_output(args.output.as_deref(), args.signing_keypair.as_deref())
+        }
+        Command::Completions(args) => {
+            write_completions(args.shell, &mut std::io::stdout());
+            Ok(())
+        }
+        Command::Doctor(_) => unreachable!("doctor runs before the settings are validated"),
+        Command::Init(args) => setup::run(args),
+        Command::Cohorts(args) => {
+            let mint = Pubkey::from_str(&args.mint_address)
+                .map_err(|e| anyhow::anyhow!("Invalid mint address '{}': {}", args.mint_address, e))?;
+            let path = HolderLedger::path_for(&args.ledger_dir, &mint.to_string());
+            let ledger = HolderLedger::load(&path)?;
+            print_cohort_report(&ledger, args.period_days.max(1) * 86_400, args.json)
+        }
+        Command::Verify(args) => {
+            let mint = Pubkey::from_str(&args.mint_address)
+                .map_err(|e| anyhow::anyhow!("Invalid mint address '{}': {}", args.mint_address, e))?;
+            let token_program = cli.parse_token_program().context("Failed to parse token program")?;
+            let rpc_client = SolanaRpcClient::new(cli.rpc_url(), cli.max_retries, cli.timeout)
+                .with_token_program(token_program)
+                .with_http_tuning(&HttpTuning::from_cli(cli))?;
+            let keys = ExplorerKeys {
+                solscan: args.solscan_api_key.clone(),
+                birdeye: args.birdeye_api_key.clone(),
+            };
+            let report = verify_mint(&rpc_client, &mint, &token_program, &keys).await?;
+            print_verify_report(&report, args.json)
+        }
+        Command::Merkle(args) => {
+            let balances = load_snapshot(&args.snapshot)?;
+            let allocations = allocate(balances, args.allocation());
+            let airdrop = MerkleAirdrop::build(&allocations, args.layout)?;
+            write_airdrop(&airdrop, args.output.as_deref())?;
+            seal_output(args.output.as_deref(), args.signing_keypair.as_deref())
+        }
+        Command::TestAlert(args) => {
+            let config = match &cli.config {
+                Some(path) => Config::load(path)?,
+                None => Config::default(),
+            };
+            let notifier = Notifier::from_cli(cli, &config)
+                .context("Failed to initialize notifier")?
+                .context("No notifier channel configured (--telegram-bot-token, --discord-webhook, ... or [channels] in --config)")?;
+            let mint = match &args.mint {
+                Some(mint) => Pubkey::from_str(mint).map_err(|e| anyhow::anyhow!("Invalid mint address '{}': {}", mint, e))?,
+                None => Pubkey::default(),
+            };
+            let notification = notifier.test_alert(&mint, unix_now());
+            let mut failed = 0;
+            for (channel, result) in notifier.send_test(&args.channels, &notification).await? {
+                match result {
+                    Ok(()) => println!("✅ {}: sent", channel),
+                    Err(e) => {
+                        failed += 1;
+                        println!("❌ {}: {:#}", channel, e);
+                    }
+                }
+            }
+            if failed > 0 {
+                return Err(anyhow::anyhow!("Test alert failed on {} channel(s)", failed));
+            }
+            Ok(())
+        }
+        Command::VerifySnapshot(args) => {
+            let signer = args
+                .signer
+                .as_deref()
+                .map(|signer| Pubkey::from_str(signer).map_err(|e| anyhow::anyhow!("Invalid signer '{}': {}", signer, e)))
+                .transpose()?;
+            let verified = verify_file(&args.file, signer.as_ref())?;
+            println!("{}: OK (sha256 {})", args.file.display(), verified.sha256);
+            if let Some(signer) = verified.signer {
+                println!("Signed by {}", signer);
+            }
+            Ok(())
+        }
+        Command::GenAlertRules(args) => {
+            let rules = AlertRuleSettings::from_cli(cli, args.job.clone()).render();
+            match &args.output {
+                Some(path) => std::fs::write(path, rules)
+                    .with_context(|| format!("Failed to write alerting rules to {}", path.display())),
+                None => {
+                    print!("{}", rules);
+                    Ok(())
+                }
+            }
+        }
+    }
+}
+
+/// Holder set of a mint read at finalized commitment, and its zero-balance owner count
+async fn confirm_at_finality(
+    rpc_client: &SolanaRpcClient,
+    mint: &Pubkey,
+) -> Result<(AccountSnapshot, HashSet<Pubkey>, usize)> {
+    let snapshot = rpc_client.get_finalized_snapshot(mint).await?;
+    let (holders, zero_balance_owners) =
+        rpc_client.holders_of(&snapshot).context("Failed to extract holders from accounts")?;
+    Ok((snapshot, holders, zero_balance_owners))
+}
+
 /// Monitor token holders for one cycle
 async fn monitor_holders(
     rpc_client: &SolanaRpcClient,
     mint: &Pubkey,
-    previous_count: Option<usize>,
-    metrics: &mut Metrics,
+    state: &mut MonitorState,
+    bus: &EventBus,
+    console: &ConsoleOutput,
 ) -> Result<usize> {
     let start_time = std::time::Instant::now();
 
     // Fetch token accounts
     let fetch_start = std::time::Instant::now();
-    let accounts = rpc_client
-        .get_token_accounts_by_mint(mint)
+    let mut snapshot = rpc_client
+        .get_token_accounts_snapshot(mint)
         .await
         .context("Failed to fetch token accounts")?;
     let fetch_elapsed = fetch_start.elapsed();
+    state.metrics.fetch_latency.record(fetch_elapsed);
+    state.last_fetch = Some(fetch_elapsed);
 
     // Extract unique holders
     let extract_start = std::time::Instant::now();
-    let holders = extract_holders(&accounts)
-        .context("Failed to extract holders from accounts")?;
+    let (holders, mut zero_balance_owners) =
+        rpc_client.holders_of(&snapshot).context("Failed to extract holders from accounts")?;
+    let mut holders = Arc::new(holders);
     let extract_elapsed = extract_start.elapsed();
 
+    // Await finality: a drop seen at confirmed commitment is re-read at finalized, and the
+    // finalized holder set becomes this poll's observation
+    if state.await_finality
+        && snapshot.degraded.is_none()
+        && state.previous_count.is_some()
+        && is_drop(&calculate_stats(holders.len(), state.previous_count)) {
+        match confirm_at_finality(rpc_client, mint).await {
+            Ok((finalized, finalized_holders, finalized_zero_balance_owners)) => {
+                info!(
+                    "Drop for {} re-checked at finalized commitment: {} -> {} holders",
+                    mint,
+                    holders.len(),
+                    finalized_holders.len()
+                );
+                snapshot = finalized;
+                holders = Arc::new(finalized_holders);
+                zero_balance_owners = finalized_zero_balance_owners;
+            }
+            Err(e) => warn!("Could not confirm drop for {} at finalized commitment, alerting on confirmed data: {:#}", mint, e),
+        }
+    }
+    let block_time = match snapshot.slot {
+        Some(slot) => rpc_client.get_block_time(slot).await,
+        None => None,
+    };
+    let mut metadata = rpc_client.count_metadata(mint, &snapshot);
+    metadata.zero_balance_owners = Some(zero_balance_owners);
+    let metadata = Arc::new(metadata);
+    let accounts = snapshot.accounts;
+
     let holder_count = holders.len();
     let elapsed = start_time.elapsed();
     
@@ -164,48 +1215,236 @@ async fn monitor_holders(
         );
     }
 
+    // Memory limit: fall back to count-only mode instead of growing further
+    let usage = state.memory_usage(holder_count);
+    debug!("Approximate memory: {}", format_mb(usage.total_bytes));
+    if let Some(limit) = state.max_memory_bytes {
+        if !state.count_only && usage.total_bytes > limit {
+            warn!(
+                "Memory usage {} exceeds --max-memory-mb ({}), switching to count-only mode",
+                format_mb(usage.total_bytes),
+                format_mb(limit)
+            );
+            state.enter_count_only(rpc_client);
+        }
+    }
+
     // Calculate statistics
-    let stats = calculate_stats(holder_count, previous_count);
+    let previous_count = state.previous_count;
+    let mut stats = calculate_stats(holder_count, previous_count);
+    stats.slot = snapshot.slot;
+    stats.block_time = block_time;
+    stats.degraded = snapshot.degraded;
+    stats.missing_supply_percent = snapshot.missing_supply_percent;
+    stats.token_accounts = Some(accounts.len());
+
+    // A partial read (largest accounts only) is reported but kept out of metrics, alerts, churn
+    // and history: it isn't comparable with full counts
+    if stats.degraded.is_some_and(|fallback| fallback.is_partial()) {
+        stats.change = 0;
+        stats.change_percent = 0.0;
+        bus.publish(HolderEvent::Observation(Observation {
+            mint: *mint,
+            stats,
+            previous_count: None,
+            elapsed,
+            churn: None,
+            holders,
+            metadata,
+        }));
+        return Ok(holder_count);
+    }
+    let metrics = &mut state.metrics;
 
     // Update metrics
     metrics.update(holder_count);
+    metrics.zero_balance_owners = Some(zero_balance_owners);
+    metrics.last_token_accounts = stats.token_accounts;
+    // Fallback sources rebuild accounts without the delegate fields
+    if !state.count_only && stats.degraded.is_none() {
+        metrics.delegations = delegation_stats(&accounts);
+    }
+    if let Some(tiers) = &state.tiers {
+        metrics.tiers = tiers.count(extract_balances(&accounts).values(), stats.timestamp as i64);
+        let counts: Vec<String> = metrics.tiers.iter().map(|count| format!("{} {}", count.tier, count.holders)).collect();
+        info!("Holders per tier of {}: {}", mint, counts.join(", "));
+        if let (Some(history), Some(dir)) = (&mut state.tier_history, &state.ledger_dir) {
+            history.record(&metrics.tiers, stats.observed_at());
+            if stats.timestamp.saturating_sub(state.tier_history_saved_at) >= LEDGER_SAVE_INTERVAL_SECS {
+                match history.save(dir) {
+                    Ok(()) => state.tier_history_saved_at = stats.timestamp,
+                    Err(e) => warn!("Failed to save tier history: {}", e),
+                }
+            }
+        }
+    }
+
+    // Churn: who entered / exited since the previous poll
+    if let Some((previous_holders, since)) = &state.previous_holders {
+        let diff = diff_holders(previous_holders, &holders);
+        metrics
+            .churn
+            .record(*since, stats.timestamp, &diff, previous_holders.len());
+    }
+    let churn = metrics.churn.stats(stats.timestamp, 3600);
+
+    // Ledger: first/last seen per wallet, flushed periodically
+    if !state.count_only {
+        let observed = state.ledger.observe(&holders, stats.timestamp);
+        if observed.returned_wallets > 0 {
+            info!(
+                "{} new and {} returning holders since last poll",
+                observed.new_wallets, observed.returned_wallets
+            );
+        }
+        if let Some(dir) = &state.ledger_dir {
+            if stats.timestamp.saturating_sub(state.ledger_saved_at) >= LEDGER_SAVE_INTERVAL_SECS {
+                match state.ledger.save(dir) {
+                    Ok(()) => state.ledger_saved_at = stats.timestamp,
+                    Err(e) => warn!("Failed to save holder ledger: {}", e),
+                }
+            }
+        }
+    }
 
     // Check for alerts
-    check_alerts(&stats, previous_count, metrics);
+    let mut fired = check_alerts(&stats, previous_count, &console.locale);
+    fired.extend(check_milestone(&stats, &mut state.milestones, &console.locale));
+    if let Some(delegations) = &metrics.delegations {
+        fired.extend(check_delegations(delegations, &mut state.delegation_alerts, &console.locale));
+    }
+    if state.ath_alerts {
+        let history = &mut state.history;
+        fired.extend(check_records(&stats, previous_count, history, state.ath_lookback_secs, &console.locale));
+    } else {
+        state.history.observe(&stats);
+    }
+    if let Some(dir) = &state.ledger_dir {
+        if stats.timestamp.saturating_sub(state.history_saved_at) >= LEDGER_SAVE_INTERVAL_SECS {
+            match state.history.save(dir) {
+                Ok(()) => state.history_saved_at = stats.timestamp,
+                Err(e) => warn!("Failed to save count history: {}", e),
+            }
+        }
+    }
+
+    // Publish the observation, then this poll's alerts with their escalation streaks
+    bus.publish(HolderEvent::Observation(Observation {
+        mint: *mint,
+        stats: stats.clone(),
+        previous_count,
+        elapsed,
+        churn: state.previous_holders.as_ref().map(|_| churn),
+        holders: holders.clone(),
+        metadata,
+    }));
+    let rules: Vec<AlertRule> = fired.iter().map(|(rule, _)| *rule).collect();
+    state.escalation.observe(&rules);
+    for (rule, message) in fired {
+        metrics.add_alert(message.clone());
+        state.history.annotate(rule, &message, &stats);
+        bus.publish(HolderEvent::Alert(AlertEvent {
+            mint: *mint,
+            rule,
+            message,
+            stats: stats.clone(),
+            previous_count,
+            streak: state.escalation.streak(rule),
+        }));
+    }
+
+    // Watched wallets: current balances, entries, exits and tier changes since the last poll
+    if let Some(watch) = &state.watch {
+        // Read once per mint; without it watched wallets only get raw balances
+        let mint_info = match rpc_client.get_mint_info(mint).await {
+            Ok(info) => Some(info),
+            Err(e) => {
+                debug!("UI balances of watched wallets on {} unknown: {:#}", mint, e);
+                None
+            }
+        };
+        let tiers = state.tiers.as_ref();
+        for event in watch.observe(mint, &accounts, holder_count, tiers, mint_info.as_ref(), stats.timestamp) {
+            bus.publish(HolderEvent::Watch(event));
+        }
+    }
 
-    // Print status
-    print_status(mint, &stats, elapsed);
+    // Sybil heuristics: raw count vs estimated organic holders
+    let quality_due = state
+        .quality_checked_at
+        .is_none_or(|at| stats.timestamp.saturating_sub(at) >= QUALITY_INTERVAL_SECS);
+    if state.quality_sample > 0 && quality_due && !state.count_only {
+        let quality = analyze_holders(rpc_client, &accounts, state.quality_sample, stats.timestamp).await;
+        console.print_quality(&quality);
+        state.quality_checked_at = Some(stats.timestamp);
+    }
+
+    state.previous_count = Some(holder_count);
+    if !state.count_only {
+        state.previous_holders = Some((holders, stats.timestamp));
+    }
 
     Ok(holder_count)
 }
 
-/// Print current status to console
-fn print_status(mint: &Pubkey, stats: &solana_holder_bot::HolderStats, elapsed: std::time::Duration) {
-    let change_str = if stats.change == 0 {
-        "±0".to_string()
-    } else if stats.change > 0 {
-        format!("+{}", stats.change)
-    } else {
-        stats.change.to_string()
-    };
+/// Print lifetime holder statistics (first-time vs returning)
+fn print_ledger_summary(summary: &LedgerSummary) {
+    if summary.unique_holders_ever == 0 {
+        return;
+    }
+    println!(
+        "Unique holders ever: {} | current: {} ({} first-time, {} returning) | former: {}",
+        summary.unique_holders_ever,
+        summary.current_holders,
+        summary.first_time_holders,
+        summary.returning_holders,
+        summary.former_holders
+    );
+}
 
-    let change_percent_str = if stats.change_percent == 0.0 {
-        "".to_string()
-    } else {
-        format!(" ({:+.1}%)", stats.change_percent)
+/// `ATH: 1520 (set 3.2 days ago) | ATL: 980 (set 41.0 days ago)`
+fn print_records_summary(history: &CountHistory, lookback_secs: Option<u64>, locale: &Locale) {
+    let now = unix_now();
+    let since = lookback_secs.map_or(0, |lookback| now.saturating_sub(lookback));
+    let (Some(high), Some(low)) = (history.high(since), history.low(since)) else {
+        return;
     };
+    let window = match lookback_secs {
+        Some(secs) => format!(" (last {} days)", secs / 86_400),
+        None => String::new(),
+    };
+    println!(
+        "ATH{}: {} (set {} days ago) | ATL: {} (set {} days ago)",
+        window,
+        locale.format_count(high.holders),
+        locale.format_float(high.days_ago(now), 1),
+        locale.format_count(low.holders),
+        locale.format_float(low.days_ago(now), 1)
+    );
+}
 
-    let timestamp_str = format_timestamp(stats.timestamp);
-
+/// One line per monitored mint: polls, last/min/max/avg holders and alert count
+fn print_mints_summary(mints: &[Pubkey], summaries: &HashMap<String, MetricsSummary>) {
     println!(
-        "MINT: {} | Holders: {} | Δ: {}{} | Time: {} | Fetch: {:.2}s",
-        mint,
-        stats.count,
-        change_str,
-        change_percent_str,
-        timestamp_str,
-        elapsed.as_secs_f64()
+        "\n{:<44} {:>6} {:>10} {:>10} {:>10} {:>12} {:>7}",
+        "MINT", "POLLS", "LAST", "MIN", "MAX", "AVG", "ALERTS"
     );
+    let count = |value: Option<usize>| value.map_or("-".to_string(), |v| v.to_string());
+    for mint in mints.iter().map(|mint| mint.to_string()) {
+        let Some(summary) = summaries.get(&mint) else {
+            continue;
+        };
+        println!(
+            "{:<44} {:>6} {:>10} {:>10} {:>10} {:>12.2} {:>7}",
+            mint,
+            summary.total_polls,
+            count(summary.last_holders),
+            count(summary.min_holders),
+            count(summary.max_holders),
+            summary.average_holders,
+            summary.alerts.len()
+        );
+    }
 }
 
 /// Print final metrics on shutdown
@@ -225,6 +1464,17 @@ fn print_final_metrics(metrics: &Metrics, mint: &Pubkey) {
     }
     
     println!("Average holders: {:.2}", metrics.average_holders());
+
+    if let Some(last) = metrics.last_holders {
+        let now = unix_now();
+        let churn = metrics.churn.stats(now, solana_holder_bot::churn::CHURN_RETENTION_SECS);
+        if churn.entered > 0 || churn.exited > 0 {
+            println!(
+                "Churn (24h): +{} entered / -{} exited ({:.1}% of holders kept, now {})",
+                churn.entered, churn.exited, churn.retention_percent, last
+            );
+        }
+    }
     
     if !metrics.alerts.is_empty() {
         println!("\n🚨 ALERTS TRIGGERED:");
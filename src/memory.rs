@@ -0,0 +1,88 @@
+use serde::Serialize;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::account::Account;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::ledger::HolderLedger;
+
+/// Approximate bytes per `HashSet<Pubkey>` entry (key + control byte at 7/8 load)
+pub const HOLDER_ENTRY_BYTES: usize = 40;
+/// Approximate bytes per ledger entry (key + `WalletRecord` + control byte at 7/8 load)
+pub const LEDGER_ENTRY_BYTES: usize = 80;
+
+/// Size of the most recent / largest fetched account set (shared by monitor and API)
+static LAST_FETCH_BYTES: AtomicUsize = AtomicUsize::new(0);
+static PEAK_FETCH_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+/// Approximate memory used by fetched accounts (data buffers plus tuple overhead)
+pub fn accounts_bytes(accounts: &[(Pubkey, Account)]) -> usize {
+    let data: usize = accounts.iter().map(|(_, account)| account.data.capacity()).sum();
+    data + std::mem::size_of_val(accounts)
+}
+
+/// Remember the size of a fetched account set
+pub fn record_fetch(bytes: usize) {
+    LAST_FETCH_BYTES.store(bytes, Ordering::Relaxed);
+    PEAK_FETCH_BYTES.fetch_max(bytes, Ordering::Relaxed);
+}
+
+pub fn holder_set_bytes(holders: usize) -> usize {
+    holders * HOLDER_ENTRY_BYTES
+}
+
+pub fn ledger_bytes(ledger: &HolderLedger) -> usize {
+    ledger.wallets.len() * LEDGER_ENTRY_BYTES
+}
+
+/// Approximate memory breakdown
+#[derive(Debug, Clone, Copy, Default, Serialize, PartialEq)]
+pub struct MemoryUsage {
+    /// Account data of the latest RPC fetch (freed after each cycle)
+    pub last_fetch_bytes: usize,
+    pub peak_fetch_bytes: usize,
+    /// Holder sets kept between polls (for churn diffs)
+    pub holder_sets_bytes: usize,
+    /// Ledgers and churn history
+    pub history_bytes: usize,
+    /// last fetch + holder sets + history
+    pub total_bytes: usize,
+}
+
+impl MemoryUsage {
+    pub fn new(holder_sets_bytes: usize, history_bytes: usize) -> Self {
+        let last_fetch_bytes = LAST_FETCH_BYTES.load(Ordering::Relaxed);
+        Self {
+            last_fetch_bytes,
+            peak_fetch_bytes: PEAK_FETCH_BYTES.load(Ordering::Relaxed),
+            holder_sets_bytes,
+            history_bytes,
+            total_bytes: last_fetch_bytes + holder_sets_bytes + history_bytes,
+        }
+    }
+}
+
+/// Format bytes as megabytes for logs
+pub fn format_mb(bytes: usize) -> String {
+    format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accounts_bytes() {
+        let accounts = vec![
+            (Pubkey::new_unique(), Account { data: vec![0; 165], ..Account::default() }),
+            (Pubkey::new_unique(), Account { data: vec![0; 40], ..Account::default() }),
+        ];
+        let overhead = 2 * std::mem::size_of::<(Pubkey, Account)>();
+        assert_eq!(accounts_bytes(&accounts), 205 + overhead);
+
+        record_fetch(100);
+        record_fetch(50);
+        let usage = MemoryUsage::new(10, 5);
+        assert_eq!(usage.peak_fetch_bytes, 100);
+        assert_eq!(usage.total_bytes, 65);
+    }
+}
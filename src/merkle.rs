@@ -0,0 +1,238 @@
+//! Airdrop Merkle trees for the `merkle` subcommand: (wallet, amount) allocations from a holder
+//! snapshot, hashed the way the common Solana merkle-distributor claim programs verify them
+
+use anyhow::{bail, Context, Result};
+use clap::ValueEnum;
+use serde::Serialize;
+use solana_program::{hash, keccak, pubkey::Pubkey};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::allocation::{pro_rata, AllocationRules};
+use crate::export::sorted_balances;
+
+/// Leaf and node hashing of the target claim program
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MerkleLayout {
+    /// Saber merkle-distributor: leaf = keccak(index u64 LE || claimant || amount u64 LE),
+    /// nodes = keccak of the sorted pair, an odd node is carried up unchanged
+    #[default]
+    Saber,
+    /// Jito merkle-distributor: leaf = sha256(0x00 || sha256(claimant || unlocked LE || locked LE)),
+    /// nodes = sha256(0x01 || sorted pair), an odd node is paired with itself
+    Jito,
+}
+
+/// How the airdrop total is split between wallets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Allocation {
+    /// Split `total` raw units in proportion to the snapshot balances
+    ProRata { total: u64 },
+    /// The same raw amount for every wallet
+    Fixed { amount: u64 },
+}
+
+/// Amount per wallet, largest balance first; wallets that would get nothing are left out
+pub fn allocate(balances: HashMap<Pubkey, u64>, allocation: Allocation) -> Vec<(Pubkey, u64)> {
+    match allocation {
+        Allocation::ProRata { total } => pro_rata(balances, &AllocationRules::new(total)),
+        Allocation::Fixed { amount } => sorted_balances(balances)
+            .into_iter()
+            .filter(|(_, balance)| *balance > 0 && amount > 0)
+            .map(|(wallet, _)| (wallet, amount))
+            .collect(),
+    }
+}
+
+/// Claim of one wallet with the sibling hashes from its leaf up to the root
+#[derive(Debug, Clone, Serialize)]
+pub struct MerkleClaim {
+    /// Leaf index (part of the Saber leaf, ignored by Jito)
+    pub index: u64,
+    pub claimant: String,
+    /// Raw token units (Jito: `amount_unlocked`, with 0 locked)
+    pub amount: u64,
+    /// Hex sibling hashes, leaf level first
+    pub proof: Vec<String>,
+}
+
+/// Airdrop tree: the root to initialize the distributor with and a proof per claimant
+#[derive(Debug, Clone, Serialize)]
+pub struct MerkleAirdrop {
+    pub layout: MerkleLayout,
+    /// Root bytes as expected by `new_distributor` (`[u8; 32]`)
+    pub merkle_root: [u8; 32],
+    pub merkle_root_hex: String,
+    pub max_total_claim: u64,
+    pub max_num_nodes: u64,
+    pub claims: Vec<MerkleClaim>,
+}
+
+impl MerkleAirdrop {
+    /// Tree over `allocations`, leaves in the given order
+    pub fn build(allocations: &[(Pubkey, u64)], layout: MerkleLayout) -> Result<Self> {
+        if allocations.is_empty() {
+            bail!("No wallets to include in the airdrop");
+        }
+        let leaves: Vec<[u8; 32]> = allocations
+            .iter()
+            .enumerate()
+            .map(|(i, (wallet, amount))| leaf_hash(layout, i as u64, wallet, *amount))
+            .collect();
+        let levels = build_levels(layout, leaves);
+        let root = levels.last().expect("at least one level")[0];
+        let claims = allocations
+            .iter()
+            .enumerate()
+            .map(|(i, (wallet, amount))| MerkleClaim {
+                index: i as u64,
+                claimant: wallet.to_string(),
+                amount: *amount,
+                proof: proof_for(layout, &levels, i).iter().map(to_hex).collect(),
+            })
+            .collect();
+        Ok(Self {
+            layout,
+            merkle_root: root,
+            merkle_root_hex: to_hex(&root),
+            max_total_claim: allocations.iter().map(|(_, amount)| *amount).sum(),
+            max_num_nodes: allocations.len() as u64,
+            claims,
+        })
+    }
+}
+
+fn leaf_hash(layout: MerkleLayout, index: u64, claimant: &Pubkey, amount: u64) -> [u8; 32] {
+    match layout {
+        MerkleLayout::Saber => keccak::hashv(&[&index.to_le_bytes(), claimant.as_ref(), &amount.to_le_bytes()]).0,
+        MerkleLayout::Jito => {
+            let node = hash::hashv(&[claimant.as_ref(), &amount.to_le_bytes(), &0u64.to_le_bytes()]);
+            hash::hashv(&[&[0u8], node.as_ref()]).to_bytes()
+        }
+    }
+}
+
+fn node_hash(layout: MerkleLayout, a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (lo, hi) = if a <= b { (a, b) } else { (b, a) };
+    match layout {
+        MerkleLayout::Saber => keccak::hashv(&[lo, hi]).0,
+        MerkleLayout::Jito => hash::hashv(&[&[1u8], lo, hi]).to_bytes(),
+    }
+}
+
+/// All tree levels, leaves first and the single root last
+fn build_levels(layout: MerkleLayout, leaves: Vec<[u8; 32]>) -> Vec<Vec<[u8; 32]>> {
+    let mut levels = vec![leaves];
+    while levels.last().expect("at least one level").len() > 1 {
+        let level = levels.last().expect("at least one level");
+        let next = level
+            .chunks(2)
+            .map(|pair| match (pair, layout) {
+                ([a, b], _) => node_hash(layout, a, b),
+                ([a], MerkleLayout::Saber) => *a,
+                ([a], MerkleLayout::Jito) => node_hash(layout, a, a),
+                _ => unreachable!("chunks of two"),
+            })
+            .collect();
+        levels.push(next);
+    }
+    levels
+}
+
+fn proof_for(layout: MerkleLayout, levels: &[Vec<[u8; 32]>], mut index: usize) -> Vec<[u8; 32]> {
+    let mut proof = Vec::new();
+    for level in &levels[..levels.len() - 1] {
+        match level.get(index ^ 1) {
+            Some(sibling) => proof.push(*sibling),
+            // Odd node at the end of a level: Jito pairs it with itself, Saber carries it up
+            None if layout == MerkleLayout::Jito => proof.push(level[index]),
+            None => {}
+        }
+        index /= 2;
+    }
+    proof
+}
+
+fn to_hex(bytes: &[u8; 32]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Recompute the root from a claim (what the claim program does on-chain)
+pub fn verify_claim(layout: MerkleLayout, root: &[u8; 32], index: u64, claimant: &Pubkey, amount: u64, proof: &[[u8; 32]]) -> bool {
+    let computed = proof
+        .iter()
+        .fold(leaf_hash(layout, index, claimant, amount), |acc, sibling| node_hash(layout, &acc, sibling));
+    &computed == root
+}
+
+/// Write the tree as JSON to `output`, or print it with a short summary to stdout
+pub fn write_airdrop(airdrop: &MerkleAirdrop, output: Option<&Path>) -> Result<()> {
+    let json = serde_json::to_string_pretty(airdrop)?;
+    match output {
+        Some(path) => {
+            std::fs::write(path, json).with_context(|| format!("Failed to write Merkle tree to {}", path.display()))?;
+            eprintln!(
+                "Merkle root {} ({} claims, {} raw units total) written to {}",
+                airdrop.merkle_root_hex,
+                airdrop.max_num_nodes,
+                airdrop.max_total_claim,
+                path.display()
+            );
+        }
+        None => println!("{}", json),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn wallets(balances: &[u64]) -> HashMap<Pubkey, u64> {
+        balances.iter().map(|b| (Pubkey::new_unique(), *b)).collect()
+    }
+
+    fn from_hex(s: &str) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        for (i, byte) in out.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&s[i * 2..i * 2 + 2], 16).unwrap();
+        }
+        out
+    }
+
+    #[test]
+    fn test_allocate() {
+        let shares = allocate(wallets(&[600, 300, 100, 0]), Allocation::ProRata { total: 10 });
+        assert_eq!(shares.iter().map(|(_, a)| *a).collect::<Vec<_>>(), vec![6, 3, 1]);
+
+        let fixed = allocate(wallets(&[5, 1, 0]), Allocation::Fixed { amount: 42 });
+        assert_eq!(fixed.len(), 2);
+        assert!(fixed.iter().all(|(_, a)| *a == 42));
+    }
+
+    #[test]
+    fn test_proofs_verify() {
+        for layout in [MerkleLayout::Saber, MerkleLayout::Jito] {
+            for n in [1, 2, 5, 8] {
+                let allocations = allocate(wallets(&vec![10; n]), Allocation::Fixed { amount: 3 });
+                let airdrop = MerkleAirdrop::build(&allocations, layout).unwrap();
+                assert_eq!(airdrop.max_total_claim, 3 * n as u64);
+                for claim in &airdrop.claims {
+                    let proof: Vec<[u8; 32]> = claim.proof.iter().map(|p| from_hex(p)).collect();
+                    let claimant = Pubkey::from_str(&claim.claimant).unwrap();
+                    assert!(
+                        verify_claim(layout, &airdrop.merkle_root, claim.index, &claimant, claim.amount, &proof),
+                        "{:?} n={} index={}",
+                        layout,
+                        n,
+                        claim.index
+                    );
+                    assert!(!verify_claim(layout, &airdrop.merkle_root, claim.index, &claimant, claim.amount + 1, &proof));
+                }
+            }
+        }
+        assert!(MerkleAirdrop::build(&[], MerkleLayout::Saber).is_err());
+    }
+}
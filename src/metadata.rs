@@ -0,0 +1,149 @@
+//! How a holder count was produced: data source, filters, commitment, dust threshold, slot and
+//! truncation check, attached to observations and API responses so consumers can judge a number
+
+use serde::Serialize;
+
+use crate::rpc_client::{AccountSnapshot, Fallback, TOKEN_2022_PROGRAM_ID};
+use crate::token_monitor::{SLIM_ACCOUNT_LEN, SLIM_ACCOUNT_OFFSET, TOKEN_ACCOUNT_LEN};
+use crate::truncation::TruncationMode;
+
+/// Backend that returned the token accounts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataSource {
+    #[default]
+    GetProgramAccounts,
+    Das,
+    LargestAccounts,
+    Cached,
+}
+
+impl From<Option<Fallback>> for DataSource {
+    fn from(fallback: Option<Fallback>) -> Self {
+        match fallback {
+            None => Self::GetProgramAccounts,
+            Some(Fallback::Das) => Self::Das,
+            Some(Fallback::LargestAccounts) => Self::LargestAccounts,
+            Some(Fallback::Cached) => Self::Cached,
+        }
+    }
+}
+
+/// Provenance of one holder count
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CountMetadata {
+    pub source: DataSource,
+    /// RPC endpoint (credentials redacted)
+    pub rpc_url: String,
+    /// Program owning the counted token accounts
+    pub token_program: String,
+    /// `confirmed`, or `finalized` for drops re-read with `--await-finality`
+    pub commitment: String,
+    /// Filters the source applied, e.g. `dataSize=165`, `memcmp@0=<mint>`
+    pub filters: Vec<String>,
+    /// Part of each account that was transferred, when not the whole account (count-only mode)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_slice: Option<String>,
+    /// An owner counts as a holder when its total raw balance is above this (ownerless accounts
+    /// are excluded, zero balances unless `zero_balance_included`)
+    pub dust_threshold: u64,
+    /// Owners whose accounts all hold zero were counted as holders (`--include-zero-balance`)
+    pub zero_balance_included: bool,
+    /// Owners with token accounts of the mint but only zero balances
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zero_balance_owners: Option<usize>,
+    /// Slot the accounts were read at, when the source reports it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slot: Option<u64>,
+    /// `--truncation-check` mode the fetch ran with
+    pub truncation_check: String,
+    /// Set when the balances fell short of the supply (response looks truncated)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub missing_supply_percent: Option<f64>,
+}
+
+impl CountMetadata {
+    /// Metadata of a snapshot of `mint` fetched from `rpc_url`
+    pub fn for_snapshot(
+        snapshot: &AccountSnapshot,
+        mint: &str,
+        rpc_url: String,
+        token_program: String,
+        truncation_check: TruncationMode,
+        count_only: bool,
+    ) -> Self {
+        let source = DataSource::from(snapshot.degraded);
+        let filters = match source {
+            // Token-2022 accounts with extensions are longer than the base layout: no size filter
+            DataSource::GetProgramAccounts if token_program == TOKEN_2022_PROGRAM_ID => vec![format!("memcmp@0={}", mint)],
+            DataSource::GetProgramAccounts => vec![format!("dataSize={}", TOKEN_ACCOUNT_LEN), format!("memcmp@0={}", mint)],
+            DataSource::Das => vec![format!("mint={}", mint)],
+            DataSource::LargestAccounts => vec![format!("mint={}", mint), "largest=20".to_string()],
+            DataSource::Cached => Vec::new(),
+        };
+        let data_slice = (count_only && source == DataSource::GetProgramAccounts)
+            .then(|| format!("offset={},length={} (owner+amount)", SLIM_ACCOUNT_OFFSET, SLIM_ACCOUNT_LEN));
+        let truncation_check = match truncation_check {
+            TruncationMode::Off => "off",
+            TruncationMode::Flag => "flag",
+            TruncationMode::Error => "error",
+        };
+        Self {
+            source,
+            rpc_url,
+            token_program,
+            commitment: if snapshot.finalized { "finalized" } else { "confirmed" }.to_string(),
+            filters,
+            data_slice,
+            dust_threshold: 0,
+            zero_balance_included: false,
+            zero_balance_owners: None,
+            slot: snapshot.slot,
+            truncation_check: truncation_check.to_string(),
+            missing_supply_percent: snapshot.missing_supply_percent,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metadata_for_snapshot() {
+        let snapshot = AccountSnapshot {
+            slot: Some(42),
+            ..Default::default()
+        };
+        let metadata = CountMetadata::for_snapshot(&snapshot, "M", "https://rpc".into(), "P".into(), TruncationMode::Flag, false);
+        assert_eq!(metadata.source, DataSource::GetProgramAccounts);
+        assert_eq!(metadata.filters, vec!["dataSize=165", "memcmp@0=M"]);
+        assert_eq!(metadata.commitment, "confirmed");
+        assert_eq!(metadata.slot, Some(42));
+        assert!(metadata.data_slice.is_none());
+
+        let snapshot = AccountSnapshot {
+            degraded: Some(Fallback::Das),
+            ..Default::default()
+        };
+        let metadata = CountMetadata::for_snapshot(&snapshot, "M", "https://rpc".into(), "P".into(), TruncationMode::Off, true);
+        assert_eq!(metadata.source, DataSource::Das);
+        assert_eq!(metadata.filters, vec!["mint=M"]);
+        // DAS returns owner and amount only: no slice was requested
+        assert!(metadata.data_slice.is_none());
+        let json = serde_json::to_value(&metadata).unwrap();
+        assert_eq!(json["source"], "das");
+        assert_eq!(json["truncation_check"], "off");
+        assert!(json.get("slot").is_none());
+
+        let metadata = CountMetadata::for_snapshot(
+            &AccountSnapshot::default(),
+            "M",
+            "https://rpc".into(),
+            TOKEN_2022_PROGRAM_ID.into(),
+            TruncationMode::Flag,
+            false,
+        );
+        assert_eq!(metadata.filters, vec!["memcmp@0=M"]);
+    }
+}
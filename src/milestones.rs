@@ -0,0 +1,140 @@
+//! Holder-count milestones ("1,000 holders!"), announced once when first crossed upwards
+
+use crate::i18n::fill;
+use crate::locale::Locale;
+use crate::token_monitor::{AlertRule, HolderStats};
+
+/// Milestone thresholds and the highest one already reached
+#[derive(Debug, Clone, Default)]
+pub struct MilestoneTracker {
+    /// Explicit thresholds (`--milestones`), sorted
+    fixed: Vec<usize>,
+    /// Every multiple of this is a milestone too (`--milestone-step`)
+    step: Option<usize>,
+    /// Highest milestone at or below the holder count seen so far
+    reached: Option<usize>,
+}
+
+impl MilestoneTracker {
+    pub fn new(mut fixed: Vec<usize>, step: Option<usize>) -> Self {
+        fixed.retain(|&value| value > 0);
+        fixed.sort_unstable();
+        fixed.dedup();
+        Self {
+            fixed,
+            step: step.filter(|&step| step > 0),
+            reached: None,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.fixed.is_empty() || self.step.is_some()
+    }
+
+    /// Highest milestone `<= count`
+    pub fn highest_at_or_below(&self, count: usize) -> Option<usize> {
+        let fixed = self.fixed.iter().rev().find(|&&value| value <= count).copied();
+        let stepped = self
+            .step
+            .map(|step| count / step * step)
+            .filter(|&value| value > 0);
+        fixed.max(stepped)
+    }
+
+    /// Smallest milestone `> count`
+    pub fn next_after(&self, count: usize) -> Option<usize> {
+        let fixed = self.fixed.iter().find(|&&value| value > count).copied();
+        let stepped = self.step.map(|step| (count / step + 1) * step);
+        match (fixed, stepped) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    /// Milestone newly crossed by `count`; the first observation only sets the baseline,
+    /// so milestones passed before the bot started aren't announced, and a milestone is
+    /// not announced again after a dip below it
+    pub fn observe(&mut self, count: usize) -> Option<usize> {
+        let highest = self.highest_at_or_below(count);
+        let baseline = match self.reached {
+            Some(reached) => reached,
+            None => {
+                self.reached = Some(highest.unwrap_or(0));
+                return None;
+            }
+        };
+        match highest {
+            Some(milestone) if milestone > baseline => {
+                self.reached = Some(milestone);
+                Some(milestone)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Milestone alert with its message when a milestone was crossed
+pub fn check_milestone(
+    stats: &HolderStats,
+    tracker: &mut MilestoneTracker,
+    locale: &Locale,
+) -> Option<(AlertRule, String)> {
+    if !tracker.is_enabled() {
+        return None;
+    }
+    let milestone = tracker.observe(stats.count)?;
+    let messages = locale.messages();
+    let mut message = fill(
+        messages.milestone_alert,
+        &[
+            ("milestone", &locale.format_count(milestone)),
+            ("holders", &locale.format_count(stats.count)),
+        ],
+    );
+    if let Some(next) = tracker.next_after(stats.count) {
+        message.push_str(&fill(messages.milestone_next, &[("next", &locale.format_count(next))]));
+    }
+    Some((AlertRule::Milestone, message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::token_monitor::calculate_stats;
+
+    #[test]
+    fn test_next_milestone() {
+        let tracker = MilestoneTracker::new(vec![10_000, 1_000], Some(5_000));
+        assert_eq!(tracker.next_after(0), Some(1_000));
+        assert_eq!(tracker.next_after(1_000), Some(5_000));
+        assert_eq!(tracker.next_after(9_999), Some(10_000));
+        assert_eq!(tracker.next_after(12_000), Some(15_000));
+        assert_eq!(tracker.highest_at_or_below(999), None);
+        assert_eq!(tracker.highest_at_or_below(12_000), Some(10_000));
+
+        let fixed_only = MilestoneTracker::new(vec![1_000], None);
+        assert_eq!(fixed_only.next_after(1_000), None);
+    }
+
+    #[test]
+    fn test_milestone_announced_once() {
+        let mut tracker = MilestoneTracker::new(vec![1_000], Some(5_000));
+        let locale = Locale::default();
+        let mut check = |count| check_milestone(&calculate_stats(count, None), &mut tracker, &locale);
+
+        // Baseline: 1,000 was already passed at startup
+        assert_eq!(check(1_200), None);
+        assert_eq!(check(4_000), None);
+        assert_eq!(
+            check(5_300),
+            Some((AlertRule::Milestone, "🎉 MILESTONE: 5000 holders reached | now 5300 | next: 10000".to_string()))
+        );
+        // Dip and recover: no repeat
+        assert_eq!(check(4_900), None);
+        assert_eq!(check(5_100), None);
+        // Jump over two milestones: only the highest is announced
+        let (rule, message) = check(16_000).unwrap();
+        assert_eq!(rule, AlertRule::Milestone);
+        assert!(message.contains("15000 holders"));
+    }
+}
@@ -0,0 +1,194 @@
+//! Multisig owners: SPL Token multisigs and Squads multisig accounts resolved to their members,
+//! plus the program-derived-address check that tells vaults and treasuries from personal wallets
+
+use serde::Serialize;
+use solana_program::hash::hashv;
+use solana_program::pubkey::Pubkey;
+use std::str::FromStr;
+
+use crate::rpc_client::TOKEN_PROGRAM_ID;
+
+pub const SQUADS_V3_PROGRAM_ID: &str = "SMPLecH534NA9acpos4G6x7uf3LWbCAwZQE9e8ZekMu";
+pub const SQUADS_V4_PROGRAM_ID: &str = "SQDS4ep65T869zMMBKyuUq6aD6EgTu8psMjkvj52pCf";
+
+/// Size of an SPL Token `Multisig` account
+const SPL_MULTISIG_LEN: usize = 355;
+/// Signer slots of an SPL Token multisig
+const SPL_MULTISIG_SIGNERS: usize = 11;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MultisigKind {
+    SplToken,
+    SquadsV3,
+    SquadsV4,
+}
+
+/// Multisig account with its signers
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Multisig {
+    pub kind: MultisigKind,
+    /// Signatures required
+    pub threshold: u16,
+    pub members: Vec<String>,
+}
+
+/// Programs whose accounts may be multisigs (their data is fetched for `parse_multisig`)
+pub fn is_multisig_program(program: &Pubkey) -> bool {
+    [TOKEN_PROGRAM_ID, SQUADS_V3_PROGRAM_ID, SQUADS_V4_PROGRAM_ID]
+        .iter()
+        .any(|id| Pubkey::from_str(id).is_ok_and(|id| id == *program))
+}
+
+/// Off-curve address (PDA): controlled by a program, never a personal wallet's key
+pub fn is_program_derived(address: &Pubkey) -> bool {
+    !address.is_on_curve()
+}
+
+/// Decode an account owned by `program` as a multisig; None for any other account
+pub fn parse_multisig(program: &Pubkey, data: &[u8]) -> Option<Multisig> {
+    match program.to_string().as_str() {
+        TOKEN_PROGRAM_ID => parse_spl_multisig(data),
+        SQUADS_V3_PROGRAM_ID => parse_squads_v3(data),
+        SQUADS_V4_PROGRAM_ID => parse_squads_v4(data),
+        _ => None,
+    }
+}
+
+/// `m: u8, n: u8, is_initialized: bool, signers: [Pubkey; 11]`
+fn parse_spl_multisig(data: &[u8]) -> Option<Multisig> {
+    if data.len() != SPL_MULTISIG_LEN || data[2] != 1 {
+        return None;
+    }
+    let (m, n) = (data[0], data[1] as usize);
+    if n == 0 || n > SPL_MULTISIG_SIGNERS || m == 0 || m as usize > n {
+        return None;
+    }
+    let members = data[3..3 + n * 32]
+        .chunks_exact(32)
+        .map(|key| Pubkey::try_from(key).unwrap().to_string())
+        .collect();
+    Some(Multisig {
+        kind: MultisigKind::SplToken,
+        threshold: m.into(),
+        members,
+    })
+}
+
+/// Anchor account discriminator: first 8 bytes of `sha256("account:<Name>")`
+fn anchor_discriminator(name: &str) -> [u8; 8] {
+    let hash = hashv(&[b"account:", name.as_bytes()]);
+    hash.to_bytes()[..8].try_into().unwrap()
+}
+
+/// Borsh `Vec<T>` of `item_len`-byte items at `offset`, returning the first 32 bytes of each item
+fn read_keys(data: &[u8], offset: usize, item_len: usize) -> Option<Vec<String>> {
+    let len = u32::from_le_bytes(data.get(offset..offset + 4)?.try_into().ok()?) as usize;
+    let items = data.get(offset + 4..offset + 4 + len.checked_mul(item_len)?)?;
+    Some(
+        items
+            .chunks_exact(item_len)
+            .map(|item| Pubkey::try_from(&item[..32]).unwrap().to_string())
+            .collect(),
+    )
+}
+
+/// Squads v3 `Ms`: threshold u16, authority_index u16, transaction_index u32,
+/// ms_change_index u32, bump u8, create_key, allow_external_execute bool, keys Vec<Pubkey>
+fn parse_squads_v3(data: &[u8]) -> Option<Multisig> {
+    if data.get(..8)? != anchor_discriminator("Ms") {
+        return None;
+    }
+    let threshold = u16::from_le_bytes(data.get(8..10)?.try_into().ok()?);
+    let members = read_keys(data, 8 + 2 + 2 + 4 + 4 + 1 + 32 + 1, 32)?;
+    Some(Multisig {
+        kind: MultisigKind::SquadsV3,
+        threshold,
+        members,
+    })
+}
+
+/// Squads v4 `Multisig`: create_key, config_authority, threshold u16, time_lock u32,
+/// transaction_index u64, stale_transaction_index u64, rent_collector Option<Pubkey>, bump u8,
+/// members Vec<{key, permissions u8}>
+fn parse_squads_v4(data: &[u8]) -> Option<Multisig> {
+    if data.get(..8)? != anchor_discriminator("Multisig") {
+        return None;
+    }
+    let threshold = u16::from_le_bytes(data.get(72..74)?.try_into().ok()?);
+    let rent_collector_len = match data.get(94)? {
+        0 => 0,
+        1 => 32,
+        _ => return None,
+    };
+    let members = read_keys(data, 95 + rent_collector_len + 1, 33)?;
+    Some(Multisig {
+        kind: MultisigKind::SquadsV4,
+        threshold,
+        members,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    fn program(id: &str) -> Pubkey {
+        Pubkey::from_str(id).unwrap()
+    }
+
+    #[test]
+    fn test_parse_spl_multisig() {
+        let signers = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+        let mut data = vec![0u8; SPL_MULTISIG_LEN];
+        data[..3].copy_from_slice(&[2, 3, 1]);
+        for (i, signer) in signers.iter().enumerate() {
+            data[3 + i * 32..3 + (i + 1) * 32].copy_from_slice(signer.as_ref());
+        }
+
+        let multisig = parse_multisig(&program(TOKEN_PROGRAM_ID), &data).unwrap();
+        assert_eq!(multisig.kind, MultisigKind::SplToken);
+        assert_eq!(multisig.threshold, 2);
+        assert_eq!(multisig.members, signers.iter().map(|s| s.to_string()).collect::<Vec<_>>());
+
+        // Token accounts and uninitialized multisigs are not multisigs
+        assert!(parse_multisig(&program(TOKEN_PROGRAM_ID), &data[..165]).is_none());
+        data[2] = 0;
+        assert!(parse_multisig(&program(TOKEN_PROGRAM_ID), &data).is_none());
+    }
+
+    #[test]
+    fn test_parse_squads_v4() {
+        let members = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let mut data = anchor_discriminator("Multisig").to_vec();
+        data.extend_from_slice(&[0; 64]); // create_key, config_authority
+        data.extend_from_slice(&2u16.to_le_bytes());
+        data.extend_from_slice(&[0; 4 + 8 + 8]);
+        data.push(1); // rent_collector: Some
+        data.extend_from_slice(&[7; 32]);
+        data.push(255); // bump
+        data.extend_from_slice(&(members.len() as u32).to_le_bytes());
+        for member in &members {
+            data.extend_from_slice(member.as_ref());
+            data.push(7); // permissions
+        }
+
+        let multisig = parse_multisig(&program(SQUADS_V4_PROGRAM_ID), &data).unwrap();
+        assert_eq!(multisig.kind, MultisigKind::SquadsV4);
+        assert_eq!(multisig.threshold, 2);
+        assert_eq!(multisig.members[1], members[1].to_string());
+
+        // Truncated member list; other Squads accounts (proposals, ...) have another discriminator
+        assert!(parse_squads_v4(&data[..data.len() - 1]).is_none());
+        data[0] ^= 1;
+        assert!(parse_multisig(&program(SQUADS_V4_PROGRAM_ID), &data).is_none());
+    }
+
+    #[test]
+    fn test_is_program_derived() {
+        let (pda, _) = Pubkey::find_program_address(&[b"vault"], &program(SQUADS_V4_PROGRAM_ID));
+        assert!(is_program_derived(&pda));
+        assert!(!is_program_derived(&Keypair::new().pubkey()));
+    }
+}
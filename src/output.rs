@@ -0,0 +1,364 @@
+use clap::ValueEnum;
+use std::io::IsTerminal;
+use std::time::Duration;
+
+use crate::churn::ChurnStats;
+use crate::events::HolderEvent;
+use crate::locale::Locale;
+use crate::quality::HolderQuality;
+use crate::sinks::{Sink, SinkFuture};
+use crate::token_monitor::HolderStats;
+
+/// Per-cycle console output format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// One `MINT: ... | Holders: ... | Accounts: ...` line per cycle (log-friendly)
+    Plain,
+    /// Aligned table rows, colored by direction of change
+    Table,
+}
+
+const GREEN: &str = "\x1b[32m";
+const RED: &str = "\x1b[31m";
+const RESET: &str = "\x1b[0m";
+
+/// Console printer for per-cycle status
+#[derive(Debug, Clone, Copy)]
+pub struct ConsoleOutput {
+    pub format: OutputFormat,
+    pub color: bool,
+    /// Suppress per-cycle status (alerts still come through the log)
+    pub quiet: bool,
+    pub locale: Locale,
+}
+
+impl ConsoleOutput {
+    pub fn new(format: OutputFormat, no_color: bool, quiet: bool) -> Self {
+        Self {
+            format,
+            color: use_color(no_color),
+            quiet,
+            locale: Locale::default(),
+        }
+    }
+
+    /// Format times and numbers with `locale` (`--timezone`, `--number-format`)
+    pub fn with_locale(mut self, locale: Locale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    /// Print table header (no-op for plain output)
+    pub fn print_header(&self) {
+        if self.format == OutputFormat::Table && !self.quiet {
+            println!("{}", table_header(&self.locale));
+        }
+    }
+
+    /// Print status for one monitoring cycle
+    /// `churn` is None until two polls are available to diff
+    pub fn print_status(
+        &self,
+        mint: &str,
+        stats: &HolderStats,
+        elapsed: Duration,
+        churn: Option<&ChurnStats>,
+    ) {
+        if self.quiet {
+            return;
+        }
+        match self.format {
+            OutputFormat::Plain => {
+                let mut line = format_plain_line(mint, stats, elapsed, &self.locale);
+                if let Some(churn) = churn {
+                    line.push_str(&format_churn(churn, &self.locale));
+                }
+                println!("{}", line);
+            }
+            OutputFormat::Table => println!("{}", format_table_row(mint, stats, elapsed, self.color, &self.locale)),
+        }
+    }
+
+    /// Print holder quality report (raw count vs estimated organic holders)
+    pub fn print_quality(&self, quality: &HolderQuality) {
+        if self.quiet {
+            return;
+        }
+        println!(
+            "Holders: {} | Estimated organic: {} ({:.1}% suspicious) | Identical amounts: {} wallets in {} clusters | Sample: {}/{} clustered by funder/creation slot",
+            quality.holders,
+            quality.estimated_organic_holders,
+            quality.suspicious_percent,
+            quality.identical_amount_wallets,
+            quality.identical_amount_clusters.len(),
+            quality.sampled_suspicious,
+            quality.sampled_wallets
+        );
+        let age = &quality.wallet_age;
+        if age.wallets > 0 {
+            println!(
+                "Wallet age: {:.1}% fresh (<7d) | {} fresh / {} 7-90d / {} old | median {:.0}d",
+                age.freshness_ratio * 100.0,
+                age.fresh,
+                age.recent,
+                age.old,
+                age.median_age_days.unwrap_or_default()
+            );
+        }
+        let accounts = &quality.accounts_per_owner;
+        if accounts.multi_account_holders > 0 {
+            println!(
+                "Accounts per holder: {} holders ({:.1}%) with several accounts | max {} ({})",
+                accounts.multi_account_holders,
+                accounts.multi_account_percent,
+                accounts.max_accounts,
+                accounts.max_accounts_owner.as_deref().unwrap_or_default()
+            );
+        }
+    }
+}
+
+/// Prints a status line per observation
+impl Sink for ConsoleOutput {
+    fn name(&self) -> &str {
+        "console"
+    }
+
+    fn handle<'a>(&'a self, event: &'a HolderEvent) -> SinkFuture<'a> {
+        if let HolderEvent::Observation(observation) = event {
+            self.print_status(
+                &observation.mint.to_string(),
+                &observation.stats,
+                observation.elapsed,
+                observation.churn.as_ref(),
+            );
+        }
+        Box::pin(async { Ok(()) })
+    }
+}
+
+/// Whether to emit ANSI colors: only for terminals, and never when NO_COLOR is set
+pub fn use_color(no_color: bool) -> bool {
+    !no_color && std::env::var_os("NO_COLOR").is_none() && std::io::stdout().is_terminal()
+}
+
+/// Shorten a mint address for narrow output: `9Avy…pump`
+pub fn abbreviate_mint(mint: &str) -> String {
+    if mint.chars().count() <= 12 {
+        return mint.to_string();
+    }
+    let head: String = mint.chars().take(4).collect();
+    let tail: String = mint.chars().skip(mint.chars().count() - 4).collect();
+    format!("{}…{}", head, tail)
+}
+
+/// Format absolute change as `±0`, `+12` or `-3`
+fn format_change(change: i64, locale: &Locale) -> String {
+    if change == 0 {
+        "±0".to_string()
+    } else if change > 0 {
+        format!("+{}", locale.format_int(change))
+    } else {
+        locale.format_int(change)
+    }
+}
+
+/// Classic single-line status output; holders are unique owners, accounts the token accounts
+/// they were counted from
+pub fn format_plain_line(mint: &str, stats: &HolderStats, elapsed: Duration, locale: &Locale) -> String {
+    let change_percent_str = if stats.change_percent == 0.0 {
+        "".to_string()
+    } else {
+        format!(" ({})", locale.format_percent(stats.change_percent))
+    };
+    let accounts_str = stats
+        .token_accounts
+        .map(|accounts| format!(" | Accounts: {}", locale.format_count(accounts)))
+        .unwrap_or_default();
+
+    format!(
+        "MINT: {} | Holders: {}{} | Δ: {}{} | Time: {} | Fetch: {:.2}s{}",
+        mint,
+        locale.format_count(stats.count),
+        accounts_str,
+        format_change(stats.change, locale),
+        change_percent_str,
+        locale.format_timestamp(stats.timestamp),
+        elapsed.as_secs_f64(),
+        format_degraded(stats)
+    )
+}
+
+/// Status line suffix for counts of doubtful quality: ` | DEGRADED (das)`,
+/// ` | TRUNCATED? (12.5% of supply missing)`
+fn format_degraded(stats: &HolderStats) -> String {
+    let mut suffix = stats
+        .degraded
+        .map(|fallback| format!(" | DEGRADED ({})", fallback.as_str()))
+        .unwrap_or_default();
+    if let Some(missing) = stats.missing_supply_percent {
+        suffix.push_str(&format!(" | TRUNCATED? ({:.1}% of supply missing)", missing));
+    }
+    suffix
+}
+
+/// Status line suffix: ` | Churn 1h: +12/-3 (97.5% kept)`
+pub fn format_churn(churn: &ChurnStats, locale: &Locale) -> String {
+    format!(
+        " | Churn {}h: +{}/-{} ({}% kept)",
+        churn.window_secs / 3600,
+        locale.format_count(churn.entered),
+        locale.format_count(churn.exited),
+        locale.format_float(churn.retention_percent, 1)
+    )
+}
+
+/// Header matching `format_table_row` column widths; TIME is as wide as the locale's timestamps
+pub fn table_header(locale: &Locale) -> String {
+    format!(
+        "{:<time$} {:<11} {:>10} {:>10} {:>8} {:>8} {:>7}",
+        "TIME",
+        "MINT",
+        "HOLDERS",
+        "ACCOUNTS",
+        "Δ",
+        "Δ%",
+        "FETCH",
+        time = locale.timestamp_width()
+    )
+}
+
+/// Aligned table row; green for growth, red for drops when `color` is set
+pub fn format_table_row(
+    mint: &str,
+    stats: &HolderStats,
+    elapsed: Duration,
+    color: bool,
+    locale: &Locale,
+) -> String {
+    // Pad before coloring so escape codes don't break alignment
+    let change = format!("{:>8}", format_change(stats.change, locale));
+    let percent = format!("{:>8}", locale.format_percent(stats.change_percent));
+    let accounts = stats.token_accounts.map_or_else(|| "-".to_string(), |accounts| locale.format_count(accounts));
+
+    let (start, end) = match (color, stats.change.signum()) {
+        (true, 1) => (GREEN, RESET),
+        (true, -1) => (RED, RESET),
+        _ => ("", ""),
+    };
+
+    format!(
+        "{:<time$} {:<11} {:>10} {:>10} {}{} {}{} {:>6.2}s{}",
+        locale.format_timestamp(stats.timestamp),
+        abbreviate_mint(mint),
+        locale.format_count(stats.count),
+        accounts,
+        start,
+        change,
+        percent,
+        end,
+        elapsed.as_secs_f64(),
+        format_degraded(stats),
+        time = locale.timestamp_width()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::locale::NumberLocale;
+    use crate::rpc_client::Fallback;
+
+    fn stats(change: i64) -> HolderStats {
+        HolderStats {
+            count: 1234,
+            timestamp: 0,
+            change,
+            change_percent: change as f64,
+            slot: None,
+            block_time: None,
+            degraded: None,
+            missing_supply_percent: None,
+            token_accounts: None,
+        }
+    }
+
+    #[test]
+    fn test_abbreviate_mint() {
+        assert_eq!(
+            abbreviate_mint("9AvytnUKsLxPxFHFqS6VLxaxt5p6BhYNr53SD2Chpump"),
+            "9Avy…pump"
+        );
+        assert_eq!(abbreviate_mint("short"), "short");
+    }
+
+    #[test]
+    fn test_table_row_alignment() {
+        let mint = "9AvytnUKsLxPxFHFqS6VLxaxt5p6BhYNr53SD2Chpump";
+        let locale = Locale::default();
+        let plain = format_table_row(mint, &stats(5), Duration::from_secs(1), false, &locale);
+        let colored = format_table_row(mint, &stats(5), Duration::from_secs(1), true, &locale);
+
+        assert!(colored.contains(GREEN));
+        assert_eq!(colored.replace(GREEN, "").replace(RESET, ""), plain);
+        assert_eq!(plain.chars().count(), table_header(&locale).chars().count());
+        assert!(format_table_row(mint, &stats(-5), Duration::ZERO, true, &locale).contains(RED));
+        assert!(!format_table_row(mint, &stats(0), Duration::ZERO, true, &locale).contains(GREEN));
+    }
+
+    #[test]
+    fn test_table_time_column_fits_timezone() {
+        let mint = "9AvytnUKsLxPxFHFqS6VLxaxt5p6BhYNr53SD2Chpump";
+        let berlin = Locale {
+            timezone: Some(crate::locale::parse_timezone("Europe/Berlin").unwrap()),
+            ..Locale::default()
+        };
+        let header = table_header(&berlin);
+        let mint_column = header.find("MINT").unwrap();
+        // Summer (CEST) and winter (CET) rows both start the MINT column under its header
+        for timestamp in [1_719_792_000, 1_702_324_800] {
+            let row = format_table_row(mint, &HolderStats { timestamp, ..stats(5) }, Duration::ZERO, false, &berlin);
+            assert_eq!(row.find("9Avy").unwrap(), mint_column, "{}", row);
+            assert_eq!(row.chars().count(), header.chars().count());
+        }
+    }
+
+    #[test]
+    fn test_plain_line_locale() {
+        let locale = Locale {
+            numbers: NumberLocale::De,
+            ..Locale::default()
+        };
+        let stats = HolderStats {
+            count: 1_234_567,
+            timestamp: 0,
+            change: 2500,
+            change_percent: 0.2,
+            slot: None,
+            block_time: None,
+            degraded: None,
+            missing_supply_percent: None,
+            token_accounts: None,
+        };
+        let line = format_plain_line("Mint", &stats, Duration::ZERO, &locale);
+        assert!(line.contains("Holders: 1.234.567 | Δ: +2.500 (+0,2%)"));
+        let with_accounts = HolderStats { token_accounts: Some(1_500_000), ..stats };
+        let line = format_plain_line("Mint", &with_accounts, Duration::ZERO, &locale);
+        assert!(line.contains("Holders: 1.234.567 | Accounts: 1.500.000 | Δ: +2.500"));
+        assert!(line.contains("1970-01-01 00:00:00 UTC"));
+        assert!(!line.contains("DEGRADED"));
+    }
+
+    #[test]
+    fn test_degraded_flag() {
+        let locale = Locale::default();
+        let mut degraded = stats(0);
+        degraded.degraded = Some(Fallback::LargestAccounts);
+        assert!(format_plain_line("Mint", &degraded, Duration::ZERO, &locale).ends_with(" | DEGRADED (largest_accounts)"));
+        assert!(format_table_row("Mint", &degraded, Duration::ZERO, false, &locale).ends_with(" | DEGRADED (largest_accounts)"));
+
+        let mut truncated = stats(0);
+        truncated.missing_supply_percent = Some(12.5);
+        assert!(format_plain_line("Mint", &truncated, Duration::ZERO, &locale).ends_with(" | TRUNCATED? (12.5% of supply missing)"));
+    }
+}
@@ -0,0 +1,250 @@
+//! Holder owners by program: wallets (system accounts) vs accounts owned by programs
+//! (multisigs, vaults, lending protocols), from the owners' own accounts
+//! Multisig owners are resolved to their members, and program-derived owners (vaults,
+//! treasuries) are kept apart from personal wallets
+
+use anyhow::Result;
+use serde::Serialize;
+use solana_program::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::str::FromStr;
+use tracing::info;
+
+use crate::multisig::{is_multisig_program, is_program_derived, parse_multisig, Multisig};
+use crate::rpc_client::SolanaRpcClient;
+
+/// Owner accounts of wallets
+pub const SYSTEM_PROGRAM_ID: &str = "11111111111111111111111111111111";
+
+/// Programs shown by name in the breakdown
+const KNOWN_PROGRAMS: &[(&str, &str)] = &[
+    ("SMPLecH534NA9acpos4G6x7uf3LWbCAwZQE9e8ZekMu", "Squads v3"),
+    ("SQDS4ep65T869zMMBKyuUq6aD6EgTu8psMjkvj52pCf", "Squads v4"),
+    ("KLend2g3cP87fffoy8q1mQqGKjrxjC8boSyAYavgmjD", "Kamino Lending"),
+    ("MFv2hWf31Z9kbCa1snEPYctwafyhdvnV7FZnsebVacA", "MarginFi v2"),
+    ("675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8", "Raydium AMM v4"),
+    ("whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc", "Orca Whirlpool"),
+    ("LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo", "Meteora DLMM"),
+    ("Stake11111111111111111111111111111111111111", "Stake program"),
+    ("BPFLoaderUpgradeab1e11111111111111111111111", "Upgradeable BPF loader"),
+];
+
+/// Owners of one program
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ProgramOwners {
+    pub program: String,
+    /// Known program name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<&'static str>,
+    pub owners: usize,
+    pub percent: f64,
+}
+
+/// Holder that is a multisig account
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct MultisigOwner {
+    pub address: String,
+    #[serde(flatten)]
+    pub multisig: Multisig,
+}
+
+/// Holder owners by the program owning their account (`GET /holders/:mint/breakdown`)
+/// Percentages are of the examined owners; with a sample they estimate the whole holder set
+#[derive(Debug, Clone, Serialize)]
+pub struct OwnerBreakdown {
+    pub holders: usize,
+    pub examined_owners: usize,
+    /// Owners whose account belongs to the System Program
+    pub wallets: usize,
+    pub wallet_percent: f64,
+    /// Owners without an account: wallets without SOL, or program addresses (PDAs) that hold
+    /// tokens without storing data, e.g. pool and vault authorities
+    pub no_account: usize,
+    pub no_account_percent: f64,
+    /// Owners whose account belongs to any other program
+    pub program_owned: usize,
+    pub program_owned_percent: f64,
+    /// Program-owned owners per program, most common first
+    pub programs: Vec<ProgramOwners>,
+    /// Off-curve owners (PDAs): vaults, treasuries, multisig vaults and pool authorities,
+    /// whatever their account; counted in the categories above as well
+    pub program_derived: usize,
+    pub program_derived_percent: f64,
+    /// On-curve owners with a System Program account or none: what looks like a person's wallet
+    pub personal_wallets: usize,
+    pub personal_wallet_percent: f64,
+    /// Owners that are multisig accounts (SPL Token, Squads), with their members
+    pub multisigs: Vec<MultisigOwner>,
+    pub computed_at: u64,
+}
+
+/// Name of a known owner program
+pub fn program_label(program: &Pubkey) -> Option<&'static str> {
+    KNOWN_PROGRAMS
+        .iter()
+        .find(|(id, _)| Pubkey::from_str(id).is_ok_and(|id| id == *program))
+        .map(|(_, label)| *label)
+}
+
+fn percent(part: usize, total: usize) -> f64 {
+    if total > 0 {
+        part as f64 / total as f64 * 100.0
+    } else {
+        0.0
+    }
+}
+
+/// Breakdown from each examined holder and the program owning its account (None: no account)
+pub fn summarize_owners(
+    holders: usize,
+    owners: &[(Pubkey, Option<Pubkey>)],
+    multisigs: Vec<MultisigOwner>,
+    now: u64,
+) -> OwnerBreakdown {
+    let system_program = Pubkey::from_str(SYSTEM_PROGRAM_ID).unwrap();
+    let examined = owners.len();
+    let mut wallets = 0;
+    let mut no_account = 0;
+    let mut program_derived = 0;
+    let mut personal_wallets = 0;
+    let mut by_program: HashMap<Pubkey, usize> = HashMap::new();
+    for (owner, owner_program) in owners {
+        let wallet_like = match owner_program {
+            Some(program) if *program == system_program => {
+                wallets += 1;
+                true
+            }
+            Some(program) => {
+                *by_program.entry(*program).or_default() += 1;
+                false
+            }
+            None => {
+                no_account += 1;
+                true
+            }
+        };
+        if is_program_derived(owner) {
+            program_derived += 1;
+        } else if wallet_like {
+            personal_wallets += 1;
+        }
+    }
+    let program_owned = by_program.values().sum();
+
+    let mut programs: Vec<ProgramOwners> = by_program
+        .into_iter()
+        .map(|(program, owners)| ProgramOwners {
+            program: program.to_string(),
+            label: program_label(&program),
+            owners,
+            percent: percent(owners, examined),
+        })
+        .collect();
+    programs.sort_by(|a, b| b.owners.cmp(&a.owners).then_with(|| a.program.cmp(&b.program)));
+
+    OwnerBreakdown {
+        holders,
+        examined_owners: examined,
+        wallets,
+        wallet_percent: percent(wallets, examined),
+        no_account,
+        no_account_percent: percent(no_account, examined),
+        program_owned,
+        program_owned_percent: percent(program_owned, examined),
+        programs,
+        program_derived,
+        program_derived_percent: percent(program_derived, examined),
+        personal_wallets,
+        personal_wallet_percent: percent(personal_wallets, examined),
+        multisigs,
+        computed_at: now,
+    }
+}
+
+/// Look up the accounts of `owners` (one getMultipleAccounts call per 100, without data),
+/// then fetch the data of the few owned by multisig programs to resolve their members
+pub async fn breakdown_owners(
+    rpc_client: &SolanaRpcClient,
+    holders: usize,
+    owners: &[Pubkey],
+    now: u64,
+) -> Result<OwnerBreakdown> {
+    let accounts = rpc_client.get_multiple_accounts(owners, false).await?;
+    let owner_programs: Vec<(Pubkey, Option<Pubkey>)> = owners
+        .iter()
+        .copied()
+        .zip(accounts.into_iter().map(|account| account.map(|account| account.owner)))
+        .collect();
+
+    let candidates: Vec<Pubkey> = owner_programs
+        .iter()
+        .filter(|(_, program)| program.as_ref().is_some_and(is_multisig_program))
+        .map(|(owner, _)| *owner)
+        .collect();
+    let mut multisigs = Vec::new();
+    if !candidates.is_empty() {
+        let accounts = rpc_client.get_multiple_accounts(&candidates, true).await?;
+        for (address, account) in candidates.iter().zip(accounts) {
+            let Some(account) = account else {
+                continue;
+            };
+            if let Some(multisig) = parse_multisig(&account.owner, &account.data) {
+                multisigs.push(MultisigOwner {
+                    address: address.to_string(),
+                    multisig,
+                });
+            }
+        }
+    }
+
+    info!(
+        "Examined the accounts of {} of {} holders ({} multisigs)",
+        owner_programs.len(),
+        holders,
+        multisigs.len()
+    );
+    Ok(summarize_owners(holders, &owner_programs, multisigs, now))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    #[test]
+    fn test_summarize_owners() {
+        let system = Pubkey::from_str(SYSTEM_PROGRAM_ID).unwrap();
+        let squads = Pubkey::from_str(KNOWN_PROGRAMS[1].0).unwrap();
+        let unknown = Pubkey::new_unique();
+        let (vault, _) = Pubkey::find_program_address(&[b"vault"], &squads);
+        let owners: Vec<(Pubkey, Option<Pubkey>)> = [
+            Some(system),
+            Some(system),
+            Some(squads),
+            Some(squads),
+            Some(unknown),
+            None,
+            Some(system),
+        ]
+        .into_iter()
+        .map(|program| (Keypair::new().pubkey(), program))
+        .chain([(vault, Some(system))])
+        .collect();
+
+        let breakdown = summarize_owners(80, &owners, Vec::new(), 42);
+        assert_eq!(breakdown.examined_owners, 8);
+        assert_eq!(breakdown.wallets, 4);
+        assert_eq!(breakdown.wallet_percent, 50.0);
+        assert_eq!(breakdown.no_account, 1);
+        assert_eq!(breakdown.program_owned, 3);
+        assert_eq!(breakdown.programs[0].label, Some("Squads v4"));
+        assert_eq!(breakdown.programs[0].percent, 25.0);
+        assert_eq!(breakdown.programs[1].program, unknown.to_string());
+        assert_eq!(breakdown.programs[1].label, None);
+        // A treasury vault holding SOL is a System Program account, but not a personal wallet
+        assert_eq!(breakdown.program_derived, 1);
+        assert_eq!(breakdown.personal_wallets, 4);
+
+        assert_eq!(summarize_owners(0, &[], Vec::new(), 0).wallet_percent, 0.0);
+    }
+}
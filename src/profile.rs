@@ -0,0 +1,206 @@
+use serde::Serialize;
+use solana_program::pubkey::Pubkey;
+use std::collections::HashSet;
+use std::str::FromStr;
+use tracing::{debug, info};
+
+use crate::rpc_client::SolanaRpcClient;
+
+/// Recent transactions inspected per sampled wallet
+pub const TRANSACTIONS_PER_WALLET: usize = 10;
+
+/// DEX / AMM / aggregator programs
+const DEX_PROGRAMS: &[&str] = &[
+    "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4", // Jupiter v6
+    "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8", // Raydium AMM v4
+    "CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK", // Raydium CLMM
+    "CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C", // Raydium CPMM
+    "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc", // Orca Whirlpool
+    "LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo", // Meteora DLMM
+    "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P", // pump.fun
+    "PhoeNiXZ8ByJGLkxNfZRnkUfjvmuYqLR89jjFHGqdXY", // Phoenix
+];
+
+/// NFT mint / marketplace programs
+const NFT_PROGRAMS: &[&str] = &[
+    "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s", // Metaplex Token Metadata
+    "BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY", // Bubblegum (compressed NFTs)
+    "CndyV3LdqHUfDLmE5naZjVN8rBZz4tqhdefbAnjHG3JR", // Candy Machine v3
+    "M2mx93ekt1fmXSVkTrUL9xVFHkmME8HTUi5Cyc5aF7K", // Magic Eden v2
+    "TSWAPaqyCSx2KABk68Shruf4rp7CxcNi8hAsbdwmHbN", // Tensor Swap
+];
+
+/// Native and liquid staking programs
+const STAKING_PROGRAMS: &[&str] = &[
+    "Stake11111111111111111111111111111111111111", // Stake program
+    "MarBmsSgKXdrN1egZf5sqe1TMai9K1rChYNDJgjq7aD", // Marinade
+    "SPoo1Ku8WFXoNDMHPsrGSTSG1Y47rzgn41SLUNakuHy", // SPL Stake Pool (Jito, ...)
+];
+
+/// Holder persona derived from recently used programs
+/// A wallet can have several of `DexTrader`, `NftCollector`, `Staker`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HolderProfile {
+    DexTrader,
+    NftCollector,
+    Staker,
+    /// Active, but none of the known programs
+    Other,
+    /// No transactions found
+    Inactive,
+}
+
+/// Number and share of sampled wallets with a profile
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ProfileShare {
+    pub profile: HolderProfile,
+    pub wallets: usize,
+    pub percent: f64,
+}
+
+/// Holder profile breakdown for a mint
+/// Shares don't add up to 100% because a wallet can match several profiles
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileBreakdown {
+    pub holders: usize,
+    pub sampled_wallets: usize,
+    pub transactions_per_wallet: usize,
+    pub profiles: Vec<ProfileShare>,
+    pub computed_at: u64,
+}
+
+fn matches_any(program: &Pubkey, known: &[&str]) -> bool {
+    known
+        .iter()
+        .any(|id| Pubkey::from_str(id).is_ok_and(|id| id == *program))
+}
+
+/// Profiles of one wallet from the programs its recent transactions invoked
+pub fn classify_programs(programs: &HashSet<Pubkey>) -> Vec<HolderProfile> {
+    if programs.is_empty() {
+        return vec![HolderProfile::Inactive];
+    }
+    let mut profiles = Vec::new();
+    if programs.iter().any(|p| matches_any(p, DEX_PROGRAMS)) {
+        profiles.push(HolderProfile::DexTrader);
+    }
+    if programs.iter().any(|p| matches_any(p, NFT_PROGRAMS)) {
+        profiles.push(HolderProfile::NftCollector);
+    }
+    if programs.iter().any(|p| matches_any(p, STAKING_PROGRAMS)) {
+        profiles.push(HolderProfile::Staker);
+    }
+    if profiles.is_empty() {
+        profiles.push(HolderProfile::Other);
+    }
+    profiles
+}
+
+/// Count profiles over classified wallets
+pub fn summarize_profiles(classified: &[Vec<HolderProfile>]) -> Vec<ProfileShare> {
+    let total = classified.len();
+    [
+        HolderProfile::DexTrader,
+        HolderProfile::NftCollector,
+        HolderProfile::Staker,
+        HolderProfile::Other,
+        HolderProfile::Inactive,
+    ]
+    .into_iter()
+    .map(|profile| {
+        let wallets = classified.iter().filter(|p| p.contains(&profile)).count();
+        let percent = if total > 0 {
+            wallets as f64 / total as f64 * 100.0
+        } else {
+            0.0
+        };
+        ProfileShare {
+            profile,
+            wallets,
+            percent,
+        }
+    })
+    .collect()
+}
+
+/// Programs used by a wallet in its latest transactions
+async fn recent_programs(rpc_client: &SolanaRpcClient, wallet: &Pubkey) -> anyhow::Result<HashSet<Pubkey>> {
+    let mut programs = HashSet::new();
+    for signature in rpc_client
+        .get_recent_signatures(wallet, TRANSACTIONS_PER_WALLET)
+        .await?
+    {
+        programs.extend(rpc_client.get_transaction_programs(&signature).await?);
+    }
+    Ok(programs)
+}
+
+/// Classify sampled holders by program interaction
+/// Costs up to `1 + TRANSACTIONS_PER_WALLET` RPC calls per wallet; wallets that fail are skipped
+pub async fn profile_holders(
+    rpc_client: &SolanaRpcClient,
+    holders: usize,
+    sample: &[Pubkey],
+    now: u64,
+) -> ProfileBreakdown {
+    let mut classified = Vec::with_capacity(sample.len());
+    for wallet in sample {
+        match recent_programs(rpc_client, wallet).await {
+            Ok(programs) => classified.push(classify_programs(&programs)),
+            Err(e) => debug!("Skipping {} in profile sample: {}", wallet, e),
+        }
+    }
+
+    info!("Profiled {} of {} sampled holders", classified.len(), sample.len());
+
+    ProfileBreakdown {
+        holders,
+        sampled_wallets: classified.len(),
+        transactions_per_wallet: TRANSACTIONS_PER_WALLET,
+        profiles: summarize_profiles(&classified),
+        computed_at: now,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn program(id: &str) -> Pubkey {
+        Pubkey::from_str(id).unwrap()
+    }
+
+    #[test]
+    fn test_classify_programs() {
+        let jupiter = program(DEX_PROGRAMS[0]);
+        let stake = program(STAKING_PROGRAMS[0]);
+        let unknown = Pubkey::new_unique();
+
+        assert_eq!(classify_programs(&HashSet::new()), vec![HolderProfile::Inactive]);
+        assert_eq!(
+            classify_programs(&[jupiter, stake, unknown].into_iter().collect()),
+            vec![HolderProfile::DexTrader, HolderProfile::Staker]
+        );
+        assert_eq!(
+            classify_programs(&[unknown].into_iter().collect()),
+            vec![HolderProfile::Other]
+        );
+    }
+
+    #[test]
+    fn test_summarize_profiles() {
+        let classified = vec![
+            vec![HolderProfile::DexTrader, HolderProfile::Staker],
+            vec![HolderProfile::DexTrader],
+            vec![HolderProfile::Inactive],
+            vec![HolderProfile::Other],
+        ];
+        let shares = summarize_profiles(&classified);
+        assert_eq!(shares[0].wallets, 2);
+        assert_eq!(shares[0].percent, 50.0);
+        assert_eq!(shares[1].wallets, 0);
+        assert_eq!(shares[2].wallets, 1);
+        assert_eq!(shares[4].profile, HolderProfile::Inactive);
+    }
+}
@@ -0,0 +1,371 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use solana_program::pubkey::Pubkey;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::Duration;
+use tracing::info;
+
+use crate::cli::Cli;
+use crate::token_monitor::{Metrics, MetricsSummary, DROP_ALERT_PERCENT, GROWTH_ALERT_PERCENT};
+
+/// Metric name prefix for everything exported by the bot
+const PREFIX: &str = "solana_holder_bot";
+
+/// Content type of the Prometheus text format
+pub const TEXT_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+/// Content type of the OpenMetrics text format
+pub const OPENMETRICS_CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// One series of a metric
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Sample {
+    pub labels: BTreeMap<&'static str, String>,
+    pub value: f64,
+}
+
+/// A metric with its series: rendered as text for `/metrics` and serialized for `/metrics.json`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MetricFamily {
+    pub name: String,
+    pub help: &'static str,
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub samples: Vec<Sample>,
+}
+
+/// Gauges and counters of the monitor metrics of each mint, one family per metric
+pub fn metric_families<'a>(mints: impl IntoIterator<Item = (&'a str, &'a MetricsSummary)>) -> Vec<MetricFamily> {
+    let mut families: Vec<MetricFamily> = Vec::new();
+    let mut metric = |kind: &'static str, name: &str, help: &'static str, labels: BTreeMap<&'static str, String>, value: f64| {
+        let name = format!("{}_{}", PREFIX, name);
+        let sample = Sample { labels, value };
+        match families.iter_mut().find(|family| family.name == name) {
+            Some(family) => family.samples.push(sample),
+            None => families.push(MetricFamily { name, help, kind, samples: vec![sample] }),
+        }
+    };
+
+    for (mint, metrics) in mints {
+        let labels = BTreeMap::from([("mint", mint.to_string())]);
+        if let Some(last) = metrics.last_holders {
+            metric("gauge", "holders", "Unique holders at the last successful poll", labels.clone(), last as f64);
+        }
+        if let Some(min) = metrics.min_holders {
+            metric("gauge", "holders_min", "Minimum unique holders observed", labels.clone(), min as f64);
+        }
+        if let Some(max) = metrics.max_holders {
+            metric("gauge", "holders_max", "Maximum unique holders observed", labels.clone(), max as f64);
+        }
+        metric("gauge", "holders_avg", "Average unique holders over all polls", labels.clone(), metrics.average_holders);
+        if let Some(delegations) = &metrics.delegations {
+            metric("gauge", "delegated_accounts", "Holder accounts with an active delegate approval", labels.clone(), delegations.delegated_accounts as f64);
+            metric("gauge", "delegated_supply_percent", "Share of the supply delegates may transfer", labels.clone(), delegations.delegated_percent);
+        }
+        if let Some(accounts) = metrics.last_token_accounts {
+            metric("gauge", "token_accounts", "Token accounts of the mint at the last successful poll", labels.clone(), accounts as f64);
+        }
+        metric("counter", "polls_total", "Successful polls since start", labels.clone(), metrics.total_polls as f64);
+        metric("counter", "alerts_total", "Alerts triggered since start", labels.clone(), metrics.alerts.len() as f64);
+        if let Some(p95) = metrics.fetch_p95_secs {
+            metric("gauge", "fetch_seconds_p95", "95th percentile of recent account fetch durations", labels.clone(), p95);
+        }
+        for tier in &metrics.tiers {
+            let mut labels = labels.clone();
+            labels.insert("tier", tier.tier.clone());
+            metric("gauge", "tier_holders", "Holders in a balance tier at the last poll", labels, tier.holders as f64);
+        }
+    }
+    families
+}
+
+/// Escape a label value for the text formats
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// Render families in Prometheus text exposition format, or OpenMetrics (`# EOF` terminated)
+/// OpenMetrics names a counter family without the `_total` suffix of its samples
+pub fn render_families(families: &[MetricFamily], openmetrics: bool) -> String {
+    let mut out = String::new();
+    for family in families {
+        let family_name = match family.kind {
+            "counter" if openmetrics => family.name.strip_suffix("_total").unwrap_or(&family.name),
+            _ => &family.name,
+        };
+        let _ = writeln!(out, "# HELP {} {}", family_name, family.help);
+        let _ = writeln!(out, "# TYPE {} {}", family_name, family.kind);
+        for sample in &family.samples {
+            let labels: Vec<String> = sample
+                .labels
+                .iter()
+                .map(|(name, value)| format!("{}=\"{}\"", name, escape_label(value)))
+                .collect();
+            let _ = writeln!(out, "{}{{{}}} {}", family.name, labels.join(","), sample.value);
+        }
+    }
+    if openmetrics {
+        out.push_str("# EOF\n");
+    }
+    out
+}
+
+/// Render monitoring metrics in Prometheus text exposition format
+pub fn render_metrics(mint: &Pubkey, metrics: &Metrics) -> String {
+    let mint = mint.to_string();
+    render_families(&metric_families([(mint.as_str(), &metrics.summary())]), false)
+}
+
+/// Push metrics to a Prometheus Pushgateway
+/// Uses PUT so the group for this job/mint is replaced on every push
+pub async fn push_to_gateway(gateway_url: &str, job: &str, mint: &Pubkey, body: String) -> Result<()> {
+    let url = format!(
+        "{}/metrics/job/{}/mint/{}",
+        gateway_url.trim_end_matches('/'),
+        job,
+        mint
+    );
+
+    let response = reqwest::Client::new()
+        .put(&url)
+        .header("Content-Type", "text/plain; version=0.0.4")
+        .timeout(Duration::from_secs(10))
+        .body(body)
+        .send()
+        .await
+        .with_context(|| format!("Failed to reach Pushgateway at {}", gateway_url))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let text = response.text().await.unwrap_or_default();
+        return Err(anyhow::anyhow!("Pushgateway returned {}: {}", status, text));
+    }
+
+    info!("Pushed metrics to Pushgateway ({})", gateway_url);
+    Ok(())
+}
+
+/// ATH / ATL window when `--ath-lookback-days` isn't set (Prometheus' default retention)
+const DEFAULT_RULES_LOOKBACK_DAYS: u64 = 15;
+
+/// Alert thresholds that the generated Prometheus rules mirror (`gen-alert-rules`)
+#[derive(Debug, Clone)]
+pub struct AlertRuleSettings {
+    /// Only match series with this `job` label
+    pub job: Option<String>,
+    pub interval_secs: u64,
+    /// 0 = no stall rule
+    pub stall_factor: u32,
+    pub milestones: Vec<usize>,
+    pub milestone_step: Option<usize>,
+    /// ATH / ATL window in days; None without `--ath-alerts`
+    pub record_lookback_days: Option<u64>,
+}
+
+/// One generated alerting rule
+struct PromRule {
+    alert: &'static str,
+    expr: String,
+    severity: &'static str,
+    summary: String,
+    labels: Vec<(&'static str, String)>,
+}
+
+impl AlertRuleSettings {
+    pub fn from_cli(cli: &Cli, job: Option<String>) -> Self {
+        Self {
+            job,
+            interval_secs: cli.interval,
+            stall_factor: cli.stall_factor,
+            milestones: cli.milestones.clone(),
+            milestone_step: cli.milestone_step,
+            record_lookback_days: cli
+                .ath_alerts
+                .then(|| cli.ath_lookback_days.unwrap_or(DEFAULT_RULES_LOOKBACK_DAYS)),
+        }
+    }
+
+    /// Rules file with the same semantics as the built-in alerts: growth / drop compare a
+    /// poll with the one an interval earlier, milestones fire when first crossed upwards
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "groups:");
+        let _ = writeln!(out, "  - name: {}", PREFIX);
+        let _ = writeln!(out, "    rules:");
+        for rule in self.rules() {
+            let _ = writeln!(out, "      - alert: {}", rule.alert);
+            let _ = writeln!(out, "        expr: {}", yaml_quote(&rule.expr));
+            let _ = writeln!(out, "        labels:");
+            let _ = writeln!(out, "          severity: {}", rule.severity);
+            for (name, value) in &rule.labels {
+                let _ = writeln!(out, "          {}: {}", name, yaml_quote(value));
+            }
+            let _ = writeln!(out, "        annotations:");
+            let _ = writeln!(out, "          summary: {}", yaml_quote(&rule.summary));
+        }
+        out
+    }
+
+    fn rules(&self) -> Vec<PromRule> {
+        let selector = match &self.job {
+            Some(job) => format!("{{job=\"{}\"}}", job),
+            None => String::new(),
+        };
+        let holders = format!("{}_holders{}", PREFIX, selector);
+        let interval = format!("{}s", self.interval_secs);
+        let previous = format!("({} offset {})", holders, interval);
+        let mut rules = vec![
+            PromRule {
+                alert: "HolderGrowth",
+                expr: format!(
+                    "({} - {}) / {} * 100 >= {}",
+                    holders, previous, previous, GROWTH_ALERT_PERCENT
+                ),
+                severity: "info",
+                summary: "Holders of {{ $labels.mint }} changed by {{ $value | humanize }}% in one poll".to_string(),
+                labels: Vec::new(),
+            },
+            PromRule {
+                alert: "HolderDrop",
+                expr: format!(
+                    "({} - {}) / {} * 100 <= {}",
+                    holders, previous, previous, DROP_ALERT_PERCENT
+                ),
+                severity: "critical",
+                summary: "Holders of {{ $labels.mint }} changed by {{ $value | humanize }}% in one poll".to_string(),
+                labels: Vec::new(),
+            },
+        ];
+
+        let mut milestones = self.milestones.clone();
+        milestones.retain(|&value| value > 0);
+        milestones.sort_unstable();
+        milestones.dedup();
+        for milestone in milestones {
+            rules.push(PromRule {
+                alert: "HolderMilestone",
+                expr: format!("{} >= {} and {} < {}", holders, milestone, previous, milestone),
+                severity: "info",
+                summary: format!("{{{{ $labels.mint }}}} reached {} holders", milestone),
+                labels: vec![("milestone", milestone.to_string())],
+            });
+        }
+        if let Some(step) = self.milestone_step.filter(|&step| step > 0) {
+            rules.push(PromRule {
+                alert: "HolderMilestone",
+                expr: format!("floor({} / {}) > floor({} / {})", holders, step, previous, step),
+                severity: "info",
+                summary: format!("{{{{ $labels.mint }}}} passed a multiple of {} holders", step),
+                labels: vec![("milestone_step", step.to_string())],
+            });
+        }
+
+        if let Some(days) = self.record_lookback_days {
+            let window = format!("[{}d] offset {}", days, interval);
+            rules.push(PromRule {
+                alert: "HolderAllTimeHigh",
+                expr: format!("{} > max_over_time({}{})", holders, holders, window),
+                severity: "info",
+                summary: format!("{{{{ $labels.mint }}}} is above its {}-day high", days),
+                labels: Vec::new(),
+            });
+            rules.push(PromRule {
+                alert: "HolderAllTimeLow",
+                expr: format!("{} < min_over_time({}{})", holders, holders, window),
+                severity: "warning",
+                summary: format!("{{{{ $labels.mint }}}} is below its {}-day low", days),
+                labels: Vec::new(),
+            });
+        }
+
+        if self.stall_factor > 0 {
+            let stall_secs = self.interval_secs * u64::from(self.stall_factor);
+            rules.push(PromRule {
+                alert: "HolderPollingStalled",
+                expr: format!("changes({}_polls_total{}[{}s]) == 0", PREFIX, selector, stall_secs),
+                severity: "critical",
+                summary: format!("No successful poll of {{{{ $labels.mint }}}} for {}s", stall_secs),
+                labels: Vec::new(),
+            });
+        }
+        rules
+    }
+}
+
+/// Single-quoted YAML scalar (no escapes except doubled quotes)
+fn yaml_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tiers::TierCount;
+
+    #[test]
+    fn test_render_metrics() {
+        let mint = Pubkey::new_unique();
+        let mut metrics = Metrics::new();
+        metrics.update(100);
+        metrics.update(120);
+
+        let text = render_metrics(&mint, &metrics);
+        let labels = format!("{{mint=\"{}\"}}", mint);
+        assert!(text.contains(&format!("solana_holder_bot_holders{} 120", labels)));
+        assert!(text.contains(&format!("solana_holder_bot_holders_min{} 100", labels)));
+        assert!(text.contains(&format!("solana_holder_bot_holders_avg{} 110", labels)));
+        assert!(text.contains("# TYPE solana_holder_bot_polls_total counter"));
+        assert!(text.contains("# TYPE solana_holder_bot_alerts_total counter"));
+        assert!(text.contains(&format!("solana_holder_bot_polls_total{} 2", labels)));
+        assert!(!text.contains("# EOF"));
+    }
+
+    #[test]
+    fn test_families_of_several_mints() {
+        let mut first = Metrics::new();
+        first.update(10);
+        first.tiers = vec![TierCount { tier: "g\"old".to_string(), holders: 2 }];
+        let second = Metrics::new();
+        let (first, second) = (first.summary(), second.summary());
+        let families = metric_families([("A", &first), ("B", &second)]);
+
+        let polls = families.iter().find(|family| family.name == "solana_holder_bot_polls_total").unwrap();
+        assert_eq!(polls.samples.len(), 2);
+        // No poll of B yet: no holders series for it
+        let holders = families.iter().find(|family| family.name == "solana_holder_bot_holders").unwrap();
+        assert_eq!(holders.samples.len(), 1);
+
+        let text = render_families(&families, true);
+        assert_eq!(text.matches("# TYPE solana_holder_bot_polls counter").count(), 1);
+        assert!(text.contains(r#"solana_holder_bot_polls_total{mint="A"} 1"#));
+        assert!(text.contains(r#"solana_holder_bot_tier_holders{mint="A",tier="g\"old"} 2"#));
+        assert!(text.ends_with("# EOF\n"));
+
+        let json = serde_json::to_value(&families).unwrap();
+        assert_eq!(json[0]["type"], "gauge");
+        assert_eq!(json[0]["samples"][0]["labels"]["mint"], "A");
+    }
+
+    #[test]
+    fn test_render_alert_rules() {
+        let settings = AlertRuleSettings {
+            job: Some("bot".to_string()),
+            interval_secs: 30,
+            stall_factor: 5,
+            milestones: vec![1000],
+            milestone_step: None,
+            record_lookback_days: None,
+        };
+        let yaml = settings.render();
+        let holders = r#"solana_holder_bot_holders{job="bot"}"#;
+        assert!(yaml.contains(&format!(
+            "expr: '({h} - ({h} offset 30s)) / ({h} offset 30s) * 100 >= 50'",
+            h = holders
+        )));
+        assert!(yaml.contains(&format!("expr: '{h} >= 1000 and ({h} offset 30s) < 1000'", h = holders)));
+        assert!(yaml.contains(r#"changes(solana_holder_bot_polls_total{job="bot"}[150s]) == 0"#));
+        assert!(yaml.contains("summary: '{{ $labels.mint }} reached 1000 holders'"));
+        assert!(!yaml.contains("HolderAllTimeHigh"));
+        assert_eq!(yaml.matches("- alert:").count(), 4);
+    }
+}
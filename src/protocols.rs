@@ -0,0 +1,273 @@
+//! Protocol deposit attribution: holders that are lending, staking, farm or pool vaults, and the
+//! depositors behind them, found through the receipt tokens the protocols mint to depositors
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use solana_program::pubkey::Pubkey;
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use tracing::info;
+
+use crate::rpc_client::SolanaRpcClient;
+use crate::token_monitor::extract_holders;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProtocolKind {
+    Lending,
+    Staking,
+    Farm,
+    Amm,
+}
+
+/// Protocol recognized by the program owning a holder's account
+#[derive(Debug, Clone, Copy)]
+pub struct Protocol {
+    pub program: &'static str,
+    pub name: &'static str,
+    pub kind: ProtocolKind,
+}
+
+pub const KNOWN_PROTOCOLS: &[Protocol] = &[
+    Protocol { program: "KLend2g3cP87fffoy8q1mQqGKjrxjC8boSyAYavgmjD", name: "Kamino Lending", kind: ProtocolKind::Lending },
+    Protocol { program: "MFv2hWf31Z9kbCa1snEPYctwafyhdvnV7FZnsebVacA", name: "MarginFi v2", kind: ProtocolKind::Lending },
+    Protocol { program: "So1endDq2YkqhipRh3WViPa8hdiSpxWy6z3Z6tMCpAo", name: "Solend", kind: ProtocolKind::Lending },
+    Protocol { program: "MarBmsSgKXdrN1egZf5sqe1TMai9K1rChYNDJgjq7aD", name: "Marinade", kind: ProtocolKind::Staking },
+    Protocol { program: "EhhTKczWMGQt46ynNeRX1WfeagwwJd7ufHvCDjRxjo5Q", name: "Raydium Farm v3", kind: ProtocolKind::Farm },
+    Protocol { program: "9KEPoZmtHUrBbhWN1v1KWLMkkvwY6WLtAVUCPRtRjP4z", name: "Raydium Farm v5", kind: ProtocolKind::Farm },
+    Protocol { program: "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8", name: "Raydium AMM v4", kind: ProtocolKind::Amm },
+    Protocol { program: "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc", name: "Orca Whirlpool", kind: ProtocolKind::Amm },
+    Protocol { program: "LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo", name: "Meteora DLMM", kind: ProtocolKind::Amm },
+];
+
+/// `[protocols]` config section
+///
+/// ```toml
+/// [protocols.authorities]
+/// "9DrvZvyWh1HuAoZxvYWMvkf2XCzryCpGgHqrMjyDWpmo" = "Kamino Lending"
+///
+/// [protocols.receipt_mints]
+/// "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v" = ["B8V6WVjPxW1UGwVDfxH2d2r8SyT4cqn7dQRK6XneVa7D"]
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProtocolConfig {
+    /// Vault authorities that have no account of their own (most PDAs), by protocol name
+    #[serde(default)]
+    pub authorities: HashMap<String, String>,
+    /// Receipt tokens minted to depositors of a mint (kTokens, cTokens, LP and farm tokens)
+    #[serde(default)]
+    pub receipt_mints: HashMap<String, Vec<String>>,
+}
+
+/// Holder recognized as a protocol vault
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ProtocolHolding {
+    pub address: String,
+    pub protocol: String,
+    /// None for authorities from the config file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<ProtocolKind>,
+    pub amount: u64,
+    /// Share of all tokens held
+    pub percent: f64,
+}
+
+/// Holder count with and without protocol deposits (`GET /holders/:mint/deposits`)
+#[derive(Debug, Clone, Serialize)]
+pub struct DepositAttribution {
+    pub holders: usize,
+    /// Largest holders whose accounts were looked up
+    pub examined_owners: usize,
+    /// Protocol vaults among the examined holders, largest first
+    pub protocol_holdings: Vec<ProtocolHolding>,
+    pub protocol_amount_percent: f64,
+    /// Holders without the protocol vaults
+    pub holders_excluding_protocols: usize,
+    pub receipt_mints: Vec<String>,
+    /// Holders of the receipt mints; None when none are configured for the mint
+    pub depositors: Option<usize>,
+    /// Holders plus depositors that only hold the receipt token, without the protocol vaults
+    pub holders_including_depositors: Option<usize>,
+    pub computed_at: u64,
+}
+
+impl ProtocolConfig {
+    /// Protocol behind `owner`, from the configured authorities or the program owning its account
+    pub fn classify(&self, owner: &Pubkey, owner_program: Option<&Pubkey>) -> Option<(String, Option<ProtocolKind>)> {
+        if let Some(name) = self.authorities.get(&owner.to_string()) {
+            return Some((name.clone(), None));
+        }
+        let program = owner_program?;
+        KNOWN_PROTOCOLS
+            .iter()
+            .find(|protocol| Pubkey::from_str(protocol.program).is_ok_and(|id| id == *program))
+            .map(|protocol| (protocol.name.to_string(), Some(protocol.kind)))
+    }
+
+    fn receipt_mints_of(&self, mint: &Pubkey) -> Vec<String> {
+        self.receipt_mints.get(&mint.to_string()).cloned().unwrap_or_default()
+    }
+
+    /// Reject addresses that aren't valid public keys
+    pub fn validate(&self) -> Result<()> {
+        let receipts = self.receipt_mints.iter().flat_map(|(mint, receipts)| std::iter::once(mint).chain(receipts));
+        for address in self.authorities.keys().chain(receipts) {
+            Pubkey::from_str(address).with_context(|| format!("Invalid address '{}' in [protocols]", address))?;
+        }
+        Ok(())
+    }
+}
+
+fn percent(part: u64, total: u64) -> f64 {
+    if total > 0 {
+        part as f64 / total as f64 * 100.0
+    } else {
+        0.0
+    }
+}
+
+/// Attribution from holder balances, the owner program of each examined holder and the
+/// receipt mint holders (None: no receipt mints)
+pub fn summarize_deposits(
+    balances: &HashMap<Pubkey, u64>,
+    owner_programs: &[(Pubkey, Option<Pubkey>)],
+    config: &ProtocolConfig,
+    receipt_mints: Vec<String>,
+    depositors: Option<&HashSet<Pubkey>>,
+    now: u64,
+) -> DepositAttribution {
+    let total: u64 = balances.values().sum();
+    let mut protocol_holdings: Vec<ProtocolHolding> = owner_programs
+        .iter()
+        .filter_map(|(owner, program)| {
+            let (protocol, kind) = config.classify(owner, program.as_ref())?;
+            let amount = balances.get(owner).copied().unwrap_or_default();
+            Some(ProtocolHolding {
+                address: owner.to_string(),
+                protocol,
+                kind,
+                amount,
+                percent: percent(amount, total),
+            })
+        })
+        .collect();
+    protocol_holdings.sort_by(|a, b| b.amount.cmp(&a.amount).then_with(|| a.address.cmp(&b.address)));
+
+    let vaults: HashSet<Pubkey> = protocol_holdings
+        .iter()
+        .filter_map(|holding| Pubkey::from_str(&holding.address).ok())
+        .collect();
+    let protocol_amount = protocol_holdings.iter().map(|holding| holding.amount).sum();
+    let holders_excluding_protocols = balances.keys().filter(|owner| !vaults.contains(owner)).count();
+    let holders_including_depositors = depositors.map(|depositors| {
+        let only_receipts = depositors
+            .iter()
+            .filter(|owner| !balances.contains_key(owner) && !vaults.contains(owner) && config.classify(owner, None).is_none())
+            .count();
+        holders_excluding_protocols + only_receipts
+    });
+
+    DepositAttribution {
+        holders: balances.len(),
+        examined_owners: owner_programs.len(),
+        protocol_holdings,
+        protocol_amount_percent: percent(protocol_amount, total),
+        holders_excluding_protocols,
+        receipt_mints,
+        depositors: depositors.map(HashSet::len),
+        holders_including_depositors,
+        computed_at: now,
+    }
+}
+
+/// Look up the accounts of the `top` largest holders of `mint` (one getMultipleAccounts call per
+/// 100) and the holders of its configured receipt mints (one getProgramAccounts call each)
+/// Protocol vaults hold large balances, so the largest holders are where to look for them
+pub async fn attribute_deposits(
+    rpc_client: &SolanaRpcClient,
+    mint: &Pubkey,
+    balances: &HashMap<Pubkey, u64>,
+    top: usize,
+    config: &ProtocolConfig,
+    now: u64,
+) -> Result<DepositAttribution> {
+    let mut largest: Vec<(&Pubkey, &u64)> = balances.iter().collect();
+    largest.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+    let owners: Vec<Pubkey> = largest.into_iter().take(top).map(|(owner, _)| *owner).collect();
+
+    let accounts = rpc_client.get_multiple_accounts(&owners, false).await?;
+    let owner_programs: Vec<(Pubkey, Option<Pubkey>)> = owners
+        .iter()
+        .copied()
+        .zip(accounts.into_iter().map(|account| account.map(|account| account.owner)))
+        .collect();
+
+    let receipt_mints = config.receipt_mints_of(mint);
+    let depositors = if receipt_mints.is_empty() {
+        None
+    } else {
+        let mut depositors = HashSet::new();
+        for receipt_mint in &receipt_mints {
+            let receipt_mint = Pubkey::from_str(receipt_mint).context("Invalid receipt mint")?;
+            let accounts = rpc_client
+                .get_token_accounts_by_mint(&receipt_mint)
+                .await
+                .with_context(|| format!("Failed to fetch holders of receipt mint {}", receipt_mint))?;
+            depositors.extend(extract_holders(&accounts)?);
+        }
+        Some(depositors)
+    };
+
+    let attribution = summarize_deposits(balances, &owner_programs, config, receipt_mints, depositors.as_ref(), now);
+    info!(
+        "Found {} protocol vaults among the {} largest of {} holders of {}",
+        attribution.protocol_holdings.len(),
+        owner_programs.len(),
+        balances.len(),
+        mint
+    );
+    Ok(attribution)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summarize_deposits() {
+        let kamino = Pubkey::from_str(KNOWN_PROTOCOLS[0].program).unwrap();
+        let system = Pubkey::from_str("11111111111111111111111111111111").unwrap();
+        let (vault, authority, wallet, depositor) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let balances = HashMap::from([(vault, 600), (authority, 300), (wallet, 100)]);
+        let owner_programs = vec![(vault, Some(kamino)), (authority, None), (wallet, Some(system))];
+        let config = ProtocolConfig {
+            authorities: HashMap::from([(authority.to_string(), "Raydium Farm v5".to_string())]),
+            receipt_mints: HashMap::new(),
+        };
+
+        let attribution = summarize_deposits(&balances, &owner_programs, &config, Vec::new(), None, 7);
+        assert_eq!(attribution.protocol_holdings.len(), 2);
+        assert_eq!(attribution.protocol_holdings[0].protocol, "Kamino Lending");
+        assert_eq!(attribution.protocol_holdings[0].kind, Some(ProtocolKind::Lending));
+        assert_eq!(attribution.protocol_holdings[1].kind, None);
+        assert_eq!(attribution.protocol_amount_percent, 90.0);
+        assert_eq!(attribution.holders_excluding_protocols, 1);
+        assert_eq!(attribution.holders_including_depositors, None);
+
+        // The wallet also holds the receipt token; only the depositor is added
+        let depositors = HashSet::from([wallet, depositor, vault]);
+        let attribution =
+            summarize_deposits(&balances, &owner_programs, &config, vec!["x".to_string()], Some(&depositors), 7);
+        assert_eq!(attribution.depositors, Some(3));
+        assert_eq!(attribution.holders_including_depositors, Some(2));
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut config = ProtocolConfig::default();
+        config.receipt_mints.insert(Pubkey::new_unique().to_string(), vec!["not-a-key".to_string()]);
+        assert!(config.validate().is_err());
+    }
+}
@@ -0,0 +1,368 @@
+use anyhow::Result;
+use serde::Serialize;
+use solana_program::pubkey::Pubkey;
+use tracing::warn;
+
+use crate::cli::Cli;
+use crate::events::HolderEvent;
+use crate::metadata::CountMetadata;
+use crate::rpc_client::Fallback;
+use crate::sinks::{Sink, SinkFuture};
+use crate::token_monitor::HolderStats;
+use crate::watch::WatchChange;
+
+/// Event emitted to message brokers
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PublishedEvent {
+    /// One holder-count observation from the monitoring loop
+    Observation {
+        mint: String,
+        holders: usize,
+        change: i64,
+        change_percent: f64,
+        timestamp: u64,
+        /// Slot the holder set was read at
+        #[serde(skip_serializing_if = "Option::is_none")]
+        slot: Option<u64>,
+        /// Block time of `slot`
+        #[serde(skip_serializing_if = "Option::is_none")]
+        block_time: Option<u64>,
+        /// Fallback backend the count came from (getProgramAccounts unavailable)
+        #[serde(skip_serializing_if = "Option::is_none")]
+        degraded: Option<Fallback>,
+        /// Share of the supply missing from the fetched accounts (response looks truncated)
+        #[serde(skip_serializing_if = "Option::is_none")]
+        missing_supply_percent: Option<f64>,
+        /// Token accounts the holders were counted from
+        #[serde(skip_serializing_if = "Option::is_none")]
+        token_accounts: Option<usize>,
+        /// How the count was produced
+        #[serde(skip_serializing_if = "Option::is_none")]
+        metadata: Option<Box<CountMetadata>>,
+    },
+    /// Alert triggered by a significant change
+    Alert {
+        mint: String,
+        message: String,
+        timestamp: u64,
+    },
+    /// Failed poll
+    Error {
+        mint: String,
+        message: String,
+        timestamp: u64,
+    },
+    /// A watched wallet started holding the token
+    WalletEntered {
+        mint: String,
+        wallet: String,
+        /// Raw balance
+        balance: u64,
+        /// Balance in whole tokens with accrued interest, when the mint account could be read
+        #[serde(skip_serializing_if = "Option::is_none")]
+        ui_balance: Option<f64>,
+        timestamp: u64,
+        holders: usize,
+        /// Balance tier, if the mint has tiers
+        #[serde(skip_serializing_if = "Option::is_none")]
+        tier: Option<String>,
+    },
+    /// A watched wallet no longer holds the token
+    WalletExited {
+        mint: String,
+        wallet: String,
+        balance: u64,
+        timestamp: u64,
+        holders: usize,
+        /// Tier held before the exit
+        #[serde(skip_serializing_if = "Option::is_none")]
+        previous_tier: Option<String>,
+    },
+    /// A watched wallet moved to another balance tier; `tier` is None below every tier
+    WalletTierChanged {
+        mint: String,
+        wallet: String,
+        balance: u64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        ui_balance: Option<f64>,
+        timestamp: u64,
+        holders: usize,
+        tier: Option<String>,
+        previous_tier: Option<String>,
+    },
+}
+
+impl PublishedEvent {
+    pub fn observation(mint: &Pubkey, stats: &HolderStats, metadata: Option<CountMetadata>) -> Self {
+        Self::Observation {
+            mint: mint.to_string(),
+            holders: stats.count,
+            change: stats.change,
+            change_percent: stats.change_percent,
+            timestamp: stats.timestamp,
+            slot: stats.slot,
+            block_time: stats.block_time,
+            degraded: stats.degraded,
+            missing_supply_percent: stats.missing_supply_percent,
+            token_accounts: stats.token_accounts,
+            metadata: metadata.map(Box::new),
+        }
+    }
+
+    pub fn alert(mint: &Pubkey, message: &str, timestamp: u64) -> Self {
+        Self::Alert {
+            mint: mint.to_string(),
+            message: message.to_string(),
+            timestamp,
+        }
+    }
+
+    /// Mint address, used as the message key so events for one token stay ordered
+    pub fn mint(&self) -> &str {
+        match self {
+            Self::Observation { mint, .. }
+            | Self::Alert { mint, .. }
+            | Self::Error { mint, .. }
+            | Self::WalletEntered { mint, .. }
+            | Self::WalletExited { mint, .. }
+            | Self::WalletTierChanged { mint, .. } => mint,
+        }
+    }
+
+    /// Value of the `type` tag
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Observation { .. } => "observation",
+            Self::Alert { .. } => "alert",
+            Self::Error { .. } => "error",
+            Self::WalletEntered { .. } => "wallet_entered",
+            Self::WalletExited { .. } => "wallet_exited",
+            Self::WalletTierChanged { .. } => "wallet_tier_changed",
+        }
+    }
+}
+
+impl From<&HolderEvent> for PublishedEvent {
+    fn from(event: &HolderEvent) -> Self {
+        match event {
+            HolderEvent::Observation(observation) => Self::observation(
+                &observation.mint,
+                &observation.stats,
+                Some(observation.metadata.as_ref().clone()),
+            ),
+            HolderEvent::Alert(alert) => Self::alert(&alert.mint, &alert.message, alert.stats.timestamp),
+            HolderEvent::Error { mint, message, timestamp } => Self::Error {
+                mint: mint.to_string(),
+                message: message.clone(),
+                timestamp: *timestamp,
+            },
+            HolderEvent::Watch(watch) => {
+                let (mint, wallet) = (watch.mint.to_string(), watch.wallet.to_string());
+                let (balance, ui_balance, timestamp, holders) =
+                    (watch.balance, watch.ui_balance, watch.timestamp, watch.holders);
+                let (tier, previous_tier) = (watch.tier.clone(), watch.previous_tier.clone());
+                match watch.change {
+                    WatchChange::Entered => {
+                        Self::WalletEntered { mint, wallet, balance, ui_balance, timestamp, holders, tier }
+                    }
+                    WatchChange::Exited => {
+                        Self::WalletExited { mint, wallet, balance, timestamp, holders, previous_tier }
+                    }
+                    WatchChange::TierChanged => {
+                        Self::WalletTierChanged { mint, wallet, balance, ui_balance, timestamp, holders, tier, previous_tier }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Publishes holder events to Kafka and/or NATS JetStream
+/// Each backend is only available when the crate is built with the matching feature
+pub struct EventPublisher {
+    #[cfg(feature = "kafka")]
+    kafka: Option<KafkaSink>,
+    #[cfg(feature = "nats")]
+    nats: Option<NatsSink>,
+}
+
+impl EventPublisher {
+    /// Build publisher from CLI arguments. Returns None when no broker is configured
+    pub async fn from_cli(cli: &Cli) -> Result<Option<Self>> {
+        if cli.kafka_brokers.is_none() && cli.nats_url.is_none() {
+            return Ok(None);
+        }
+
+        #[cfg(not(feature = "kafka"))]
+        if cli.kafka_brokers.is_some() {
+            return Err(anyhow::anyhow!(
+                "--kafka-brokers requires building with `--features kafka`"
+            ));
+        }
+
+        #[cfg(not(feature = "nats"))]
+        if cli.nats_url.is_some() {
+            return Err(anyhow::anyhow!(
+                "--nats-url requires building with `--features nats`"
+            ));
+        }
+
+        #[cfg(feature = "kafka")]
+        let kafka = match &cli.kafka_brokers {
+            Some(brokers) => {
+                let sink = KafkaSink::new(brokers, cli.kafka_topic.clone())?;
+                tracing::info!("Publishing events to Kafka topic '{}' ({})", cli.kafka_topic, brokers);
+                Some(sink)
+            }
+            None => None,
+        };
+
+        #[cfg(feature = "nats")]
+        let nats = match &cli.nats_url {
+            Some(url) => {
+                let sink = NatsSink::connect(url, cli.nats_subject.clone()).await?;
+                tracing::info!("Publishing events to NATS subject '{}' ({})", cli.nats_subject, url);
+                Some(sink)
+            }
+            None => None,
+        };
+
+        Ok(Some(Self {
+            #[cfg(feature = "kafka")]
+            kafka,
+            #[cfg(feature = "nats")]
+            nats,
+        }))
+    }
+
+    /// Publish event to all configured brokers
+    /// Failures are logged and never interrupt monitoring
+    pub async fn publish(&self, event: &PublishedEvent) {
+        let payload = match serde_json::to_vec(event) {
+            Ok(payload) => payload,
+            Err(e) => {
+                warn!("Failed to serialize event: {}", e);
+                return;
+            }
+        };
+
+        #[cfg(feature = "kafka")]
+        if let Some(kafka) = &self.kafka {
+            if let Err(e) = kafka.send(event.mint(), &payload).await {
+                warn!("Failed to publish event to Kafka: {}", e);
+            }
+        }
+
+        #[cfg(feature = "nats")]
+        if let Some(nats) = &self.nats {
+            if let Err(e) = nats.send(payload).await {
+                warn!("Failed to publish event to NATS: {}", e);
+            }
+        }
+
+        #[cfg(not(any(feature = "kafka", feature = "nats")))]
+        let _ = payload;
+    }
+}
+
+/// Forwards observations, alerts and failed polls to the brokers
+impl Sink for EventPublisher {
+    fn name(&self) -> &str {
+        "publisher"
+    }
+
+    fn handle<'a>(&'a self, event: &'a HolderEvent) -> SinkFuture<'a> {
+        Box::pin(async move {
+            self.publish(&PublishedEvent::from(event)).await;
+            Ok(())
+        })
+    }
+}
+
+#[cfg(feature = "kafka")]
+struct KafkaSink {
+    producer: rdkafka::producer::FutureProducer,
+    topic: String,
+}
+
+#[cfg(feature = "kafka")]
+impl KafkaSink {
+    fn new(brokers: &str, topic: String) -> Result<Self> {
+        use anyhow::Context;
+
+        let producer = rdkafka::ClientConfig::new()
+            .set("bootstrap.servers", brokers)
+            .set("message.timeout.ms", "5000")
+            .create()
+            .context("Failed to create Kafka producer")?;
+        Ok(Self { producer, topic })
+    }
+
+    async fn send(&self, key: &str, payload: &[u8]) -> Result<()> {
+        let record = rdkafka::producer::FutureRecord::to(&self.topic)
+            .key(key)
+            .payload(payload);
+        self.producer
+            .send(record, std::time::Duration::from_secs(5))
+            .await
+            .map_err(|(e, _)| anyhow::anyhow!("Kafka delivery failed: {}", e))?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "nats")]
+struct NatsSink {
+    jetstream: async_nats::jetstream::Context,
+    subject: String,
+}
+
+#[cfg(feature = "nats")]
+impl NatsSink {
+    async fn connect(url: &str, subject: String) -> Result<Self> {
+        use anyhow::Context;
+
+        let client = async_nats::connect(url)
+            .await
+            .with_context(|| format!("Failed to connect to NATS at {}", url))?;
+        Ok(Self {
+            jetstream: async_nats::jetstream::new(client),
+            subject,
+        })
+    }
+
+    async fn send(&self, payload: Vec<u8>) -> Result<()> {
+        use anyhow::Context;
+
+        let ack = self
+            .jetstream
+            .publish(self.subject.clone(), payload.into())
+            .await
+            .context("Failed to publish to JetStream")?;
+        ack.await.context("JetStream did not acknowledge event")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_serialization() {
+        let mint = Pubkey::new_unique();
+        let event = PublishedEvent::alert(&mint, "test alert", 42);
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["type"], "alert");
+        assert_eq!(json["mint"], mint.to_string());
+        assert_eq!(json["timestamp"], 42);
+
+        let event = PublishedEvent::from(&HolderEvent::Error {
+            mint,
+            message: "timeout".to_string(),
+            timestamp: 43,
+        });
+        assert_eq!(event.kind(), "error");
+        assert_eq!(serde_json::to_value(&event).unwrap()["type"], "error");
+    }
+}
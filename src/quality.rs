@@ -0,0 +1,495 @@
+use serde::Serialize;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::account::Account;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use tracing::{debug, info};
+
+use crate::rpc_client::SolanaRpcClient;
+use crate::token_monitor::{accounts_per_owner, extract_balances};
+
+/// Minimum wallets holding exactly the same amount to be flagged
+pub const MIN_AMOUNT_CLUSTER: usize = 5;
+/// Minimum sampled wallets sharing a funder / creation window to be flagged
+pub const MIN_SAMPLE_CLUSTER: usize = 3;
+/// Wallets whose first transaction falls into the same window are "created together" (~1 minute)
+pub const CREATION_SLOT_WINDOW: u64 = 150;
+/// Signature pages scanned per sampled wallet when looking for its first transaction
+const FIRST_ACTIVITY_MAX_PAGES: usize = 2;
+/// Clusters listed per heuristic in the report
+const MAX_LISTED_CLUSTERS: usize = 10;
+/// Wallets younger than this count as fresh
+pub const FRESH_WALLET_SECS: u64 = 7 * 86_400;
+/// Wallets at least this old count as old
+pub const OLD_WALLET_SECS: u64 = 90 * 86_400;
+
+/// Where a sampled wallet came from
+#[derive(Debug, Clone, PartialEq)]
+pub struct WalletOrigin {
+    pub wallet: Pubkey,
+    /// Fee payer of the wallet's first transaction, if it wasn't the wallet itself
+    pub funder: Option<Pubkey>,
+    pub first_slot: u64,
+    pub first_block_time: Option<i64>,
+    /// False when the history was too long to reach the real first transaction
+    pub complete: bool,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct AmountCluster {
+    /// Raw token amount (without decimals)
+    pub amount: u64,
+    pub wallets: usize,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct FunderCluster {
+    pub funder: String,
+    pub wallets: usize,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SlotCluster {
+    pub start_slot: u64,
+    pub end_slot: u64,
+    pub wallets: usize,
+}
+
+/// Age distribution of sampled wallets (age = time since their first transaction)
+///
+/// Wallets whose history is longer than the scanned pages have thousands of transactions
+/// and count as old.
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct WalletAgeStats {
+    /// Sampled wallets with a known age
+    pub wallets: usize,
+    /// Younger than 7 days
+    pub fresh: usize,
+    /// 7 to 90 days
+    pub recent: usize,
+    /// 90 days or older
+    pub old: usize,
+    /// fresh / wallets; a high ratio hints at bot-inflated holder counts
+    pub freshness_ratio: f64,
+    pub median_age_days: Option<f64>,
+}
+
+/// Bucket sampled wallets by age at `now`
+pub fn wallet_age_stats(origins: &[WalletOrigin], now: u64) -> WalletAgeStats {
+    let mut stats = WalletAgeStats::default();
+    let mut ages = Vec::new();
+
+    for origin in origins {
+        let age = match (origin.complete, origin.first_block_time) {
+            (false, _) => OLD_WALLET_SECS,
+            (true, Some(time)) => now.saturating_sub(time.max(0) as u64),
+            (true, None) => continue,
+        };
+        if age < FRESH_WALLET_SECS {
+            stats.fresh += 1;
+        } else if age < OLD_WALLET_SECS {
+            stats.recent += 1;
+        } else {
+            stats.old += 1;
+        }
+        ages.push(age);
+    }
+
+    stats.wallets = ages.len();
+    if !ages.is_empty() {
+        ages.sort_unstable();
+        stats.freshness_ratio = stats.fresh as f64 / ages.len() as f64;
+        stats.median_age_days = Some(ages[ages.len() / 2] as f64 / 86_400.0);
+    }
+    stats
+}
+
+/// Holders with this many token accounts of the mint
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct AccountCountBucket {
+    pub accounts: usize,
+    pub holders: usize,
+}
+
+/// How many token accounts each holder spreads its balance over; one wallet with many accounts
+/// (beyond its ATA) is a common airdrop-farming pattern
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct AccountsPerOwnerStats {
+    /// Holders with more than one account
+    pub multi_account_holders: usize,
+    pub multi_account_percent: f64,
+    pub max_accounts: usize,
+    /// Holder with `max_accounts` accounts, when it has more than one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_accounts_owner: Option<String>,
+    /// Holders per account count, ascending
+    pub distribution: Vec<AccountCountBucket>,
+}
+
+/// Distribution of `counts` (accounts per owner) over the owners in `balances`
+pub fn accounts_per_owner_stats(
+    counts: &HashMap<Pubkey, usize>,
+    balances: &HashMap<Pubkey, u64>,
+) -> AccountsPerOwnerStats {
+    let mut stats = AccountsPerOwnerStats::default();
+    let mut distribution: BTreeMap<usize, usize> = BTreeMap::new();
+    let mut max_owner = None;
+    for wallet in balances.keys() {
+        let accounts = counts.get(wallet).copied().unwrap_or(1);
+        *distribution.entry(accounts).or_default() += 1;
+        if accounts > 1 {
+            stats.multi_account_holders += 1;
+        }
+        // Ties go to the smaller address so the report is stable
+        if accounts > stats.max_accounts
+            || (accounts == stats.max_accounts && max_owner.is_some_and(|owner: Pubkey| *wallet < owner))
+        {
+            stats.max_accounts = accounts;
+            max_owner = Some(*wallet);
+        }
+    }
+    if !balances.is_empty() {
+        stats.multi_account_percent =
+            (stats.multi_account_holders as f64 / balances.len() as f64 * 1000.0).round() / 10.0;
+    }
+    if stats.max_accounts > 1 {
+        stats.max_accounts_owner = max_owner.map(|owner| owner.to_string());
+    }
+    stats.distribution =
+        distribution.into_iter().map(|(accounts, holders)| AccountCountBucket { accounts, holders }).collect();
+    stats
+}
+
+/// Holder quality report: raw count next to an organic estimate
+///
+/// Identical-amount clusters are computed over all holders. Funding source and creation
+/// time need two RPC calls per wallet, so they are checked on a sample and extrapolated.
+#[derive(Debug, Clone, Serialize)]
+pub struct HolderQuality {
+    pub holders: usize,
+    pub estimated_organic_holders: usize,
+    pub suspicious_percent: f64,
+    pub identical_amount_wallets: usize,
+    pub identical_amount_clusters: Vec<AmountCluster>,
+    pub sampled_wallets: usize,
+    pub sampled_suspicious: usize,
+    pub funder_clusters: Vec<FunderCluster>,
+    pub creation_slot_clusters: Vec<SlotCluster>,
+    pub wallet_age: WalletAgeStats,
+    pub accounts_per_owner: AccountsPerOwnerStats,
+    pub computed_at: u64,
+}
+
+/// Group holders by exact balance, keeping groups of at least `min_cluster` wallets
+pub fn identical_amount_groups(
+    balances: &HashMap<Pubkey, u64>,
+    min_cluster: usize,
+) -> HashMap<u64, Vec<Pubkey>> {
+    let mut groups: HashMap<u64, Vec<Pubkey>> = HashMap::new();
+    for (wallet, amount) in balances {
+        groups.entry(*amount).or_default().push(*wallet);
+    }
+    groups.retain(|_, wallets| wallets.len() >= min_cluster);
+    groups
+}
+
+/// Group sampled wallets by the account that funded them
+pub fn funder_groups(origins: &[WalletOrigin], min_cluster: usize) -> HashMap<Pubkey, Vec<Pubkey>> {
+    let mut groups: HashMap<Pubkey, Vec<Pubkey>> = HashMap::new();
+    for origin in origins {
+        if let Some(funder) = origin.funder {
+            groups.entry(funder).or_default().push(origin.wallet);
+        }
+    }
+    groups.retain(|_, wallets| wallets.len() >= min_cluster);
+    groups
+}
+
+/// Group sampled wallets whose first transaction landed within `window` slots of each other
+/// Only wallets with a complete history take part (otherwise the first slot is unknown)
+pub fn creation_slot_groups(
+    origins: &[WalletOrigin],
+    window: u64,
+    min_cluster: usize,
+) -> Vec<(SlotCluster, Vec<Pubkey>)> {
+    let mut created: Vec<&WalletOrigin> = origins.iter().filter(|o| o.complete).collect();
+    created.sort_by_key(|o| o.first_slot);
+
+    let mut groups = Vec::new();
+    let mut current: Vec<&WalletOrigin> = Vec::new();
+    let mut flush = |current: &mut Vec<&WalletOrigin>| {
+        if current.len() >= min_cluster {
+            let cluster = SlotCluster {
+                start_slot: current[0].first_slot,
+                end_slot: current[current.len() - 1].first_slot,
+                wallets: current.len(),
+            };
+            groups.push((cluster, current.iter().map(|o| o.wallet).collect()));
+        }
+        current.clear();
+    };
+
+    for origin in created {
+        if current
+            .first()
+            .is_some_and(|start| origin.first_slot - start.first_slot > window)
+        {
+            flush(&mut current);
+        }
+        current.push(origin);
+    }
+    flush(&mut current);
+    groups
+}
+
+/// Organic holders = all holders minus identical-amount wallets minus the suspicious
+/// share of the sample applied to the remaining holders
+pub fn estimate_organic(
+    holders: usize,
+    identical_amount_wallets: usize,
+    sampled: usize,
+    sampled_suspicious: usize,
+) -> usize {
+    let remaining = holders.saturating_sub(identical_amount_wallets);
+    if sampled == 0 {
+        return remaining;
+    }
+    let suspicious = (remaining as f64 * sampled_suspicious as f64 / sampled as f64).round() as usize;
+    remaining.saturating_sub(suspicious)
+}
+
+/// Evenly spaced sample of `size` wallets (sorted by key, so repeated runs pick the same ones)
+pub fn sample_wallets<'a>(wallets: impl Iterator<Item = &'a Pubkey>, size: usize) -> Vec<Pubkey> {
+    let mut wallets: Vec<Pubkey> = wallets.copied().collect();
+    if size == 0 || wallets.is_empty() {
+        return Vec::new();
+    }
+    wallets.sort();
+    let step = (wallets.len() / size).max(1);
+    wallets.into_iter().step_by(step).take(size).collect()
+}
+
+/// Look up first transaction and funder of each wallet; wallets that fail are skipped
+pub async fn fetch_origins(rpc_client: &SolanaRpcClient, wallets: &[Pubkey]) -> Vec<WalletOrigin> {
+    let mut origins = Vec::with_capacity(wallets.len());
+    for wallet in wallets {
+        let first = match rpc_client.get_first_activity(wallet, FIRST_ACTIVITY_MAX_PAGES).await {
+            Ok(Some(first)) => first,
+            Ok(None) => continue,
+            Err(e) => {
+                debug!("Skipping {} in quality sample: {}", wallet, e);
+                continue;
+            }
+        };
+        // Only a complete history gives the real first transaction (and thus the funder)
+        let funder = if first.complete {
+            match rpc_client.get_transaction_fee_payer(&first.signature).await {
+                Ok(payer) => payer.filter(|payer| payer != wallet),
+                Err(e) => {
+                    debug!("No funder for {}: {}", wallet, e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        origins.push(WalletOrigin {
+            wallet: *wallet,
+            funder,
+            first_slot: first.slot,
+            first_block_time: first.block_time,
+            complete: first.complete,
+        });
+    }
+    origins
+}
+
+/// Run all heuristics for one snapshot of the mint's token accounts
+/// `sample_size` wallets are looked up over RPC (0 = identical amounts and accounts per owner only)
+pub async fn analyze_holders(
+    rpc_client: &SolanaRpcClient,
+    accounts: &[(Pubkey, Account)],
+    sample_size: usize,
+    now: u64,
+) -> HolderQuality {
+    let balances = &extract_balances(accounts);
+    let accounts_per_owner = accounts_per_owner_stats(&accounts_per_owner(accounts), balances);
+    let amount_groups = identical_amount_groups(balances, MIN_AMOUNT_CLUSTER);
+    let flagged: HashSet<Pubkey> = amount_groups.values().flatten().copied().collect();
+
+    // Sample only from holders not already flagged, so the extrapolation doesn't double count
+    let sample = sample_wallets(balances.keys().filter(|w| !flagged.contains(w)), sample_size);
+    let origins = fetch_origins(rpc_client, &sample).await;
+
+    let funders = funder_groups(&origins, MIN_SAMPLE_CLUSTER);
+    let slots = creation_slot_groups(&origins, CREATION_SLOT_WINDOW, MIN_SAMPLE_CLUSTER);
+    let sampled_suspicious = funders
+        .values()
+        .flatten()
+        .chain(slots.iter().flat_map(|(_, wallets)| wallets))
+        .collect::<HashSet<_>>()
+        .len();
+
+    let wallet_age = wallet_age_stats(&origins, now);
+
+    let holders = balances.len();
+    let estimated_organic_holders =
+        estimate_organic(holders, flagged.len(), origins.len(), sampled_suspicious);
+    let suspicious_percent = if holders > 0 {
+        (holders - estimated_organic_holders) as f64 / holders as f64 * 100.0
+    } else {
+        0.0
+    };
+
+    let mut identical_amount_clusters: Vec<AmountCluster> = amount_groups
+        .iter()
+        .map(|(amount, wallets)| AmountCluster {
+            amount: *amount,
+            wallets: wallets.len(),
+        })
+        .collect();
+    identical_amount_clusters.sort_by(|a, b| b.wallets.cmp(&a.wallets).then(a.amount.cmp(&b.amount)));
+    identical_amount_clusters.truncate(MAX_LISTED_CLUSTERS);
+
+    let mut funder_clusters: Vec<FunderCluster> = funders
+        .iter()
+        .map(|(funder, wallets)| FunderCluster {
+            funder: funder.to_string(),
+            wallets: wallets.len(),
+        })
+        .collect();
+    funder_clusters.sort_by_key(|cluster| std::cmp::Reverse(cluster.wallets));
+    funder_clusters.truncate(MAX_LISTED_CLUSTERS);
+
+    let mut creation_slot_clusters: Vec<SlotCluster> =
+        slots.into_iter().map(|(cluster, _)| cluster).collect();
+    creation_slot_clusters.sort_by_key(|cluster| std::cmp::Reverse(cluster.wallets));
+    creation_slot_clusters.truncate(MAX_LISTED_CLUSTERS);
+
+    info!(
+        "Holder quality: {} holders, ~{} organic ({} identical-amount, {}/{} sampled suspicious, {} with several accounts)",
+        holders,
+        estimated_organic_holders,
+        flagged.len(),
+        sampled_suspicious,
+        origins.len(),
+        accounts_per_owner.multi_account_holders
+    );
+
+    HolderQuality {
+        holders,
+        estimated_organic_holders,
+        suspicious_percent,
+        identical_amount_wallets: flagged.len(),
+        identical_amount_clusters,
+        sampled_wallets: origins.len(),
+        sampled_suspicious,
+        funder_clusters,
+        creation_slot_clusters,
+        wallet_age,
+        accounts_per_owner,
+        computed_at: now,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn origin(slot: u64, funder: Option<Pubkey>) -> WalletOrigin {
+        WalletOrigin {
+            wallet: Pubkey::new_unique(),
+            funder,
+            first_slot: slot,
+            first_block_time: None,
+            complete: true,
+        }
+    }
+
+    #[test]
+    fn test_identical_amount_groups() {
+        let mut balances = HashMap::new();
+        for _ in 0..5 {
+            balances.insert(Pubkey::new_unique(), 1_000);
+        }
+        for amount in 1..=4 {
+            balances.insert(Pubkey::new_unique(), amount);
+        }
+        balances.insert(Pubkey::new_unique(), 2_000);
+
+        let groups = identical_amount_groups(&balances, MIN_AMOUNT_CLUSTER);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[&1_000].len(), 5);
+    }
+
+    #[test]
+    fn test_funder_and_slot_groups() {
+        let funder = Pubkey::new_unique();
+        let origins = vec![
+            origin(1_000, Some(funder)),
+            origin(1_050, Some(funder)),
+            origin(1_100, Some(funder)),
+            origin(5_000, None),
+            origin(90_000, Some(Pubkey::new_unique())),
+        ];
+
+        let funders = funder_groups(&origins, MIN_SAMPLE_CLUSTER);
+        assert_eq!(funders[&funder].len(), 3);
+
+        let slots = creation_slot_groups(&origins, CREATION_SLOT_WINDOW, MIN_SAMPLE_CLUSTER);
+        assert_eq!(slots.len(), 1);
+        assert_eq!(
+            slots[0].0,
+            SlotCluster { start_slot: 1_000, end_slot: 1_100, wallets: 3 }
+        );
+    }
+
+    #[test]
+    fn test_wallet_age_stats() {
+        let now = 200 * 86_400;
+        let with_time = |days_ago: u64, complete| WalletOrigin {
+            first_block_time: Some((now - days_ago * 86_400) as i64),
+            complete,
+            ..origin(0, None)
+        };
+        let origins = vec![
+            with_time(1, true),
+            with_time(2, true),
+            with_time(30, true),
+            with_time(1, false),
+            origin(0, None),
+        ];
+
+        let stats = wallet_age_stats(&origins, now);
+        assert_eq!(stats.wallets, 4);
+        assert_eq!((stats.fresh, stats.recent, stats.old), (2, 1, 1));
+        assert!((stats.freshness_ratio - 0.5).abs() < 1e-9);
+        assert_eq!(stats.median_age_days, Some(30.0));
+    }
+
+    #[test]
+    fn test_accounts_per_owner_stats() {
+        let (farmer, holder, other) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let balances = HashMap::from([(farmer, 10), (holder, 5), (other, 7)]);
+        // The emptied wallet has accounts but isn't a holder
+        let counts = HashMap::from([(farmer, 6), (holder, 1), (other, 2), (Pubkey::new_unique(), 3)]);
+
+        let stats = accounts_per_owner_stats(&counts, &balances);
+        assert_eq!(stats.multi_account_holders, 2);
+        assert_eq!(stats.multi_account_percent, 66.7);
+        assert_eq!(stats.max_accounts, 6);
+        assert_eq!(stats.max_accounts_owner, Some(farmer.to_string()));
+        let distribution: Vec<(usize, usize)> = stats.distribution.iter().map(|b| (b.accounts, b.holders)).collect();
+        assert_eq!(distribution, vec![(1, 1), (2, 1), (6, 1)]);
+
+        let single = accounts_per_owner_stats(&HashMap::from([(holder, 1)]), &HashMap::from([(holder, 5)]));
+        assert_eq!((single.max_accounts, single.max_accounts_owner), (1, None));
+    }
+
+    #[test]
+    fn test_estimate_organic() {
+        // 1000 holders, 100 identical-amount, 10 of 50 sampled suspicious -> 20% of 900
+        assert_eq!(estimate_organic(1_000, 100, 50, 10), 720);
+        assert_eq!(estimate_organic(1_000, 100, 0, 0), 900);
+        assert_eq!(sample_wallets([Pubkey::new_unique()].iter(), 0).len(), 0);
+    }
+}
@@ -0,0 +1,177 @@
+//! Quiet hours: non-critical alerts are held back and delivered as one digest afterwards
+
+use chrono::{Datelike, TimeZone, Timelike, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Deserializer};
+
+use crate::locale::parse_timezone;
+use crate::token_monitor::AlertRule;
+
+const WEEKDAYS: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+const MINUTES_PER_DAY: u32 = 24 * 60;
+
+/// `[quiet_hours]` config section
+///
+/// ```toml
+/// [quiet_hours]
+/// ranges = ["mon-fri 22:00-08:00", "sat-sun 00:00-10:00"]
+/// timezone = "Europe/Berlin"
+/// critical = ["drop"]
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct QuietHours {
+    pub ranges: Vec<TimeRange>,
+    /// Timezone of the ranges; defaults to `--timezone`, then UTC
+    #[serde(default, deserialize_with = "deserialize_timezone")]
+    pub timezone: Option<Tz>,
+    /// Rules that are delivered immediately even during quiet hours
+    #[serde(default = "default_critical")]
+    pub critical: Vec<AlertRule>,
+}
+
+fn default_critical() -> Vec<AlertRule> {
+    vec![AlertRule::Drop]
+}
+
+fn deserialize_timezone<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Tz>, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    parse_timezone(&name).map(Some).map_err(serde::de::Error::custom)
+}
+
+impl QuietHours {
+    /// Whether `timestamp` falls into a quiet range (`fallback_tz` when no timezone is set)
+    pub fn is_quiet(&self, timestamp: u64, fallback_tz: Option<Tz>) -> bool {
+        let Some(utc) = Utc.timestamp_opt(timestamp as i64, 0).single() else {
+            return false;
+        };
+        let local = utc.with_timezone(&self.timezone.or(fallback_tz).unwrap_or(Tz::UTC));
+        let weekday = local.weekday().num_days_from_monday() as usize;
+        let minute = local.hour() * 60 + local.minute();
+        self.ranges.iter().any(|range| range.contains(weekday, minute))
+    }
+
+    pub fn is_critical(&self, rule: AlertRule) -> bool {
+        self.critical.contains(&rule)
+    }
+}
+
+/// `DAYS HH:MM-HH:MM`, where DAYS is `daily`, a weekday (`sat`) or a span (`mon-fri`);
+/// a range ending before it starts runs past midnight into the next day
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct TimeRange {
+    /// Indexed from Monday; the day the range starts on
+    days: [bool; 7],
+    start: u32,
+    end: u32,
+}
+
+impl TimeRange {
+    fn contains(&self, weekday: usize, minute: u32) -> bool {
+        if self.start <= self.end {
+            self.days[weekday] && (self.start..self.end).contains(&minute)
+        } else {
+            let yesterday = (weekday + 6) % 7;
+            (self.days[weekday] && minute >= self.start) || (self.days[yesterday] && minute < self.end)
+        }
+    }
+}
+
+impl TryFrom<String> for TimeRange {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let invalid = || format!("invalid quiet hours range '{}' (expected e.g. 'mon-fri 22:00-08:00')", value);
+        let (days, times) = value.trim().split_once(' ').ok_or_else(invalid)?;
+        let (start, end) = times.trim().split_once('-').ok_or_else(invalid)?;
+        let start = parse_minutes(start).ok_or_else(invalid)?;
+        let end = parse_minutes(end).ok_or_else(invalid)?;
+        if start == end {
+            return Err(invalid());
+        }
+        Ok(Self {
+            days: parse_days(days).ok_or_else(invalid)?,
+            start,
+            end,
+        })
+    }
+}
+
+fn parse_minutes(time: &str) -> Option<u32> {
+    let (hours, minutes) = time.split_once(':')?;
+    let (hours, minutes): (u32, u32) = (hours.parse().ok()?, minutes.parse().ok()?);
+    let total = hours * 60 + minutes;
+    (minutes < 60 && total <= MINUTES_PER_DAY).then_some(total)
+}
+
+fn parse_days(days: &str) -> Option<[bool; 7]> {
+    let days = days.to_ascii_lowercase();
+    let index = |day: &str| WEEKDAYS.iter().position(|d| *d == day);
+    let mut result = [false; 7];
+    match days.split_once('-') {
+        _ if days == "daily" || days == "*" => result = [true; 7],
+        Some((first, last)) => {
+            let (first, last) = (index(first)?, index(last)?);
+            let mut day = first;
+            loop {
+                result[day] = true;
+                if day == last {
+                    break;
+                }
+                day = (day + 1) % 7;
+            }
+        }
+        None => result[index(&days)?] = true,
+    }
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quiet(ranges: &[&str]) -> QuietHours {
+        QuietHours {
+            ranges: ranges
+                .iter()
+                .map(|range| TimeRange::try_from(range.to_string()).unwrap())
+                .collect(),
+            timezone: None,
+            critical: default_critical(),
+        }
+    }
+
+    // 2024-01-15 is a Monday
+    const MONDAY: u64 = 1705276800;
+    const HOUR: u64 = 3600;
+
+    #[test]
+    fn test_ranges() {
+        let hours = quiet(&["mon-fri 22:00-08:00", "sun 12:00-13:00"]);
+        assert!(hours.is_quiet(MONDAY + 23 * HOUR, None));
+        // Tuesday 03:00 is covered by Monday's overnight range
+        assert!(hours.is_quiet(MONDAY + 27 * HOUR, None));
+        assert!(!hours.is_quiet(MONDAY + 12 * HOUR, None));
+        // Monday 03:00 would need a Sunday overnight range
+        assert!(!hours.is_quiet(MONDAY + 3 * HOUR, None));
+        assert!(hours.is_quiet(MONDAY + 6 * 24 * HOUR + 12 * HOUR, None));
+
+        // 23:00 UTC is 00:00 in Berlin (CET)
+        let berlin = parse_timezone("Europe/Berlin").ok();
+        let local = quiet(&["daily 23:30-01:00"]);
+        assert!(local.is_quiet(MONDAY + 23 * HOUR, berlin));
+        assert!(!local.is_quiet(MONDAY + 23 * HOUR, None));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for range in ["mon 22:00", "funday 10:00-11:00", "mon 25:00-26:00", "mon 10:00-10:00"] {
+            assert!(TimeRange::try_from(range.to_string()).is_err(), "{}", range);
+        }
+        let weekend = TimeRange::try_from("sat-sun 00:00-24:00".to_string()).unwrap();
+        assert_eq!(weekend.days, [false, false, false, false, false, true, true]);
+        let wrapping = TimeRange::try_from("fri-mon 09:00-10:00".to_string()).unwrap();
+        assert_eq!(wrapping.days, [true, false, false, false, true, true, true]);
+    }
+}
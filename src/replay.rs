@@ -0,0 +1,143 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use tracing::info;
+
+use crate::milestones::{check_milestone, MilestoneTracker};
+use crate::output::ConsoleOutput;
+use crate::token_monitor::{calculate_stats, check_alerts, Metrics};
+
+/// One recorded holder observation
+/// Matches `observation` events published to Kafka/NATS; extra fields are ignored
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct RecordedObservation {
+    pub timestamp: u64,
+    pub holders: usize,
+    /// Slot the count was read at (events published since slots were recorded)
+    #[serde(default)]
+    pub slot: Option<u64>,
+    /// Block time of `slot`
+    #[serde(default)]
+    pub block_time: Option<u64>,
+    /// Event type when the line comes from a published event stream
+    #[serde(rename = "type", default)]
+    pub kind: Option<String>,
+}
+
+/// Load observations from a JSONL file, skipping blank lines and non-observation events
+/// Observations are returned sorted by timestamp
+pub fn load_observations(path: &Path) -> Result<Vec<RecordedObservation>> {
+    let data = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read replay file {}", path.display()))?;
+    parse_observations(&data).with_context(|| format!("Invalid replay file {}", path.display()))
+}
+
+fn parse_observations(data: &str) -> Result<Vec<RecordedObservation>> {
+    let mut observations = Vec::new();
+    for (index, line) in data.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let observation: RecordedObservation = match serde_json::from_str(line) {
+            Ok(observation) => observation,
+            // Alert and error events have no `holders` field
+            Err(_) if line.contains("\"type\":\"alert\"") || line.contains("\"type\":\"error\"") => continue,
+            Err(e) => return Err(anyhow::anyhow!("line {}: {}", index + 1, e)),
+        };
+        if observation.kind.as_deref().is_none_or(|kind| kind == "observation") {
+            observations.push(observation);
+        }
+    }
+    observations.sort_by_key(|o| o.timestamp);
+    Ok(observations)
+}
+
+/// Feed recorded observations through stats, metrics and alert rules
+/// `speed` > 0 replays gaps between observations `speed` times faster than real time;
+/// 0 replays without any delay
+pub async fn replay(
+    observations: &[RecordedObservation],
+    speed: f64,
+    mint: &str,
+    console: &ConsoleOutput,
+    metrics: &mut Metrics,
+    milestones: &mut MilestoneTracker,
+) {
+    info!("Replaying {} observations (speed: {})", observations.len(), speed);
+    let mut previous: Option<&RecordedObservation> = None;
+
+    for observation in observations {
+        if let Some(prev) = previous {
+            if speed > 0.0 {
+                let gap = observation.timestamp.saturating_sub(prev.timestamp) as f64;
+                tokio::time::sleep(Duration::from_secs_f64(gap / speed)).await;
+            }
+        }
+
+        let previous_count = previous.map(|p| p.holders);
+        let mut stats = calculate_stats(observation.holders, previous_count);
+        stats.timestamp = observation.timestamp;
+        stats.slot = observation.slot;
+        stats.block_time = observation.block_time;
+
+        metrics.update(observation.holders);
+        let mut fired = check_alerts(&stats, previous_count, &console.locale);
+        fired.extend(check_milestone(&stats, milestones, &console.locale));
+        for (_, message) in fired {
+            metrics.add_alert(message);
+        }
+        console.print_status(mint, &stats, Duration::ZERO, None);
+
+        previous = Some(observation);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_observations() {
+        let data = r#"
+{"type":"observation","mint":"M","holders":120,"change":20,"change_percent":20.0,"timestamp":200}
+{"type":"alert","mint":"M","message":"x","timestamp":200}
+{"type":"error","mint":"M","message":"timeout","timestamp":250}
+{"timestamp":100,"holders":100}
+"#;
+        let observations = parse_observations(data).unwrap();
+        assert_eq!(observations.len(), 2);
+        assert_eq!(observations[0].holders, 100);
+        assert_eq!(observations[1].timestamp, 200);
+
+        assert!(parse_observations("{\"holders\": 1}").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_replay_alerts() {
+        let observations: Vec<RecordedObservation> = [(0, 100), (30, 160), (60, 100)]
+            .into_iter()
+            .map(|(timestamp, holders)| RecordedObservation {
+                timestamp,
+                holders,
+                slot: None,
+                block_time: None,
+                kind: None,
+            })
+            .collect();
+        let console = ConsoleOutput::new(crate::output::OutputFormat::Plain, true, true);
+        let mut metrics = Metrics::new();
+
+        let mut milestones = MilestoneTracker::new(vec![150], None);
+
+        replay(&observations, 0.0, "M", &console, &mut metrics, &mut milestones).await;
+
+        assert_eq!(metrics.total_polls, 3);
+        assert_eq!(metrics.alerts.len(), 3);
+        assert!(metrics.alerts[0].contains("GROWTH"));
+        assert!(metrics.alerts[1].contains("MILESTONE"));
+        assert!(metrics.alerts[2].contains("DROP"));
+    }
+}
@@ -0,0 +1,114 @@
+//! Retry budget shared by everything that uses the RPC client (monitoring loop, API cache
+//! refresher, API cache misses), so a flapping RPC doesn't multiply per-request retries
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// At most `limit` retries per `window`; first attempts are never limited
+#[derive(Debug)]
+pub struct RetryBudget {
+    limit: u32,
+    window: Duration,
+    state: Mutex<BudgetState>,
+}
+
+#[derive(Debug)]
+struct BudgetState {
+    window_start: Instant,
+    used: u32,
+    /// Retries refused in the current window
+    denied: u32,
+    /// Exhaustion of the current window was already reported
+    reported: bool,
+}
+
+impl RetryBudget {
+    pub fn new(limit: u32, window: Duration) -> Self {
+        Self {
+            limit,
+            window,
+            state: Mutex::new(BudgetState {
+                window_start: Instant::now(),
+                used: 0,
+                denied: 0,
+                reported: false,
+            }),
+        }
+    }
+
+    pub fn limit(&self) -> u32 {
+        self.limit
+    }
+
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// Take one retry from the budget; false when the window's budget is used up
+    pub fn try_acquire(&self) -> bool {
+        self.try_acquire_at(Instant::now())
+    }
+
+    fn try_acquire_at(&self, now: Instant) -> bool {
+        let mut state = self.state.lock().unwrap();
+        state.roll(now, self.window);
+        if state.used < self.limit {
+            state.used += 1;
+            true
+        } else {
+            state.denied += 1;
+            false
+        }
+    }
+
+    /// Retries refused so far in this window, returned once per window (for a single alert)
+    pub fn take_exhaustion(&self) -> Option<u32> {
+        self.take_exhaustion_at(Instant::now())
+    }
+
+    fn take_exhaustion_at(&self, now: Instant) -> Option<u32> {
+        let mut state = self.state.lock().unwrap();
+        state.roll(now, self.window);
+        if state.denied == 0 || state.reported {
+            return None;
+        }
+        state.reported = true;
+        Some(state.denied)
+    }
+}
+
+impl BudgetState {
+    fn roll(&mut self, now: Instant, window: Duration) {
+        if now.duration_since(self.window_start) >= window {
+            self.window_start = now;
+            self.used = 0;
+            self.denied = 0;
+            self.reported = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_window() {
+        let budget = RetryBudget::new(2, Duration::from_secs(60));
+        let start = Instant::now();
+        assert!(budget.try_acquire_at(start));
+        assert!(budget.try_acquire_at(start));
+        assert_eq!(budget.take_exhaustion_at(start), None);
+        assert!(!budget.try_acquire_at(start + Duration::from_secs(10)));
+        assert!(!budget.try_acquire_at(start + Duration::from_secs(20)));
+
+        // Reported once per window
+        assert_eq!(budget.take_exhaustion_at(start + Duration::from_secs(30)), Some(2));
+        assert!(!budget.try_acquire_at(start + Duration::from_secs(40)));
+        assert_eq!(budget.take_exhaustion_at(start + Duration::from_secs(50)), None);
+
+        // Next window
+        assert!(budget.try_acquire_at(start + Duration::from_secs(61)));
+        assert_eq!(budget.take_exhaustion_at(start + Duration::from_secs(62)), None);
+    }
+}
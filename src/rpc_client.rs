@@ -1,47 +1,429 @@
 use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use solana_account_decoder::parse_token::UiTokenAmount;
+use solana_account_decoder::UiDataSliceConfig;
+use solana_rpc_client::http_sender::HttpSender;
 use solana_client::nonblocking::rpc_client::RpcClient;
-use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClientConfig};
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcTransactionConfig};
 use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_client::rpc_request::RpcRequest;
+use solana_client::rpc_response::{OptionalContext, Response as RpcResponse, RpcKeyedAccount};
+use solana_client::rpc_sender::{RpcSender, RpcTransportStats};
 use solana_program::pubkey::Pubkey;
 use solana_sdk::account::Account;
 use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::hash::Hash;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::VersionedTransaction;
+use solana_transaction_status::option_serializer::OptionSerializer;
+use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, UiTransactionEncoding};
+use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tokio::time::sleep;
 use tracing::{debug, error, info, warn};
 
+use crate::diagnostics::{diagnose, Diagnostic};
+use crate::extensions::MintInfo;
+use crate::http_tuning::HttpTuning;
+use crate::memory;
+use crate::metadata::CountMetadata;
+use crate::redact::{redact_in, redact_url};
+use crate::retry_budget::RetryBudget;
+use crate::rpc_logging::LoggingSender;
+use crate::rpc_stats::{MethodStats, RpcHealth, RpcStats, StatsSender};
+use crate::token_monitor::{
+    encode_token_account, extract_holders, total_amount, zero_balance_owners, SLIM_ACCOUNT_LEN, SLIM_ACCOUNT_OFFSET,
+    TOKEN_ACCOUNT_LEN,
+};
+use crate::truncation::{SupplyCheck, TruncationMode, DEFAULT_TOLERANCE_PERCENT};
+
+/// Oldest transaction found for an address
+#[derive(Debug, Clone)]
+pub struct FirstActivity {
+    pub signature: Signature,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    /// False when the history was longer than the scanned pages,
+    /// i.e. the address is at least this old but possibly older
+    pub complete: bool,
+}
+
+/// SPL Token program (used unless overridden with `with_token_program`)
+pub const TOKEN_PROGRAM_ID: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+/// Token-2022 program: its token accounts may carry extensions after the 165-byte base layout
+pub const TOKEN_2022_PROGRAM_ID: &str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb";
+
+/// Signatures per getSignaturesForAddress page (RPC maximum)
+const SIGNATURES_PAGE_LIMIT: usize = 1000;
+
+/// Addresses per getMultipleAccounts call (RPC maximum)
+pub const MULTIPLE_ACCOUNTS_LIMIT: usize = 100;
+
+/// Token accounts per DAS `getTokenAccounts` page (provider maximum)
+const DAS_PAGE_LIMIT: usize = 1000;
+
+/// Where a degraded result came from when the RPC rejects getProgramAccounts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Fallback {
+    /// DAS `getTokenAccounts`: every account, but without a context slot
+    Das,
+    /// getTokenLargestAccounts: the 20 largest accounts only, a lower bound of the count
+    LargestAccounts,
+    /// The last value fetched before the RPC started failing
+    Cached,
+}
+
+impl Fallback {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Das => "das",
+            Self::LargestAccounts => "largest_accounts",
+            Self::Cached => "cached",
+        }
+    }
+
+    /// Only part of the holders was read: not comparable with full counts
+    pub fn is_partial(&self) -> bool {
+        matches!(self, Self::LargestAccounts)
+    }
+}
+
+/// Whether the RPC serves filtered getProgramAccounts for the token program
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ProgramAccountsSupport {
+    Supported,
+    /// Token program excluded from the secondary indexes (public RPCs): fallbacks are used
+    Rejected { reason: String },
+    /// No answer within `--timeout`
+    TimedOut,
+    Failed { error: String },
+}
+
+/// Result of the startup capability probe
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RpcCapabilities {
+    /// Round trip of getSlot
+    pub latency_ms: u64,
+    pub program_accounts: ProgramAccountsSupport,
+    /// Round trip of the (empty) getProgramAccounts probe
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub program_accounts_latency_ms: Option<u64>,
+}
+
+/// Error text of RPCs that refuse getProgramAccounts for the token program
+fn is_program_accounts_rejection(error: &str) -> bool {
+    error.contains("excluded from account secondary indexes") || error.contains("this RPC method unavailable")
+}
+
+/// One page of DAS `getTokenAccounts`
+#[derive(Debug, Deserialize)]
+struct DasTokenAccounts {
+    #[serde(default)]
+    token_accounts: Vec<DasTokenAccount>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DasTokenAccount {
+    address: String,
+    owner: String,
+    amount: u64,
+}
+
 /// RPC client wrapper with retry logic and health checks
 pub struct SolanaRpcClient {
     client: RpcClient,
+    /// Sender behind `client`, for calls `RpcClient::send` refuses (named params)
+    sender: SharedSender,
     max_retries: u32,
-    #[allow(dead_code)]
+    /// Per-attempt deadline of every RPC call (`--timeout`)
     timeout: Duration,
+    /// Program that owns the token accounts being counted
+    token_program: Pubkey,
+    /// Fetch only owner + amount of each account (memory limit reached)
+    count_only: AtomicBool,
+    /// Retries shared by every caller of this client (`--retry-budget`)
+    retry_budget: Option<RetryBudget>,
+    /// Highest context slot of any answer so far (0: none yet), sent as `minContextSlot`
+    last_slot: AtomicU64,
+    /// The RPC refused getProgramAccounts for the token program: use the fallbacks
+    program_accounts_rejected: AtomicBool,
+    /// Compare fetched balances with the mint supply to catch truncated responses
+    truncation_mode: TruncationMode,
+    truncation_tolerance_percent: f64,
+    /// Count owners whose token accounts all hold zero as holders (`--include-zero-balance`)
+    include_zero_balance: bool,
+    /// Calls, errors and latency per JSON-RPC method (`GET /rpc-stats`)
+    stats: RpcStats,
+    /// Diagnostics already returned by `new_diagnostics`
+    reported_diagnostics: Mutex<HashSet<String>>,
+    /// Mint decimals and extensions read so far, see `get_mint_info`
+    mint_infos: Mutex<HashMap<Pubkey, MintInfo>>,
+}
+
+/// Instrumented sender shared between the `RpcClient` and raw calls with named params
+/// (DAS `getTokenAccounts`), which `RpcClient::send` rejects
+#[derive(Clone)]
+struct SharedSender(Arc<dyn RpcSender + Send + Sync>);
+
+#[async_trait]
+impl RpcSender for SharedSender {
+    async fn send(&self, request: RpcRequest, params: Value) -> solana_client::client_error::Result<Value> {
+        self.0.send(request, params).await
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        self.0.get_transport_stats()
+    }
+
+    fn url(&self) -> String {
+        self.0.url()
+    }
+}
+
+/// RPC client sending through `sender`, with every call counted in `stats`
+fn instrumented_client(sender: impl RpcSender + Send + Sync + 'static, stats: &RpcStats) -> (RpcClient, SharedSender) {
+    let sender = SharedSender(Arc::new(StatsSender::new(sender, stats.clone())));
+    let client = RpcClient::new_sender(sender.clone(), RpcClientConfig::with_commitment(CommitmentConfig::confirmed()));
+    (client, sender)
+}
+
+/// Token accounts read at one slot
+#[derive(Debug, Clone, Default)]
+pub struct AccountSnapshot {
+    pub accounts: Vec<(Pubkey, Account)>,
+    /// None when the RPC doesn't report the context slot
+    pub slot: Option<u64>,
+    /// Set when getProgramAccounts was rejected and a fallback answered instead
+    pub degraded: Option<Fallback>,
+    /// Share of the supply missing from `accounts` when the response looks truncated
+    pub missing_supply_percent: Option<f64>,
+    /// Read at finalized rather than confirmed commitment
+    pub finalized: bool,
 }
 
 impl SolanaRpcClient {
     /// Create new RPC client
     pub fn new(rpc_url: String, max_retries: u32, timeout_secs: u64) -> Self {
-        let client = RpcClient::new_with_commitment(
-            rpc_url.clone(),
-            CommitmentConfig::confirmed(),
-        );
+        let stats = RpcStats::default();
+        let (client, sender) = instrumented_client(HttpSender::new(rpc_url.clone()), &stats);
         
-        info!("Initialized RPC client: {}", rpc_url);
+        info!("Initialized RPC client: {}", redact_url(&rpc_url));
         
         Self {
             client,
+            sender,
             max_retries,
             timeout: Duration::from_secs(timeout_secs),
+            token_program: Pubkey::from_str(TOKEN_PROGRAM_ID).unwrap(),
+            count_only: AtomicBool::new(false),
+            retry_budget: None,
+            last_slot: AtomicU64::new(0),
+            program_accounts_rejected: AtomicBool::new(false),
+            truncation_mode: TruncationMode::default(),
+            truncation_tolerance_percent: DEFAULT_TOLERANCE_PERCENT,
+            include_zero_balance: false,
+            stats,
+            reported_diagnostics: Mutex::default(),
+            mint_infos: Mutex::default(),
+        }
+    }
+
+    /// Talk to the RPC through an HTTP client with these settings (pool, keepalive, HTTP version,
+    /// per-request timeout, User-Agent, wire logging) instead of solana-client's default one
+    pub fn with_http_tuning(mut self, tuning: &HttpTuning) -> Result<Self> {
+        let sender = HttpSender::new_with_client(self.client.url(), tuning.build_client()?);
+        (self.client, self.sender) = if tuning.log_requests {
+            instrumented_client(LoggingSender::new(sender), &self.stats)
+        } else {
+            instrumented_client(sender, &self.stats)
+        };
+        info!("RPC HTTP client: {}", tuning.describe());
+        Ok(self)
+    }
+
+    /// Per-method call statistics since startup
+    pub fn rpc_stats(&self) -> Vec<MethodStats> {
+        self.stats.snapshot()
+    }
+
+    /// Last answered call and failures since
+    pub fn rpc_health(&self) -> RpcHealth {
+        self.stats.health()
+    }
+
+    /// Current problems with the endpoint and what to do about them
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        diagnose(&self.stats.snapshot())
+    }
+
+    /// Diagnostics not returned by an earlier call, so each problem is reported once
+    pub fn new_diagnostics(&self) -> Vec<Diagnostic> {
+        let mut reported = self.reported_diagnostics.lock().unwrap();
+        self.diagnostics().into_iter().filter(|d| reported.insert(d.key())).collect()
+    }
+
+    /// Highest slot the RPC has answered at
+    pub fn last_slot(&self) -> Option<u64> {
+        self.min_context_slot()
+    }
+
+    fn min_context_slot(&self) -> Option<u64> {
+        Some(self.last_slot.load(Ordering::Relaxed)).filter(|slot| *slot > 0)
+    }
+
+    fn observe_slot(&self, slot: u64) {
+        self.last_slot.fetch_max(slot, Ordering::Relaxed);
+    }
+
+    /// How `snapshot` of `mint` was produced by this client
+    pub fn count_metadata(&self, mint: &Pubkey, snapshot: &AccountSnapshot) -> CountMetadata {
+        let mut metadata = CountMetadata::for_snapshot(
+            snapshot,
+            &mint.to_string(),
+            self.rpc_url(),
+            self.token_program.to_string(),
+            self.truncation_mode,
+            self.is_count_only(),
+        );
+        metadata.zero_balance_included = self.include_zero_balance;
+        metadata
+    }
+
+    /// Count wallets whose token accounts all hold zero as holders too (`--include-zero-balance`)
+    pub fn with_zero_balance_holders(mut self, include: bool) -> Self {
+        self.include_zero_balance = include;
+        self
+    }
+
+    /// Holders of a snapshot, and the owners with only zero-balance accounts (part of the
+    /// holders with `--include-zero-balance`)
+    pub fn holders_of(&self, snapshot: &AccountSnapshot) -> Result<(HashSet<Pubkey>, usize)> {
+        let mut holders = extract_holders(&snapshot.accounts)?;
+        let zero_balance = zero_balance_owners(&snapshot.accounts, &holders);
+        let zero_balance_count = zero_balance.len();
+        if self.include_zero_balance {
+            holders.extend(zero_balance);
+        }
+        Ok((holders, zero_balance_count))
+    }
+
+    /// Check fetched balances against getTokenSupply (`--truncation-check`); a snapshot missing
+    /// more than `tolerance_percent` of the supply is flagged or fails
+    pub fn with_truncation_check(mut self, mode: TruncationMode, tolerance_percent: f64) -> Self {
+        self.truncation_mode = mode;
+        self.truncation_tolerance_percent = tolerance_percent;
+        self
+    }
+
+    /// Limit retries across all callers per time window (first attempts are not limited)
+    pub fn with_retry_budget(mut self, retry_budget: Option<RetryBudget>) -> Self {
+        self.retry_budget = retry_budget;
+        self
+    }
+
+    pub fn retry_budget(&self) -> Option<&RetryBudget> {
+        self.retry_budget.as_ref()
+    }
+
+    /// Whether another retry may be made; always true without a budget
+    fn acquire_retry(&self) -> bool {
+        self.retry_budget.as_ref().is_none_or(RetryBudget::try_acquire)
+    }
+
+    /// Request only owner + amount slices of token accounts (~4x less data per account)
+    pub fn set_count_only(&self, count_only: bool) {
+        self.count_only.store(count_only, Ordering::Relaxed);
+    }
+
+    pub fn is_count_only(&self) -> bool {
+        self.count_only.load(Ordering::Relaxed)
+    }
+
+    /// Whether the counted accounts belong to Token-2022 (extension-aware fetch and supply check)
+    pub fn is_token_2022(&self) -> bool {
+        self.token_program.to_string() == TOKEN_2022_PROGRAM_ID
+    }
+
+    /// Query token accounts of another program (forks, devnet deployments, migrations)
+    pub fn with_token_program(mut self, token_program: Pubkey) -> Self {
+        if token_program.to_string() != TOKEN_PROGRAM_ID {
+            info!("Using token program: {}", token_program);
         }
+        self.token_program = token_program;
+        self
     }
 
     /// Check RPC connection health
     pub async fn health_check(&self) -> Result<()> {
+        self.get_slot().await.context("RPC health check failed")?;
+        Ok(())
+    }
+
+    /// Current slot of the RPC
+    pub async fn get_slot(&self) -> Result<u64> {
+        self.client.get_slot().await.map_err(|e| self.redacted_error(e)).context("Failed to get slot")
+    }
+
+    /// Health check plus a tiny filtered getProgramAccounts query (accounts of the all-zero mint,
+    /// which has none), so an RPC that can't serve the monitor is reported before the first poll
+    /// A rejection switches straight to the fallbacks; only a failed getSlot is an error
+    pub async fn probe_capabilities(&self) -> Result<RpcCapabilities> {
+        let start = std::time::Instant::now();
+        self.health_check().await?;
+        let latency_ms = start.elapsed().as_millis() as u64;
+
+        let mut filters = vec![RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, Pubkey::default().to_bytes().to_vec()))];
+        if !self.is_token_2022() {
+            filters.insert(0, RpcFilterType::DataSize(TOKEN_ACCOUNT_LEN as u64));
+        }
+        let config = RpcProgramAccountsConfig {
+            filters: Some(filters),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(solana_account_decoder::UiAccountEncoding::Base64),
+                data_slice: Some(UiDataSliceConfig { offset: 0, length: 0 }),
+                ..Default::default()
+            },
+            with_context: None,
+            sort_results: None,
+        };
+        let start = std::time::Instant::now();
+        let request = self
+            .client
+            .send::<Vec<RpcKeyedAccount>>(RpcRequest::GetProgramAccounts, json!([self.token_program.to_string(), config]));
+        let program_accounts = match tokio::time::timeout(self.timeout, request).await {
+            Ok(Ok(_)) => ProgramAccountsSupport::Supported,
+            Ok(Err(e)) => {
+                let error = format!("{}", self.redacted_error(e));
+                if is_program_accounts_rejection(&error) {
+                    self.program_accounts_rejected.store(true, Ordering::Relaxed);
+                    self.stats.mark_unsupported(&RpcRequest::GetProgramAccounts.to_string());
+                    ProgramAccountsSupport::Rejected { reason: error }
+                } else {
+                    ProgramAccountsSupport::Failed { error }
+                }
+            }
+            Err(_) => ProgramAccountsSupport::TimedOut,
+        };
+        let program_accounts_latency_ms =
+            (program_accounts == ProgramAccountsSupport::Supported).then(|| start.elapsed().as_millis() as u64);
+        Ok(RpcCapabilities { latency_ms, program_accounts, program_accounts_latency_ms })
+    }
+
+    /// Genesis hash of the cluster behind this RPC
+    pub async fn get_genesis_hash(&self) -> Result<Hash> {
         self.client
-            .get_slot()
+            .get_genesis_hash()
             .await
-            .context("RPC health check failed")?;
-        Ok(())
+            .map_err(|e| self.redacted_error(e))
+            .context("Failed to get genesis hash")
     }
 
     /// Get token accounts by mint with retry logic and timeout
@@ -49,18 +431,28 @@ impl SolanaRpcClient {
         &self,
         mint: &Pubkey,
     ) -> Result<Vec<(Pubkey, Account)>> {
+        Ok(self.get_token_accounts_snapshot(mint).await?.accounts)
+    }
+
+    /// Token accounts of a mint with the slot they were read at
+    /// The request asks for at least the highest slot this client has seen, so a load-balanced
+    /// RPC never answers from a node behind an earlier answer
+    pub async fn get_token_accounts_snapshot(&self, mint: &Pubkey) -> Result<AccountSnapshot> {
         let start_time = std::time::Instant::now();
         let mut last_error = None;
+        let mut budget_exhausted = false;
         
         for attempt in 0..self.max_retries {
             // Apply timeout to each attempt
             let result = tokio::time::timeout(
                 self.timeout,
-                self._get_token_accounts_by_mint(mint)
+                self.fetch_checked(mint)
             ).await;
             
             match result {
-                Ok(Ok(accounts)) => {
+                Ok(Ok(snapshot)) => {
+                    let accounts = &snapshot.accounts;
+                    memory::record_fetch(memory::accounts_bytes(accounts));
                     let elapsed = start_time.elapsed();
                     if attempt > 0 {
                         info!("Successfully retrieved {} accounts after {} retries (total time: {:.2}s)", 
@@ -75,7 +467,7 @@ impl SolanaRpcClient {
                         warn!("RPC request took {:.2}s (consider using a faster RPC endpoint)", elapsed.as_secs_f64());
                     }
                     
-                    return Ok(accounts);
+                    return Ok(snapshot);
                 }
                 Ok(Err(e)) => {
                     let error_msg = format!("{}", e);
@@ -87,6 +479,11 @@ impl SolanaRpcClient {
                         error_msg
                     );
                     if attempt < self.max_retries - 1 {
+                        if !self.acquire_retry() {
+                            warn!("RPC retry budget exhausted, not retrying {}", mint);
+                            budget_exhausted = true;
+                            break;
+                        }
                         let delay = Self::exponential_backoff(attempt);
                         warn!("Retrying in {:?}...", delay);
                         sleep(delay).await;
@@ -108,6 +505,11 @@ impl SolanaRpcClient {
                         self.max_retries
                     );
                     if attempt < self.max_retries - 1 {
+                        if !self.acquire_retry() {
+                            warn!("RPC retry budget exhausted, not retrying {}", mint);
+                            budget_exhausted = true;
+                            break;
+                        }
                         let delay = Self::exponential_backoff(attempt);
                         warn!("Retrying in {:?}...", delay);
                         sleep(delay).await;
@@ -117,45 +519,154 @@ impl SolanaRpcClient {
         }
 
         let total_elapsed = start_time.elapsed();
+        if budget_exhausted {
+            error!("Failed to get token accounts, retry budget exhausted (total time: {:.2}s)",
+                total_elapsed.as_secs_f64());
+            return Err(last_error.unwrap().context("Failed to get token accounts (retry budget exhausted)"));
+        }
         error!("Failed to get token accounts after {} retries (total time: {:.2}s)", 
             self.max_retries, total_elapsed.as_secs_f64());
         Err(last_error.unwrap().context("Failed to get token accounts after all retries"))
     }
 
+    /// Token accounts of a mint at finalized commitment (single attempt, no fallbacks)
+    /// Finalized data lags confirmed by ~30 slots, so no `minContextSlot` is requested
+    pub async fn get_finalized_snapshot(&self, mint: &Pubkey) -> Result<AccountSnapshot> {
+        let snapshot = tokio::time::timeout(
+            self.timeout,
+            self._try_get_program_accounts(mint, CommitmentConfig::finalized()),
+        )
+        .await
+        .map_err(|_| anyhow::anyhow!("RPC request timed out after {:?}", self.timeout))??;
+        memory::record_fetch(memory::accounts_bytes(&snapshot.accounts));
+        Ok(AccountSnapshot { finalized: true, ..snapshot })
+    }
+
+    /// Fetch token accounts and run the truncation check on them
+    async fn fetch_checked(&self, mint: &Pubkey) -> Result<AccountSnapshot> {
+        let mut snapshot = self._get_token_accounts_by_mint(mint).await?;
+        self.check_truncation(mint, &mut snapshot).await?;
+        Ok(snapshot)
+    }
+
+    /// Compare the balances of a snapshot with the mint supply (see `truncation`)
+    /// A failed supply lookup skips the check; partial fallbacks are never checked
+    async fn check_truncation(&self, mint: &Pubkey, snapshot: &mut AccountSnapshot) -> Result<()> {
+        if self.truncation_mode == TruncationMode::Off || snapshot.degraded.is_some_and(|f| f.is_partial()) {
+            return Ok(());
+        }
+        let supply = match self.get_token_supply(mint, snapshot.slot).await {
+            Ok(supply) => supply,
+            Err(e) => {
+                debug!("Truncation check skipped for {}: {:#}", mint, e);
+                return Ok(());
+            }
+        };
+        let mut accounted = total_amount(&snapshot.accounts);
+        if self.is_token_2022() {
+            // Fees harvested to the mint are part of the supply but of no token account
+            accounted += u128::from(self.mint_withheld_amount(mint).await);
+        }
+        let check = SupplyCheck { supply, accounted };
+        if !check.is_suspect(self.truncation_tolerance_percent) {
+            return Ok(());
+        }
+
+        let missing = check.missing_percent();
+        let message = format!(
+            "Response for {} looks truncated: {} accounts hold {:.2}% less than the token supply",
+            mint,
+            snapshot.accounts.len(),
+            missing
+        );
+        if self.truncation_mode == TruncationMode::Error {
+            return Err(anyhow::anyhow!(message));
+        }
+        warn!("{} (the holder count is likely too low)", message);
+        snapshot.missing_supply_percent = Some(missing);
+        Ok(())
+    }
+
+    /// Transfer fees withheld on a Token-2022 mint (0 when unknown)
+    async fn mint_withheld_amount(&self, mint: &Pubkey) -> u64 {
+        match self.get_multiple_accounts(&[*mint], true).await {
+            Ok(mut accounts) => accounts
+                .pop()
+                .flatten()
+                .and_then(|account| MintInfo::parse(&account.data))
+                .and_then(|info| info.transfer_fee)
+                .map_or(0, |fee| fee.withheld_amount),
+            Err(e) => {
+                debug!("Mint withheld fees of {} unknown: {:#}", mint, e);
+                0
+            }
+        }
+    }
+
+    /// Raw supply of a mint (getTokenSupply), read no earlier than `min_context_slot` so that it is
+    /// compared with balances of the same slot rather than of a node that is behind
+    pub async fn get_token_supply(&self, mint: &Pubkey, min_context_slot: Option<u64>) -> Result<u64> {
+        let mut config = json!({"commitment": "confirmed"});
+        if let Some(slot) = min_context_slot {
+            config["minContextSlot"] = json!(slot);
+        }
+        let supply = self
+            .client
+            .send::<RpcResponse<UiTokenAmount>>(RpcRequest::GetTokenSupply, json!([mint.to_string(), config]))
+            .await
+            .map_err(|e| self.redacted_error(e))
+            .with_context(|| format!("Failed to get token supply of {}", mint))?;
+        self.observe_slot(supply.context.slot);
+        supply.value.amount.parse().context("Invalid token supply from RPC")
+    }
+
+    /// Decimals and Token-2022 extensions of a mint, read once per mint (the interest rate of an
+    /// interest-bearing mint is picked up again after a restart; withheld fees are read fresh by
+    /// the truncation check)
+    pub async fn get_mint_info(&self, mint: &Pubkey) -> Result<MintInfo> {
+        if let Some(info) = self.mint_infos.lock().unwrap().get(mint) {
+            return Ok(*info);
+        }
+        let account = self
+            .get_multiple_accounts(&[*mint], true)
+            .await?
+            .pop()
+            .flatten()
+            .with_context(|| format!("Mint account {} not found", mint))?;
+        let info = MintInfo::parse(&account.data).with_context(|| format!("{} is not a mint account", mint))?;
+        self.mint_infos.lock().unwrap().insert(*mint, info);
+        Ok(info)
+    }
+
     /// Internal method to fetch token accounts with pagination
     async fn _get_token_accounts_by_mint(
         &self,
         mint: &Pubkey,
-    ) -> Result<Vec<(Pubkey, Account)>> {
+    ) -> Result<AccountSnapshot> {
+        // Known to be rejected by this RPC: go straight to the fallbacks
+        if self.program_accounts_rejected.load(Ordering::Relaxed) {
+            return self.fallback_snapshot(mint).await;
+        }
+
         // Try getProgramAccounts first (works on private RPCs)
-        match self._try_get_program_accounts(mint).await {
-            Ok(accounts) if !accounts.is_empty() => {
-                info!("Successfully fetched {} accounts using getProgramAccounts", accounts.len());
-                return Ok(accounts);
+        match self._try_get_program_accounts(mint, CommitmentConfig::confirmed()).await {
+            Ok(snapshot) if !snapshot.accounts.is_empty() => {
+                info!("Successfully fetched {} accounts using getProgramAccounts", snapshot.accounts.len());
+                return Ok(snapshot);
             }
             Ok(_) => {
                 warn!("getProgramAccounts returned empty result");
             }
             Err(e) => {
-                let error_str = format!("{}", e);
                 // Check if it's the known public RPC limitation
-                if error_str.contains("excluded from account secondary indexes") 
-                    || error_str.contains("this RPC method unavailable") {
-                    return Err(anyhow::anyhow!(
-                        "Public RPC endpoint '{}' does not support getProgramAccounts for Token Program.\n\
-                        This is a known limitation of public RPC endpoints.\n\n\
-                        SOLUTIONS:\n\
-                        1. Use a private RPC endpoint:\n\
-                           - Helius: https://mainnet.helius-rpc.com/?api-key=YOUR_KEY\n\
-                           - QuickNode: https://your-endpoint.solana-mainnet.quiknode.pro/YOUR_KEY/\n\
-                           - Alchemy: https://solana-mainnet.g.alchemy.com/v2/YOUR_KEY\n\
-                        2. Or try alternative public RPCs:\n\
-                           - https://rpc.ankr.com/solana\n\
-                           - https://solana-api.projectserum.com\n\n\
-                        Example: cargo run -- {} --rpc-url https://rpc.ankr.com/solana --interval 30",
-                        self.client.url(),
-                        mint
-                    ));
+                if is_program_accounts_rejection(&format!("{:#}", e)) {
+                    warn!(
+                        "RPC {} rejects getProgramAccounts for the token program, switching to degraded mode",
+                        self.rpc_url()
+                    );
+                    self.program_accounts_rejected.store(true, Ordering::Relaxed);
+                    self.stats.mark_unsupported(&RpcRequest::GetProgramAccounts.to_string());
+                    return self.fallback_snapshot(mint).await;
                 }
                 warn!("getProgramAccounts failed: {}", e);
             }
@@ -167,32 +678,125 @@ impl SolanaRpcClient {
         ))
     }
 
+    /// Degraded mode: every account from DAS `getTokenAccounts` when the provider has it,
+    /// otherwise only the largest accounts
+    async fn fallback_snapshot(&self, mint: &Pubkey) -> Result<AccountSnapshot> {
+        let das_error = match self.das_token_accounts(mint).await {
+            Ok(accounts) => {
+                info!("Degraded mode: {} accounts of {} from DAS getTokenAccounts", accounts.len(), mint);
+                return Ok(AccountSnapshot { accounts, degraded: Some(Fallback::Das), ..Default::default() });
+            }
+            Err(e) => e,
+        };
+        debug!("DAS getTokenAccounts unavailable: {:#}", das_error);
+
+        match self.largest_token_accounts(mint).await {
+            Ok(accounts) => {
+                warn!(
+                    "Degraded mode: only the {} largest accounts of {} are available, the holder count is a lower bound",
+                    accounts.len(),
+                    mint
+                );
+                Ok(AccountSnapshot { accounts, degraded: Some(Fallback::LargestAccounts), ..Default::default() })
+            }
+            Err(e) => Err(anyhow::anyhow!(
+                "RPC {} rejects getProgramAccounts for the token program and no fallback is available \
+                (DAS getTokenAccounts: {:#}; getTokenLargestAccounts: {:#}). Use an RPC that supports \
+                getProgramAccounts or DAS (Helius, QuickNode, Alchemy)",
+                self.rpc_url(),
+                das_error,
+                e
+            )),
+        }
+    }
+
+    /// Every token account of `mint` via the DAS `getTokenAccounts` method, page by page
+    /// DAS reports owner and amount only; accounts are rebuilt in the SPL layout
+    async fn das_token_accounts(&self, mint: &Pubkey) -> Result<Vec<(Pubkey, Account)>> {
+        let mut accounts = Vec::new();
+        let mut page = 1;
+        loop {
+            let response = self
+                .sender
+                .send(
+                    RpcRequest::Custom { method: "getTokenAccounts" },
+                    json!({"mint": mint.to_string(), "page": page, "limit": DAS_PAGE_LIMIT}),
+                )
+                .await
+                .map_err(|e| self.redacted_error(e))?;
+            let response: DasTokenAccounts =
+                serde_json::from_value(response).context("Invalid DAS getTokenAccounts response")?;
+            let count = response.token_accounts.len();
+            for entry in response.token_accounts {
+                let address = Pubkey::from_str(&entry.address).context("Invalid account address from DAS")?;
+                let owner = Pubkey::from_str(&entry.owner).context("Invalid owner address from DAS")?;
+                let account = Account {
+                    lamports: 0,
+                    data: encode_token_account(mint, &owner, entry.amount),
+                    owner: self.token_program,
+                    executable: false,
+                    rent_epoch: 0,
+                };
+                accounts.push((address, account));
+            }
+            if count < DAS_PAGE_LIMIT {
+                return Ok(accounts);
+            }
+            page += 1;
+        }
+    }
+
+    /// The largest token accounts of `mint` (getTokenLargestAccounts, at most 20) with their data
+    async fn largest_token_accounts(&self, mint: &Pubkey) -> Result<Vec<(Pubkey, Account)>> {
+        let largest = self
+            .client
+            .get_token_largest_accounts(mint)
+            .await
+            .map_err(|e| self.redacted_error(e))?;
+        let addresses = largest
+            .iter()
+            .map(|balance| Pubkey::from_str(&balance.address))
+            .collect::<Result<Vec<_>, _>>()
+            .context("Invalid account address from RPC")?;
+        let accounts = self.get_multiple_accounts(&addresses, true).await?;
+        Ok(addresses
+            .into_iter()
+            .zip(accounts)
+            .filter_map(|(address, account)| account.map(|account| (address, account)))
+            .collect())
+    }
+
     /// Try to get accounts using getProgramAccounts with optimized filters
     async fn _try_get_program_accounts(
         &self,
         mint: &Pubkey,
-    ) -> Result<Vec<(Pubkey, Account)>> {
-        let token_program_id = Pubkey::from_str("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA")
-            .context("Failed to parse Token Program ID")?;
+        commitment: CommitmentConfig,
+    ) -> Result<AccountSnapshot> {
+        let token_program_id = self.token_program;
 
         let mint_bytes = mint.as_ref();
         
-        // Use DataSize filter (165 bytes = standard SPL Token account size)
-        // and Memcmp filter for mint address at offset 0
-        let filters = vec![
-            RpcFilterType::DataSize(165),
-            RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, mint_bytes.to_vec())),
-        ];
+        // Memcmp filter for mint address at offset 0, plus a DataSize filter (165 bytes = standard
+        // SPL Token account size) unless Token-2022 accounts with extensions (longer) are counted
+        let mut filters = vec![RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, mint_bytes.to_vec()))];
+        if !self.is_token_2022() {
+            filters.insert(0, RpcFilterType::DataSize(TOKEN_ACCOUNT_LEN as u64));
+        }
 
         let config = RpcProgramAccountsConfig {
             filters: Some(filters),
             account_config: RpcAccountInfoConfig {
                 encoding: Some(solana_account_decoder::UiAccountEncoding::Base64),
-                commitment: Some(CommitmentConfig::confirmed()),
-                data_slice: None, // Load full data to parse amount
-                min_context_slot: None,
+                commitment: Some(commitment),
+                // Full data by default; owner + amount only in count-only mode
+                data_slice: self.is_count_only().then_some(UiDataSliceConfig {
+                    offset: SLIM_ACCOUNT_OFFSET,
+                    length: SLIM_ACCOUNT_LEN,
+                }),
+                min_context_slot: if commitment.is_finalized() { None } else { self.min_context_slot() },
             },
-            with_context: None,
+            // Report the slot the accounts were read at
+            with_context: Some(true),
             sort_results: None,
         };
 
@@ -202,19 +806,36 @@ impl SolanaRpcClient {
         let fetch_start = std::time::Instant::now();
         debug!("Fetching token accounts for mint: {}", mint);
         debug!("Using token program ID: {}", token_program_id);
-        debug!("RPC URL: {}", self.client.url());
+        debug!("RPC URL: {}", self.rpc_url());
 
-        let accounts = self
+        let response: OptionalContext<Vec<RpcKeyedAccount>> = self
             .client
-            .get_program_accounts_with_config(&token_program_id, config)
+            .send(RpcRequest::GetProgramAccounts, json!([token_program_id.to_string(), config]))
             .await
+            .map_err(|e| self.redacted_error(e))
             .with_context(|| {
                 format!(
                     "Failed to fetch program accounts from RPC {} for mint {}",
-                    self.client.url(),
+                    self.rpc_url(),
                     mint
                 )
             })?;
+        // Providers that ignore `withContext` answer with the bare list
+        let (keyed_accounts, slot) = match response {
+            OptionalContext::Context(response) => (response.value, Some(response.context.slot)),
+            OptionalContext::NoContext(value) => (value, None),
+        };
+        if let Some(slot) = slot {
+            self.observe_slot(slot);
+        }
+        let accounts = keyed_accounts
+            .into_iter()
+            .map(|keyed| {
+                let pubkey = Pubkey::from_str(&keyed.pubkey).context("Invalid account address from RPC")?;
+                let account = keyed.account.decode::<Account>().context("Failed to decode account data")?;
+                Ok((pubkey, account))
+            })
+            .collect::<Result<Vec<_>>>()?;
 
         let fetch_elapsed = fetch_start.elapsed();
         debug!("Fetched {} accounts from RPC in {:.2}s", accounts.len(), fetch_elapsed.as_secs_f64());
@@ -224,28 +845,172 @@ impl SolanaRpcClient {
             warn!("RPC fetch took {:.2}s - consider using a faster RPC endpoint", fetch_elapsed.as_secs_f64());
         }
 
-        let mut all_accounts = Vec::new();
+        match slot {
+            Some(slot) => info!("Total token accounts found: {} (slot {})", accounts.len(), slot),
+            None => info!("Total token accounts found: {}", accounts.len()),
+        }
+        Ok(AccountSnapshot { accounts, slot, ..Default::default() })
+    }
+
+
+    /// Find the oldest transaction of an address by walking its signature history backwards
+    /// Scans at most `max_pages` pages of 1000 signatures; returns None for addresses without history
+    pub async fn get_first_activity(
+        &self,
+        address: &Pubkey,
+        max_pages: usize,
+    ) -> Result<Option<FirstActivity>> {
+        let mut before = None;
+        let mut oldest = None;
 
-        // Convert UiAccount to Account format
-        for (pubkey, account_data) in accounts {
-            // account_data.data is already Vec<u8> when using Base64 encoding
-            // The RPC client automatically decodes it
-            let data = account_data.data.clone();
+        for _ in 0..max_pages.max(1) {
+            let config = GetConfirmedSignaturesForAddress2Config {
+                before,
+                until: None,
+                limit: Some(SIGNATURES_PAGE_LIMIT),
+                commitment: Some(CommitmentConfig::confirmed()),
+            };
+            let page = self
+                .client
+                .get_signatures_for_address_with_config(address, config)
+                .await
+                .map_err(|e| self.redacted_error(e))
+                .with_context(|| format!("Failed to get signatures for {}", address))?;
 
-            let account = Account {
-                lamports: account_data.lamports,
-                data,
-                owner: account_data.owner,
-                executable: account_data.executable,
-                rent_epoch: account_data.rent_epoch,
+            let Some(last) = page.last() else {
+                break;
             };
-            all_accounts.push((pubkey, account));
+            let signature = Signature::from_str(&last.signature)
+                .with_context(|| format!("Invalid signature from RPC: {}", last.signature))?;
+            let complete = page.len() < SIGNATURES_PAGE_LIMIT;
+            oldest = Some(FirstActivity {
+                signature,
+                slot: last.slot,
+                block_time: last.block_time,
+                complete,
+            });
+
+            if complete {
+                break;
+            }
+            before = Some(signature);
+        }
+
+        Ok(oldest)
+    }
+
+    /// Signatures of the latest `limit` transactions of an address (newest first)
+    pub async fn get_recent_signatures(&self, address: &Pubkey, limit: usize) -> Result<Vec<Signature>> {
+        let config = GetConfirmedSignaturesForAddress2Config {
+            before: None,
+            until: None,
+            limit: Some(limit.min(SIGNATURES_PAGE_LIMIT)),
+            commitment: Some(CommitmentConfig::confirmed()),
+        };
+        let statuses = self
+            .client
+            .get_signatures_for_address_with_config(address, config)
+            .await
+            .map_err(|e| self.redacted_error(e))
+            .with_context(|| format!("Failed to get signatures for {}", address))?;
+
+        Ok(statuses
+            .iter()
+            .filter_map(|status| Signature::from_str(&status.signature).ok())
+            .collect())
+    }
+
+    /// Block time (unix seconds) of `slot`; None for skipped slots, slots the node no longer
+    /// stores, or when the lookup fails
+    pub async fn get_block_time(&self, slot: u64) -> Option<u64> {
+        match self.client.get_block_time(slot).await {
+            Ok(time) => u64::try_from(time).ok(),
+            Err(e) => {
+                debug!("No block time for slot {}: {}", slot, self.redacted_error(e));
+                None
+            }
+        }
+    }
+
+    /// Accounts at `addresses` in input order (None where no account exists), fetched in batches
+    /// of `MULTIPLE_ACCOUNTS_LIMIT`; without `with_data` only lamports and owner are transferred
+    pub async fn get_multiple_accounts(&self, addresses: &[Pubkey], with_data: bool) -> Result<Vec<Option<Account>>> {
+        let config = RpcAccountInfoConfig {
+            encoding: Some(solana_account_decoder::UiAccountEncoding::Base64),
+            data_slice: (!with_data).then_some(UiDataSliceConfig { offset: 0, length: 0 }),
+            commitment: Some(CommitmentConfig::confirmed()),
+            // Not older than the holder snapshot the addresses came from
+            min_context_slot: self.min_context_slot(),
+        };
+        let mut accounts = Vec::with_capacity(addresses.len());
+        for batch in addresses.chunks(MULTIPLE_ACCOUNTS_LIMIT) {
+            let response = self
+                .client
+                .get_multiple_accounts_with_config(batch, config.clone())
+                .await
+                .map_err(|e| self.redacted_error(e))
+                .with_context(|| format!("Failed to get {} accounts", batch.len()))?;
+            self.observe_slot(response.context.slot);
+            accounts.extend(response.value);
+        }
+        Ok(accounts)
+    }
+
+    /// Fee payer of a transaction (first static account key)
+    pub async fn get_transaction_fee_payer(&self, signature: &Signature) -> Result<Option<Pubkey>> {
+        Ok(self
+            .get_versioned_transaction(signature)
+            .await?
+            .and_then(|tx| tx.message.static_account_keys().first().copied()))
+    }
+
+    /// Programs invoked by the top-level instructions of a transaction
+    pub async fn get_transaction_programs(&self, signature: &Signature) -> Result<Vec<Pubkey>> {
+        let Some(tx) = self.get_versioned_transaction(signature).await? else {
+            return Ok(Vec::new());
+        };
+        let keys = tx.message.static_account_keys();
+        Ok(tx
+            .message
+            .instructions()
+            .iter()
+            .filter_map(|ix| keys.get(ix.program_id_index as usize).copied())
+            .collect())
+    }
+
+    /// Mints of the token balances after a transaction (`postTokenBalances`), first seen first
+    pub async fn get_transaction_token_mints(&self, signature: &Signature) -> Result<Vec<Pubkey>> {
+        let transaction = self.get_transaction(signature).await?;
+        let mut mints = Vec::new();
+        if let Some(OptionSerializer::Some(balances)) = transaction.transaction.meta.map(|meta| meta.post_token_balances) {
+            for balance in balances {
+                if let Ok(mint) = Pubkey::from_str(&balance.mint) {
+                    if !mints.contains(&mint) {
+                        mints.push(mint);
+                    }
+                }
+            }
         }
+        Ok(mints)
+    }
 
-        info!("Total token accounts found: {}", all_accounts.len());
-        Ok(all_accounts)
+    /// Fetch and decode a transaction (None if the RPC returned an undecodable encoding)
+    async fn get_versioned_transaction(&self, signature: &Signature) -> Result<Option<VersionedTransaction>> {
+        Ok(self.get_transaction(signature).await?.transaction.transaction.decode())
     }
 
+    async fn get_transaction(&self, signature: &Signature) -> Result<EncodedConfirmedTransactionWithStatusMeta> {
+        let config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(CommitmentConfig::confirmed()),
+            max_supported_transaction_version: Some(0),
+        };
+        self.client
+            .get_transaction_with_config(signature, config)
+            .await
+            .map_err(|e| self.redacted_error(e))
+            .with_context(|| format!("Failed to get transaction {}", signature))
+    }
 
     /// Exponential backoff delay
     fn exponential_backoff(attempt: u32) -> Duration {
@@ -254,9 +1019,14 @@ impl SolanaRpcClient {
         Duration::from_millis(delay_ms.min(10000)) // Cap at 10 seconds
     }
 
-    /// Get RPC URL
+    /// RPC URL with API keys masked (`redact_url`), safe for logs and error messages
     pub fn rpc_url(&self) -> String {
-        self.client.url().to_string()
+        redact_url(&self.client.url())
+    }
+
+    /// Client errors quote the request URL: mask its credentials before the error is logged
+    fn redacted_error(&self, e: solana_client::client_error::ClientError) -> anyhow::Error {
+        anyhow::anyhow!(redact_in(&e.to_string(), &self.client.url()))
     }
 }
 
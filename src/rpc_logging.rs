@@ -0,0 +1,106 @@
+//! JSON-RPC wire logging (`--log-rpc-requests`): wraps the HTTP sender and logs every call's
+//! method, request/response size and duration under the `rpc_wire` target, with credentials in
+//! the endpoint URL masked. Sizes are those of the serialized JSON-RPC request and `result` value.
+
+use async_trait::async_trait;
+use serde_json::Value;
+use solana_client::client_error::Result;
+use solana_client::rpc_request::RpcRequest;
+use solana_client::rpc_sender::{RpcSender, RpcTransportStats};
+use std::time::Instant;
+use tracing::{debug, enabled, trace, Level};
+
+use crate::redact::{redact_in, redact_url};
+
+/// Longest params excerpt logged at trace level
+const MAX_PARAMS_LOGGED: usize = 512;
+
+/// `RpcSender` that logs each request around the wrapped sender
+pub struct LoggingSender<S> {
+    inner: S,
+    /// Endpoint as it may appear in logs
+    redacted_url: String,
+}
+
+impl<S: RpcSender> LoggingSender<S> {
+    pub fn new(inner: S) -> Self {
+        let redacted_url = redact_url(&inner.url());
+        Self { inner, redacted_url }
+    }
+}
+
+fn json_len(value: &Value) -> usize {
+    serde_json::to_vec(value).map(|bytes| bytes.len()).unwrap_or_default()
+}
+
+fn excerpt(text: &str) -> &str {
+    match text.char_indices().nth(MAX_PARAMS_LOGGED) {
+        Some((end, _)) => &text[..end],
+        None => text,
+    }
+}
+
+#[async_trait]
+impl<S: RpcSender + Send + Sync> RpcSender for LoggingSender<S> {
+    async fn send(&self, request: RpcRequest, params: Value) -> Result<Value> {
+        let request_bytes = json_len(&request.build_request_json(0, params.clone()));
+        if enabled!(target: "rpc_wire", Level::TRACE) {
+            let params = redact_in(&params.to_string(), &self.inner.url());
+            trace!(target: "rpc_wire", method = %request, params = excerpt(&params), "RPC request");
+        }
+        let started = Instant::now();
+        let result = self.inner.send(request, params).await;
+        let elapsed_ms = started.elapsed().as_millis() as u64;
+        match &result {
+            Ok(value) => debug!(
+                target: "rpc_wire",
+                endpoint = %self.redacted_url,
+                method = %request,
+                request_bytes,
+                response_bytes = json_len(value),
+                elapsed_ms,
+                "RPC call"
+            ),
+            Err(e) => debug!(
+                target: "rpc_wire",
+                endpoint = %self.redacted_url,
+                method = %request,
+                request_bytes,
+                elapsed_ms,
+                error = %redact_in(&e.to_string(), &self.inner.url()),
+                "RPC call failed"
+            ),
+        }
+        result
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        self.inner.get_transport_stats()
+    }
+
+    fn url(&self) -> String {
+        self.inner.url()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_rpc_client::mock_sender::MockSender;
+
+    #[tokio::test]
+    async fn test_passes_calls_through() {
+        let sender = LoggingSender::new(MockSender::new("succeeds"));
+        assert_eq!(sender.url(), MockSender::new("succeeds").url());
+        let slot = sender.send(RpcRequest::GetSlot, Value::Null).await.unwrap();
+        assert_eq!(slot, MockSender::new("succeeds").send(RpcRequest::GetSlot, Value::Null).await.unwrap());
+    }
+
+    #[test]
+    fn test_excerpt() {
+        let long = "x".repeat(MAX_PARAMS_LOGGED + 10);
+        assert_eq!(excerpt(&long).len(), MAX_PARAMS_LOGGED);
+        assert_eq!(excerpt("[1,2]"), "[1,2]");
+        assert_eq!(json_len(&serde_json::json!({"a": 1})), 7);
+    }
+}
@@ -0,0 +1,274 @@
+//! Per-method RPC statistics (`GET /rpc-stats`): calls, errors and latency of every JSON-RPC
+//! method the bot sends, recorded by a sender wrapped around the HTTP transport
+
+use async_trait::async_trait;
+use serde::Serialize;
+use serde_json::Value;
+use solana_client::client_error::{ClientErrorKind, Result};
+use solana_client::rpc_request::{RpcError, RpcRequest};
+use solana_client::rpc_sender::{RpcSender, RpcTransportStats};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::latency::LatencySamples;
+
+#[derive(Debug, Default)]
+struct MethodCounters {
+    calls: u64,
+    errors: u64,
+    timeouts: u64,
+    /// The RPC answered "method not found" or refuses the method for our queries
+    unsupported: bool,
+    total_secs: f64,
+    max_secs: f64,
+    recent: LatencySamples,
+}
+
+/// Statistics of one method since startup (percentiles over the most recent calls)
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct MethodStats {
+    pub method: String,
+    pub calls: u64,
+    /// Failed calls, timeouts included
+    pub errors: u64,
+    pub timeouts: u64,
+    pub error_rate_percent: f64,
+    pub timeout_rate_percent: f64,
+    pub avg_ms: f64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+    pub unsupported: bool,
+}
+
+/// How a call ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallOutcome {
+    Ok,
+    Error,
+    /// HTTP timeout, or the caller gave up waiting (the call was dropped)
+    TimedOut,
+    /// JSON-RPC "method not found"
+    Unsupported,
+}
+
+impl CallOutcome {
+    fn of(result: &Result<Value>) -> Self {
+        let Err(e) = result else {
+            return Self::Ok;
+        };
+        match e.kind() {
+            ClientErrorKind::Reqwest(e) if e.is_timeout() => Self::TimedOut,
+            ClientErrorKind::RpcError(RpcError::RpcResponseError { code: METHOD_NOT_FOUND, .. }) => Self::Unsupported,
+            _ => Self::Error,
+        }
+    }
+}
+
+/// JSON-RPC error code of an unknown method
+const METHOD_NOT_FOUND: i64 = -32601;
+
+fn percent(part: u64, total: u64) -> f64 {
+    (part as f64 / total as f64 * 10000.0).round() / 100.0
+}
+
+/// Liveness of the RPC for `GET /health`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct RpcHealth {
+    /// Unix time of the last call that got an answer
+    pub last_success_at: Option<u64>,
+    /// Calls failed or timed out since then
+    pub consecutive_failures: u64,
+}
+
+/// Counters per JSON-RPC method, shared by the client and the API
+#[derive(Debug, Clone, Default)]
+pub struct RpcStats {
+    methods: Arc<Mutex<HashMap<String, MethodCounters>>>,
+    health: Arc<Mutex<RpcHealth>>,
+}
+
+fn to_ms(secs: f64) -> f64 {
+    (secs * 1000.0 * 10.0).round() / 10.0
+}
+
+impl RpcStats {
+    pub fn record(&self, method: &str, duration: Duration, outcome: CallOutcome) {
+        let mut methods = self.methods.lock().unwrap();
+        let counters = methods.entry(method.to_string()).or_default();
+        let secs = duration.as_secs_f64();
+        counters.calls += 1;
+        match outcome {
+            CallOutcome::Ok => {}
+            CallOutcome::Error => counters.errors += 1,
+            CallOutcome::TimedOut => {
+                counters.errors += 1;
+                counters.timeouts += 1;
+            }
+            CallOutcome::Unsupported => {
+                counters.errors += 1;
+                counters.unsupported = true;
+            }
+        }
+        counters.total_secs += secs;
+        counters.max_secs = counters.max_secs.max(secs);
+        counters.recent.record(duration);
+        drop(methods);
+
+        let mut health = self.health.lock().unwrap();
+        match outcome {
+            // "Method not found" is still an answer
+            CallOutcome::Ok | CallOutcome::Unsupported => {
+                health.last_success_at = SystemTime::now().duration_since(UNIX_EPOCH).ok().map(|d| d.as_secs());
+                health.consecutive_failures = 0;
+            }
+            CallOutcome::Error | CallOutcome::TimedOut => health.consecutive_failures += 1,
+        }
+    }
+
+    pub fn health(&self) -> RpcHealth {
+        *self.health.lock().unwrap()
+    }
+
+    /// Flag a method the RPC refuses even though it answered (e.g. getProgramAccounts excluded
+    /// for the token program)
+    pub fn mark_unsupported(&self, method: &str) {
+        self.methods.lock().unwrap().entry(method.to_string()).or_default().unsupported = true;
+    }
+
+    /// Every method called so far, most called first
+    pub fn snapshot(&self) -> Vec<MethodStats> {
+        let methods = self.methods.lock().unwrap();
+        let mut stats: Vec<MethodStats> = methods
+            .iter()
+            .map(|(method, counters)| MethodStats {
+                method: method.clone(),
+                calls: counters.calls,
+                errors: counters.errors,
+                timeouts: counters.timeouts,
+                error_rate_percent: percent(counters.errors, counters.calls),
+                timeout_rate_percent: percent(counters.timeouts, counters.calls),
+                avg_ms: to_ms(counters.total_secs / counters.calls.max(1) as f64),
+                p50_ms: to_ms(counters.recent.percentile(0.5).unwrap_or_default()),
+                p95_ms: to_ms(counters.recent.percentile(0.95).unwrap_or_default()),
+                max_ms: to_ms(counters.max_secs),
+                unsupported: counters.unsupported,
+            })
+            .collect();
+        stats.sort_by(|a, b| b.calls.cmp(&a.calls).then_with(|| a.method.cmp(&b.method)));
+        stats
+    }
+}
+
+/// Records a call dropped before it finished (a caller-side timeout) as timed out
+struct InFlight<'a> {
+    stats: &'a RpcStats,
+    method: String,
+    started: Instant,
+    outcome: Option<CallOutcome>,
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        let outcome = self.outcome.unwrap_or(CallOutcome::TimedOut);
+        self.stats.record(&self.method, self.started.elapsed(), outcome);
+    }
+}
+
+/// `RpcSender` that records every call into `RpcStats`
+pub struct StatsSender<S> {
+    inner: S,
+    stats: RpcStats,
+}
+
+impl<S> StatsSender<S> {
+    pub fn new(inner: S, stats: RpcStats) -> Self {
+        Self { inner, stats }
+    }
+}
+
+#[async_trait]
+impl<S: RpcSender + Send + Sync> RpcSender for StatsSender<S> {
+    async fn send(&self, request: RpcRequest, params: Value) -> Result<Value> {
+        let mut call = InFlight { stats: &self.stats, method: request.to_string(), started: Instant::now(), outcome: None };
+        let result = self.inner.send(request, params).await;
+        call.outcome = Some(CallOutcome::of(&result));
+        result
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        self.inner.get_transport_stats()
+    }
+
+    fn url(&self) -> String {
+        self.inner.url()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_rpc_client::mock_sender::MockSender;
+
+    #[test]
+    fn test_snapshot() {
+        let stats = RpcStats::default();
+        stats.record("getSlot", Duration::from_millis(100), CallOutcome::Ok);
+        stats.record("getSlot", Duration::from_millis(300), CallOutcome::TimedOut);
+        stats.record("getProgramAccounts", Duration::from_secs(4), CallOutcome::Ok);
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot[0].method, "getSlot");
+        assert_eq!(snapshot[0].calls, 2);
+        assert_eq!(snapshot[0].error_rate_percent, 50.0);
+        assert_eq!(snapshot[0].timeout_rate_percent, 50.0);
+        assert_eq!(snapshot[0].avg_ms, 200.0);
+        assert_eq!(snapshot[0].p95_ms, 300.0);
+        assert_eq!(snapshot[1].max_ms, 4000.0);
+        assert!(!snapshot[1].unsupported);
+        stats.mark_unsupported("getProgramAccounts");
+        assert!(stats.snapshot()[1].unsupported);
+
+        let health = stats.health();
+        assert!(health.last_success_at.is_some());
+        assert_eq!(health.consecutive_failures, 0);
+        stats.record("getSlot", Duration::from_millis(100), CallOutcome::Error);
+        stats.record("getSlot", Duration::from_millis(100), CallOutcome::TimedOut);
+        assert_eq!(stats.health().consecutive_failures, 2);
+    }
+
+    #[tokio::test]
+    async fn test_sender_records() {
+        let stats = RpcStats::default();
+        let sender = StatsSender::new(MockSender::new("succeeds"), stats.clone());
+        sender.send(RpcRequest::GetSlot, Value::Null).await.unwrap();
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.len(), 1);
+        assert_eq!(snapshot[0].method, "getSlot");
+        assert_eq!(snapshot[0].errors, 0);
+
+        // A call the caller stops waiting for counts as a timeout
+        let stalled = StatsSender::new(Stalled, stats.clone());
+        let call = stalled.send(RpcRequest::GetTokenSupply, Value::Null);
+        assert!(tokio::time::timeout(Duration::from_millis(10), call).await.is_err());
+        let supply = stats.snapshot().into_iter().find(|m| m.method == "getTokenSupply").unwrap();
+        assert_eq!(supply.timeouts, 1);
+    }
+
+    struct Stalled;
+
+    #[async_trait]
+    impl RpcSender for Stalled {
+        async fn send(&self, _request: RpcRequest, _params: Value) -> Result<Value> {
+            std::future::pending().await
+        }
+
+        fn get_transport_stats(&self) -> RpcTransportStats {
+            RpcTransportStats::default()
+        }
+
+        fn url(&self) -> String {
+            "stalled".to_string()
+        }
+    }
+}
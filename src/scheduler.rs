@@ -0,0 +1,456 @@
+//! Poll scheduling for several mints: each mint has its own interval (`[polling]` of the config
+//! file puts primary mints on a short one and background mints on a long one), polls of mints
+//! sharing an interval are spread evenly over it (or a window of it, optionally aligned to
+//! wall-clock multiples of the interval) instead of all firing at the same tick, and at most
+//! `max_concurrent` fetches run at once.
+
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use solana_program::pubkey::Pubkey;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+/// Order in which due mints get a fetch slot when the concurrency limit is reached
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    High,
+    #[default]
+    Normal,
+    Low,
+}
+
+/// `[polling]` of the config file: primary mints are polled often and first, background mints
+/// rarely and last; the rest use `--interval`
+///
+/// ```toml
+/// [polling]
+/// primary = ["<MINT>"]
+/// primary_interval = 10
+/// background = ["<MINT>"]
+/// background_interval = 600
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PollingConfig {
+    #[serde(default)]
+    pub primary: Vec<String>,
+    /// Seconds between polls of primary mints
+    pub primary_interval: Option<u64>,
+    #[serde(default)]
+    pub background: Vec<String>,
+    /// Seconds between polls of background mints
+    pub background_interval: Option<u64>,
+}
+
+impl PollingConfig {
+    /// Valid mints, each in one class, and an interval above 0 for every non-empty class
+    pub fn validate(&self) -> Result<()> {
+        for (class, mints, interval) in [
+            ("primary", &self.primary, self.primary_interval),
+            ("background", &self.background, self.background_interval),
+        ] {
+            for mint in mints {
+                Pubkey::from_str(mint).map_err(|e| anyhow::anyhow!("Polling: invalid {} mint '{}': {}", class, mint, e))?;
+            }
+            match interval {
+                Some(0) => return Err(anyhow::anyhow!("Polling: {}_interval must be greater than 0", class)),
+                None if !mints.is_empty() => {
+                    return Err(anyhow::anyhow!("Polling: {} mints need {}_interval", class, class));
+                }
+                _ => {}
+            }
+        }
+        if let Some(mint) = self.primary.iter().find(|mint| self.background.contains(mint)) {
+            return Err(anyhow::anyhow!("Polling: {} is both primary and background", mint));
+        }
+        Ok(())
+    }
+
+    /// Interval and default priority of a mint: primary ones are `high`, background ones `low`
+    pub fn schedule_of(&self, mint: &str, default_interval: Duration) -> (Duration, Priority) {
+        if self.primary.iter().any(|m| m == mint) {
+            (self.primary_interval.map_or(default_interval, Duration::from_secs), Priority::High)
+        } else if self.background.iter().any(|m| m == mint) {
+            (self.background_interval.map_or(default_interval, Duration::from_secs), Priority::Low)
+        } else {
+            (default_interval, Priority::Normal)
+        }
+    }
+}
+
+/// Where in the interval each key's polls land
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Stagger {
+    /// Offsets are spread over this part of the interval (capped at the interval); zero polls
+    /// every key together; None spreads over the whole interval
+    pub window: Option<Duration>,
+    /// Offsets count from wall-clock multiples of the interval (:00, :30, ...) instead of the start
+    pub align: bool,
+}
+
+impl Stagger {
+    /// Offset of key `i` of `n` sharing `interval` from the window start
+    pub fn offset(&self, i: usize, n: usize, interval: Duration) -> Duration {
+        self.window.map_or(interval, |window| window.min(interval)) * i as u32 / n.max(1) as u32
+    }
+}
+
+/// Faster polling of every key for a while (launch mode)
+#[derive(Debug, Clone, Copy)]
+struct Boost {
+    interval: Duration,
+    from: Instant,
+    until: Instant,
+}
+
+struct Entry<K> {
+    key: K,
+    priority: Priority,
+    interval: Duration,
+    next_due: Instant,
+    running: bool,
+}
+
+/// Per-key poll schedule with a global concurrency limit
+pub struct Scheduler<K> {
+    max_concurrent: usize,
+    entries: Vec<Entry<K>>,
+    boost: Option<Boost>,
+}
+
+impl<K: Clone + PartialEq> Scheduler<K> {
+    /// Key `i` of `n` is first due at `start + interval * i / n`; higher priority keys go first
+    pub fn new(keys: Vec<(K, Priority)>, interval: Duration, max_concurrent: usize, start: Instant) -> Self {
+        let keys = keys.into_iter().map(|(key, priority)| (key, priority, interval)).collect();
+        Self::staggered(keys, max_concurrent, start, Stagger::default(), Duration::ZERO)
+    }
+
+    /// Keys with their own interval; key `i` of the `n` sharing an interval is first due at
+    /// `base + stagger.offset(i, n, interval)`, where `base` is `start` or, with `stagger.align`,
+    /// the last multiple of the interval on the wall clock (`unix_now` is the wall clock at
+    /// `start`); aligned slots already past wait for the next interval
+    pub fn staggered(
+        keys: Vec<(K, Priority, Duration)>,
+        max_concurrent: usize,
+        start: Instant,
+        stagger: Stagger,
+        unix_now: Duration,
+    ) -> Self {
+        let mut keys = keys;
+        keys.sort_by_key(|(_, priority, _)| *priority);
+        let mut entries: Vec<Entry<K>> = Vec::with_capacity(keys.len());
+        for (i, (key, priority, interval)) in keys.iter().cloned().enumerate() {
+            let same_interval = |other: &&(K, Priority, Duration)| other.2 == interval;
+            let position = keys[..i].iter().filter(same_interval).count();
+            let count = keys.iter().filter(same_interval).count();
+            let base = if stagger.align && !interval.is_zero() {
+                let into_interval = Duration::from_nanos((unix_now.as_nanos() % interval.as_nanos()) as u64);
+                start.checked_sub(into_interval).unwrap_or(start)
+            } else {
+                start
+            };
+            let mut next_due = base + stagger.offset(position, count, interval);
+            while next_due < start {
+                next_due += interval;
+            }
+            entries.push(Entry { key, priority, interval, next_due, running: false });
+        }
+        Self {
+            max_concurrent: max_concurrent.max(1),
+            entries,
+            boost: None,
+        }
+    }
+
+    /// Poll every key at least every `interval` between `from` and `until`; the first boosted
+    /// polls are spread over `interval` from `from` (or `start` once the window is open), and
+    /// the keys' own intervals resume after `until`
+    pub fn with_boost(mut self, interval: Duration, from: Instant, until: Instant, start: Instant) -> Self {
+        let first = from.max(start);
+        let count = self.entries.len().max(1) as u32;
+        for (i, entry) in self.entries.iter_mut().enumerate() {
+            let due = first + interval * i as u32 / count;
+            if due < until {
+                entry.next_due = entry.next_due.min(due);
+            }
+        }
+        self.boost = Some(Boost { interval, from, until });
+        self
+    }
+
+    /// Seconds between polls of a key
+    pub fn interval_of(&self, key: &K) -> Option<Duration> {
+        self.entries.iter().find(|e| e.key == *key).map(|e| e.interval)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Number of keys currently being fetched
+    pub fn running(&self) -> usize {
+        self.entries.iter().filter(|e| e.running).count()
+    }
+
+    /// Keys due at `now` that fit in the free fetch slots, by priority then due time;
+    /// they are marked running until `complete`
+    pub fn take_due(&mut self, now: Instant) -> Vec<K> {
+        let free = self.max_concurrent.saturating_sub(self.running());
+        let mut due: Vec<&mut Entry<K>> = self
+            .entries
+            .iter_mut()
+            .filter(|e| !e.running && e.next_due <= now)
+            .collect();
+        due.sort_by_key(|e| (e.priority, e.next_due));
+        due.into_iter()
+            .take(free)
+            .map(|e| {
+                e.running = true;
+                e.key.clone()
+            })
+            .collect()
+    }
+
+    /// Mark a fetch finished and schedule the next one; slots missed while running
+    /// (or waiting for a free slot) are skipped rather than polled in a burst
+    pub fn complete(&mut self, key: &K, now: Instant) {
+        let boost = self.boost.filter(|boost| now < boost.until && !boost.interval.is_zero());
+        if let Some(entry) = self.entries.iter_mut().find(|e| e.key == *key) {
+            entry.running = false;
+            let previous = entry.next_due;
+            while entry.next_due <= now {
+                entry.next_due += entry.interval;
+            }
+            if let Some(boost) = boost {
+                let mut fast = previous.max(boost.from);
+                while fast <= now {
+                    fast += boost.interval;
+                }
+                if fast < boost.until {
+                    entry.next_due = entry.next_due.min(fast);
+                }
+            }
+        }
+    }
+
+    /// Make every idle key due at `now` (e.g. on SIGHUP)
+    pub fn poll_now(&mut self, now: Instant) {
+        for entry in self.entries.iter_mut().filter(|e| !e.running) {
+            entry.next_due = now;
+        }
+    }
+
+    /// Start scheduling a key, first due at `now` (no-op for a known key)
+    pub fn add(&mut self, key: K, priority: Priority, interval: Duration, now: Instant) {
+        if !self.entries.iter().any(|e| e.key == key) {
+            self.entries.push(Entry { key, priority, interval, next_due: now, running: false });
+        }
+    }
+
+    /// Change the priority and interval of a key (config reload); the next poll keeps its due
+    /// time unless that is more than the new interval away
+    pub fn set_schedule(&mut self, key: &K, priority: Priority, interval: Duration, now: Instant) {
+        if let Some(entry) = self.entries.iter_mut().find(|e| e.key == *key) {
+            entry.priority = priority;
+            entry.interval = interval;
+            entry.next_due = entry.next_due.min(now + interval);
+        }
+    }
+
+    /// Stop scheduling a key
+    pub fn remove(&mut self, key: &K) {
+        self.entries.retain(|e| e.key != *key);
+    }
+
+    /// When the next key becomes due; `None` while all fetch slots are busy
+    /// (the next wakeup is then a completed fetch)
+    pub fn next_wakeup(&self) -> Option<Instant> {
+        if self.running() >= self.max_concurrent {
+            return None;
+        }
+        self.entries
+            .iter()
+            .filter(|e| !e.running)
+            .map(|e| e.next_due)
+            .min()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INTERVAL: Duration = Duration::from_secs(30);
+
+    #[test]
+    fn test_polls_spread_over_interval() {
+        let start = Instant::now();
+        let mut scheduler = Scheduler::new(
+            vec![("a", Priority::Normal), ("b", Priority::Normal), ("c", Priority::High)],
+            INTERVAL,
+            3,
+            start,
+        );
+
+        assert_eq!(scheduler.take_due(start), vec!["c"]);
+        assert_eq!(scheduler.next_wakeup(), Some(start + Duration::from_secs(10)));
+        assert_eq!(scheduler.take_due(start + Duration::from_secs(10)), vec!["a"]);
+        assert_eq!(scheduler.take_due(start + Duration::from_secs(20)), vec!["b"]);
+    }
+
+    #[test]
+    fn test_concurrency_limit_and_priority() {
+        let start = Instant::now();
+        let mut scheduler = Scheduler::new(
+            vec![("low", Priority::Low), ("a", Priority::Normal), ("high", Priority::High)],
+            INTERVAL,
+            2,
+            start,
+        );
+
+        let later = start + INTERVAL;
+        assert_eq!(scheduler.take_due(later), vec!["high", "a"]);
+        assert!(scheduler.take_due(later).is_empty());
+        assert_eq!(scheduler.next_wakeup(), None);
+
+        scheduler.complete(&"high", later);
+        assert_eq!(scheduler.take_due(later), vec!["low"]);
+    }
+
+    #[test]
+    fn test_set_schedule() {
+        let start = Instant::now();
+        let mut scheduler = Scheduler::new(vec![("a", Priority::Normal), ("b", Priority::Normal)], INTERVAL, 1, start);
+        let later = start + INTERVAL;
+
+        // "b" becomes primary: polled first and every 10s from now on
+        scheduler.set_schedule(&"b", Priority::High, Duration::from_secs(10), start);
+        assert_eq!(scheduler.interval_of(&"b"), Some(Duration::from_secs(10)));
+        assert_eq!(scheduler.take_due(later), vec!["b"]);
+        scheduler.complete(&"b", later);
+        assert_eq!(scheduler.take_due(later), vec!["a"]);
+        scheduler.complete(&"a", later);
+        assert_eq!(scheduler.next_wakeup(), Some(later + Duration::from_secs(10)));
+
+        // Unknown keys are ignored
+        scheduler.set_schedule(&"c", Priority::High, INTERVAL, later);
+        assert_eq!(scheduler.interval_of(&"c"), None);
+    }
+
+    #[test]
+    fn test_stagger_window_and_alignment() {
+        let start = Instant::now();
+        let keys: Vec<(&str, Priority, Duration)> =
+            ["a", "b", "c"].into_iter().map(|key| (key, Priority::Normal, INTERVAL)).collect();
+
+        // Spread over the first 15s of the interval
+        let stagger = Stagger { window: Some(Duration::from_secs(15)), align: false };
+        let mut scheduler = Scheduler::staggered(keys.clone(), 3, start, stagger, Duration::ZERO);
+        assert_eq!(scheduler.take_due(start), vec!["a"]);
+        assert_eq!(scheduler.next_wakeup(), Some(start + Duration::from_secs(5)));
+
+        // No window: everything together
+        let stagger = Stagger { window: Some(Duration::ZERO), align: false };
+        let mut scheduler = Scheduler::staggered(keys.clone(), 3, start, stagger, Duration::ZERO);
+        assert_eq!(scheduler.take_due(start), vec!["a", "b", "c"]);
+
+        // Started 12s into a wall-clock interval: slots at :00 (past, moves to :30), :10 (past) and :20
+        let stagger = Stagger { window: None, align: true };
+        let unix_now = Duration::from_secs(1_700_000_022);
+        let mut scheduler = Scheduler::staggered(keys, 3, start, stagger, unix_now);
+        let boundary = start - Duration::from_secs(12);
+        assert!(scheduler.take_due(start).is_empty());
+        assert_eq!(scheduler.next_wakeup(), Some(boundary + Duration::from_secs(20)));
+        assert_eq!(scheduler.take_due(boundary + Duration::from_secs(20)), vec!["c"]);
+        assert_eq!(scheduler.take_due(boundary + Duration::from_secs(30)), vec!["a"]);
+        assert_eq!(scheduler.take_due(boundary + Duration::from_secs(40)), vec!["b"]);
+    }
+
+    #[test]
+    fn test_per_key_intervals() {
+        let start = Instant::now();
+        let fast = Duration::from_secs(10);
+        let slow = Duration::from_secs(600);
+        let keys = vec![
+            ("bg1", Priority::Low, slow),
+            ("primary", Priority::High, fast),
+            ("bg2", Priority::Low, slow),
+        ];
+        let mut scheduler = Scheduler::staggered(keys, 3, start, Stagger::default(), Duration::ZERO);
+        assert_eq!(scheduler.interval_of(&"bg2"), Some(slow));
+
+        // Each interval group is staggered on its own
+        assert_eq!(scheduler.take_due(start), vec!["primary", "bg1"]);
+        scheduler.complete(&"primary", start);
+        scheduler.complete(&"bg1", start);
+        assert_eq!(scheduler.next_wakeup(), Some(start + fast));
+        for tick in 1..30 {
+            assert_eq!(scheduler.take_due(start + fast * tick), vec!["primary"]);
+            scheduler.complete(&"primary", start + fast * tick);
+        }
+        assert_eq!(scheduler.take_due(start + fast * 30), vec!["primary", "bg2"]);
+    }
+
+    #[test]
+    fn test_boost_then_back_off() {
+        let start = Instant::now();
+        let fast = Duration::from_secs(5);
+        let keys = vec![("a", Priority::Normal), ("b", Priority::Normal)];
+        let mut scheduler =
+            Scheduler::new(keys, INTERVAL, 2, start).with_boost(fast, start, start + Duration::from_secs(20), start);
+
+        // "b" would wait 15s for its slot; boosted it goes 2.5s in
+        assert_eq!(scheduler.take_due(start), vec!["a"]);
+        assert_eq!(scheduler.next_wakeup(), Some(start + fast / 2));
+        scheduler.complete(&"a", start + Duration::from_secs(1));
+        assert_eq!(scheduler.take_due(start + fast), vec!["b", "a"]);
+        scheduler.complete(&"a", start + Duration::from_secs(6));
+        assert_eq!(scheduler.take_due(start + fast * 2), vec!["a"]);
+
+        // Past the window: back on the 30s grid
+        scheduler.complete(&"a", start + Duration::from_secs(16));
+        assert_eq!(scheduler.take_due(start + Duration::from_secs(20)), Vec::<&str>::new());
+        assert_eq!(scheduler.take_due(start + Duration::from_secs(40)), vec!["a"]);
+    }
+
+    #[test]
+    fn test_polling_config() {
+        let primary = Pubkey::new_unique().to_string();
+        let background = Pubkey::new_unique().to_string();
+        let config = PollingConfig {
+            primary: vec![primary.clone()],
+            primary_interval: Some(10),
+            background: vec![background.clone()],
+            background_interval: Some(600),
+        };
+        assert!(config.validate().is_ok());
+        assert_eq!(config.schedule_of(&primary, INTERVAL), (Duration::from_secs(10), Priority::High));
+        assert_eq!(config.schedule_of(&background, INTERVAL), (Duration::from_secs(600), Priority::Low));
+        assert_eq!(config.schedule_of("other", INTERVAL), (INTERVAL, Priority::Normal));
+
+        let missing_interval = PollingConfig { primary_interval: None, ..config.clone() };
+        assert!(missing_interval.validate().is_err());
+        let both = PollingConfig { background: vec![primary], ..config };
+        assert!(both.validate().is_err());
+    }
+
+    #[test]
+    fn test_complete_skips_missed_slots() {
+        let start = Instant::now();
+        let mut scheduler = Scheduler::new(vec![("a", Priority::Normal)], INTERVAL, 1, start);
+
+        assert_eq!(scheduler.take_due(start), vec!["a"]);
+        // Fetch took 2.5 intervals: next poll at the following slot, not three at once
+        scheduler.complete(&"a", start + INTERVAL * 5 / 2);
+        assert_eq!(scheduler.next_wakeup(), Some(start + INTERVAL * 3));
+
+        scheduler.poll_now(start + INTERVAL * 5 / 2);
+        assert_eq!(scheduler.take_due(start + INTERVAL * 5 / 2), vec!["a"]);
+        scheduler.remove(&"a");
+        assert!(scheduler.is_empty());
+        scheduler.add("b", Priority::Normal, INTERVAL, start);
+        assert_eq!(scheduler.take_due(start), vec!["b"]);
+    }
+}
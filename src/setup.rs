@@ -0,0 +1,390 @@
+//! Interactive setup (`solana-holder-bot init`): asks for the RPC provider, mints, alert channels
+//! and thresholds, checks the answers the way the bot would parse them and writes a `SOLBOT_*`
+//! environment file for `docker run --env-file`, docker-compose `env_file` or systemd `EnvironmentFile=`
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use solana_sdk::pubkey::Pubkey;
+use std::io::{BufRead, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::cli::{Cli, InitArgs};
+use crate::cluster::Cluster;
+
+/// One answer: the flag it stands for and its `SOLBOT_*` variable
+#[derive(Debug, Clone, PartialEq)]
+pub struct Setting {
+    pub flag: &'static str,
+    pub env: &'static str,
+    pub value: String,
+    /// Holds a credential (API key, bot token, webhook URL)
+    pub secret: bool,
+}
+
+/// Everything the wizard asked
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SetupAnswers {
+    pub mints: Vec<String>,
+    pub settings: Vec<Setting>,
+}
+
+impl SetupAnswers {
+    fn set(&mut self, flag: &'static str, env: &'static str, value: impl Into<String>) {
+        self.settings.push(Setting { flag, env, value: value.into(), secret: false });
+    }
+
+    fn set_secret(&mut self, flag: &'static str, env: &'static str, value: impl Into<String>) {
+        self.settings.push(Setting { flag, env, value: value.into(), secret: true });
+    }
+
+    /// Command-line equivalent of the answers
+    pub fn args(&self) -> Vec<String> {
+        let mut args = vec!["solana-holder-bot".to_string()];
+        for setting in &self.settings {
+            // `--flag=value`: a group chat ID like -1001... would otherwise read as a flag
+            if setting.value == "true" {
+                args.push(setting.flag.to_string());
+            } else {
+                args.push(format!("{}={}", setting.flag, setting.value));
+            }
+        }
+        args.extend(self.mints.iter().cloned());
+        args
+    }
+
+    /// Parse the answers like the bot does at startup
+    pub fn validate(&self) -> Result<Cli> {
+        let cli = Cli::try_parse_from(self.args()).map_err(|e| anyhow::anyhow!("{}", e.to_string().trim_end()))?;
+        cli.parse_mints()?;
+        Ok(cli)
+    }
+
+    /// `KEY=value` lines without quotes (docker `--env-file` keeps quotes as part of the value)
+    pub fn render_env_file(&self) -> String {
+        let mut out = String::from("# solana-holder-bot settings written by `solana-holder-bot init`\n");
+        out.push_str("# Every line is a command-line flag: SOLBOT_RPC_URL is --rpc-url, and so on\n");
+        out.push_str(&format!("SOLBOT_MINT_ADDRESS={}\n", self.mints.join(",")));
+        for setting in &self.settings {
+            out.push_str(&format!("{}={}\n", setting.env, setting.value));
+        }
+        out
+    }
+
+    /// The answers for the terminal, credentials hidden
+    pub fn summary(&self) -> String {
+        let mut out = format!("  SOLBOT_MINT_ADDRESS = {}\n", self.mints.join(","));
+        for setting in &self.settings {
+            let value = if setting.secret { "(hidden)" } else { setting.value.as_str() };
+            out.push_str(&format!("  {} = {}\n", setting.env, value));
+        }
+        out
+    }
+
+    /// Whether any notifier channel was set up
+    pub fn has_channels(&self) -> bool {
+        self.settings.iter().any(|setting| CHANNEL_FLAGS.contains(&setting.flag))
+    }
+}
+
+/// Flags of the notifier channels the wizard asks about
+const CHANNEL_FLAGS: [&str; 4] = ["--telegram-bot-token", "--discord-webhook", "--slack-webhook", "--webhook-url"];
+
+/// Prompts on `out`, answers from `input`
+struct Prompter<'a> {
+    input: &'a mut dyn BufRead,
+    out: &'a mut dyn Write,
+}
+
+impl Prompter<'_> {
+    /// One trimmed line; the default when it is empty
+    fn ask(&mut self, question: &str, default: &str) -> Result<String> {
+        if default.is_empty() {
+            write!(self.out, "{}: ", question)?;
+        } else {
+            write!(self.out, "{} [{}]: ", question, default)?;
+        }
+        self.out.flush()?;
+        let mut line = String::new();
+        if self.input.read_line(&mut line)? == 0 {
+            return Err(anyhow::anyhow!("Setup aborted: no more input"));
+        }
+        let answer = line.trim();
+        Ok(if answer.is_empty() { default.to_string() } else { answer.to_string() })
+    }
+
+    /// Ask until `parse` accepts the answer
+    fn ask_with<T>(&mut self, question: &str, default: &str, parse: impl Fn(&str) -> Result<T, String>) -> Result<T> {
+        loop {
+            let answer = self.ask(question, default)?;
+            match parse(&answer) {
+                Ok(value) => return Ok(value),
+                Err(e) => writeln!(self.out, "  {}", e)?,
+            }
+        }
+    }
+
+    fn confirm(&mut self, question: &str, default: bool) -> Result<bool> {
+        self.ask_with(question, if default { "y" } else { "n" }, |answer| match answer.to_lowercase().as_str() {
+            "y" | "yes" => Ok(true),
+            "n" | "no" => Ok(false),
+            _ => Err("Answer y or n".to_string()),
+        })
+    }
+}
+
+fn parse_mints(answer: &str) -> Result<Vec<String>, String> {
+    let mut mints: Vec<String> = Vec::new();
+    for mint in answer.split([',', ' ']).filter(|mint| !mint.is_empty()) {
+        Pubkey::from_str(mint).map_err(|e| format!("Invalid mint address '{}': {}", mint, e))?;
+        if !mints.iter().any(|m| m == mint) {
+            mints.push(mint.to_string());
+        }
+    }
+    if mints.is_empty() {
+        return Err("At least one mint address is required".to_string());
+    }
+    Ok(mints)
+}
+
+fn parse_url(answer: &str) -> Result<String, String> {
+    if answer.starts_with("https://") || answer.starts_with("http://") {
+        Ok(answer.to_string())
+    } else {
+        Err(format!("'{}' is not an http(s) URL", answer))
+    }
+}
+
+fn parse_url_with_prefix(answer: &str, prefixes: &[&str]) -> Result<String, String> {
+    if prefixes.iter().any(|prefix| answer.starts_with(prefix)) {
+        Ok(answer.to_string())
+    } else {
+        Err(format!("Expected a URL starting with {}", prefixes.join(" or ")))
+    }
+}
+
+/// `123456:ABC...` as issued by @BotFather
+fn parse_telegram_token(answer: &str) -> Result<String, String> {
+    match answer.split_once(':') {
+        Some((id, secret)) if !id.is_empty() && id.bytes().all(|b| b.is_ascii_digit()) && !secret.is_empty() => {
+            Ok(answer.to_string())
+        }
+        _ => Err("Expected a bot token like 123456789:AAE... from @BotFather".to_string()),
+    }
+}
+
+/// Numeric chat ID (negative for groups) or `@channel`
+fn parse_telegram_chat(answer: &str) -> Result<String, String> {
+    if answer.parse::<i64>().is_ok() || (answer.len() > 1 && answer.starts_with('@')) {
+        Ok(answer.to_string())
+    } else {
+        Err("Expected a numeric chat ID (e.g. -1001234567890) or @channel_name".to_string())
+    }
+}
+
+fn parse_optional<T: FromStr>(answer: &str, what: &str) -> Result<Option<T>, String> {
+    if answer.is_empty() || answer == "none" {
+        return Ok(None);
+    }
+    answer.parse().map(Some).map_err(|_| format!("'{}' is not a valid {}", answer, what))
+}
+
+/// RPC endpoints offered by the wizard
+const RPC_PROVIDERS: &str = "  1) Public Solana mainnet endpoint (rate-limited, fine for a try)
+  2) Helius (API key)
+  3) Other provider (QuickNode, Triton, Alchemy, own node): paste the full URL";
+
+/// Ask the questions; the answers are not validated as a whole yet
+pub fn run_wizard(input: &mut dyn BufRead, out: &mut dyn Write) -> Result<SetupAnswers> {
+    let mut p = Prompter { input, out };
+    let mut answers = SetupAnswers::default();
+
+    writeln!(p.out, "RPC provider:\n{}", RPC_PROVIDERS)?;
+    let provider = p.ask_with("Choose 1-3", "1", |answer| match answer {
+        "1" | "2" | "3" => Ok(answer.to_string()),
+        _ => Err("Choose 1, 2 or 3".to_string()),
+    })?;
+    match provider.as_str() {
+        "2" => {
+            let key = p.ask_with("Helius API key", "", |answer| {
+                if answer.is_empty() {
+                    Err("The API key is required".to_string())
+                } else {
+                    Ok(answer.to_string())
+                }
+            })?;
+            answers.set_secret("--rpc-url", "SOLBOT_RPC_URL", format!("https://mainnet.helius-rpc.com/?api-key={}", key));
+        }
+        "3" => {
+            let url = p.ask_with("RPC URL", "", parse_url)?;
+            answers.set_secret("--rpc-url", "SOLBOT_RPC_URL", url);
+        }
+        _ => answers.set("--rpc-url", "SOLBOT_RPC_URL", Cluster::Mainnet.default_rpc_url()),
+    }
+
+    answers.mints = p.ask_with("Token mint address(es), comma-separated", "", parse_mints)?;
+
+    if p.confirm("Send alerts to Telegram?", false)? {
+        let token = p.ask_with("  Bot token (from @BotFather)", "", parse_telegram_token)?;
+        let chat = p.ask_with("  Chat ID", "", parse_telegram_chat)?;
+        answers.set_secret("--telegram-bot-token", "SOLBOT_TELEGRAM_BOT_TOKEN", token);
+        answers.set("--telegram-chat-id", "SOLBOT_TELEGRAM_CHAT_ID", chat);
+    }
+    if p.confirm("Send alerts to Discord?", false)? {
+        let url = p.ask_with("  Webhook URL", "", |answer| {
+            parse_url_with_prefix(answer, &["https://discord.com/api/webhooks/", "https://discordapp.com/api/webhooks/"])
+        })?;
+        answers.set_secret("--discord-webhook", "SOLBOT_DISCORD_WEBHOOK", url);
+    }
+    if p.confirm("Send alerts to Slack?", false)? {
+        let url = p.ask_with("  Incoming webhook URL", "", |answer| {
+            parse_url_with_prefix(answer, &["https://hooks.slack.com/"])
+        })?;
+        answers.set_secret("--slack-webhook", "SOLBOT_SLACK_WEBHOOK", url);
+    }
+    if p.confirm("POST alerts as JSON to another webhook?", false)? {
+        let url = p.ask_with("  Webhook URL", "", parse_url)?;
+        answers.set_secret("--webhook-url", "SOLBOT_WEBHOOK_URL", url);
+    }
+
+    let interval = p.ask_with("Poll interval in seconds", "30", |answer| match answer.parse::<u64>() {
+        Ok(secs) if secs >= 5 => Ok(secs),
+        _ => Err("Use a whole number of seconds, at least 5".to_string()),
+    })?;
+    answers.set("--interval", "SOLBOT_INTERVAL", interval.to_string());
+
+    let step = p.ask_with("Announce every N holders (empty: no milestones)", "", |answer| {
+        match parse_optional::<usize>(answer, "holder count")? {
+            Some(0) => Err("Use a count above 0, or leave empty".to_string()),
+            step => Ok(step),
+        }
+    })?;
+    if let Some(step) = step {
+        answers.set("--milestone-step", "SOLBOT_MILESTONE_STEP", step.to_string());
+    }
+
+    if p.confirm("Alert on all-time high / low holder counts?", true)? {
+        answers.set("--ath-alerts", "SOLBOT_ATH_ALERTS", "true");
+    }
+
+    let delegation = p.ask_with(
+        "Alert when one delegate may move this % of the supply (0: off)",
+        "10",
+        |answer| match answer.parse::<f64>() {
+            Ok(percent) if (0.0..=100.0).contains(&percent) => Ok(percent),
+            _ => Err("Use a percentage between 0 and 100".to_string()),
+        },
+    )?;
+    answers.set("--delegation-alert-percent", "SOLBOT_DELEGATION_ALERT_PERCENT", delegation.to_string());
+
+    Ok(answers)
+}
+
+/// Write the env file readable by its owner only (it holds the RPC key and webhook URLs)
+fn write_env_file(path: &Path, contents: &str) -> Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(path).with_context(|| format!("Failed to create {}", path.display()))?;
+    file.write_all(contents.as_bytes())
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// `solana-holder-bot init`
+pub fn run(args: &InitArgs) -> Result<()> {
+    if args.output.exists() && !args.force {
+        return Err(anyhow::anyhow!("{} already exists (use --force to overwrite it)", args.output.display()));
+    }
+    let stdin = std::io::stdin();
+    let mut input = stdin.lock();
+    let mut out = std::io::stdout();
+    writeln!(out, "solana-holder-bot setup: press Enter to accept the [default]\n")?;
+    let answers = run_wizard(&mut input, &mut out)?;
+    answers.validate().context("The answers don't form a valid configuration")?;
+    write!(out, "\n{}", answers.summary())?;
+    write_env_file(&args.output, &answers.render_env_file())?;
+
+    let path = args.output.display();
+    writeln!(out, "\nWrote {}. Start the bot with it:", path)?;
+    writeln!(out, "  docker run --env-file {} solana-holder-bot:latest", path)?;
+    writeln!(out, "  or EnvironmentFile={} in a systemd unit / env_file in docker-compose", path)?;
+    if answers.has_channels() {
+        // `.` looks a bare file name up in $PATH
+        let source = if args.output.components().count() > 1 { path.to_string() } else { format!("./{}", path) };
+        writeln!(out, "Check the alert channels first: (set -a; . {}; solana-holder-bot test-alert)", source)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    const MINT: &str = "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263";
+
+    fn wizard(script: &str) -> (Result<SetupAnswers>, String) {
+        let mut input = Cursor::new(script.as_bytes().to_vec());
+        let mut out = Vec::new();
+        let answers = run_wizard(&mut input, &mut out);
+        (answers, String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn test_wizard_writes_valid_settings() {
+        let script = format!(
+            "2\nhelius-key\nnot-a-mint\n{mint}, {mint}\ny\n123:ABC\n-1001\nn\nn\nn\n2\n30\n5000\n\n0\n",
+            mint = MINT
+        );
+        let (answers, out) = wizard(&script);
+        let answers = answers.unwrap();
+        assert!(out.contains("Invalid mint address 'not-a-mint'"));
+        assert!(out.contains("at least 5"));
+        assert_eq!(answers.mints, vec![MINT.to_string()]);
+        assert!(answers.has_channels());
+        assert!(!answers.summary().contains("helius-key"));
+
+        let env = answers.render_env_file();
+        assert!(env.contains(&format!("SOLBOT_MINT_ADDRESS={}\n", MINT)));
+        assert!(env.contains("SOLBOT_RPC_URL=https://mainnet.helius-rpc.com/?api-key=helius-key\n"));
+        assert!(env.contains("SOLBOT_TELEGRAM_CHAT_ID=-1001\n"));
+        assert!(env.contains("SOLBOT_MILESTONE_STEP=5000\n"));
+        assert!(env.contains("SOLBOT_ATH_ALERTS=true\n"));
+        assert!(env.contains("SOLBOT_DELEGATION_ALERT_PERCENT=0\n"));
+
+        let cli = answers.validate().unwrap();
+        assert_eq!(cli.interval, 30);
+        assert_eq!(cli.milestone_step, Some(5000));
+        assert!(cli.ath_alerts);
+        assert_eq!(cli.telegram_bot_token.as_deref(), Some("123:ABC"));
+    }
+
+    #[test]
+    fn test_wizard_defaults_and_eof() {
+        let (answers, _) = wizard(&format!("\n{}\n\n\n\n\n\n\nn\n\n", MINT));
+        let cli = answers.unwrap().validate().unwrap();
+        assert_eq!(cli.rpc_url(), Cluster::Mainnet.default_rpc_url());
+        assert_eq!(cli.delegation_alert_percent, 10.0);
+        assert!(!cli.ath_alerts);
+
+        let (answers, _) = wizard("1\n");
+        assert!(answers.unwrap_err().to_string().contains("aborted"));
+
+        // `init` runs without a mint address
+        let cli = Cli::try_parse_from(["solana-holder-bot", "init"]).unwrap();
+        let Some(crate::cli::Command::Init(args)) = cli.command else { panic!("expected init") };
+        assert_eq!(args.output, Path::new("solana-holder-bot.env"));
+    }
+
+    #[test]
+    fn test_answer_parsers() {
+        assert!(parse_telegram_token("123456:AAE-x").is_ok());
+        assert!(parse_telegram_token("abc:def").is_err());
+        assert!(parse_telegram_chat("@holders").is_ok());
+        assert!(parse_telegram_chat("holders").is_err());
+        assert!(parse_url_with_prefix("https://hooks.slack.com/services/T/B/x", &["https://hooks.slack.com/"]).is_ok());
+        assert!(parse_url("ftp://example.com").is_err());
+        assert_eq!(parse_optional::<usize>("", "count"), Ok(None));
+    }
+}
@@ -0,0 +1,92 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::signal;
+use tokio::sync::Notify;
+use tracing::{error, info};
+
+/// Control flags set from OS signal handlers
+/// - Ctrl+C / SIGTERM request graceful shutdown (final metrics are still printed)
+/// - SIGHUP requests a reload: the config file is read again and every mint is polled immediately
+#[derive(Clone, Default)]
+pub struct ControlSignals {
+    shutdown: Arc<AtomicBool>,
+    reload: Arc<AtomicBool>,
+    wake: Arc<Notify>,
+}
+
+impl ControlSignals {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_shutdown(&self) -> bool {
+        self.shutdown.load(Ordering::SeqCst)
+    }
+
+    /// Returns true once per received SIGHUP
+    pub fn take_reload(&self) -> bool {
+        self.reload.swap(false, Ordering::SeqCst)
+    }
+
+    pub fn request_shutdown(&self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        self.wake.notify_one();
+    }
+
+    pub fn request_reload(&self) {
+        self.reload.store(true, Ordering::SeqCst);
+        self.wake.notify_one();
+    }
+
+    /// Resolves when shutdown or reload was requested
+    /// Uses a stored permit, so a signal received mid-cycle is not lost
+    pub async fn woken(&self) {
+        self.wake.notified().await;
+    }
+
+    /// Install Ctrl+C, SIGTERM and SIGHUP handlers
+    pub fn spawn_handlers(&self) {
+        let signals = self.clone();
+        tokio::spawn(async move {
+            match signal::ctrl_c().await {
+                Ok(()) => {
+                    info!("Received shutdown signal (Ctrl+C)");
+                    signals.request_shutdown();
+                }
+                Err(err) => {
+                    error!("Failed to listen for shutdown signal: {}", err);
+                }
+            }
+        });
+
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{signal, SignalKind};
+
+            let signals = self.clone();
+            tokio::spawn(async move {
+                match signal(SignalKind::terminate()) {
+                    Ok(mut sigterm) => {
+                        sigterm.recv().await;
+                        info!("Received shutdown signal (SIGTERM)");
+                        signals.request_shutdown();
+                    }
+                    Err(err) => error!("Failed to listen for SIGTERM: {}", err),
+                }
+            });
+
+            let signals = self.clone();
+            tokio::spawn(async move {
+                match signal(SignalKind::hangup()) {
+                    Ok(mut sighup) => {
+                        while sighup.recv().await.is_some() {
+                            info!("Received SIGHUP, reloading the config");
+                            signals.request_reload();
+                        }
+                    }
+                    Err(err) => error!("Failed to listen for SIGHUP: {}", err),
+                }
+            });
+        }
+    }
+}
@@ -0,0 +1,214 @@
+//! Integrity and provenance of exported snapshots and airdrop lists: every file gets a
+//! `<file>.sha256` checksum (`sha256sum -c` format) and, with a keypair, a `<file>.sig` Ed25519
+//! signature over the file contents that `verify-snapshot` (or any Ed25519 library) can check
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use solana_program::pubkey::Pubkey;
+use solana_sdk::signature::{read_keypair_file, Keypair, Signature, Signer};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+pub const CHECKSUM_SUFFIX: &str = ".sha256";
+pub const SIGNATURE_SUFFIX: &str = ".sig";
+
+/// Hex SHA-256 of `data`
+pub fn sha256_hex(data: &[u8]) -> String {
+    solana_program::hash::hash(data)
+        .to_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// `<hex>  <name>` line as written by `sha256sum`
+pub fn checksum_line(data: &[u8], name: &str) -> String {
+    format!("{}  {}\n", sha256_hex(data), name)
+}
+
+/// Contents of a `.sig` file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignatureFile {
+    /// Always `ed25519`
+    pub algorithm: String,
+    /// Public key of the signer (base58)
+    pub signer: String,
+    /// Hex SHA-256 of the signed file
+    pub sha256: String,
+    /// Ed25519 signature of the file contents (base58)
+    pub signature: String,
+}
+
+/// Signs snapshots with a Solana keypair file
+pub struct SnapshotSigner {
+    keypair: Keypair,
+}
+
+impl SnapshotSigner {
+    pub fn new(keypair: Keypair) -> Self {
+        Self { keypair }
+    }
+
+    /// Load a keypair file as written by `solana-keygen new`
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let keypair = read_keypair_file(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read keypair {}: {}", path.display(), e))?;
+        Ok(Self::new(keypair))
+    }
+
+    pub fn pubkey(&self) -> Pubkey {
+        self.keypair.pubkey()
+    }
+
+    pub fn sign(&self, data: &[u8]) -> SignatureFile {
+        SignatureFile {
+            algorithm: "ed25519".to_string(),
+            signer: self.pubkey().to_string(),
+            sha256: sha256_hex(data),
+            signature: self.keypair.sign_message(data).to_string(),
+        }
+    }
+}
+
+/// Check `data` against a signature file; returns the signer
+pub fn verify_signature(data: &[u8], signature: &SignatureFile) -> Result<Pubkey> {
+    if signature.algorithm != "ed25519" {
+        return Err(anyhow::anyhow!("Unsupported signature algorithm '{}'", signature.algorithm));
+    }
+    if signature.sha256 != sha256_hex(data) {
+        return Err(anyhow::anyhow!("Checksum in the signature file does not match the data"));
+    }
+    let signer = Pubkey::from_str(&signature.signer).context("Invalid signer in the signature file")?;
+    let sig = Signature::from_str(&signature.signature).context("Invalid signature in the signature file")?;
+    if !sig.verify(signer.as_ref(), data) {
+        return Err(anyhow::anyhow!("Signature does not match the data"));
+    }
+    Ok(signer)
+}
+
+/// Sidecar files of `data` named `name`: the checksum, and the signature with a signer
+pub fn sidecars(data: &[u8], name: &str, signer: Option<&SnapshotSigner>) -> Result<Vec<(&'static str, Vec<u8>)>> {
+    let mut files = vec![(CHECKSUM_SUFFIX, checksum_line(data, name).into_bytes())];
+    if let Some(signer) = signer {
+        files.push((SIGNATURE_SUFFIX, serde_json::to_vec_pretty(&signer.sign(data))?));
+    }
+    Ok(files)
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Write the sidecars of the file at `path` next to it
+pub fn write_sidecars(path: &Path, signer: Option<&SnapshotSigner>) -> Result<()> {
+    let data = std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let name = path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+    for (suffix, contents) in sidecars(&data, &name, signer)? {
+        let sidecar = with_suffix(path, suffix);
+        std::fs::write(&sidecar, contents).with_context(|| format!("Failed to write {}", sidecar.display()))?;
+    }
+    Ok(())
+}
+
+/// What `verify_file` checked
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Verification {
+    pub sha256: String,
+    pub checksum_checked: bool,
+    pub signer: Option<Pubkey>,
+}
+
+/// Check a file against its `.sha256` and `.sig` sidecars (each is optional, but at least one must
+/// exist); with `expected_signer` the signature is required and must be theirs
+pub fn verify_file(path: &Path, expected_signer: Option<&Pubkey>) -> Result<Verification> {
+    let data = std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let sha256 = sha256_hex(&data);
+
+    let checksum_path = with_suffix(path, CHECKSUM_SUFFIX);
+    let checksum_checked = checksum_path.exists();
+    if checksum_checked {
+        let line = std::fs::read_to_string(&checksum_path)
+            .with_context(|| format!("Failed to read {}", checksum_path.display()))?;
+        let expected = line.split_whitespace().next().unwrap_or_default();
+        if !expected.eq_ignore_ascii_case(&sha256) {
+            return Err(anyhow::anyhow!("{} does not match {}", path.display(), checksum_path.display()));
+        }
+    }
+
+    let signature_path = with_suffix(path, SIGNATURE_SUFFIX);
+    let signer = if signature_path.exists() {
+        let json = std::fs::read_to_string(&signature_path)
+            .with_context(|| format!("Failed to read {}", signature_path.display()))?;
+        let signature: SignatureFile = serde_json::from_str(&json)
+            .with_context(|| format!("Invalid signature file {}", signature_path.display()))?;
+        Some(verify_signature(&data, &signature).with_context(|| format!("{} failed verification", path.display()))?)
+    } else {
+        None
+    };
+
+    match (expected_signer, signer) {
+        (Some(_), None) => return Err(anyhow::anyhow!("No signature file {}", signature_path.display())),
+        (Some(expected), Some(signer)) if *expected != signer => {
+            return Err(anyhow::anyhow!("Signed by {}, expected {}", signer, expected))
+        }
+        _ => {}
+    }
+    if !checksum_checked && signer.is_none() {
+        return Err(anyhow::anyhow!("No {} or {} file next to {}", CHECKSUM_SUFFIX, SIGNATURE_SUFFIX, path.display()));
+    }
+    Ok(Verification { sha256, checksum_checked, signer })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sha256_hex() {
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            checksum_line(b"abc", "a.csv"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad  a.csv\n"
+        );
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let signer = SnapshotSigner::new(Keypair::new());
+        let data = b"owner,amount\n";
+        let signature = signer.sign(data);
+        assert_eq!(verify_signature(data, &signature).unwrap(), signer.pubkey());
+
+        assert!(verify_signature(b"owner,amount\nforged,1\n", &signature).is_err());
+        let other = SnapshotSigner::new(Keypair::new());
+        let wrong_signer = SignatureFile { signer: other.pubkey().to_string(), ..signature };
+        assert!(verify_signature(data, &wrong_signer).is_err());
+    }
+
+    #[test]
+    fn test_verify_file() {
+        let dir = std::env::temp_dir().join(format!("solbot-signing-{}", Pubkey::new_unique()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("airdrop.csv");
+        std::fs::write(&path, "wallet,amount\n").unwrap();
+        assert!(verify_file(&path, None).is_err());
+
+        let signer = SnapshotSigner::new(Keypair::new());
+        write_sidecars(&path, Some(&signer)).unwrap();
+        let checksum = std::fs::read_to_string(dir.join("airdrop.csv.sha256")).unwrap();
+        assert!(checksum.ends_with("  airdrop.csv\n"));
+        let verified = verify_file(&path, Some(&signer.pubkey())).unwrap();
+        assert!(verified.checksum_checked);
+        assert_eq!(verified.signer, Some(signer.pubkey()));
+        assert!(verify_file(&path, Some(&Pubkey::new_unique())).is_err());
+
+        std::fs::write(&path, "wallet,amount\nforged,1\n").unwrap();
+        assert!(verify_file(&path, None).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
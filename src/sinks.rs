@@ -0,0 +1,485 @@
+//! Event sinks: destinations subscribed to the event bus
+//!
+//! Built-in consumers (console, API cache, notifiers, brokers) implement [`Sink`]; custom ones
+//! are registered in code with [`SinkRegistry::register`] or, without touching the crate, as
+//! external-process plugins from `[sinks.<name>]` config entries
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::pin::Pin;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::AsyncWriteExt;
+use tokio::process::{Child, ChildStdin, Command};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::events::{EventBus, HolderEvent};
+use crate::publisher::PublishedEvent;
+
+/// Future returned by [`Sink`] methods
+pub type SinkFuture<'a> = Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>>;
+
+/// Builds a sink of one `type` from its `[sinks.<name>]` options
+pub type SinkFactory = fn(name: &str, options: &toml::Table) -> Result<Box<dyn Sink>>;
+
+/// Exit grace period of a plugin process after its stdin is closed
+const PLUGIN_EXIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Wait before restarting a failed plugin, doubled per failure in a row
+const RESTART_MIN: Duration = Duration::from_secs(5);
+const RESTART_MAX: Duration = Duration::from_secs(60);
+
+/// Destination of monitor events; each registered sink runs on its own bus subscription,
+/// so a slow sink never delays polls or other sinks
+pub trait Sink: Send + Sync {
+    /// Shown in logs
+    fn name(&self) -> &str;
+
+    /// Handle one event; errors are logged and the sink keeps receiving events
+    fn handle<'a>(&'a self, event: &'a HolderEvent) -> SinkFuture<'a>;
+
+    /// Called once after the last event, on shutdown
+    fn close(&self) -> SinkFuture<'_> {
+        Box::pin(async { Ok(()) })
+    }
+}
+
+impl<T: Sink + ?Sized> Sink for Arc<T> {
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+
+    fn handle<'a>(&'a self, event: &'a HolderEvent) -> SinkFuture<'a> {
+        (**self).handle(event)
+    }
+
+    fn close(&self) -> SinkFuture<'_> {
+        (**self).close()
+    }
+}
+
+/// `[sinks.<name>]` config section
+///
+/// ```toml
+/// [sinks.clickhouse]
+/// type = "exec"
+/// command = ["/usr/local/bin/holders-to-clickhouse", "--table", "holders"]
+/// events = ["observation"]
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct SinkConfig {
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// Event types passed to the sink (`observation`, `alert`, `error`, `wallet_entered`,
+    /// `wallet_exited`, `wallet_tier_changed`); all when unset
+    #[serde(default)]
+    pub events: Option<Vec<String>>,
+    /// Options of the sink type
+    #[serde(flatten)]
+    pub options: toml::Table,
+}
+
+struct Registered {
+    sink: Box<dyn Sink>,
+    events: Option<HashSet<String>>,
+}
+
+/// Sinks to start plus the factories that turn config entries into sinks
+pub struct SinkRegistry {
+    factories: HashMap<String, SinkFactory>,
+    sinks: Vec<Registered>,
+}
+
+impl Default for SinkRegistry {
+    fn default() -> Self {
+        Self {
+            factories: HashMap::from([("exec".to_string(), ExecSink::from_options as SinkFactory)]),
+            sinks: Vec::new(),
+        }
+    }
+}
+
+impl SinkRegistry {
+    /// Make `type = "<kind>"` usable in `[sinks.*]` entries
+    pub fn with_factory(mut self, kind: &str, factory: SinkFactory) -> Self {
+        self.factories.insert(kind.to_string(), factory);
+        self
+    }
+
+    /// Add a sink receiving every event
+    pub fn register(&mut self, sink: impl Sink + 'static) {
+        self.sinks.push(Registered {
+            sink: Box::new(sink),
+            events: None,
+        });
+    }
+
+    /// Add the sinks of `[sinks.*]` config entries, in name order
+    pub fn register_configured(&mut self, configured: &HashMap<String, SinkConfig>) -> Result<()> {
+        let mut names: Vec<&String> = configured.keys().collect();
+        names.sort();
+        for name in names {
+            let config = &configured[name];
+            let factory = self.factories.get(&config.kind).ok_or_else(|| {
+                let mut known: Vec<&str> = self.factories.keys().map(String::as_str).collect();
+                known.sort();
+                anyhow::anyhow!("Sink '{}': unknown type '{}' (known: {})", name, config.kind, known.join(", "))
+            })?;
+            if let Some(events) = &config.events {
+                if let Some(unknown) = events.iter().find(|kind| !HolderEvent::KINDS.contains(&kind.as_str())) {
+                    return Err(anyhow::anyhow!(
+                        "Sink '{}': unknown event type '{}' (known: {})",
+                        name,
+                        unknown,
+                        HolderEvent::KINDS.join(", ")
+                    ));
+                }
+            }
+            let sink = factory(name, &config.options).with_context(|| format!("Sink '{}'", name))?;
+            info!("Registered {} sink '{}'", config.kind, name);
+            self.sinks.push(Registered {
+                sink,
+                events: config.events.as_ref().map(|events| events.iter().cloned().collect()),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.sinks.iter().map(|registered| registered.sink.name()).collect()
+    }
+
+    /// Subscribe every sink to `bus`; each task ends (closing its sink) when the bus is closed
+    pub fn spawn(self, bus: &EventBus) -> Vec<JoinHandle<()>> {
+        self.sinks
+            .into_iter()
+            .map(|Registered { sink, events }| {
+                let mut subscription = bus.subscribe(sink.name());
+                tokio::spawn(async move {
+                    while let Some(event) = subscription.next().await {
+                        if events.as_ref().is_some_and(|events| !events.contains(event.kind())) {
+                            continue;
+                        }
+                        if let Err(e) = sink.handle(&event).await {
+                            warn!("Sink '{}' failed to handle {} event: {:#}", sink.name(), event.kind(), e);
+                        }
+                    }
+                    if let Err(e) = sink.close().await {
+                        warn!("Failed to close sink '{}': {:#}", sink.name(), e);
+                    }
+                })
+            })
+            .collect()
+    }
+}
+
+/// External-process plugin: a long-running command that reads one JSON event per line on stdin
+/// (the same JSON as the message broker events). The command is started on the first event and,
+/// if it exits, restarted with the first event after a backoff (5s, doubling up to 60s; events in
+/// between are skipped); stdout is discarded, stderr goes to the bot's stderr
+pub struct ExecSink {
+    name: String,
+    command: Vec<String>,
+    plugin: Mutex<PluginProcess>,
+}
+
+/// Running plugin and the restart backoff after failures
+#[derive(Default)]
+struct PluginProcess {
+    /// Process, its stdin and when it was started
+    running: Option<(Child, ChildStdin, Instant)>,
+    /// Last failure and the wait before the next start
+    failed: Option<(Instant, Duration)>,
+}
+
+impl PluginProcess {
+    fn in_backoff(&self, now: Instant) -> bool {
+        self.failed.is_some_and(|(at, backoff)| now.duration_since(at) < backoff)
+    }
+
+    /// Record a failure of a plugin that ran for `ran_for` and return the wait before the next
+    /// start; a plugin that ran longer than the longest backoff starts over at the shortest one
+    fn record_failure(&mut self, ran_for: Duration, now: Instant) -> Duration {
+        let backoff = match self.failed {
+            Some((_, previous)) if ran_for < RESTART_MAX => (previous * 2).min(RESTART_MAX),
+            _ => RESTART_MIN,
+        };
+        self.failed = Some((now, backoff));
+        backoff
+    }
+}
+
+impl ExecSink {
+    pub fn new(name: &str, command: Vec<String>) -> Result<Self> {
+        if command.first().is_none_or(|program| program.trim().is_empty()) {
+            return Err(anyhow::anyhow!("command must not be empty"));
+        }
+        Ok(Self {
+            name: name.to_string(),
+            command,
+            plugin: Mutex::new(PluginProcess::default()),
+        })
+    }
+
+    /// Options: `command = ["program", "arg", ...]`
+    pub fn from_options(name: &str, options: &toml::Table) -> Result<Box<dyn Sink>> {
+        if let Some(unknown) = options.keys().find(|key| key.as_str() != "command") {
+            return Err(anyhow::anyhow!("unknown option '{}' for exec sink", unknown));
+        }
+        let command = options
+            .get("command")
+            .and_then(|command| command.as_array())
+            .ok_or_else(|| anyhow::anyhow!("exec sink needs command = [\"program\", \"arg\", ...]"))?
+            .iter()
+            .map(|arg| arg.as_str().map(str::to_string))
+            .collect::<Option<Vec<String>>>()
+            .ok_or_else(|| anyhow::anyhow!("command arguments must be strings"))?;
+        Ok(Box::new(Self::new(name, command)?))
+    }
+
+    fn start(&self) -> Result<(Child, ChildStdin)> {
+        let mut child = Command::new(&self.command[0])
+            .args(&self.command[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Failed to start plugin '{}'", self.command[0]))?;
+        let stdin = child.stdin.take().context("Plugin stdin is not piped")?;
+        info!("Started plugin for sink '{}' (pid {})", self.name, child.id().unwrap_or_default());
+        Ok((child, stdin))
+    }
+}
+
+impl Sink for ExecSink {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn handle<'a>(&'a self, event: &'a HolderEvent) -> SinkFuture<'a> {
+        Box::pin(async move {
+            let mut line = serde_json::to_vec(&PublishedEvent::from(event))?;
+            line.push(b'\n');
+
+            let mut plugin = self.plugin.lock().await;
+            if plugin.running.is_none() {
+                // The failure was reported when the backoff started
+                if plugin.in_backoff(Instant::now()) {
+                    return Ok(());
+                }
+                match self.start() {
+                    Ok((child, stdin)) => plugin.running = Some((child, stdin, Instant::now())),
+                    Err(e) => {
+                        let backoff = plugin.record_failure(Duration::ZERO, Instant::now());
+                        return Err(e.context(format!(
+                            "Retrying in {}s, events until then are skipped",
+                            backoff.as_secs()
+                        )));
+                    }
+                }
+            }
+            let (_, stdin, _) = plugin.running.as_mut().expect("plugin process started above");
+            if let Err(e) = stdin.write_all(&line).await {
+                let (_, _, started_at) = plugin.running.take().expect("plugin process is running");
+                let backoff = plugin.record_failure(started_at.elapsed(), Instant::now());
+                return Err(e).context(format!(
+                    "Plugin exited; restarting in {}s, events until then are skipped",
+                    backoff.as_secs()
+                ));
+            }
+            Ok(())
+        })
+    }
+
+    fn close(&self) -> SinkFuture<'_> {
+        Box::pin(async move {
+            let Some((mut child, stdin, _)) = self.plugin.lock().await.running.take() else {
+                return Ok(());
+            };
+            // EOF on stdin tells the plugin to finish
+            drop(stdin);
+            match tokio::time::timeout(PLUGIN_EXIT_TIMEOUT, child.wait()).await {
+                Ok(status) => {
+                    let status = status?;
+                    if !status.success() {
+                        warn!("Plugin of sink '{}' exited with {}", self.name, status);
+                    }
+                }
+                Err(_) => {
+                    warn!("Plugin of sink '{}' didn't exit in time, killing it", self.name);
+                    child.kill().await?;
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::pubkey::Pubkey;
+    use std::sync::Mutex as StdMutex;
+
+    /// Records the timestamps of the events it receives
+    #[derive(Default)]
+    struct Recorder {
+        seen: StdMutex<Vec<u64>>,
+        closed: StdMutex<bool>,
+    }
+
+    impl Sink for Recorder {
+        fn name(&self) -> &str {
+            "recorder"
+        }
+
+        fn handle<'a>(&'a self, event: &'a HolderEvent) -> SinkFuture<'a> {
+            if let HolderEvent::Error { timestamp, .. } = event {
+                self.seen.lock().unwrap().push(*timestamp);
+            }
+            Box::pin(async { Ok(()) })
+        }
+
+        fn close(&self) -> SinkFuture<'_> {
+            *self.closed.lock().unwrap() = true;
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    fn error(timestamp: u64) -> HolderEvent {
+        HolderEvent::Error {
+            mint: Pubkey::new_unique(),
+            message: "timeout".to_string(),
+            timestamp,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_registered_sink_receives_events() {
+        let recorder = Arc::new(Recorder::default());
+        let mut registry = SinkRegistry::default();
+        registry.register(recorder.clone());
+        assert_eq!(registry.names(), vec!["recorder"]);
+
+        let bus = EventBus::default();
+        let handles = registry.spawn(&bus);
+        bus.publish(error(1));
+        bus.publish(error(2));
+        bus.close();
+        for handle in handles {
+            handle.await.unwrap();
+        }
+        assert_eq!(*recorder.seen.lock().unwrap(), vec![1, 2]);
+        assert!(*recorder.closed.lock().unwrap());
+    }
+
+    #[test]
+    fn test_configured_sinks() {
+        let configured: HashMap<String, SinkConfig> = toml::from_str(
+            r#"
+            [plugin]
+            type = "exec"
+            command = ["cat"]
+            events = ["alert", "error"]
+            "#,
+        )
+        .unwrap();
+        let mut registry = SinkRegistry::default();
+        registry.register_configured(&configured).unwrap();
+        assert_eq!(registry.names(), vec!["plugin"]);
+
+        let invalid = [
+            r#"[x]
+            type = "carrier-pigeon""#,
+            r#"[x]
+            type = "exec"
+            command = []"#,
+            r#"[x]
+            type = "exec"
+            command = ["cat"]
+            events = ["trade"]"#,
+            r#"[x]
+            type = "exec"
+            command = ["cat"]
+            shell = true"#,
+        ];
+        for config in invalid {
+            let configured: HashMap<String, SinkConfig> = toml::from_str(config).unwrap();
+            assert!(SinkRegistry::default().register_configured(&configured).is_err(), "{}", config);
+        }
+
+        // Custom types come from factories registered in code
+        let configured: HashMap<String, SinkConfig> = toml::from_str("[x]\ntype = \"recorder\"").unwrap();
+        let factory: SinkFactory = |_, _| Ok(Box::new(Recorder::default()));
+        let mut registry = SinkRegistry::default().with_factory("recorder", factory);
+        registry.register_configured(&configured).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_exec_sink_writes_json_lines() {
+        let dir = std::env::temp_dir().join(format!("solbot-exec-sink-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let output = dir.join("events.jsonl");
+        let script = format!("cat > {}", output.display());
+        let sink = ExecSink::new("plugin", vec!["sh".to_string(), "-c".to_string(), script]).unwrap();
+
+        sink.handle(&error(7)).await.unwrap();
+        sink.handle(&error(8)).await.unwrap();
+        sink.close().await.unwrap();
+
+        let written = std::fs::read_to_string(&output).unwrap();
+        let lines: Vec<serde_json::Value> = written.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["type"], "error");
+        assert_eq!(lines[1]["timestamp"], 8);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_exec_sink_backs_off_after_exit() {
+        let dir = std::env::temp_dir().join(format!("solbot-exec-backoff-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let starts = dir.join("starts");
+        let script = format!("echo started >> {}", starts.display());
+        let sink = ExecSink::new("plugin", vec!["sh".to_string(), "-c".to_string(), script]).unwrap();
+
+        // The plugin exits at once: a write fails within a few events
+        let mut failed = false;
+        for timestamp in 0..50 {
+            if sink.handle(&error(timestamp)).await.is_err() {
+                failed = true;
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(failed);
+
+        // Later events are skipped instead of respawning the plugin for each of them
+        for timestamp in 50..60 {
+            sink.handle(&error(timestamp)).await.unwrap();
+        }
+        assert_eq!(std::fs::read_to_string(&starts).unwrap().lines().count(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_plugin_restart_backoff() {
+        let now = Instant::now();
+        let mut plugin = PluginProcess::default();
+        assert!(!plugin.in_backoff(now));
+        let backoffs: Vec<u64> = (0..6)
+            .map(|_| plugin.record_failure(Duration::from_secs(1), now).as_secs())
+            .collect();
+        assert_eq!(backoffs, vec![5, 10, 20, 40, 60, 60]);
+        assert!(plugin.in_backoff(now + Duration::from_secs(59)));
+        assert!(!plugin.in_backoff(now + RESTART_MAX));
+        // Ran for a while before failing: back to the shortest wait
+        assert_eq!(plugin.record_failure(RESTART_MAX, now), RESTART_MIN);
+    }
+}
@@ -0,0 +1,423 @@
+//! Scheduled holder snapshots to S3-compatible object storage (`--s3-bucket`, `s3` feature):
+//! every `--snapshot-interval-hours` the full balance list of each monitored mint is uploaded
+//! as gzipped CSV (the export format) under `<prefix><mint>/<timestamp>.csv.gz`, and snapshots
+//! older than `--snapshot-retention-days` are deleted. With `--snapshot-webhook-url` each
+//! finished (or failed) snapshot is POSTed there, so ETL jobs don't have to poll the bucket.
+//! Each snapshot is uploaded with a `.sha256` checksum and, with `--snapshot-signing-keypair`,
+//! a `.sig` Ed25519 signature (see `signing`)
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDateTime};
+use serde::Serialize;
+use solana_program::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+use crate::cli::Cli;
+use crate::export::{sorted_balances, ExportChunks, ExportFormat};
+use crate::extensions::MintInfo;
+use crate::rpc_client::SolanaRpcClient;
+use crate::signing::{sha256_hex, sidecars, SnapshotSigner, CHECKSUM_SUFFIX, SIGNATURE_SUFFIX};
+use crate::token_monitor::extract_balances;
+
+/// Timestamp part of snapshot keys (UTC, sorts chronologically)
+const KEY_TIME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+const KEY_SUFFIX: &str = ".csv.gz";
+
+/// Where and how often snapshots are stored
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotSettings {
+    pub bucket: String,
+    /// Custom endpoint of an S3-compatible store (MinIO, R2, ...); AWS when unset
+    pub endpoint: Option<String>,
+    pub region: String,
+    /// Key prefix, e.g. `holder-snapshots/`
+    pub prefix: String,
+    pub interval: Duration,
+    /// Snapshots older than this are deleted (None keeps everything)
+    pub retention_secs: Option<u64>,
+    /// Receives a `SnapshotEvent` after every snapshot
+    pub webhook_url: Option<String>,
+    /// Keypair file signing every snapshot
+    pub signing_keypair: Option<PathBuf>,
+}
+
+impl SnapshotSettings {
+    /// Settings from the CLI; None unless `--s3-bucket` is given
+    pub fn from_cli(cli: &Cli) -> Option<Self> {
+        let bucket = cli.s3_bucket.clone()?;
+        let mut prefix = cli.s3_prefix.clone();
+        if !prefix.is_empty() && !prefix.ends_with('/') {
+            prefix.push('/');
+        }
+        Some(Self {
+            bucket,
+            endpoint: cli.s3_endpoint.clone(),
+            region: cli.s3_region.clone(),
+            prefix,
+            interval: Duration::from_secs(cli.snapshot_interval_hours.max(1) * 3600),
+            retention_secs: (cli.snapshot_retention_days > 0).then(|| cli.snapshot_retention_days * 86_400),
+            webhook_url: cli.snapshot_webhook_url.clone(),
+            signing_keypair: cli.snapshot_signing_keypair.clone(),
+        })
+    }
+
+    /// Key of the snapshot of `mint` taken at `timestamp`
+    pub fn key_for(&self, mint: &Pubkey, timestamp: u64) -> String {
+        let time = DateTime::from_timestamp(timestamp as i64, 0).unwrap_or_default();
+        format!("{}{}/{}{}", self.prefix, mint, time.format(KEY_TIME_FORMAT), KEY_SUFFIX)
+    }
+
+    /// Prefix listing every snapshot of `mint`
+    pub fn mint_prefix(&self, mint: &Pubkey) -> String {
+        format!("{}{}/", self.prefix, mint)
+    }
+
+    /// Snapshot keys past the retention period (keys that aren't snapshots are left alone)
+    pub fn expired<'a>(&self, keys: &'a [String], now: u64) -> Vec<&'a String> {
+        let Some(retention) = self.retention_secs else {
+            return Vec::new();
+        };
+        keys.iter()
+            .filter(|key| key_timestamp(key).is_some_and(|taken| taken + retention < now))
+            .collect()
+    }
+}
+
+/// When a snapshot was taken, from its key (or the key of its checksum / signature)
+pub fn key_timestamp(key: &str) -> Option<u64> {
+    let name = key.rsplit('/').next()?;
+    let name = name
+        .strip_suffix(CHECKSUM_SUFFIX)
+        .or_else(|| name.strip_suffix(SIGNATURE_SUFFIX))
+        .unwrap_or(name)
+        .strip_suffix(KEY_SUFFIX)?;
+    let time = NaiveDateTime::parse_from_str(name, KEY_TIME_FORMAT).ok()?;
+    u64::try_from(time.and_utc().timestamp()).ok()
+}
+
+/// Body of the snapshot webhook
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapshotEvent {
+    /// Always `snapshot`
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub mint: String,
+    pub ok: bool,
+    pub taken_at: u64,
+    /// `s3://<bucket>/<key>`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub holders: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size_bytes: Option<usize>,
+    /// Hex SHA-256 of the uploaded (gzipped) object
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    /// Public key that signed the snapshot (`<location>.sig`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signer: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl SnapshotEvent {
+    fn failed(mint: &Pubkey, taken_at: u64, error: String) -> Self {
+        Self {
+            kind: "snapshot",
+            mint: mint.to_string(),
+            ok: false,
+            taken_at,
+            location: None,
+            holders: None,
+            size_bytes: None,
+            sha256: None,
+            signer: None,
+            error: Some(error),
+        }
+    }
+}
+
+/// Gzipped `owner,amount` CSV of holder balances, largest first, with a `ui_amount` column at
+/// `now` when the mint is known
+pub fn encode_snapshot(balances: HashMap<Pubkey, u64>, mint: Option<MintInfo>, now: u64) -> std::io::Result<Vec<u8>> {
+    let mut chunks = ExportChunks::new(sorted_balances(balances), ExportFormat::Csv, true);
+    if let Some(mint) = mint {
+        chunks = chunks.with_ui_amounts(mint, now as i64);
+    }
+    Ok(chunks.collect::<std::io::Result<Vec<_>>>()?.concat())
+}
+
+/// Take and upload one snapshot per mint, then apply the retention policy
+async fn snapshot_all(
+    store: &S3Store,
+    settings: &SnapshotSettings,
+    rpc_client: &SolanaRpcClient,
+    http_client: &reqwest::Client,
+    signer: Option<&SnapshotSigner>,
+    mints: &[Pubkey],
+) {
+    for mint in mints {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let event = match snapshot_mint(store, settings, rpc_client, signer, mint, now).await {
+            Ok(event) => {
+                info!(
+                    "Uploaded holder snapshot of {} ({} holders) to {}",
+                    mint,
+                    event.holders.unwrap_or_default(),
+                    event.location.as_deref().unwrap_or_default()
+                );
+                event
+            }
+            Err(e) => {
+                error!("Holder snapshot of {} failed: {:#}", mint, e);
+                SnapshotEvent::failed(mint, now, format!("{:#}", e))
+            }
+        };
+        if let Some(url) = &settings.webhook_url {
+            let sent = http_client
+                .post(url)
+                .timeout(Duration::from_secs(10))
+                .json(&event)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status());
+            if let Err(e) = sent {
+                warn!("Failed to deliver snapshot webhook for {}: {}", mint, e);
+            }
+        }
+        if settings.retention_secs.is_some() {
+            if let Err(e) = apply_retention(store, settings, mint, now).await {
+                warn!("Snapshot retention for {} failed: {:#}", mint, e);
+            }
+        }
+    }
+}
+
+async fn snapshot_mint(
+    store: &S3Store,
+    settings: &SnapshotSettings,
+    rpc_client: &SolanaRpcClient,
+    signer: Option<&SnapshotSigner>,
+    mint: &Pubkey,
+    now: u64,
+) -> Result<SnapshotEvent> {
+    let accounts = rpc_client.get_token_accounts_by_mint(mint).await?;
+    let balances = extract_balances(&accounts);
+    let holders = balances.len();
+    let mint_info = match rpc_client.get_mint_info(mint).await {
+        Ok(info) => Some(info),
+        Err(e) => {
+            warn!("Snapshot of {} without UI amounts: {:#}", mint, e);
+            None
+        }
+    };
+    let body = encode_snapshot(balances, mint_info, now).context("Failed to encode snapshot")?;
+    let (size_bytes, sha256) = (body.len(), sha256_hex(&body));
+    let key = settings.key_for(mint, now);
+    let name = key.rsplit('/').next().unwrap_or_default();
+    let sidecars = sidecars(&body, name, signer)?;
+    store.put(&key, body, "application/gzip").await?;
+    // Sidecars after the snapshot, so a consumer seeing them can fetch it
+    for (suffix, contents) in sidecars {
+        store.put(&format!("{}{}", key, suffix), contents, "text/plain; charset=utf-8").await?;
+    }
+    Ok(SnapshotEvent {
+        kind: "snapshot",
+        mint: mint.to_string(),
+        ok: true,
+        taken_at: now,
+        location: Some(format!("s3://{}/{}", settings.bucket, key)),
+        holders: Some(holders),
+        size_bytes: Some(size_bytes),
+        sha256: Some(sha256),
+        signer: signer.map(|signer| signer.pubkey().to_string()),
+        error: None,
+    })
+}
+
+async fn apply_retention(store: &S3Store, settings: &SnapshotSettings, mint: &Pubkey, now: u64) -> Result<()> {
+    let keys = store.list(&settings.mint_prefix(mint)).await?;
+    for key in settings.expired(&keys, now) {
+        store.delete(key).await?;
+        info!("Deleted expired holder snapshot {}", key);
+    }
+    Ok(())
+}
+
+/// Start the snapshot schedule for `mints`; the first snapshot is taken right away
+pub fn spawn_snapshots(settings: SnapshotSettings, rpc_client: Arc<SolanaRpcClient>, mints: Vec<Pubkey>) -> Result<()> {
+    let store = S3Store::new(&settings)?;
+    let signer = settings
+        .signing_keypair
+        .as_deref()
+        .map(SnapshotSigner::from_file)
+        .transpose()?;
+    info!(
+        "Holder snapshots every {}h to s3://{}/{}",
+        settings.interval.as_secs() / 3600,
+        settings.bucket,
+        settings.prefix
+    );
+    tokio::spawn(async move {
+        let http_client = reqwest::Client::new();
+        let mut ticks = tokio::time::interval(settings.interval);
+        ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            ticks.tick().await;
+            snapshot_all(&store, &settings, &rpc_client, &http_client, signer.as_ref(), &mints).await;
+        }
+    });
+    Ok(())
+}
+
+/// Bucket client; credentials come from `AWS_ACCESS_KEY_ID` / `AWS_SECRET_ACCESS_KEY` (or the
+/// other sources of the AWS credential chain)
+#[cfg(feature = "s3")]
+struct S3Store {
+    bucket: Box<s3::Bucket>,
+}
+
+#[cfg(feature = "s3")]
+impl S3Store {
+    fn new(settings: &SnapshotSettings) -> Result<Self> {
+        let region = match &settings.endpoint {
+            Some(endpoint) => s3::Region::Custom {
+                region: settings.region.clone(),
+                endpoint: endpoint.clone(),
+            },
+            None => settings.region.parse().context("Invalid --s3-region")?,
+        };
+        let credentials = s3::creds::Credentials::default().context("No S3 credentials (AWS_ACCESS_KEY_ID / AWS_SECRET_ACCESS_KEY)")?;
+        let mut bucket = s3::Bucket::new(&settings.bucket, region, credentials).context("Invalid S3 bucket settings")?;
+        // Custom endpoints rarely support virtual-hosted buckets
+        if settings.endpoint.is_some() {
+            bucket = bucket.with_path_style();
+        }
+        Ok(Self { bucket })
+    }
+
+    async fn put(&self, key: &str, body: Vec<u8>, content_type: &str) -> Result<()> {
+        let response = self
+            .bucket
+            .put_object_with_content_type(key, &body, content_type)
+            .await
+            .with_context(|| format!("Failed to upload {}", key))?;
+        if !(200..300).contains(&response.status_code()) {
+            return Err(anyhow::anyhow!("Upload of {} failed with status {}", key, response.status_code()));
+        }
+        Ok(())
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<String>> {
+        let pages = self
+            .bucket
+            .list(prefix.to_string(), None)
+            .await
+            .with_context(|| format!("Failed to list {}", prefix))?;
+        Ok(pages
+            .into_iter()
+            .flat_map(|page| page.contents)
+            .map(|object| object.key)
+            .collect())
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        self.bucket
+            .delete_object(key)
+            .await
+            .with_context(|| format!("Failed to delete {}", key))?;
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "s3"))]
+struct S3Store;
+
+#[cfg(not(feature = "s3"))]
+impl S3Store {
+    fn new(_settings: &SnapshotSettings) -> Result<Self> {
+        Err(anyhow::anyhow!("--s3-bucket requires building with `--features s3`"))
+    }
+
+    async fn put(&self, _key: &str, _body: Vec<u8>, _content_type: &str) -> Result<()> {
+        unreachable!("S3Store is never built without the s3 feature")
+    }
+
+    async fn list(&self, _prefix: &str) -> Result<Vec<String>> {
+        unreachable!("S3Store is never built without the s3 feature")
+    }
+
+    async fn delete(&self, _key: &str) -> Result<()> {
+        unreachable!("S3Store is never built without the s3 feature")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    fn settings(retention_days: Option<u64>) -> SnapshotSettings {
+        SnapshotSettings {
+            bucket: "audit".to_string(),
+            endpoint: None,
+            region: "us-east-1".to_string(),
+            prefix: "holder-snapshots/".to_string(),
+            interval: Duration::from_secs(6 * 3600),
+            retention_secs: retention_days.map(|days| days * 86_400),
+            webhook_url: None,
+            signing_keypair: None,
+        }
+    }
+
+    #[test]
+    fn test_keys_and_retention() {
+        let mint = Pubkey::new_unique();
+        let settings = settings(Some(30));
+        let key = settings.key_for(&mint, 1_702_320_000);
+        assert_eq!(key, format!("holder-snapshots/{}/20231211T184000Z.csv.gz", mint));
+        assert_eq!(key_timestamp(&key), Some(1_702_320_000));
+        assert!(key.starts_with(&settings.mint_prefix(&mint)));
+
+        let now = 1_702_320_000 + 31 * 86_400;
+        let recent = settings.key_for(&mint, now - 86_400);
+        let checksum = format!("{}.sha256", key);
+        let keys = vec![key.clone(), checksum.clone(), recent, format!("{}notes.txt", settings.mint_prefix(&mint))];
+        assert_eq!(settings.expired(&keys, now), vec![&key, &checksum]);
+        assert!(self::settings(None).expired(&keys, now).is_empty());
+    }
+
+    #[test]
+    fn test_failed_event() {
+        let event = SnapshotEvent::failed(&Pubkey::new_unique(), 1, "RPC down".to_string());
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["type"], "snapshot");
+        assert_eq!(json["ok"], false);
+        assert!(json.get("sha256").is_none());
+    }
+
+    #[test]
+    fn test_encode_snapshot() {
+        let (whale, shrimp) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let balances: HashMap<Pubkey, u64> = [(whale, 100), (shrimp, 1)].into_iter().collect();
+        let body = encode_snapshot(balances.clone(), None, 0).unwrap();
+        let mut csv = String::new();
+        GzDecoder::new(&body[..]).read_to_string(&mut csv).unwrap();
+        assert_eq!(csv, format!("owner,amount\n{},100\n{},1\n", whale, shrimp));
+
+        let mint = MintInfo { decimals: 2, transfer_fee: None, interest: None };
+        let body = encode_snapshot(balances, Some(mint), 0).unwrap();
+        let mut csv = String::new();
+        GzDecoder::new(&body[..]).read_to_string(&mut csv).unwrap();
+        assert_eq!(csv, format!("owner,amount,ui_amount\n{},100,1\n{},1,0.01\n", whale, shrimp));
+    }
+}
@@ -0,0 +1,181 @@
+//! Cross-token overview for `GET /stats/summary`: unique wallets over all tracked tokens, the
+//! token that grew most in 24h and an hourly sparkline per token
+
+use serde::Serialize;
+use solana_program::pubkey::Pubkey;
+use std::collections::{HashSet, VecDeque};
+
+/// Span covered by growth and sparklines
+pub const SUMMARY_WINDOW_SECS: u64 = 24 * 3600;
+
+/// One sparkline point per hour
+const POINT_SECS: u64 = 3600;
+
+/// Last holder count of each hour over the past day, kept in memory per tracked mint
+#[derive(Debug, Clone, Default)]
+pub struct RecentCounts {
+    points: VecDeque<(u64, usize)>,
+}
+
+impl RecentCounts {
+    pub fn record(&mut self, now: u64, holders: usize) {
+        match self.points.back_mut() {
+            Some(last) if last.0 / POINT_SECS == now / POINT_SECS => *last = (now, holders),
+            _ => self.points.push_back((now, holders)),
+        }
+        // One extra point, so the oldest one is at least a day old once there is a day of data
+        while self.points.len() > 2 && self.points[1].0 + SUMMARY_WINDOW_SECS <= now {
+            self.points.pop_front();
+        }
+    }
+
+    /// Hourly counts, oldest first
+    pub fn sparkline(&self) -> Vec<usize> {
+        self.points.iter().map(|(_, holders)| *holders).collect()
+    }
+
+    /// Change from the oldest point of the window to the latest (None with fewer than two points)
+    pub fn growth(&self) -> Option<i64> {
+        let (first, last) = (self.points.front()?, self.points.back()?);
+        (self.points.len() > 1).then(|| last.1 as i64 - first.1 as i64)
+    }
+
+    /// Seconds between the oldest and latest point
+    pub fn span_secs(&self) -> u64 {
+        match (self.points.front(), self.points.back()) {
+            (Some(first), Some(last)) => last.0 - first.0,
+            _ => 0,
+        }
+    }
+
+    pub fn memory_bytes(&self) -> usize {
+        self.points.capacity() * std::mem::size_of::<(u64, usize)>()
+    }
+}
+
+/// One token of the overview
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenSummary {
+    pub mint: String,
+    pub holders: usize,
+    pub last_updated: u64,
+    /// Change over the last 24h (or since the token entered the cache, see `growth_span_secs`)
+    pub growth_24h: Option<i64>,
+    pub growth_24h_percent: Option<f64>,
+    pub growth_span_secs: u64,
+    /// Last count of each hour, oldest first
+    pub sparkline: Vec<usize>,
+}
+
+impl TokenSummary {
+    pub fn new(mint: String, holders: usize, last_updated: u64, recent: Option<&RecentCounts>) -> Self {
+        let growth = recent.and_then(RecentCounts::growth);
+        let start = growth.map(|growth| holders as i64 - growth);
+        Self {
+            mint,
+            holders,
+            last_updated,
+            growth_24h: growth,
+            growth_24h_percent: growth
+                .zip(start)
+                .filter(|(_, start)| *start > 0)
+                .map(|(growth, start)| growth as f64 / start as f64 * 100.0),
+            growth_span_secs: recent.map_or(0, RecentCounts::span_secs),
+            sparkline: recent.map(RecentCounts::sparkline).unwrap_or_default(),
+        }
+    }
+}
+
+/// Overview of all tracked tokens
+#[derive(Debug, Clone, Serialize)]
+pub struct StatsSummary {
+    pub tokens: usize,
+    /// Sum of the holder counts (wallets holding several tokens counted once per token)
+    pub holders_total: usize,
+    /// Wallets holding at least one of the tokens, counted once
+    pub unique_wallets: usize,
+    /// False when some tokens have no holder set in memory (count-only under `--max-memory-mb`):
+    /// their holders are missing from `unique_wallets`
+    pub unique_wallets_complete: bool,
+    /// Token with the largest 24h growth (absolute)
+    pub most_grown_24h: Option<TokenSummary>,
+    pub per_token: Vec<TokenSummary>,
+}
+
+impl StatsSummary {
+    /// Aggregate tokens with their holder sets (None when not kept in memory)
+    pub fn build<'a>(tokens: Vec<(TokenSummary, Option<&'a HashSet<Pubkey>>)>) -> Self {
+        let mut wallets: HashSet<&'a Pubkey> = HashSet::new();
+        let mut complete = true;
+        let mut per_token = Vec::with_capacity(tokens.len());
+        for (token, holders) in tokens {
+            match holders {
+                Some(holders) => wallets.extend(holders),
+                None => complete = false,
+            }
+            per_token.push(token);
+        }
+        per_token.sort_by(|a, b| b.holders.cmp(&a.holders).then_with(|| a.mint.cmp(&b.mint)));
+        let most_grown_24h = per_token
+            .iter()
+            .filter(|token| token.growth_24h.is_some())
+            .max_by_key(|token| token.growth_24h)
+            .cloned();
+        Self {
+            tokens: per_token.len(),
+            holders_total: per_token.iter().map(|token| token.holders).sum(),
+            unique_wallets: wallets.len(),
+            unique_wallets_complete: complete,
+            most_grown_24h,
+            per_token,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recent_counts() {
+        let mut recent = RecentCounts::default();
+        assert_eq!(recent.growth(), None);
+        recent.record(0, 100);
+        assert_eq!(recent.growth(), None);
+        // Same hour: replaces the point
+        recent.record(1800, 110);
+        assert_eq!(recent.sparkline(), vec![110]);
+        for hour in 1..=30 {
+            recent.record(hour * POINT_SECS, 110 + hour as usize);
+        }
+        // 24 hours back plus the point just before the window
+        assert_eq!(recent.sparkline().len(), 25);
+        assert_eq!(recent.growth(), Some(24));
+        assert_eq!(recent.span_secs(), SUMMARY_WINDOW_SECS);
+    }
+
+    #[test]
+    fn test_summary() {
+        let (a, b, c) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let first: HashSet<Pubkey> = [a, b].into_iter().collect();
+        let second: HashSet<Pubkey> = [b, c].into_iter().collect();
+        let mut growing = RecentCounts::default();
+        growing.record(0, 1);
+        growing.record(POINT_SECS, 2);
+
+        let summary = StatsSummary::build(vec![
+            (TokenSummary::new("A".into(), 2, 0, None), Some(&first)),
+            (TokenSummary::new("B".into(), 2, 0, Some(&growing)), Some(&second)),
+        ]);
+        assert_eq!(summary.holders_total, 4);
+        assert_eq!(summary.unique_wallets, 3);
+        assert!(summary.unique_wallets_complete);
+        let most_grown = summary.most_grown_24h.unwrap();
+        assert_eq!(most_grown.mint, "B");
+        assert_eq!(most_grown.growth_24h_percent, Some(100.0));
+
+        let partial = StatsSummary::build(vec![(TokenSummary::new("C".into(), 5, 0, None), None)]);
+        assert!(!partial.unique_wallets_complete);
+        assert!(partial.most_grown_24h.is_none());
+    }
+}
@@ -0,0 +1,50 @@
+use std::time::Duration;
+
+/// Tell systemd the service finished startup (Type=notify)
+pub fn notify_ready() {
+    #[cfg(unix)]
+    notify(&[sd_notify::NotifyState::Ready]);
+}
+
+/// Tell systemd the service is shutting down
+pub fn notify_stopping() {
+    #[cfg(unix)]
+    notify(&[sd_notify::NotifyState::Stopping]);
+}
+
+/// Keep-alive ping for `WatchdogSec=`
+pub fn notify_watchdog() {
+    #[cfg(unix)]
+    notify(&[sd_notify::NotifyState::Watchdog]);
+}
+
+/// Free-form status shown by `systemctl status`
+pub fn notify_status(status: &str) {
+    #[cfg(unix)]
+    notify(&[sd_notify::NotifyState::Status(status)]);
+    #[cfg(not(unix))]
+    let _ = status;
+}
+
+/// How often to ping the watchdog, if systemd enabled one for this process
+/// Returns half of `WatchdogSec=` as recommended by sd_watchdog_enabled(3)
+pub fn watchdog_interval() -> Option<Duration> {
+    #[cfg(unix)]
+    {
+        let mut usec = 0u64;
+        if sd_notify::watchdog_enabled(false, &mut usec) && usec > 0 {
+            let period = Duration::from_micros(usec / 2);
+            tracing::info!("systemd watchdog enabled (ping every {:?})", period);
+            return Some(period);
+        }
+    }
+    None
+}
+
+/// Send notification; silently ignored when not running under systemd
+#[cfg(unix)]
+fn notify(state: &[sd_notify::NotifyState]) {
+    if let Err(e) = sd_notify::notify(false, state) {
+        tracing::debug!("sd_notify failed: {}", e);
+    }
+}
@@ -0,0 +1,173 @@
+//! User-defined notification texts (minijinja templates from the `[templates]` config section)
+
+use anyhow::{Context, Result};
+use minijinja::Environment;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::locale::Locale;
+use crate::token_monitor::AlertRule;
+
+/// `[templates]` config section; unset templates keep the built-in text
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TemplateConfig {
+    pub alert: Option<String>,
+    pub heartbeat: Option<String>,
+}
+
+/// Variables available to the `alert` template
+#[derive(Debug, Clone, Serialize)]
+pub struct AlertContext {
+    pub mint: String,
+    pub symbol: Option<String>,
+    pub rule: AlertRule,
+    /// Built-in alert text
+    pub message: String,
+    pub holders: usize,
+    pub previous_holders: Option<usize>,
+    pub change: i64,
+    pub change_percent: f64,
+    /// Consecutive polls the rule has fired
+    pub streak: u32,
+    pub timestamp: u64,
+}
+
+/// One mint in the `heartbeat` template's `mints` list
+#[derive(Debug, Clone, Serialize)]
+pub struct HeartbeatMint {
+    pub mint: String,
+    pub symbol: Option<String>,
+    /// Holder count of the last successful poll
+    pub holders: Option<usize>,
+    pub last_poll_secs_ago: Option<u64>,
+}
+
+#[derive(Serialize)]
+struct HeartbeatContext<'a> {
+    mints: &'a [HeartbeatMint],
+    timestamp: u64,
+}
+
+/// Compiled message templates
+///
+/// Besides the minijinja builtins, templates can use `num` (`{{ holders|num }}`,
+/// `{{ change_percent|num(1) }}`) and `datetime` (`{{ timestamp|datetime }}`), which
+/// follow `--number-format` and `--timezone`.
+#[derive(Default)]
+pub struct MessageTemplates {
+    env: Environment<'static>,
+}
+
+impl MessageTemplates {
+    /// Compile configured templates; syntax errors fail at startup
+    pub fn new(config: &TemplateConfig, locale: Locale) -> Result<Self> {
+        let mut env = Environment::new();
+        env.add_filter("num", move |value: f64, decimals: Option<usize>| match decimals {
+            Some(decimals) => locale.format_float(value, decimals),
+            None if value.fract() == 0.0 => locale.format_int(value as i64),
+            None => locale.format_float(value, 2),
+        });
+        env.add_filter("datetime", move |secs: u64| locale.format_timestamp(secs));
+        for (name, source) in [("alert", &config.alert), ("heartbeat", &config.heartbeat)] {
+            if let Some(source) = source {
+                env.add_template_owned(name, source.clone())
+                    .with_context(|| format!("Invalid {} template", name))?;
+            }
+        }
+        Ok(Self { env })
+    }
+
+    pub fn render_alert(&self, context: &AlertContext) -> Option<String> {
+        self.render("alert", context)
+    }
+
+    pub fn render_heartbeat(&self, mints: &[HeartbeatMint], timestamp: u64) -> Option<String> {
+        self.render("heartbeat", &HeartbeatContext { mints, timestamp })
+    }
+
+    /// None when the template isn't configured or fails to render (the caller falls back
+    /// to the built-in text, so a template bug never swallows an alert)
+    fn render<S: Serialize>(&self, name: &str, context: &S) -> Option<String> {
+        let template = self.env.get_template(name).ok()?;
+        match template.render(context) {
+            Ok(text) => Some(text),
+            Err(e) => {
+                warn!("Failed to render {} template: {}", name, e);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alert() -> AlertContext {
+        AlertContext {
+            mint: "Mint111".to_string(),
+            symbol: Some("BONK".to_string()),
+            rule: AlertRule::Growth,
+            message: "🚀 SIGNIFICANT GROWTH".to_string(),
+            holders: 1500,
+            previous_holders: Some(1000),
+            change: 500,
+            change_percent: 50.0,
+            streak: 1,
+            timestamp: 1702324800,
+        }
+    }
+
+    #[test]
+    fn test_render_templates() {
+        let templates = MessageTemplates::new(&TemplateConfig {
+            alert: Some("{{ symbol or mint }} {{ rule }}: {{ previous_holders }} -> {{ holders }} (+{{ change_percent|round(1) }}%)".to_string()),
+            heartbeat: Some("{% for m in mints %}{{ m.symbol or m.mint }}={{ m.holders }};{% endfor %}".to_string()),
+        }, Locale::default())
+        .unwrap();
+
+        assert_eq!(
+            templates.render_alert(&alert()).unwrap(),
+            "BONK growth: 1000 -> 1500 (+50.0%)"
+        );
+        let mints = vec![HeartbeatMint {
+            mint: "Mint111".to_string(),
+            symbol: None,
+            holders: Some(7),
+            last_poll_secs_ago: Some(3),
+        }];
+        assert_eq!(templates.render_heartbeat(&mints, 0).unwrap(), "Mint111=7;");
+    }
+
+    #[test]
+    fn test_unset_and_invalid_templates() {
+        assert!(MessageTemplates::default().render_alert(&alert()).is_none());
+        assert!(MessageTemplates::new(&TemplateConfig {
+            alert: Some("{{ unclosed".to_string()),
+            heartbeat: None,
+        }, Locale::default())
+        .is_err());
+    }
+
+    #[test]
+    fn test_locale_filters() {
+        let locale = Locale {
+            timezone: Some(crate::locale::parse_timezone("Europe/Berlin").unwrap()),
+            numbers: crate::locale::NumberLocale::En,
+            ..Locale::default()
+        };
+        let templates = MessageTemplates::new(&TemplateConfig {
+            alert: Some("{{ holders|num }} {{ change_percent|num(1) }} {{ timestamp|datetime }}".to_string()),
+            heartbeat: None,
+        }, locale)
+        .unwrap();
+
+        let mut context = alert();
+        context.holders = 1_234_567;
+        assert_eq!(
+            templates.render_alert(&context).unwrap(),
+            "1,234,567 50.0 2023-12-11 21:00:00 CET"
+        );
+    }
+}
@@ -0,0 +1,433 @@
+//! Test support (feature `test-util`): a mock JSON-RPC server and token account fixtures,
+//! so the RPC client, retry logic and API handlers can be tested without mainnet.
+//! Also built for the crate's own unit tests, which share its fixtures.
+
+use axum::{extract::State, http::HeaderMap, response::Json, routing::post, Router};
+use base64::Engine;
+use serde_json::{json, Value};
+use solana_program::pubkey::Pubkey;
+use solana_sdk::account::Account;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::task::JoinHandle;
+
+use crate::cluster::Cluster;
+use crate::rpc_client::TOKEN_PROGRAM_ID;
+use crate::token_monitor::encode_token_account;
+
+pub use crate::token_monitor::TOKEN_ACCOUNT_LEN;
+
+/// Token account with `mint`, `owner` and `amount` set (initialized state)
+pub fn token_account(mint: &Pubkey, owner: &Pubkey, amount: u64) -> (Pubkey, Account) {
+    let account = Account {
+        lamports: 2_039_280,
+        data: encode_token_account(mint, owner, amount),
+        owner: Pubkey::from_str(TOKEN_PROGRAM_ID).unwrap(),
+        executable: false,
+        rent_epoch: 0,
+    };
+    (Pubkey::new_unique(), account)
+}
+
+/// Block time the mock server reports for `slot`: 400ms slots counted from a fixed epoch
+pub fn mock_block_time(slot: u64) -> u64 {
+    1_600_000_000 + slot * 2 / 5
+}
+
+/// Account at `address` owned by `program` (e.g. a holder's wallet or multisig account)
+pub fn owner_account(address: &Pubkey, program: &Pubkey, data: Vec<u8>) -> (Pubkey, Account) {
+    let account = Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: *program,
+        executable: false,
+        rent_epoch: 0,
+    };
+    (*address, account)
+}
+
+/// Canned getProgramAccounts result for `mint`: 4 holders, one of them with two accounts,
+/// plus one zero-balance account that must not be counted
+pub fn canned_program_accounts(mint: &Pubkey) -> Vec<(Pubkey, Account)> {
+    let whale = Pubkey::new_unique();
+    vec![
+        token_account(mint, &whale, 1_000_000),
+        token_account(mint, &whale, 500_000),
+        token_account(mint, &Pubkey::new_unique(), 1_000),
+        token_account(mint, &Pubkey::new_unique(), 1_000),
+        token_account(mint, &Pubkey::new_unique(), 1),
+        token_account(mint, &Pubkey::new_unique(), 0),
+    ]
+}
+
+/// How many slots finalized commitment trails the mock's current slot
+pub const FINALIZED_LAG: u64 = 32;
+
+/// Shared state of a running mock server
+#[derive(Default)]
+struct MockState {
+    accounts: Vec<(Pubkey, Account)>,
+    genesis_hash: String,
+    slot: u64,
+    /// Accounts served to getProgramAccounts at finalized commitment (None: same as `accounts`)
+    finalized_accounts: Option<Vec<(Pubkey, Account)>>,
+    /// getProgramAccounts is refused like on public RPCs
+    reject_program_accounts: bool,
+    /// Serve DAS getTokenAccounts
+    das: bool,
+    /// getTokenSupply answer (None: the balances of the served accounts)
+    supply: Option<u64>,
+    /// getProgramAccounts calls left that fail with a JSON-RPC error
+    failures_left: AtomicUsize,
+    requests: Mutex<HashMap<String, usize>>,
+    user_agent: Mutex<Option<String>>,
+}
+
+/// Builder for `MockRpcServer`
+pub struct MockRpcBuilder {
+    accounts: Vec<(Pubkey, Account)>,
+    genesis_hash: String,
+    slot: u64,
+    finalized_accounts: Option<Vec<(Pubkey, Account)>>,
+    reject_program_accounts: bool,
+    das: bool,
+    supply: Option<u64>,
+    fail_first: usize,
+}
+
+impl Default for MockRpcBuilder {
+    fn default() -> Self {
+        Self {
+            accounts: Vec::new(),
+            genesis_hash: Cluster::Mainnet.genesis_hash().unwrap().to_string(),
+            slot: 250_000_000,
+            finalized_accounts: None,
+            reject_program_accounts: false,
+            das: false,
+            supply: None,
+            fail_first: 0,
+        }
+    }
+}
+
+impl MockRpcBuilder {
+    /// Accounts served by getProgramAccounts (filtered by program, size and memcmp) and
+    /// getMultipleAccounts
+    pub fn accounts(mut self, accounts: Vec<(Pubkey, Account)>) -> Self {
+        self.accounts = accounts;
+        self
+    }
+
+    /// Genesis hash returned by getGenesisHash (mainnet by default)
+    pub fn genesis_hash(mut self, hash: &str) -> Self {
+        self.genesis_hash = hash.to_string();
+        self
+    }
+
+    /// Slot of getSlot and of every response context (250000000 by default); requests with a
+    /// higher `minContextSlot` fail like on a node that is behind
+    pub fn slot(mut self, slot: u64) -> Self {
+        self.slot = slot;
+        self
+    }
+
+    /// Accounts getProgramAccounts returns at finalized commitment, answered `FINALIZED_LAG`
+    /// slots behind `slot` (e.g. a drop that a fork reverts before finalization)
+    pub fn finalized_accounts(mut self, accounts: Vec<(Pubkey, Account)>) -> Self {
+        self.finalized_accounts = Some(accounts);
+        self
+    }
+
+    /// Refuse getProgramAccounts for the token program like public RPCs do
+    pub fn reject_program_accounts(mut self) -> Self {
+        self.reject_program_accounts = true;
+        self
+    }
+
+    /// Serve DAS getTokenAccounts (Helius-style `{mint, page, limit}` params)
+    pub fn das(mut self) -> Self {
+        self.das = true;
+        self
+    }
+
+    /// Supply reported by getTokenSupply; more than the served balances looks like a truncated
+    /// getProgramAccounts response
+    pub fn supply(mut self, supply: u64) -> Self {
+        self.supply = Some(supply);
+        self
+    }
+
+    /// Fail the first `n` getProgramAccounts calls (to exercise retries)
+    pub fn fail_first(mut self, n: usize) -> Self {
+        self.fail_first = n;
+        self
+    }
+
+    /// Bind to a random local port and start serving
+    pub async fn start(self) -> MockRpcServer {
+        let state = Arc::new(MockState {
+            accounts: self.accounts,
+            genesis_hash: self.genesis_hash,
+            slot: self.slot,
+            finalized_accounts: self.finalized_accounts,
+            reject_program_accounts: self.reject_program_accounts,
+            das: self.das,
+            supply: self.supply,
+            failures_left: AtomicUsize::new(self.fail_first),
+            requests: Mutex::new(HashMap::new()),
+            user_agent: Mutex::new(None),
+        });
+
+        let app = Router::new()
+            .route("/", post(handle_rpc))
+            .with_state(state.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Failed to bind mock RPC server");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        });
+
+        MockRpcServer { url, state, handle }
+    }
+}
+
+/// Mock Solana JSON-RPC server on localhost
+/// Supports getHealth, getSlot, getBlockTime (see `mock_block_time`), getVersion, getGenesisHash, getProgramAccounts, getMultipleAccounts,
+/// getTokenLargestAccounts, getTokenSupply, DAS getTokenAccounts (see `MockRpcBuilder::das`) and getSignaturesForAddress (always empty);
+/// anything else returns "method not found"
+pub struct MockRpcServer {
+    pub url: String,
+    state: Arc<MockState>,
+    handle: JoinHandle<()>,
+}
+
+impl MockRpcServer {
+    pub fn builder() -> MockRpcBuilder {
+        MockRpcBuilder::default()
+    }
+
+    /// How many times a JSON-RPC method was called
+    pub fn request_count(&self, method: &str) -> usize {
+        self.state
+            .requests
+            .lock()
+            .unwrap()
+            .get(method)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// User-Agent header of the last request
+    pub fn last_user_agent(&self) -> Option<String> {
+        self.state.user_agent.lock().unwrap().clone()
+    }
+}
+
+impl Drop for MockRpcServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+async fn handle_rpc(State(state): State<Arc<MockState>>, headers: HeaderMap, Json(request): Json<Value>) -> Json<Value> {
+    let id = request["id"].clone();
+    let method = request["method"].as_str().unwrap_or_default().to_string();
+    *state.requests.lock().unwrap().entry(method.clone()).or_default() += 1;
+    *state.user_agent.lock().unwrap() =
+        headers.get("user-agent").and_then(|value| value.to_str().ok()).map(str::to_string);
+
+    // Finalized reads see an older slot
+    let finalized = request["params"][1]["commitment"].as_str() == Some("finalized");
+    let slot = if finalized { state.slot.saturating_sub(FINALIZED_LAG) } else { state.slot };
+
+    let result = match method.as_str() {
+        "getHealth" => Ok(json!("ok")),
+        "getSlot" => Ok(json!(state.slot)),
+        "getBlockTime" => Ok(json!(mock_block_time(request["params"][0].as_u64().unwrap_or(0)))),
+        "getVersion" => Ok(json!({"solana-core": "2.0.0", "feature-set": 0})),
+        "getGenesisHash" => Ok(json!(state.genesis_hash)),
+        "getSignaturesForAddress" => Ok(json!([])),
+        _ if request["params"][1]["minContextSlot"].as_u64().is_some_and(|min| min > slot) => Err(json!({
+            "code": -32016,
+            "message": "Minimum context slot has not been reached",
+            "data": {"contextSlot": slot}
+        })),
+        "getMultipleAccounts" => Ok(multiple_accounts(&state.accounts, &request["params"], state.slot)),
+        "getProgramAccounts" if state.reject_program_accounts => Err(json!({
+            "code": -32010,
+            "message": format!("{} excluded from account secondary indexes; this RPC method unavailable for key", TOKEN_PROGRAM_ID)
+        })),
+        "getTokenAccounts" if state.das => Ok(das_token_accounts(&state.accounts, &request["params"])),
+        "getTokenLargestAccounts" => Ok(largest_accounts(&state.accounts, &request["params"], slot)),
+        "getTokenSupply" => {
+            let mint = request["params"][0].as_str().unwrap_or_default();
+            let supply = state
+                .supply
+                .unwrap_or_else(|| token_accounts_of(&state.accounts, mint).map(|(_, _, amount)| amount).sum());
+            Ok(json!({
+                "context": {"slot": slot},
+                "value": {"amount": supply.to_string(), "decimals": 0, "uiAmount": supply as f64, "uiAmountString": supply.to_string()}
+            }))
+        }
+        "getProgramAccounts" => {
+            let failed = state
+                .failures_left
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                .is_ok();
+            if failed {
+                Err(json!({"code": -32005, "message": "Node is behind (mock failure)"}))
+            } else {
+                let accounts = match &state.finalized_accounts {
+                    Some(accounts) if finalized => accounts,
+                    _ => &state.accounts,
+                };
+                Ok(program_accounts(accounts, &request["params"], slot))
+            }
+        }
+        _ => Err(json!({"code": -32601, "message": "Method not found"})),
+    };
+
+    Json(match result {
+        Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+        Err(error) => json!({"jsonrpc": "2.0", "id": id, "error": error}),
+    })
+}
+
+/// Apply program / dataSize / memcmp filters and encode accounts as base64
+/// With `withContext` the list is wrapped in `{context, value}`
+fn program_accounts(accounts: &[(Pubkey, Account)], params: &Value, slot: u64) -> Value {
+    let program = params[0].as_str().and_then(|p| Pubkey::from_str(p).ok());
+    let filters = params[1]["filters"].as_array().cloned().unwrap_or_default();
+
+    let matching: Vec<Value> = accounts
+        .iter()
+        .filter(|(_, account)| program.is_none_or(|p| account.owner == p))
+        .filter(|(_, account)| filters.iter().all(|f| filter_matches(f, &account.data)))
+        .map(|(pubkey, account)| {
+            json!({
+                "pubkey": pubkey.to_string(),
+                "account": encode_account(account, &Value::Null),
+            })
+        })
+        .collect();
+    if params[1]["withContext"].as_bool() == Some(true) {
+        return json!({"context": {"slot": slot}, "value": matching});
+    }
+    json!(matching)
+}
+
+/// Token account fields of served accounts holding `mint`: (address, owner, amount)
+fn token_accounts_of<'a>(accounts: &'a [(Pubkey, Account)], mint: &'a str) -> impl Iterator<Item = (&'a Pubkey, Pubkey, u64)> + 'a {
+    accounts
+        .iter()
+        .filter(|(_, account)| account.data.len() >= TOKEN_ACCOUNT_LEN)
+        .filter(move |(_, account)| Pubkey::try_from(&account.data[..32]).is_ok_and(|m| m.to_string() == mint))
+        .map(|(address, account)| {
+            let owner = Pubkey::try_from(&account.data[32..64]).unwrap();
+            let amount = u64::from_le_bytes(account.data[64..72].try_into().unwrap());
+            (address, owner, amount)
+        })
+}
+
+/// One page of DAS getTokenAccounts
+fn das_token_accounts(accounts: &[(Pubkey, Account)], params: &Value) -> Value {
+    let mint = params["mint"].as_str().unwrap_or_default();
+    let page = params["page"].as_u64().unwrap_or(1).max(1) as usize;
+    let limit = params["limit"].as_u64().unwrap_or(100) as usize;
+    let all: Vec<Value> = token_accounts_of(accounts, mint)
+        .map(|(address, owner, amount)| {
+            json!({
+                "address": address.to_string(),
+                "mint": mint,
+                "owner": owner.to_string(),
+                "amount": amount,
+                "delegated_amount": 0,
+                "frozen": false
+            })
+        })
+        .collect();
+    let token_accounts: Vec<Value> = all.iter().skip((page - 1) * limit).take(limit).cloned().collect();
+    json!({"total": all.len(), "limit": limit, "page": page, "token_accounts": token_accounts})
+}
+
+/// getTokenLargestAccounts: the 20 largest accounts of the mint in `params[0]`
+fn largest_accounts(accounts: &[(Pubkey, Account)], params: &Value, slot: u64) -> Value {
+    let mint = params[0].as_str().unwrap_or_default();
+    let mut largest: Vec<(&Pubkey, Pubkey, u64)> = token_accounts_of(accounts, mint).collect();
+    largest.sort_by_key(|(_, _, amount)| std::cmp::Reverse(*amount));
+    let value: Vec<Value> = largest
+        .iter()
+        .take(20)
+        .map(|(address, _, amount)| {
+            json!({
+                "address": address.to_string(),
+                "amount": amount.to_string(),
+                "decimals": 0,
+                "uiAmount": *amount as f64,
+                "uiAmountString": amount.to_string()
+            })
+        })
+        .collect();
+    json!({"context": {"slot": slot}, "value": value})
+}
+
+/// Look up every requested address among the served accounts (null when missing)
+fn multiple_accounts(accounts: &[(Pubkey, Account)], params: &Value, slot: u64) -> Value {
+    let addresses = params[0].as_array().cloned().unwrap_or_default();
+    let value: Vec<Value> = addresses
+        .iter()
+        .map(|address| {
+            let address = address.as_str().and_then(|a| Pubkey::from_str(a).ok());
+            accounts
+                .iter()
+                .find(|(pubkey, _)| Some(*pubkey) == address)
+                .map_or(Value::Null, |(_, account)| encode_account(account, &params[1]["dataSlice"]))
+        })
+        .collect();
+    json!({"context": {"slot": slot}, "value": value})
+}
+
+/// Account JSON with base64 data, cut to `data_slice` (`{"offset", "length"}`) if given
+fn encode_account(account: &Account, data_slice: &Value) -> Value {
+    let data = match (data_slice["offset"].as_u64(), data_slice["length"].as_u64()) {
+        (Some(offset), Some(length)) => {
+            let start = (offset as usize).min(account.data.len());
+            let end = (start + length as usize).min(account.data.len());
+            &account.data[start..end]
+        }
+        _ => &account.data[..],
+    };
+    json!({
+        "lamports": account.lamports,
+        "owner": account.owner.to_string(),
+        "data": [base64::engine::general_purpose::STANDARD.encode(data), "base64"],
+        "executable": account.executable,
+        "rentEpoch": account.rent_epoch,
+        "space": account.data.len(),
+    })
+}
+
+fn filter_matches(filter: &Value, data: &[u8]) -> bool {
+    if let Some(size) = filter["dataSize"].as_u64() {
+        return data.len() as u64 == size;
+    }
+    let memcmp = &filter["memcmp"];
+    if memcmp.is_null() {
+        return true;
+    }
+    let offset = memcmp["offset"].as_u64().unwrap_or(0) as usize;
+    let bytes = match (memcmp["encoding"].as_str(), &memcmp["bytes"]) {
+        (Some("base64"), Value::String(s)) => base64::engine::general_purpose::STANDARD
+            .decode(s)
+            .unwrap_or_default(),
+        // Base58 only as 32-byte keys (enough for the mint filter)
+        (_, Value::String(s)) => Pubkey::from_str(s).map(|p| p.to_bytes().to_vec()).unwrap_or_default(),
+        (_, Value::Array(raw)) => raw.iter().filter_map(|b| b.as_u64()).map(|b| b as u8).collect(),
+        _ => return false,
+    };
+    data.get(offset..offset + bytes.len()) == Some(bytes.as_slice())
+}
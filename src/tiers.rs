@@ -0,0 +1,145 @@
+//! Balance tiers per mint (`[[tiers.<MINT>]]` in the config file), e.g. bronze ≥ 1k, silver ≥ 10k,
+//! gold ≥ 100k tokens: holder counts per tier in the monitor metrics and the tier of every watched
+//! wallet, for role syncing in NFT / utility projects
+//! Balances are compared in UI amounts, so interest accrued on a Token-2022 interest-bearing mint
+//! moves holders up the tiers
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use solana_program::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use crate::extensions::MintInfo;
+
+/// One `[[tiers.<MINT>]]` entry
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TierConfig {
+    pub name: String,
+    /// Minimum balance in whole tokens
+    pub min: f64,
+}
+
+/// Check every mint's tiers: valid mint, non-empty unique names, positive distinct minimums
+pub fn validate_tiers(tiers: &HashMap<String, Vec<TierConfig>>) -> Result<()> {
+    for (mint, entries) in tiers {
+        Pubkey::from_str(mint).map_err(|e| anyhow::anyhow!("Tiers: invalid mint '{}': {}", mint, e))?;
+        for (i, tier) in entries.iter().enumerate() {
+            if tier.name.trim().is_empty() {
+                return Err(anyhow::anyhow!("Tiers of {}: tier names must not be empty", mint));
+            }
+            if tier.min.is_nan() || tier.min <= 0.0 {
+                return Err(anyhow::anyhow!("Tiers of {}: min of '{}' must be greater than 0", mint, tier.name));
+            }
+            if let Some(other) = entries[..i].iter().find(|other| other.name == tier.name || other.min == tier.min) {
+                return Err(anyhow::anyhow!("Tiers of {}: '{}' and '{}' clash", mint, other.name, tier.name));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Holders in one tier at the last poll
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TierCount {
+    pub tier: String,
+    /// Holders with at least the tier's minimum but below the next tier
+    pub holders: usize,
+}
+
+/// A mint's tiers with minimums in whole tokens, lowest first
+#[derive(Debug, Clone, PartialEq)]
+pub struct TierSchedule {
+    tiers: Vec<(String, f64)>,
+    /// Decimals and interest that turn raw balances into UI amounts
+    mint: MintInfo,
+}
+
+impl TierSchedule {
+    pub fn new(tiers: &[TierConfig], mint: MintInfo) -> Self {
+        let mut tiers: Vec<(String, f64)> = tiers.iter().map(|tier| (tier.name.clone(), tier.min)).collect();
+        tiers.sort_by(|a, b| a.1.total_cmp(&b.1));
+        Self { tiers, mint }
+    }
+
+    /// Highest tier whose minimum the raw balance reaches at `now` (unix seconds)
+    pub fn tier_of(&self, raw: u64, now: i64) -> Option<&str> {
+        let amount = self.mint.ui_amount(raw, now);
+        self.tiers.iter().rev().find(|(_, min)| amount >= *min).map(|(name, _)| name.as_str())
+    }
+
+    /// Holders per tier at `now`, highest tier first; holders below every tier are not counted
+    pub fn count<'a>(&self, balances: impl IntoIterator<Item = &'a u64>, now: i64) -> Vec<TierCount> {
+        let mut counts = vec![0usize; self.tiers.len()];
+        for raw in balances {
+            let amount = self.mint.ui_amount(*raw, now);
+            if let Some(i) = self.tiers.iter().rposition(|(_, min)| amount >= *min) {
+                counts[i] += 1;
+            }
+        }
+        self.tiers
+            .iter()
+            .zip(counts)
+            .rev()
+            .map(|((tier, _), holders)| TierCount { tier: tier.clone(), holders })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extensions::InterestRate;
+
+    fn tiers() -> Vec<TierConfig> {
+        [("silver", 10_000.0), ("bronze", 1_000.0), ("gold", 100_000.0)]
+            .into_iter()
+            .map(|(name, min)| TierConfig { name: name.to_string(), min })
+            .collect()
+    }
+
+    fn mint(decimals: u8) -> MintInfo {
+        MintInfo { decimals, transfer_fee: None, interest: None }
+    }
+
+    #[test]
+    fn test_schedule() {
+        let schedule = TierSchedule::new(&tiers(), mint(6));
+        assert_eq!(schedule.tier_of(999_999_999, 0), None);
+        assert_eq!(schedule.tier_of(1_000_000_000, 0), Some("bronze"));
+        assert_eq!(schedule.tier_of(250_000_000_000, 0), Some("gold"));
+
+        let balances = [5, 2_000_000_000, 3_000_000_000, 20_000_000_000, 100_000_000_000];
+        let counts = schedule.count(&balances, 0);
+        let pairs: Vec<(&str, usize)> = counts.iter().map(|count| (count.tier.as_str(), count.holders)).collect();
+        assert_eq!(pairs, vec![("gold", 1), ("silver", 1), ("bronze", 2)]);
+    }
+
+    #[test]
+    fn test_interest_bearing_schedule() {
+        // 10% a year since 0: a balance just under bronze reaches it after a year of interest
+        let interest = InterestRate {
+            initialization_timestamp: 0,
+            pre_update_average_rate: 1_000,
+            last_update_timestamp: 0,
+            current_rate: 1_000,
+        };
+        let schedule = TierSchedule::new(&tiers(), MintInfo { interest: Some(interest), ..mint(6) });
+        let year = 365 * 86_400;
+        assert_eq!(schedule.tier_of(950_000_000, 0), None);
+        assert_eq!(schedule.tier_of(950_000_000, year), Some("bronze"));
+        assert_eq!(schedule.count(&[950_000_000], year)[2].holders, 1);
+    }
+
+    #[test]
+    fn test_validate() {
+        let mint = Pubkey::new_unique().to_string();
+        assert!(validate_tiers(&HashMap::from([(mint.clone(), tiers())])).is_ok());
+        let mut clashing = tiers();
+        clashing.push(TierConfig { name: "gold".to_string(), min: 5.0 });
+        assert!(validate_tiers(&HashMap::from([(mint.clone(), clashing)])).is_err());
+        let zero = vec![TierConfig { name: "any".to_string(), min: 0.0 }];
+        assert!(validate_tiers(&HashMap::from([(mint, zero)])).is_err());
+        assert!(validate_tiers(&HashMap::from([("not-a-mint".to_string(), tiers())])).is_err());
+    }
+}
@@ -1,10 +1,22 @@
 use anyhow::Result;
+use bytemuck::{Pod, Zeroable};
 use solana_program::pubkey::Pubkey;
 use solana_sdk::account::Account;
-use std::collections::HashSet;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::time::SystemTime;
 use tracing::{debug, info, warn};
 
+use crate::churn::ChurnTracker;
+use crate::delegations::DelegationStats;
+use crate::extensions::{is_token_account, withheld_amount};
+use crate::i18n::fill;
+use crate::latency::LatencySamples;
+use crate::locale::Locale;
+use crate::rpc_client::Fallback;
+use crate::tiers::TierCount;
+
 /// Token holder monitoring statistics
 #[derive(Debug, Clone)]
 pub struct HolderStats {
@@ -12,16 +24,52 @@ pub struct HolderStats {
     pub timestamp: u64,
     pub change: i64,
     pub change_percent: f64,
+    /// Slot the holder set was read at, when the RPC reports it
+    pub slot: Option<u64>,
+    /// Block time of `slot` (unix seconds), when the RPC resolves it
+    pub block_time: Option<u64>,
+    /// Fallback backend the count came from when getProgramAccounts is unavailable
+    pub degraded: Option<Fallback>,
+    /// Share of the supply missing from the fetched accounts, set when the RPC response looks
+    /// truncated (see `truncation`)
+    pub missing_supply_percent: Option<f64>,
+    /// Token accounts of the mint the count was taken from; `count` is unique owners, one owner
+    /// can have several accounts
+    pub token_accounts: Option<usize>,
+}
+
+impl HolderStats {
+    /// On-chain time of the observation: the block time of its slot, else the local poll time
+    pub fn observed_at(&self) -> u64 {
+        self.block_time.unwrap_or(self.timestamp)
+    }
 }
 
 /// Metrics tracker for holder monitoring
 #[derive(Debug, Default)]
 pub struct Metrics {
+    pub last_holders: Option<usize>,
     pub min_holders: Option<usize>,
     pub max_holders: Option<usize>,
     pub total_polls: usize,
     pub total_holders_sum: usize,
     pub alerts: Vec<String>,
+    /// Holders entering/exiting over rolling windows
+    pub churn: ChurnTracker,
+    /// Durations of the recent account fetches
+    pub fetch_latency: LatencySamples,
+    /// Holders per balance tier at the last poll (empty without `[tiers]` for the mint)
+    pub tiers: Vec<TierCount>,
+    /// Unix time of the last poll that succeeded
+    pub last_success_at: Option<u64>,
+    /// Polls failed since the last success
+    pub consecutive_failures: u32,
+    /// Owners with only zero-balance accounts at the last poll
+    pub zero_balance_owners: Option<usize>,
+    /// Token accounts (not owners) at the last poll
+    pub last_token_accounts: Option<usize>,
+    /// Approvals and close authorities at the last full poll
+    pub delegations: Option<DelegationStats>,
 }
 
 impl Metrics {
@@ -32,6 +80,7 @@ impl Metrics {
     pub fn update(&mut self, holder_count: usize) {
         self.total_polls += 1;
         self.total_holders_sum += holder_count;
+        self.last_holders = Some(holder_count);
 
         if self.min_holders.is_none() || holder_count < self.min_holders.unwrap() {
             self.min_holders = Some(holder_count);
@@ -50,68 +99,281 @@ impl Metrics {
         }
     }
 
+    /// Count a finished poll for staleness reporting (`GET /health`)
+    pub fn record_poll(&mut self, succeeded: bool, now: u64) {
+        if succeeded {
+            self.last_success_at = Some(now);
+            self.consecutive_failures = 0;
+        } else {
+            self.consecutive_failures += 1;
+        }
+    }
+
     pub fn add_alert(&mut self, message: String) {
         warn!("ALERT: {}", message);
         self.alerts.push(message);
     }
+
+    /// Session summary (what is printed on shutdown) for the API
+    pub fn summary(&self) -> MetricsSummary {
+        MetricsSummary {
+            total_polls: self.total_polls,
+            last_holders: self.last_holders,
+            min_holders: self.min_holders,
+            max_holders: self.max_holders,
+            average_holders: self.average_holders(),
+            alerts: self.alerts.clone(),
+            fetch_p50_secs: self.fetch_latency.percentile(0.5),
+            fetch_p95_secs: self.fetch_latency.percentile(0.95),
+            fetch_p99_secs: self.fetch_latency.percentile(0.99),
+            tiers: self.tiers.clone(),
+            last_success_at: self.last_success_at,
+            consecutive_failures: self.consecutive_failures,
+            zero_balance_owners: self.zero_balance_owners,
+            last_token_accounts: self.last_token_accounts,
+            delegations: self.delegations.clone(),
+        }
+    }
 }
 
+/// Snapshot of `Metrics` for one monitored mint
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MetricsSummary {
+    pub total_polls: usize,
+    /// Unique owners with a balance at the last poll
+    pub last_holders: Option<usize>,
+    pub min_holders: Option<usize>,
+    pub max_holders: Option<usize>,
+    pub average_holders: f64,
+    /// Token accounts at the last poll (an owner may have several)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_token_accounts: Option<usize>,
+    pub alerts: Vec<String>,
+    /// Account fetch duration percentiles over the recent polls
+    pub fetch_p50_secs: Option<f64>,
+    pub fetch_p95_secs: Option<f64>,
+    pub fetch_p99_secs: Option<f64>,
+    /// Holders per balance tier, highest first
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tiers: Vec<TierCount>,
+    pub last_success_at: Option<u64>,
+    pub consecutive_failures: u32,
+    /// Wallets with token accounts but no balance at the last poll (counted as holders with
+    /// `--include-zero-balance`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zero_balance_owners: Option<usize>,
+    /// Holder accounts with approvals / close authorities, largest delegates
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delegations: Option<DelegationStats>,
+}
+
+/// Accounts per parallel chunk; smaller account sets are parsed on the calling thread
+const PARALLEL_CHUNK_SIZE: usize = 16_384;
+
 /// Extract unique token holders from token accounts
+/// Large account sets are parsed in parallel chunks (rayon) and the per-chunk sets merged
 pub fn extract_holders(accounts: &[(Pubkey, Account)]) -> Result<HashSet<Pubkey>> {
-    let mut holders = HashSet::new();
+    let (holders, zero_balance_count) = if accounts.len() <= PARALLEL_CHUNK_SIZE {
+        extract_chunk(accounts)
+    } else {
+        accounts
+            .par_chunks(PARALLEL_CHUNK_SIZE)
+            .map(extract_chunk)
+            .reduce(
+                || (HashSet::new(), 0),
+                |(a, zero_a), (b, zero_b)| {
+                    // Extend the larger set to keep rehashing to a minimum
+                    let (mut larger, smaller) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+                    larger.extend(smaller);
+                    (larger, zero_a + zero_b)
+                },
+            )
+    };
+
+    info!(
+        "Extracted {} unique holders ({} zero-balance accounts filtered)",
+        holders.len(),
+        zero_balance_count
+    );
+
+    Ok(holders)
+}
+
+/// Holders and zero-balance account count of one slice of accounts
+fn extract_chunk(accounts: &[(Pubkey, Account)]) -> (HashSet<Pubkey>, usize) {
+    let mut holders = HashSet::with_capacity(accounts.len());
     let mut zero_balance_count = 0;
 
     for (token_account_pubkey, account) in accounts {
-        // Parse token account data
-        // TokenAccount structure: mint(32) + owner(32) + amount(8) + ...
-        // Amount is at offset 64, 8 bytes (u64 little-endian)
-        if account.data.len() < 72 {
-            debug!(
+        match owner_and_amount(&account.data) {
+            Some((owner, amount)) if amount > 0 => {
+                if owner != Pubkey::default() {
+                    holders.insert(owner);
+                }
+            }
+            Some(_) => zero_balance_count += 1,
+            None => debug!(
                 "Token account {} has invalid data length: {}",
                 token_account_pubkey,
                 account.data.len()
-            );
-            continue;
+            ),
         }
+    }
 
-        // Parse amount directly from bytes (faster than unpacking full struct)
-        let amount_bytes: [u8; 8] = account.data[64..72]
-            .try_into()
-            .unwrap_or([0; 8]);
-        let amount = u64::from_le_bytes(amount_bytes);
-
-        if amount > 0 {
-            // Parse owner from bytes (offset 32, 32 bytes)
-            if account.data.len() >= 64 {
-                let owner_bytes: [u8; 32] = account.data[32..64]
-                    .try_into()
-                    .unwrap_or([0; 32]);
-                let owner = Pubkey::try_from(owner_bytes.as_ref())
-                    .unwrap_or_else(|_| {
-                        debug!("Invalid owner bytes in account {}", token_account_pubkey);
-                        Pubkey::default()
-                    });
-                
-                if owner != Pubkey::default() {
-                    holders.insert(owner);
-                    debug!(
-                        "Found holder: {} with balance: {}",
-                        owner, amount
-                    );
-                }
+    (holders, zero_balance_count)
+}
+
+/// SPL token account layout (165 bytes) for zero-copy reads
+/// Multi-byte integers are kept as little-endian byte arrays so the struct has alignment 1
+/// and can be cast from any account data slice without copying
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct TokenAccountLayout {
+    pub mint: [u8; 32],
+    pub owner: [u8; 32],
+    pub amount: [u8; 8],
+    pub delegate_tag: [u8; 4],
+    pub delegate: [u8; 32],
+    pub state: u8,
+    pub is_native_tag: [u8; 4],
+    pub is_native: [u8; 8],
+    pub delegated_amount: [u8; 8],
+    pub close_authority_tag: [u8; 4],
+    pub close_authority: [u8; 32],
+}
+
+/// Size of `TokenAccountLayout` (equal to `spl_token::state::Account::LEN`)
+pub const TOKEN_ACCOUNT_LEN: usize = std::mem::size_of::<TokenAccountLayout>();
+
+impl TokenAccountLayout {
+    /// View account data as a token account (None if it is too short or not a token account)
+    /// Token-2022 accounts with extensions are accepted: only the base layout is read
+    pub fn from_data(data: &[u8]) -> Option<&Self> {
+        if !is_token_account(data) {
+            return None;
+        }
+        bytemuck::try_from_bytes(&data[..TOKEN_ACCOUNT_LEN]).ok()
+    }
+
+    pub fn mint(&self) -> Pubkey {
+        Pubkey::new_from_array(self.mint)
+    }
+
+    pub fn owner(&self) -> Pubkey {
+        Pubkey::new_from_array(self.owner)
+    }
+
+    pub fn amount(&self) -> u64 {
+        u64::from_le_bytes(self.amount)
+    }
+
+    /// Address approved to transfer up to `delegated_amount`
+    pub fn delegate(&self) -> Option<Pubkey> {
+        (u32::from_le_bytes(self.delegate_tag) == 1).then(|| Pubkey::new_from_array(self.delegate))
+    }
+
+    pub fn delegated_amount(&self) -> u64 {
+        u64::from_le_bytes(self.delegated_amount)
+    }
+
+    /// Address allowed to close the account, when it is not the owner by default
+    pub fn close_authority(&self) -> Option<Pubkey> {
+        (u32::from_le_bytes(self.close_authority_tag) == 1).then(|| Pubkey::new_from_array(self.close_authority))
+    }
+}
+
+/// Data of an initialized token account holding `amount` of `mint` for `owner`
+/// (rebuilds accounts that a provider reports without their data, e.g. DAS)
+pub fn encode_token_account(mint: &Pubkey, owner: &Pubkey, amount: u64) -> Vec<u8> {
+    let layout = TokenAccountLayout {
+        mint: mint.to_bytes(),
+        owner: owner.to_bytes(),
+        amount: amount.to_le_bytes(),
+        state: 1, // AccountState::Initialized
+        ..TokenAccountLayout::zeroed()
+    };
+    bytemuck::bytes_of(&layout).to_vec()
+}
+
+/// Owner + amount slice of a token account fetched in count-only mode (see `memory`)
+pub const SLIM_ACCOUNT_OFFSET: usize = 32;
+pub const SLIM_ACCOUNT_LEN: usize = 40;
+
+/// Owner and raw amount of an SPL token account (None for malformed data)
+/// Accepts full accounts and owner+amount slices
+fn owner_and_amount(data: &[u8]) -> Option<(Pubkey, u64)> {
+    if data.len() == SLIM_ACCOUNT_LEN {
+        let owner = Pubkey::try_from(&data[..32]).ok()?;
+        let amount = u64::from_le_bytes(data[32..40].try_into().ok()?);
+        return Some((owner, amount));
+    }
+    let account = TokenAccountLayout::from_data(data)?;
+    Some((account.owner(), account.amount()))
+}
+
+/// Sum of the raw balances of all accounts (zero-balance and ownerless ones included), plus
+/// Token-2022 transfer fees withheld in them: both count towards the supply
+pub fn total_amount(accounts: &[(Pubkey, Account)]) -> u128 {
+    accounts
+        .iter()
+        .filter_map(|(_, account)| {
+            let (_, amount) = owner_and_amount(&account.data)?;
+            Some(u128::from(amount) + u128::from(withheld_amount(&account.data)))
+        })
+        .sum()
+}
+
+/// Total raw balance per holder (owners with several token accounts are summed)
+pub fn extract_balances(accounts: &[(Pubkey, Account)]) -> HashMap<Pubkey, u64> {
+    let mut balances: HashMap<Pubkey, u64> = HashMap::new();
+    for (_, account) in accounts {
+        if let Some((owner, amount)) = owner_and_amount(&account.data) {
+            if amount > 0 && owner != Pubkey::default() {
+                *balances.entry(owner).or_default() += amount;
             }
-        } else {
-            zero_balance_count += 1;
         }
     }
+    balances
+}
 
-    info!(
-        "Extracted {} unique holders ({} zero-balance accounts filtered)",
-        holders.len(),
-        zero_balance_count
-    );
+/// Token accounts of the mint per owner, empty accounts included (ownerless accounts are skipped)
+pub fn accounts_per_owner(accounts: &[(Pubkey, Account)]) -> HashMap<Pubkey, usize> {
+    let mut counts: HashMap<Pubkey, usize> = HashMap::new();
+    for (_, account) in accounts {
+        if let Some((owner, _)) = owner_and_amount(&account.data) {
+            if owner != Pubkey::default() {
+                *counts.entry(owner).or_default() += 1;
+            }
+        }
+    }
+    counts
+}
 
-    Ok(holders)
+/// Owners with token accounts of the mint that all hold a zero balance: wallets that held or
+/// were sent the token before but are not among `holders` (`--include-zero-balance`)
+pub fn zero_balance_owners(accounts: &[(Pubkey, Account)], holders: &HashSet<Pubkey>) -> HashSet<Pubkey> {
+    let mut owners = HashSet::new();
+    for (_, account) in accounts {
+        if let Some((owner, 0)) = owner_and_amount(&account.data) {
+            if owner != Pubkey::default() && !holders.contains(&owner) {
+                owners.insert(owner);
+            }
+        }
+    }
+    owners
+}
+
+/// Total raw balance of each of `wallets` that holds the token (absent when it holds none)
+pub fn balances_of(accounts: &[(Pubkey, Account)], wallets: &HashSet<Pubkey>) -> HashMap<Pubkey, u64> {
+    let mut balances: HashMap<Pubkey, u64> = HashMap::new();
+    for (_, account) in accounts {
+        if let Some((owner, amount)) = owner_and_amount(&account.data) {
+            if amount > 0 && wallets.contains(&owner) {
+                *balances.entry(owner).or_default() += amount;
+            }
+        }
+    }
+    balances
 }
 
 /// Calculate holder statistics
@@ -145,46 +407,97 @@ pub fn calculate_stats(
         timestamp,
         change,
         change_percent,
+        slot: None,
+        block_time: None,
+        degraded: None,
+        missing_supply_percent: None,
+        token_accounts: None,
     }
 }
 
+/// Built-in alert rules (names used in the config file)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertRule {
+    /// Holders up 50% or more since the previous poll
+    Growth,
+    /// Holders down 20% or more since the previous poll
+    Drop,
+    /// Holders crossed a `--milestones` / `--milestone-step` threshold
+    Milestone,
+    /// Holders above the highest count of the `--ath-lookback-days` window
+    Ath,
+    /// Holders below the lowest count of the `--ath-lookback-days` window
+    Atl,
+    /// One delegate may transfer `--delegation-alert-percent` of the supply or more
+    Delegation,
+}
+
+/// Change between two polls (in percent) that triggers the growth alert
+pub const GROWTH_ALERT_PERCENT: f64 = 50.0;
+/// Change between two polls (in percent) that triggers the drop alert
+pub const DROP_ALERT_PERCENT: f64 = -20.0;
+
+/// Whether `stats` triggers the drop alert (given a previous poll)
+pub fn is_drop(stats: &HolderStats) -> bool {
+    stats.change_percent <= DROP_ALERT_PERCENT
+}
+
 /// Check for significant changes and generate alerts
-pub fn check_alerts(
-    stats: &HolderStats,
-    previous_count: Option<usize>,
-    metrics: &mut Metrics,
-) {
+/// Returns each rule that fired with its message
+pub fn check_alerts(stats: &HolderStats, previous_count: Option<usize>, locale: &Locale) -> Vec<(AlertRule, String)> {
+    let mut fired = Vec::new();
     if let Some(prev) = previous_count {
         // +50% growth alert
-        if stats.change_percent >= 50.0 {
-            let message = format!(
-                "🚀 SIGNIFICANT GROWTH: +{} holders (+{:.1}%) | {} -> {}",
-                stats.change, stats.change_percent, prev, stats.count
-            );
-            metrics.add_alert(message);
+        if stats.change_percent >= GROWTH_ALERT_PERCENT {
+            fired.push((AlertRule::Growth, alert_text(locale.messages().growth_alert, stats, prev, locale)));
         }
 
         // -20% drop alert
-        if stats.change_percent <= -20.0 {
-            let message = format!(
-                "⚠️ SIGNIFICANT DROP: {} holders ({:.1}%) | {} -> {}",
-                stats.change, stats.change_percent, prev, stats.count
-            );
-            metrics.add_alert(message);
+        if is_drop(stats) {
+            fired.push((AlertRule::Drop, alert_text(locale.messages().drop_alert, stats, prev, locale)));
         }
     }
+    fired
+}
+
+fn alert_text(template: &str, stats: &HolderStats, previous: usize, locale: &Locale) -> String {
+    fill(
+        template,
+        &[
+            ("change", &locale.format_int(stats.change)),
+            ("percent", &locale.format_percent(stats.change_percent)),
+            ("previous", &locale.format_count(previous)),
+            ("holders", &locale.format_count(stats.count)),
+        ],
+    )
 }
 
-/// Format timestamp for display
+/// Format timestamp for display (UTC; see `Locale::format_timestamp` for `--timezone`)
 pub fn format_timestamp(secs: u64) -> String {
-    let datetime = std::time::UNIX_EPOCH + std::time::Duration::from_secs(secs);
-    let datetime: chrono::DateTime<chrono::Utc> = datetime.into();
-    datetime.format("%Y-%m-%d %H:%M:%S UTC").to_string()
+    Locale::default().format_timestamp(secs)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_util::token_account;
+
+    #[test]
+    fn test_metrics_summary() {
+        let mut metrics = Metrics::new();
+        metrics.update(100);
+        metrics.update(150);
+        metrics.add_alert("growth".to_string());
+
+        let summary = metrics.summary();
+        assert_eq!(summary.total_polls, 2);
+        assert_eq!(summary.last_holders, Some(150));
+        assert_eq!(summary.min_holders, Some(100));
+        assert_eq!(summary.max_holders, Some(150));
+        assert_eq!(summary.average_holders, 125.0);
+        assert_eq!(summary.alerts, vec!["growth".to_string()]);
+    }
 
     #[test]
     fn test_calculate_stats() {
@@ -194,32 +507,117 @@ mod tests {
         assert!((stats.change_percent - 25.0).abs() < 0.1);
     }
 
+    #[test]
+    fn test_layout_matches_spl_token() {
+        use spl_token::solana_program::program_pack::Pack;
+        use spl_token::solana_program::pubkey::Pubkey as SplPubkey;
+
+        assert_eq!(TOKEN_ACCOUNT_LEN, spl_token::state::Account::LEN);
+
+        let account = spl_token::state::Account {
+            mint: SplPubkey::new_from_array([1; 32]),
+            owner: SplPubkey::new_from_array([2; 32]),
+            amount: 42,
+            state: spl_token::state::AccountState::Initialized,
+            ..Default::default()
+        };
+        let mut data = vec![0u8; spl_token::state::Account::LEN];
+        account.pack_into_slice(&mut data);
+
+        let layout = TokenAccountLayout::from_data(&data).unwrap();
+        assert_eq!(layout.mint(), Pubkey::new_from_array([1; 32]));
+        assert_eq!(layout.owner(), Pubkey::new_from_array([2; 32]));
+        assert_eq!(layout.amount(), 42);
+        assert_eq!(layout.state, 1);
+        assert!(TokenAccountLayout::from_data(&data[..72]).is_none());
+    }
+
+    #[test]
+    fn test_extract_slim_accounts() {
+        let owner = Pubkey::new_unique();
+        let mut data = owner.to_bytes().to_vec();
+        data.extend_from_slice(&7u64.to_le_bytes());
+        let accounts = vec![(Pubkey::new_unique(), Account { data, ..Account::default() })];
+
+        assert_eq!(extract_balances(&accounts)[&owner], 7);
+    }
+
+    #[test]
+    fn test_extract_holders_parallel() {
+        let mint = Pubkey::new_unique();
+        // Owners repeat across chunk boundaries; every 4th account is empty
+        let owners: Vec<Pubkey> = (0..999).map(|_| Pubkey::new_unique()).collect();
+        let accounts: Vec<(Pubkey, Account)> = (0..PARALLEL_CHUNK_SIZE * 3)
+            .map(|i| token_account(&mint, &owners[i % owners.len()], (i % 4) as u64))
+            .collect();
+
+        let holders = extract_holders(&accounts).unwrap();
+        assert_eq!(holders.len(), owners.len());
+        assert_eq!(holders, extract_chunk(&accounts).0);
+    }
+
+    #[test]
+    fn test_zero_balance_owners() {
+        let mint = Pubkey::new_unique();
+        let (holder, emptied, ownerless) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::default());
+        // The holder also has an empty account: it still counts only as a holder
+        let accounts = vec![
+            token_account(&mint, &holder, 5),
+            token_account(&mint, &holder, 0),
+            token_account(&mint, &emptied, 0),
+            token_account(&mint, &emptied, 0),
+            token_account(&mint, &ownerless, 0),
+        ];
+
+        let holders = extract_holders(&accounts).unwrap();
+        let zero = zero_balance_owners(&accounts, &holders);
+        assert_eq!(holders, HashSet::from([holder]));
+        assert_eq!(zero, HashSet::from([emptied]));
+    }
+
     #[test]
     fn test_check_alerts_growth() {
-        let mut metrics = Metrics::new();
         let stats = HolderStats {
             count: 150,
             timestamp: 0,
             change: 50,
             change_percent: 50.0,
+            slot: None,
+            block_time: None,
+            degraded: None,
+            missing_supply_percent: None,
+            token_accounts: None,
         };
-        check_alerts(&stats, Some(100), &mut metrics);
-        assert_eq!(metrics.alerts.len(), 1);
-        assert!(metrics.alerts[0].contains("GROWTH"));
+        let fired = check_alerts(&stats, Some(100), &Locale::default());
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].0, AlertRule::Growth);
+        assert!(fired[0].1.contains("GROWTH"));
     }
 
     #[test]
     fn test_check_alerts_drop() {
-        let mut metrics = Metrics::new();
         let stats = HolderStats {
             count: 80,
             timestamp: 0,
             change: -20,
             change_percent: -20.0,
+            slot: None,
+            block_time: None,
+            degraded: None,
+            missing_supply_percent: None,
+            token_accounts: None,
+        };
+        let fired = check_alerts(&stats, Some(100), &Locale::default());
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].0, AlertRule::Drop);
+        assert!(fired[0].1.contains("DROP"));
+
+        let russian = Locale {
+            language: crate::i18n::Language::Ru,
+            ..Locale::default()
         };
-        check_alerts(&stats, Some(100), &mut metrics);
-        assert_eq!(metrics.alerts.len(), 1);
-        assert!(metrics.alerts[0].contains("DROP"));
+        let fired = check_alerts(&stats, Some(100), &russian);
+        assert_eq!(fired[0].1, "⚠️ РЕЗКОЕ ПАДЕНИЕ: -20 держателей (-20.0%) | 100 -> 80");
     }
 }
 
@@ -0,0 +1,69 @@
+//! Truncation guard: some RPCs cap the getProgramAccounts response size and silently drop
+//! accounts. The balances of all token accounts of a mint add up to its supply, so a response
+//! whose balances fall short of `getTokenSupply` is missing accounts.
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+/// What to do when a response looks truncated (`--truncation-check`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum TruncationMode {
+    /// Don't compare with the supply
+    Off,
+    /// Keep the count but flag it (`missing_supply_percent`)
+    #[default]
+    Flag,
+    /// Fail the fetch (retried like any RPC error)
+    Error,
+}
+
+/// Default share of the supply that may be missing before truncation is suspected; supply
+/// and accounts are read in separate requests, so mints and burns in between are tolerated
+pub const DEFAULT_TOLERANCE_PERCENT: f64 = 1.0;
+
+/// Balances of a fetched account set compared with the mint supply
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct SupplyCheck {
+    /// Raw supply reported by getTokenSupply
+    pub supply: u64,
+    /// Sum of the raw balances of the fetched accounts
+    pub accounted: u128,
+}
+
+impl SupplyCheck {
+    /// Share of the supply not found in the fetched accounts (0 when nothing is missing)
+    pub fn missing_percent(&self) -> f64 {
+        if self.supply == 0 {
+            return 0.0;
+        }
+        let missing = u128::from(self.supply).saturating_sub(self.accounted);
+        missing as f64 / self.supply as f64 * 100.0
+    }
+
+    pub fn is_suspect(&self, tolerance_percent: f64) -> bool {
+        self.missing_percent() > tolerance_percent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_percent() {
+        let complete = SupplyCheck { supply: 1_000, accounted: 1_000 };
+        assert_eq!(complete.missing_percent(), 0.0);
+        assert!(!complete.is_suspect(DEFAULT_TOLERANCE_PERCENT));
+
+        // Burned after the accounts were read: more balance than supply is not a truncation
+        assert_eq!(SupplyCheck { supply: 900, accounted: 1_000 }.missing_percent(), 0.0);
+
+        let small_gap = SupplyCheck { supply: 1_000, accounted: 995 };
+        assert!(!small_gap.is_suspect(DEFAULT_TOLERANCE_PERCENT));
+        let truncated = SupplyCheck { supply: 1_000, accounted: 600 };
+        assert_eq!(truncated.missing_percent(), 40.0);
+        assert!(truncated.is_suspect(DEFAULT_TOLERANCE_PERCENT));
+
+        assert_eq!(SupplyCheck { supply: 0, accounted: 0 }.missing_percent(), 0.0);
+    }
+}
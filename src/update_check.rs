@@ -0,0 +1,110 @@
+//! Startup check for a newer release on GitHub: logs a notice, never downloads or installs
+//! anything. Disabled with `--no-update-check`
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+use crate::build_info::VERSION;
+use crate::http_tuning::DEFAULT_USER_AGENT;
+
+/// Latest non-prerelease release of the project
+pub const RELEASES_URL: &str = "https://api.github.com/repos/alcheremnyh/sol-bot/releases/latest";
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Deserialize)]
+struct Release {
+    tag_name: String,
+    html_url: String,
+}
+
+/// A release newer than the running binary
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AvailableUpdate {
+    pub version: String,
+    pub url: String,
+}
+
+/// `1.2.3` from `v1.2.3`, `1.2` or `1.2.3-rc.1` (pre-release and build suffixes are dropped)
+pub fn parse_version(value: &str) -> Option<(u64, u64, u64)> {
+    let value = value.trim().trim_start_matches(['v', 'V']);
+    let core = value.split(['-', '+']).next()?;
+    let mut parts = core.split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next().map_or(Some(0), |part| part.parse().ok())?;
+    let patch = parts.next().map_or(Some(0), |part| part.parse().ok())?;
+    if parts.next().is_some() {
+        return None;
+    }
+    Some((major, minor, patch))
+}
+
+/// True when `latest` parses and is newer than `current`
+pub fn is_newer(latest: &str, current: &str) -> bool {
+    match (parse_version(latest), parse_version(current)) {
+        (Some(latest), Some(current)) => latest > current,
+        _ => false,
+    }
+}
+
+/// Ask GitHub for the latest release; None when the running version is current
+pub async fn check(url: &str) -> Result<Option<AvailableUpdate>> {
+    let client = reqwest::Client::builder()
+        .timeout(TIMEOUT)
+        // GitHub rejects API requests without a User-Agent
+        .user_agent(DEFAULT_USER_AGENT)
+        .build()?;
+    let release: Release = client
+        .get(url)
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await
+        .context("Unexpected release response")?;
+    if !is_newer(&release.tag_name, VERSION) {
+        return Ok(None);
+    }
+    let version = release.tag_name.trim_start_matches(['v', 'V']).to_string();
+    Ok(Some(AvailableUpdate { version, url: release.html_url }))
+}
+
+/// Run the check in the background; failures only show up at debug level
+pub fn spawn() {
+    tokio::spawn(async {
+        match check(RELEASES_URL).await {
+            Ok(Some(update)) => warn!(
+                "solana-holder-bot {} is available (running {}): {} (disable this check with --no-update-check)",
+                update.version, VERSION, update.url
+            ),
+            Ok(None) => debug!("solana-holder-bot {} is the latest release", VERSION),
+            Err(e) => debug!("Update check failed: {:#}", e),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version("v1.2.3"), Some((1, 2, 3)));
+        assert_eq!(parse_version("0.4"), Some((0, 4, 0)));
+        assert_eq!(parse_version("2.0.0-rc.1"), Some((2, 0, 0)));
+        assert_eq!(parse_version("1.2.3.4"), None);
+        assert_eq!(parse_version("latest"), None);
+    }
+
+    #[test]
+    fn test_is_newer() {
+        assert!(is_newer("v0.10.0", "0.9.7"));
+        assert!(is_newer("1.0.1", "1.0.0"));
+        assert!(!is_newer("v1.0.0", "1.0.0"));
+        assert!(!is_newer("0.9.0", "1.0.0"));
+        assert!(!is_newer("nightly", "1.0.0"));
+    }
+}
@@ -0,0 +1,425 @@
+//! `verify` subcommand: cross-check the holder count against explorer holder APIs and explain
+//! the difference from what the bot filters (zero balances, dust, frozen accounts, Token-2022)
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::Value;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::account::Account;
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::extensions::{withheld_amount, MintInfo};
+use crate::rpc_client::SolanaRpcClient;
+use crate::token_monitor::TokenAccountLayout;
+
+const SOLSCAN_API_URL: &str = "https://pro-api.solscan.io/v2.0";
+const BIRDEYE_API_URL: &str = "https://public-api.birdeye.so";
+
+/// Size of an SPL mint without extensions; Token-2022 mints with extensions are longer
+const MINT_LEN: usize = 82;
+/// `AccountState::Frozen` in the token account `state` byte
+const FROZEN_STATE: u8 = 2;
+/// Explorer counts this close to an explained count (share of the explorer count) are a match
+const MATCH_TOLERANCE: f64 = 0.005;
+
+/// What the bot's count includes and leaves out, from the mint's token accounts
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct CountFactors {
+    /// Owners with a non-zero balance: the bot's holder count
+    pub holders: usize,
+    pub token_accounts: usize,
+    pub zero_balance_accounts: usize,
+    /// Owners whose every account is empty (counted by explorers that list token accounts)
+    pub zero_balance_owners: usize,
+    /// Owners holding the token in more than one account
+    pub multi_account_owners: usize,
+    /// Holders with frozen accounts only
+    pub frozen_holders: usize,
+    /// Holders with less than one whole token (UI amount, interest included)
+    pub dust_holders: usize,
+    pub decimals: Option<u8>,
+    /// Program owning the mint account
+    pub mint_program: Option<String>,
+    /// The mint has Token-2022 extensions (their token accounts are only fetched with
+    /// `--token-program` set to Token-2022)
+    pub has_extensions: bool,
+    /// Token-2022 transfer fees withheld in token accounts and on the mint (raw units, in the
+    /// supply but in nobody's balance)
+    pub withheld_fees: u64,
+    /// Token-2022 interest-bearing mint: UI amounts grow over the raw balances
+    pub interest_bearing: bool,
+}
+
+/// One explorer's count against the bot's
+#[derive(Debug, Clone, Serialize)]
+pub struct ExplorerCheck {
+    pub source: &'static str,
+    pub holders: Option<usize>,
+    /// Explorer minus bot
+    pub difference: Option<i64>,
+    pub difference_percent: Option<f64>,
+    pub likely_causes: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VerifyReport {
+    pub mint: String,
+    pub token_program: String,
+    pub factors: CountFactors,
+    pub explorers: Vec<ExplorerCheck>,
+}
+
+/// Explorer API keys; explorers without a key are skipped
+#[derive(Debug, Clone, Default)]
+pub struct ExplorerKeys {
+    pub solscan: Option<String>,
+    pub birdeye: Option<String>,
+}
+
+/// Count factors from the mint's token accounts and its mint account data (`now` in unix
+/// seconds, for interest accrued by interest-bearing mints)
+pub fn analyze_accounts(accounts: &[(Pubkey, Account)], mint: Option<&MintInfo>, now: i64) -> CountFactors {
+    // Per owner: total balance, accounts, frozen accounts with a balance
+    let mut owners: HashMap<Pubkey, (u64, usize, usize)> = HashMap::new();
+    let mut factors = CountFactors {
+        decimals: mint.map(|mint| mint.decimals),
+        interest_bearing: mint.is_some_and(|mint| mint.interest.is_some()),
+        withheld_fees: mint.and_then(|mint| mint.transfer_fee).map_or(0, |fee| fee.withheld_amount),
+        ..CountFactors::default()
+    };
+    for (_, account) in accounts {
+        let Some(layout) = TokenAccountLayout::from_data(&account.data) else {
+            continue;
+        };
+        factors.token_accounts += 1;
+        factors.withheld_fees += withheld_amount(&account.data);
+        let amount = layout.amount();
+        if amount == 0 {
+            factors.zero_balance_accounts += 1;
+        }
+        let entry = owners.entry(layout.owner()).or_default();
+        entry.0 += amount;
+        entry.1 += 1;
+        if layout.state == FROZEN_STATE && amount > 0 {
+            entry.2 += 1;
+        }
+    }
+
+    for (balance, accounts, frozen) in owners.values() {
+        if *balance == 0 {
+            factors.zero_balance_owners += 1;
+            continue;
+        }
+        factors.holders += 1;
+        if *accounts > 1 {
+            factors.multi_account_owners += 1;
+        }
+        if *frozen > 0 && *frozen == *accounts {
+            factors.frozen_holders += 1;
+        }
+        if mint.is_some_and(|mint| mint.ui_amount(*balance, now) < 1.0) {
+            factors.dust_holders += 1;
+        }
+    }
+    factors
+}
+
+/// Explanations whose adjusted count matches the explorer's, or every applicable one when none does
+pub fn likely_causes(factors: &CountFactors, explorer: usize, token_program: &str) -> Vec<String> {
+    let mut causes = Vec::new();
+    if let Some(program) = &factors.mint_program {
+        if program != token_program {
+            causes.push(format!(
+                "the mint belongs to {} but the bot counts accounts of {} (use --token-program {})",
+                program, token_program, program
+            ));
+        }
+    }
+    let bot = factors.holders as i64;
+    let candidates = [
+        (bot + factors.zero_balance_owners as i64, "the explorer also counts owners with only empty accounts"),
+        (
+            (factors.token_accounts - factors.zero_balance_accounts) as i64,
+            "the explorer counts token accounts rather than owners (some owners have several)",
+        ),
+        (factors.token_accounts as i64, "the explorer counts every token account, empty ones included"),
+        (bot - factors.dust_holders as i64, "the explorer filters dust holders (less than one token)"),
+        (bot - factors.frozen_holders as i64, "the explorer leaves out holders with frozen accounts"),
+    ];
+    let tolerance = ((explorer as f64 * MATCH_TOLERANCE).round() as i64).max(1);
+    let explorer = explorer as i64;
+    if explorer == bot {
+        return causes;
+    }
+    let mut matched: Vec<String> = candidates
+        .iter()
+        .filter(|(adjusted, _)| *adjusted != bot && (adjusted - explorer).abs() <= tolerance)
+        .map(|(_, cause)| cause.to_string())
+        .collect();
+    if matched.is_empty() {
+        // No single factor explains it: list the ones pointing the right way
+        matched = candidates
+            .iter()
+            .filter(|(adjusted, _)| (*adjusted > bot) == (explorer > bot) && *adjusted != bot)
+            .map(|(_, cause)| format!("{} (partly)", cause))
+            .collect();
+    }
+    if explorer > bot {
+        matched.push("the explorer's index may lag behind or include accounts closed since".to_string());
+    }
+    causes.extend(matched);
+    causes
+}
+
+/// `data.total` of Solscan's `/token/holders`
+pub fn parse_solscan_holders(response: &Value) -> Option<usize> {
+    response["data"]["total"].as_u64().map(|total| total as usize)
+}
+
+/// `data.holder` of Birdeye's `/defi/token_overview`
+pub fn parse_birdeye_holders(response: &Value) -> Option<usize> {
+    response["data"]["holder"].as_u64().map(|holder| holder as usize)
+}
+
+async fn get_json(request: reqwest::RequestBuilder, source: &str) -> Result<Value> {
+    let response = request
+        .timeout(Duration::from_secs(15))
+        .send()
+        .await
+        .with_context(|| format!("Failed to reach {}", source))?;
+    let status = response.status();
+    if !status.is_success() {
+        let text = response.text().await.unwrap_or_default();
+        return Err(anyhow::anyhow!("{} returned {}: {}", source, status, text));
+    }
+    response.json().await.with_context(|| format!("Invalid JSON from {}", source))
+}
+
+async fn fetch_solscan(client: &reqwest::Client, key: &str, mint: &Pubkey) -> Result<usize> {
+    let url = format!("{}/token/holders?address={}&page=1&page_size=10", SOLSCAN_API_URL, mint);
+    let response = get_json(client.get(url).header("token", key), "Solscan").await?;
+    parse_solscan_holders(&response).context("Solscan response has no data.total")
+}
+
+async fn fetch_birdeye(client: &reqwest::Client, key: &str, mint: &Pubkey) -> Result<usize> {
+    let url = format!("{}/defi/token_overview?address={}", BIRDEYE_API_URL, mint);
+    let request = client.get(url).header("X-API-KEY", key).header("x-chain", "solana");
+    let response = get_json(request, "Birdeye").await?;
+    parse_birdeye_holders(&response).context("Birdeye response has no data.holder")
+}
+
+fn check(source: &'static str, result: Result<usize>, factors: &CountFactors, token_program: &str) -> ExplorerCheck {
+    match result {
+        Ok(holders) => {
+            let difference = holders as i64 - factors.holders as i64;
+            ExplorerCheck {
+                source,
+                holders: Some(holders),
+                difference: Some(difference),
+                difference_percent: (factors.holders > 0)
+                    .then(|| difference as f64 / factors.holders as f64 * 100.0),
+                likely_causes: likely_causes(factors, holders, token_program),
+                error: None,
+            }
+        }
+        Err(e) => ExplorerCheck {
+            source,
+            holders: None,
+            difference: None,
+            difference_percent: None,
+            likely_causes: Vec::new(),
+            error: Some(format!("{:#}", e)),
+        },
+    }
+}
+
+/// Count the mint's holders over RPC and compare with every explorer that has a key
+pub async fn verify_mint(
+    rpc_client: &SolanaRpcClient,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    keys: &ExplorerKeys,
+) -> Result<VerifyReport> {
+    let mint_account = rpc_client
+        .get_multiple_accounts(&[*mint], true)
+        .await?
+        .pop()
+        .flatten()
+        .with_context(|| format!("Mint account {} not found", mint))?;
+    let mint_info = MintInfo::parse(&mint_account.data);
+    // Accounts of another token program than the mint's come back empty, which the client reports
+    // as an error; that mismatch is what the report is for
+    let accounts = match rpc_client.get_token_accounts_by_mint(mint).await {
+        Ok(accounts) => accounts,
+        Err(_) if mint_account.owner != *token_program => Vec::new(),
+        Err(e) => return Err(e),
+    };
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() as i64);
+    let mut factors = analyze_accounts(&accounts, mint_info.as_ref(), now);
+    factors.mint_program = Some(mint_account.owner.to_string());
+    factors.has_extensions = mint_account.data.len() > MINT_LEN;
+
+    let token_program = token_program.to_string();
+    let client = reqwest::Client::new();
+    let mut explorers = Vec::new();
+    if let Some(key) = &keys.solscan {
+        explorers.push(check("solscan", fetch_solscan(&client, key, mint).await, &factors, &token_program));
+    }
+    if let Some(key) = &keys.birdeye {
+        explorers.push(check("birdeye", fetch_birdeye(&client, key, mint).await, &factors, &token_program));
+    }
+
+    Ok(VerifyReport {
+        mint: mint.to_string(),
+        token_program,
+        factors,
+        explorers,
+    })
+}
+
+/// Print a report as text or JSON
+pub fn print_verify_report(report: &VerifyReport, json: bool) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(report)?);
+        return Ok(());
+    }
+
+    let factors = &report.factors;
+    println!("Holder count check for {}", report.mint);
+    println!("  Bot count:             {}", factors.holders);
+    println!("  Token accounts:        {} ({} empty)", factors.token_accounts, factors.zero_balance_accounts);
+    println!("  Owners, only empty:    {}", factors.zero_balance_owners);
+    println!("  Owners, 2+ accounts:   {}", factors.multi_account_owners);
+    println!("  Frozen-only holders:   {}", factors.frozen_holders);
+    match factors.decimals {
+        Some(_) => println!("  Dust (< 1 token):      {}", factors.dust_holders),
+        None => println!("  Dust (< 1 token):      unknown (no decimals)"),
+    }
+    if factors.interest_bearing {
+        println!("  Interest-bearing mint: dust uses UI amounts with accrued interest");
+    }
+    if factors.withheld_fees > 0 {
+        println!("  Withheld fees (raw):   {}", factors.withheld_fees);
+    }
+    if report.explorers.is_empty() {
+        println!("\nNo explorer keys given (--solscan-api-key, --birdeye-api-key)");
+    }
+    for explorer in &report.explorers {
+        match (explorer.holders, explorer.difference) {
+            (Some(holders), Some(difference)) => println!(
+                "\n{}: {} holders ({:+}, {:+.2}%)",
+                explorer.source,
+                holders,
+                difference,
+                explorer.difference_percent.unwrap_or_default()
+            ),
+            _ => println!("\n{}: {}", explorer.source, explorer.error.as_deref().unwrap_or("no count")),
+        }
+        for cause in &explorer.likely_causes {
+            println!("  - {}", cause);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::token_account;
+
+    #[test]
+    fn test_analyze_accounts() {
+        let mint = Pubkey::new_unique();
+        let (whale, dust, empty, frozen) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let mut frozen_account = token_account(&mint, &frozen, 5_000_000);
+        frozen_account.1.data[108] = FROZEN_STATE;
+        let accounts = vec![
+            token_account(&mint, &whale, 9_000_000),
+            token_account(&mint, &whale, 1),
+            token_account(&mint, &dust, 10),
+            token_account(&mint, &empty, 0),
+            frozen_account,
+        ];
+
+        let mint_info = MintInfo { decimals: 6, transfer_fee: None, interest: None };
+        let factors = analyze_accounts(&accounts, Some(&mint_info), 0);
+        assert_eq!(factors.holders, 3);
+        assert_eq!(factors.token_accounts, 5);
+        assert_eq!(factors.zero_balance_accounts, 1);
+        assert_eq!(factors.zero_balance_owners, 1);
+        assert_eq!(factors.multi_account_owners, 1);
+        assert_eq!(factors.frozen_holders, 1);
+        assert_eq!(factors.dust_holders, 1);
+    }
+
+    #[test]
+    fn test_analyze_token_2022_accounts() {
+        let mint = Pubkey::new_unique();
+        // 0.9 tokens with 250 raw units of transfer fees withheld (TransferFeeAmount extension)
+        let mut account = token_account(&mint, &Pubkey::new_unique(), 900_000);
+        account.1.data.push(crate::extensions::ACCOUNT_TYPE_ACCOUNT);
+        account.1.data.extend_from_slice(&2u16.to_le_bytes());
+        account.1.data.extend_from_slice(&8u16.to_le_bytes());
+        account.1.data.extend_from_slice(&250u64.to_le_bytes());
+        let accounts = vec![account];
+
+        let plain = MintInfo { decimals: 6, transfer_fee: None, interest: None };
+        let factors = analyze_accounts(&accounts, Some(&plain), 0);
+        assert_eq!(factors.token_accounts, 1);
+        assert_eq!(factors.withheld_fees, 250);
+        assert_eq!(factors.dust_holders, 1);
+
+        // 100% a year: after a year the 0.9 raw tokens read as ~2.4 and are no longer dust
+        let interest = crate::extensions::InterestRate {
+            initialization_timestamp: 0,
+            pre_update_average_rate: 10_000,
+            last_update_timestamp: 0,
+            current_rate: 10_000,
+        };
+        let bearing = MintInfo { interest: Some(interest), ..plain };
+        assert_eq!(analyze_accounts(&accounts, Some(&bearing), 0).dust_holders, 1);
+        let factors = analyze_accounts(&accounts, Some(&bearing), 31_556_926);
+        assert!(factors.interest_bearing);
+        assert_eq!(factors.dust_holders, 0);
+    }
+
+    #[test]
+    fn test_likely_causes() {
+        let factors = CountFactors {
+            holders: 1000,
+            token_accounts: 1300,
+            zero_balance_accounts: 200,
+            zero_balance_owners: 150,
+            dust_holders: 400,
+            ..CountFactors::default()
+        };
+        let program = crate::rpc_client::TOKEN_PROGRAM_ID;
+        assert!(likely_causes(&factors, 1000, program).is_empty());
+        assert_eq!(likely_causes(&factors, 600, program), vec!["the explorer filters dust holders (less than one token)"]);
+        let causes = likely_causes(&factors, 1150, program);
+        assert!(causes[0].contains("empty accounts"));
+        assert!(likely_causes(&factors, 1100, program)[0].contains("rather than owners"));
+
+        let token_2022 = CountFactors {
+            mint_program: Some(Pubkey::new_unique().to_string()),
+            ..factors
+        };
+        assert!(likely_causes(&token_2022, 1000, program)[0].contains("--token-program"));
+    }
+
+    #[test]
+    fn test_parse_explorer_responses() {
+        let solscan = serde_json::json!({"success": true, "data": {"total": 1234, "items": []}});
+        assert_eq!(parse_solscan_holders(&solscan), Some(1234));
+        let birdeye = serde_json::json!({"success": true, "data": {"holder": 99}});
+        assert_eq!(parse_birdeye_holders(&birdeye), Some(99));
+        assert_eq!(parse_birdeye_holders(&serde_json::json!({"success": false})), None);
+    }
+}
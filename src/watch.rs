@@ -0,0 +1,330 @@
+//! Wallet watch: wallets of interest per monitored mint, from `--watch-wallet` (every mint) or
+//! `POST /watch/:mint/:wallet`. Each poll updates their balance; a wallet entering or leaving the
+//! holders is published as a `wallet_entered` / `wallet_exited` event, a holder moving between the
+//! mint's balance tiers as `wallet_tier_changed` (SSE, brokers, sinks) and, with
+//! `--watch-webhook-url`, POSTed there, e.g. for token-gated Discord roles
+
+use anyhow::Result;
+use serde::Serialize;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::account::Account;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::info;
+
+use crate::events::HolderEvent;
+use crate::publisher::PublishedEvent;
+use crate::redact::redact_in;
+use crate::sinks::{Sink, SinkFuture};
+use crate::extensions::MintInfo;
+use crate::tiers::TierSchedule;
+use crate::token_monitor::balances_of;
+
+/// Timeout of one webhook delivery
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchChange {
+    Entered,
+    Exited,
+    /// Still a holder, in another balance tier
+    TierChanged,
+}
+
+/// A watched wallet entered or left the holders of a mint, or changed tier
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchEvent {
+    pub change: WatchChange,
+    pub mint: Pubkey,
+    pub wallet: Pubkey,
+    /// Raw balance after the change (0 on exit)
+    pub balance: u64,
+    /// Balance in whole tokens with accrued interest, when the mint account could be read
+    pub ui_balance: Option<f64>,
+    pub timestamp: u64,
+    /// Holder count of the poll that showed the change
+    pub holders: usize,
+    /// Balance tier after the change, if the mint has tiers
+    pub tier: Option<String>,
+    pub previous_tier: Option<String>,
+}
+
+/// A watched wallet as returned by `GET /watch`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WatchedWallet {
+    pub mint: String,
+    pub wallet: String,
+    /// Whether the wallet holds the mint; None until the mint is polled
+    pub holding: Option<bool>,
+    /// Raw balance at the last poll
+    pub balance: Option<u64>,
+    /// Balance in whole tokens with accrued interest, when the mint account could be read
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ui_balance: Option<f64>,
+    /// Balance tier at the last poll, if the mint has tiers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tier: Option<String>,
+    pub registered_at: u64,
+    /// Last poll that included the wallet
+    pub observed_at: Option<u64>,
+    /// Last entry, exit or tier change
+    pub changed_at: Option<u64>,
+}
+
+impl WatchedWallet {
+    fn new(mint: &Pubkey, wallet: &Pubkey, now: u64) -> Self {
+        Self {
+            mint: mint.to_string(),
+            wallet: wallet.to_string(),
+            holding: None,
+            balance: None,
+            ui_balance: None,
+            tier: None,
+            registered_at: now,
+            observed_at: None,
+            changed_at: None,
+        }
+    }
+}
+
+/// Watched wallets and their membership, shared by the monitor and the API
+/// The first poll after a wallet is registered is its baseline: only later changes are events
+pub struct WalletWatch {
+    /// `--watch-wallet`: watched on every monitored mint
+    global: HashSet<Pubkey>,
+    entries: Mutex<HashMap<(Pubkey, Pubkey), WatchedWallet>>,
+    webhook_url: Option<String>,
+    http_client: reqwest::Client,
+}
+
+impl WalletWatch {
+    pub fn new(global: impl IntoIterator<Item = Pubkey>, webhook_url: Option<String>) -> Self {
+        Self {
+            global: global.into_iter().collect(),
+            entries: Mutex::default(),
+            webhook_url,
+            http_client: reqwest::Client::new(),
+        }
+    }
+
+    pub fn global_wallets(&self) -> usize {
+        self.global.len()
+    }
+
+    pub fn webhook_url(&self) -> Option<&str> {
+        self.webhook_url.as_deref()
+    }
+
+    /// Watch `wallet` on `mint`; registering it again keeps its state
+    pub fn register(&self, mint: &Pubkey, wallet: &Pubkey, now: u64) -> WatchedWallet {
+        let mut entries = self.entries.lock().expect("watch lock poisoned");
+        entries
+            .entry((*mint, *wallet))
+            .or_insert_with(|| WatchedWallet::new(mint, wallet, now))
+            .clone()
+    }
+
+    /// Watched wallets, optionally of one mint, ordered by mint then wallet
+    pub fn list(&self, mint: Option<&Pubkey>) -> Vec<WatchedWallet> {
+        let entries = self.entries.lock().expect("watch lock poisoned");
+        let mut list: Vec<WatchedWallet> = entries
+            .iter()
+            .filter(|((entry_mint, _), _)| mint.is_none_or(|mint| mint == entry_mint))
+            .map(|(_, entry)| entry.clone())
+            .collect();
+        list.sort_by(|a, b| (&a.mint, &a.wallet).cmp(&(&b.mint, &b.wallet)));
+        list
+    }
+
+    /// Update the watched wallets of `mint` from a full poll's accounts; returns the entries, exits
+    /// and tier changes. `mint_info` (None: unknown) gives the UI balances
+    pub fn observe(
+        &self,
+        mint: &Pubkey,
+        accounts: &[(Pubkey, Account)],
+        holders: usize,
+        tiers: Option<&TierSchedule>,
+        mint_info: Option<&MintInfo>,
+        now: u64,
+    ) -> Vec<WatchEvent> {
+        let watched: HashSet<Pubkey> = {
+            let mut entries = self.entries.lock().expect("watch lock poisoned");
+            for wallet in &self.global {
+                entries.entry((*mint, *wallet)).or_insert_with(|| WatchedWallet::new(mint, wallet, now));
+            }
+            entries.keys().filter(|(entry_mint, _)| entry_mint == mint).map(|(_, wallet)| *wallet).collect()
+        };
+        if watched.is_empty() {
+            return Vec::new();
+        }
+        let balances = balances_of(accounts, &watched);
+
+        let mut entries = self.entries.lock().expect("watch lock poisoned");
+        let mut events = Vec::new();
+        for wallet in watched {
+            let Some(entry) = entries.get_mut(&(*mint, wallet)) else {
+                continue;
+            };
+            let balance = balances.get(&wallet).copied().unwrap_or_default();
+            let holding = balance > 0;
+            let ui_balance = mint_info.map(|info| info.ui_amount(balance, now as i64));
+            let tier = tiers.and_then(|tiers| tiers.tier_of(balance, now as i64)).map(str::to_string);
+            let change = match entry.holding {
+                Some(was) if was != holding => Some(if holding { WatchChange::Entered } else { WatchChange::Exited }),
+                Some(true) if entry.tier != tier => Some(WatchChange::TierChanged),
+                _ => None,
+            };
+            if let Some(change) = change {
+                events.push(WatchEvent {
+                    change,
+                    mint: *mint,
+                    wallet,
+                    balance,
+                    ui_balance,
+                    timestamp: now,
+                    holders,
+                    tier: tier.clone(),
+                    previous_tier: entry.tier.clone(),
+                });
+                entry.changed_at = Some(now);
+            }
+            entry.tier = tier;
+            entry.holding = Some(holding);
+            entry.balance = Some(balance);
+            entry.ui_balance = ui_balance;
+            entry.observed_at = Some(now);
+        }
+        events.sort_by_key(|event| event.wallet.to_string());
+        events
+    }
+
+    async fn deliver(&self, url: &str, event: &PublishedEvent) -> Result<()> {
+        self.http_client
+            .post(url)
+            .timeout(WEBHOOK_TIMEOUT)
+            .json(event)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| anyhow::anyhow!(redact_in(&e.to_string(), url)))?;
+        Ok(())
+    }
+}
+
+/// Logs watch events and POSTs them to `--watch-webhook-url`
+impl Sink for WalletWatch {
+    fn name(&self) -> &str {
+        "watch"
+    }
+
+    fn handle<'a>(&'a self, event: &'a HolderEvent) -> SinkFuture<'a> {
+        Box::pin(async move {
+            let HolderEvent::Watch(watch) = event else {
+                return Ok(());
+            };
+            match watch.change {
+                WatchChange::Entered => info!("Watched wallet {} joined the holders of {}", watch.wallet, watch.mint),
+                WatchChange::Exited => info!("Watched wallet {} left the holders of {}", watch.wallet, watch.mint),
+                WatchChange::TierChanged => info!(
+                    "Watched wallet {} moved from tier {} to {} on {}",
+                    watch.wallet,
+                    watch.previous_tier.as_deref().unwrap_or("none"),
+                    watch.tier.as_deref().unwrap_or("none"),
+                    watch.mint
+                ),
+            }
+            match &self.webhook_url {
+                Some(url) => self.deliver(url, &PublishedEvent::from(event)).await,
+                None => Ok(()),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc_client::TOKEN_PROGRAM_ID;
+    use crate::tiers::TierConfig;
+    use crate::token_monitor::encode_token_account;
+    use std::str::FromStr;
+
+    fn account(mint: &Pubkey, owner: &Pubkey, amount: u64) -> (Pubkey, Account) {
+        let account = Account {
+            lamports: 0,
+            data: encode_token_account(mint, owner, amount),
+            owner: Pubkey::from_str(TOKEN_PROGRAM_ID).unwrap(),
+            executable: false,
+            rent_epoch: 0,
+        };
+        (Pubkey::new_unique(), account)
+    }
+
+    #[test]
+    fn test_observe() {
+        let (global, registered, other) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let mint = Pubkey::new_unique();
+        let watch = WalletWatch::new([global], None);
+        watch.register(&mint, &registered, 100);
+
+        // Baseline: nothing reported
+        let accounts = vec![account(&mint, &global, 5), account(&mint, &other, 7)];
+        assert!(watch.observe(&mint, &accounts, 2, None, None, 200).is_empty());
+        let list = watch.list(Some(&mint));
+        assert_eq!(list.len(), 2);
+        let entry = list.iter().find(|entry| entry.wallet == global.to_string()).unwrap();
+        assert_eq!((entry.holding, entry.balance, entry.ui_balance), (Some(true), Some(5), None));
+
+        let accounts = vec![account(&mint, &registered, 3), account(&mint, &registered, 4), account(&mint, &other, 7)];
+        let events = watch.observe(&mint, &accounts, 2, None, None, 300);
+        assert_eq!(events.len(), 2);
+        let entered = events.iter().find(|event| event.wallet == registered).unwrap();
+        assert_eq!((entered.change, entered.balance), (WatchChange::Entered, 7));
+        let exited = events.iter().find(|event| event.wallet == global).unwrap();
+        assert_eq!((exited.change, exited.balance), (WatchChange::Exited, 0));
+
+        // Registering again keeps the state
+        assert_eq!(watch.register(&mint, &registered, 400).changed_at, Some(300));
+        assert!(watch.list(Some(&Pubkey::new_unique())).is_empty());
+    }
+
+    #[test]
+    fn test_tier_changes() {
+        let wallet = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let mint_info = MintInfo { decimals: 2, transfer_fee: None, interest: None };
+        let tiers = TierSchedule::new(
+            &[
+                TierConfig { name: "bronze".to_string(), min: 1.0 },
+                TierConfig { name: "silver".to_string(), min: 10.0 },
+            ],
+            mint_info,
+        );
+        let watch = WalletWatch::new([wallet], None);
+        let observe = |amount: u64, now: u64| {
+            let accounts = vec![account(&mint, &wallet, amount)];
+            watch.observe(&mint, &accounts, 1, Some(&tiers), Some(&mint_info), now)
+        };
+
+        assert!(observe(150, 100).is_empty());
+        assert_eq!(watch.list(None)[0].tier.as_deref(), Some("bronze"));
+        assert_eq!(watch.list(None)[0].ui_balance, Some(1.5));
+        // Same tier: nothing to report
+        assert!(observe(900, 200).is_empty());
+
+        let events = observe(1_000, 300);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].change, WatchChange::TierChanged);
+        assert_eq!((events[0].tier.as_deref(), events[0].previous_tier.as_deref()), (Some("silver"), Some("bronze")));
+
+        let events = observe(0, 400);
+        assert_eq!(events[0].change, WatchChange::Exited);
+        assert_eq!((events[0].tier.as_deref(), events[0].previous_tier.as_deref()), (None, Some("silver")));
+
+        // Entering below every tier
+        let events = observe(50, 500);
+        assert_eq!(events[0].change, WatchChange::Entered);
+        assert_eq!(events[0].tier, None);
+    }
+}
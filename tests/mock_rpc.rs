@@ -0,0 +1,918 @@
+//! Integration tests against the mock JSON-RPC server
+//! Run with `cargo test --features test-util`
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use clap::Parser;
+use solana_holder_bot::api::{create_api_router, HolderCache, MonitorMetrics};
+use solana_holder_bot::api_keys::{ApiKeyConfig, ApiKeys};
+use solana_holder_bot::cli::Cli;
+use solana_holder_bot::cluster::Cluster;
+use solana_holder_bot::doctor::{diagnose, CheckStatus};
+use solana_holder_bot::events::{AlertEvent, EventBus, HolderEvent, Observation};
+use solana_holder_bot::history::CountHistory;
+use solana_holder_bot::http_tuning::{HttpTuning, DEFAULT_USER_AGENT};
+use solana_holder_bot::protocols::ProtocolConfig;
+use solana_holder_bot::rpc_client::{Fallback, ProgramAccountsSupport};
+use solana_holder_bot::sinks::SinkRegistry;
+use solana_holder_bot::test_util::{
+    canned_program_accounts, mock_block_time, owner_account, token_account, MockRpcServer, FINALIZED_LAG,
+};
+use solana_holder_bot::token_monitor::AlertRule;
+use solana_holder_bot::truncation::TruncationMode;
+use solana_holder_bot::{calculate_stats, extract_holders, Metrics, SolanaRpcClient};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use std::io::Read;
+use std::str::FromStr;
+use std::sync::Arc;
+use tower::ServiceExt;
+
+#[tokio::test]
+async fn test_fetch_holders_from_mock() {
+    let mint = Pubkey::new_unique();
+    let other_mint = Pubkey::new_unique();
+    let mut accounts = canned_program_accounts(&mint);
+    accounts.push(token_account(&other_mint, &Pubkey::new_unique(), 5));
+    let server = MockRpcServer::builder().accounts(accounts).start().await;
+
+    let client = SolanaRpcClient::new(server.url.clone(), 3, 5);
+    client.health_check().await.unwrap();
+
+    let accounts = client.get_token_accounts_by_mint(&mint).await.unwrap();
+    assert_eq!(accounts.len(), 6);
+    assert_eq!(extract_holders(&accounts).unwrap().len(), 4);
+}
+
+#[tokio::test]
+async fn test_zero_balance_holders() {
+    let mint = Pubkey::new_unique();
+    let server = MockRpcServer::builder().accounts(canned_program_accounts(&mint)).start().await;
+
+    let client = SolanaRpcClient::new(server.url.clone(), 3, 5);
+    let snapshot = client.get_token_accounts_snapshot(&mint).await.unwrap();
+    let (holders, zero_balance_owners) = client.holders_of(&snapshot).unwrap();
+    assert_eq!((holders.len(), zero_balance_owners), (4, 1));
+    assert!(!client.count_metadata(&mint, &snapshot).zero_balance_included);
+
+    let client = SolanaRpcClient::new(server.url.clone(), 3, 5).with_zero_balance_holders(true);
+    let (holders, zero_balance_owners) = client.holders_of(&snapshot).unwrap();
+    assert_eq!((holders.len(), zero_balance_owners), (5, 1));
+    assert!(client.count_metadata(&mint, &snapshot).zero_balance_included);
+}
+
+#[tokio::test]
+async fn test_rpc_user_agent() {
+    let server = MockRpcServer::builder().start().await;
+    let client = SolanaRpcClient::new(server.url.clone(), 1, 5)
+        .with_http_tuning(&HttpTuning::default())
+        .unwrap();
+    client.health_check().await.unwrap();
+    assert_eq!(server.last_user_agent().as_deref(), Some(DEFAULT_USER_AGENT));
+
+    let tuning = HttpTuning { user_agent: Some("acme-bot/1.0".to_string()), ..HttpTuning::default() };
+    let client = SolanaRpcClient::new(server.url.clone(), 1, 5).with_http_tuning(&tuning).unwrap();
+    client.health_check().await.unwrap();
+    assert_eq!(server.last_user_agent().as_deref(), Some("acme-bot/1.0"));
+}
+
+#[tokio::test]
+async fn test_snapshot_slot() {
+    let mint = Pubkey::new_unique();
+    let server = MockRpcServer::builder()
+        .accounts(canned_program_accounts(&mint))
+        .slot(123_456)
+        .start()
+        .await;
+    let client = Arc::new(SolanaRpcClient::new(server.url.clone(), 1, 5));
+    assert_eq!(client.last_slot(), None);
+
+    let snapshot = client.get_token_accounts_snapshot(&mint).await.unwrap();
+    assert_eq!(snapshot.slot, Some(123_456));
+    assert_eq!(snapshot.accounts.len(), 6);
+    // Later requests ask for at least this slot, which the mock has reached
+    assert_eq!(client.last_slot(), Some(123_456));
+    client.get_multiple_accounts(&[mint], false).await.unwrap();
+    assert_eq!(client.get_block_time(123_456).await, Some(mock_block_time(123_456)));
+
+    let router = create_api_router(Arc::new(HolderCache::new(client, 60, 2)));
+    let request = Request::builder().uri(format!("/holders/{}", mint)).body(Body::empty()).unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["holders"], 4);
+    assert_eq!(json["token_accounts"], 6);
+    assert_eq!(json["slot"], 123_456);
+    assert_eq!(json["block_time"], mock_block_time(123_456));
+    let metadata = &json["metadata"];
+    assert_eq!(metadata["source"], "get_program_accounts");
+    assert_eq!(metadata["commitment"], "confirmed");
+    assert_eq!(metadata["filters"][1], format!("memcmp@0={}", mint));
+    assert_eq!(metadata["dust_threshold"], 0);
+    assert_eq!(metadata["slot"], 123_456);
+}
+
+#[tokio::test]
+async fn test_finalized_snapshot() {
+    let mint = Pubkey::new_unique();
+    let mut dropped = canned_program_accounts(&mint);
+    dropped.truncate(2);
+    let server = MockRpcServer::builder()
+        .accounts(dropped)
+        .finalized_accounts(canned_program_accounts(&mint))
+        .slot(1_000)
+        .start()
+        .await;
+    let client = SolanaRpcClient::new(server.url.clone(), 1, 5);
+
+    let confirmed = client.get_token_accounts_snapshot(&mint).await.unwrap();
+    assert_eq!(extract_holders(&confirmed.accounts).unwrap().len(), 1);
+    // Finalized is behind the slot already seen, so no minContextSlot is sent
+    let finalized = client.get_finalized_snapshot(&mint).await.unwrap();
+    assert_eq!(finalized.slot, Some(1_000 - FINALIZED_LAG));
+    assert_eq!(extract_holders(&finalized.accounts).unwrap().len(), 4);
+    assert_eq!(client.last_slot(), Some(1_000));
+}
+
+#[tokio::test]
+async fn test_capability_probe() {
+    let mint = Pubkey::new_unique();
+    let server = MockRpcServer::builder().accounts(canned_program_accounts(&mint)).start().await;
+    let client = SolanaRpcClient::new(server.url.clone(), 1, 5);
+    let capabilities = client.probe_capabilities().await.unwrap();
+    assert_eq!(capabilities.program_accounts, ProgramAccountsSupport::Supported);
+    assert!(capabilities.program_accounts_latency_ms.is_some());
+
+    // A rejecting RPC is detected up front: the first poll goes straight to the fallback
+    let server = MockRpcServer::builder()
+        .accounts(canned_program_accounts(&mint))
+        .reject_program_accounts()
+        .das()
+        .start()
+        .await;
+    let client = SolanaRpcClient::new(server.url.clone(), 1, 5);
+    let capabilities = client.probe_capabilities().await.unwrap();
+    assert!(matches!(capabilities.program_accounts, ProgramAccountsSupport::Rejected { .. }));
+    assert_eq!(capabilities.program_accounts_latency_ms, None);
+    let snapshot = client.get_token_accounts_snapshot(&mint).await.unwrap();
+    assert_eq!(snapshot.degraded, Some(Fallback::Das));
+    assert_eq!(server.request_count("getProgramAccounts"), 1);
+}
+
+#[tokio::test]
+async fn test_degraded_fallbacks() {
+    let mint = Pubkey::new_unique();
+
+    // DAS provider: every account, flagged as degraded
+    let server = MockRpcServer::builder()
+        .accounts(canned_program_accounts(&mint))
+        .reject_program_accounts()
+        .das()
+        .start()
+        .await;
+    let client = Arc::new(SolanaRpcClient::new(server.url.clone(), 1, 5));
+    let snapshot = client.get_token_accounts_snapshot(&mint).await.unwrap();
+    assert_eq!(snapshot.degraded, Some(Fallback::Das));
+    assert_eq!(extract_holders(&snapshot.accounts).unwrap().len(), 4);
+    // The rejection is remembered: the next poll goes straight to DAS
+    client.get_token_accounts_snapshot(&mint).await.unwrap();
+    assert_eq!(server.request_count("getProgramAccounts"), 1);
+    assert_eq!(server.request_count("getTokenAccounts"), 2);
+
+    let router = create_api_router(Arc::new(HolderCache::new(client, 60, 2)));
+    let request = Request::builder().uri(format!("/holders/{}", mint)).body(Body::empty()).unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["holders"], 4);
+    assert_eq!(json["degraded"], "das");
+
+    // No DAS: only the largest accounts
+    let server = MockRpcServer::builder()
+        .accounts(canned_program_accounts(&mint))
+        .reject_program_accounts()
+        .start()
+        .await;
+    let client = SolanaRpcClient::new(server.url.clone(), 1, 5);
+    let snapshot = client.get_token_accounts_snapshot(&mint).await.unwrap();
+    assert_eq!(snapshot.degraded, Some(Fallback::LargestAccounts));
+    assert_eq!(extract_holders(&snapshot.accounts).unwrap().len(), 4);
+    assert_eq!(server.request_count("getTokenLargestAccounts"), 1);
+}
+
+#[tokio::test]
+async fn test_truncation_check() {
+    let mint = Pubkey::new_unique();
+    // Served balances add up to 1_502_001: a supply of 2_000_000 means a quarter is missing
+    let server = MockRpcServer::builder()
+        .accounts(canned_program_accounts(&mint))
+        .supply(2_000_000)
+        .start()
+        .await;
+
+    let client = SolanaRpcClient::new(server.url.clone(), 1, 5).with_truncation_check(TruncationMode::Flag, 1.0);
+    let snapshot = client.get_token_accounts_snapshot(&mint).await.unwrap();
+    let missing = snapshot.missing_supply_percent.unwrap();
+    assert!((missing - 24.89995).abs() < 0.001);
+
+    let client = SolanaRpcClient::new(server.url.clone(), 2, 5).with_truncation_check(TruncationMode::Error, 1.0);
+    let error = client.get_token_accounts_snapshot(&mint).await.unwrap_err();
+    assert!(format!("{:#}", error).contains("looks truncated"));
+    // Retried like any RPC error
+    assert_eq!(server.request_count("getProgramAccounts"), 3);
+
+    let client = SolanaRpcClient::new(server.url.clone(), 1, 5).with_truncation_check(TruncationMode::Error, 30.0);
+    assert!(client.get_token_accounts_snapshot(&mint).await.unwrap().missing_supply_percent.is_none());
+
+    // Complete response
+    let server = MockRpcServer::builder().accounts(canned_program_accounts(&mint)).start().await;
+    let client = SolanaRpcClient::new(server.url.clone(), 1, 5);
+    let snapshot = client.get_token_accounts_snapshot(&mint).await.unwrap();
+    assert!(snapshot.missing_supply_percent.is_none());
+    assert_eq!(server.request_count("getTokenSupply"), 1);
+
+    // The supply is pinned to the slot of the account read: a node behind it refuses
+    let slot = snapshot.slot.unwrap();
+    assert_eq!(client.get_token_supply(&mint, Some(slot)).await.unwrap(), 1_502_001);
+    assert!(client.get_token_supply(&mint, Some(slot + 1)).await.is_err());
+}
+
+#[tokio::test]
+async fn test_retry_after_rpc_error() {
+    let mint = Pubkey::new_unique();
+    let server = MockRpcServer::builder()
+        .accounts(canned_program_accounts(&mint))
+        .fail_first(1)
+        .start()
+        .await;
+
+    let client = SolanaRpcClient::new(server.url.clone(), 2, 5);
+    let accounts = client.get_token_accounts_by_mint(&mint).await.unwrap();
+    assert_eq!(accounts.len(), 6);
+    assert_eq!(server.request_count("getProgramAccounts"), 2);
+
+    // No retries left: the error surfaces
+    let server = MockRpcServer::builder().fail_first(5).start().await;
+    let client = SolanaRpcClient::new(server.url.clone(), 1, 5);
+    assert!(client.get_token_accounts_by_mint(&mint).await.is_err());
+}
+
+#[tokio::test]
+async fn test_genesis_check() {
+    let devnet = Cluster::Devnet.genesis_hash().unwrap();
+    let server = MockRpcServer::builder().genesis_hash(devnet).start().await;
+    let client = SolanaRpcClient::new(server.url.clone(), 1, 5);
+
+    let genesis = client.get_genesis_hash().await.unwrap();
+    assert!(Cluster::Devnet.verify_genesis(&genesis).is_ok());
+    assert!(Cluster::Mainnet.verify_genesis(&genesis).is_err());
+}
+
+#[tokio::test]
+async fn test_doctor_report() {
+    let mint = Pubkey::new_unique();
+    let token_program = Pubkey::from_str(solana_holder_bot::rpc_client::TOKEN_PROGRAM_ID).unwrap();
+    let mut accounts = canned_program_accounts(&mint);
+    accounts.push(owner_account(&mint, &token_program, vec![0; 82]));
+    // A slot whose mock block time is now, so the clock check passes
+    let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
+    let server = MockRpcServer::builder().accounts(accounts).slot((now - 1_600_000_000) * 5 / 2).start().await;
+
+    let mint_arg = mint.to_string();
+    let args = ["solana-holder-bot", "--rpc-url", &server.url, "--cluster", "devnet", "--webhook-url", &server.url, &mint_arg, "doctor"];
+    let mut cli = Cli::try_parse_from(args).unwrap();
+    let report = diagnose(&mut cli).await;
+    let status = |name: &str| report.check(name).unwrap_or_else(|| panic!("no {} check", name)).status;
+
+    assert_eq!(status("settings"), CheckStatus::Pass);
+    assert_eq!(status("dns 127.0.0.1"), CheckStatus::Pass);
+    assert_eq!(status("rpc"), CheckStatus::Pass);
+    assert_eq!(status("getProgramAccounts"), CheckStatus::Pass);
+    // The mock serves the mainnet genesis hash
+    assert_eq!(status("cluster"), CheckStatus::Fail);
+    assert_eq!(status("clock"), CheckStatus::Pass);
+    assert_eq!(status(&format!("mint {}", mint)), CheckStatus::Pass);
+    assert_eq!(status("channel webhook"), CheckStatus::Pass);
+    assert_eq!(report.count(CheckStatus::Fail), 1);
+}
+
+#[tokio::test]
+async fn test_api_holders_endpoint() {
+    let mint = Pubkey::new_unique();
+    let server = MockRpcServer::builder()
+        .accounts(canned_program_accounts(&mint))
+        .start()
+        .await;
+    let client = Arc::new(SolanaRpcClient::new(server.url.clone(), 1, 5));
+    let router = create_api_router(Arc::new(HolderCache::new(client, 60, 2)));
+
+    let request = |uri: String| Request::builder().uri(uri).body(Body::empty()).unwrap();
+
+    let response = router
+        .clone()
+        .oneshot(request(format!("/holders/{}", mint)))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["holders"], 4);
+    assert_eq!(json["cached"], false);
+
+    // Second request is served from cache without another RPC call
+    let response = router
+        .clone()
+        .oneshot(request(format!("/holders/{}", mint)))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(server.request_count("getProgramAccounts"), 1);
+
+    let response = router.oneshot(request("/holders/not-a-mint".to_string())).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_api_stats_summary() {
+    let (token, lp) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let (both, token_only, lp_only) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    let server = MockRpcServer::builder()
+        .accounts(vec![
+            token_account(&token, &both, 10),
+            token_account(&token, &token_only, 5),
+            token_account(&lp, &both, 1),
+            token_account(&lp, &lp_only, 2),
+        ])
+        .start()
+        .await;
+    let client = Arc::new(SolanaRpcClient::new(server.url.clone(), 1, 5));
+    let cache = Arc::new(HolderCache::new(client, 60, 2));
+    cache.get_holder_count(&token.to_string()).await.unwrap();
+    cache.get_holder_count(&lp.to_string()).await.unwrap();
+    let router = create_api_router(cache);
+
+    let request = Request::builder().uri("/stats/summary").body(Body::empty()).unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["tokens"], 2);
+    assert_eq!(json["holders_total"], 4);
+    assert_eq!(json["unique_wallets"], 3);
+    assert_eq!(json["unique_wallets_complete"], true);
+    assert_eq!(json["per_token"][0]["sparkline"], serde_json::json!([2]));
+    // One observation per token: no growth yet
+    assert!(json["most_grown_24h"].is_null());
+}
+
+#[tokio::test]
+async fn test_api_monitor_metrics_endpoint() {
+    let mint = Pubkey::new_unique();
+    let mut metrics = Metrics::new();
+    metrics.update(10);
+    metrics.update(20);
+    metrics.record_poll(true, 1_700_000_000);
+    metrics.record_poll(false, 1_700_000_060);
+    let monitor_metrics = MonitorMetrics::default();
+    monitor_metrics
+        .write()
+        .await
+        .insert(mint.to_string(), metrics.summary());
+
+    let client = Arc::new(SolanaRpcClient::new("http://127.0.0.1:1".to_string(), 1, 1));
+    let router = create_api_router(Arc::new(
+        HolderCache::new(client, 60, 2).with_monitor_metrics(monitor_metrics),
+    ));
+    let request = |uri: String| Request::builder().uri(uri).body(Body::empty()).unwrap();
+
+    let response = router
+        .clone()
+        .oneshot(request(format!("/monitor/{}/metrics", mint)))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["mint"], mint.to_string());
+    assert_eq!(json["total_polls"], 2);
+    assert_eq!(json["min_holders"], 10);
+    assert_eq!(json["average_holders"], 15.0);
+
+    // Mints that are not monitored (even if cached by the API) are not found
+    let response = router
+        .clone()
+        .oneshot(request(format!("/monitor/{}/metrics", Pubkey::new_unique())))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    // Prometheus text and JSON carry the same values
+    let response = router.clone().oneshot(request("/metrics".to_string())).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers()["content-type"].to_str().unwrap().starts_with("text/plain"));
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let text = String::from_utf8(body.to_vec()).unwrap();
+    assert!(text.contains(&format!("solana_holder_bot_holders_min{{mint=\"{}\"}} 10", mint)));
+
+    let response = router.clone().oneshot(request("/metrics.json".to_string())).await.unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let families = json["metrics"].as_array().unwrap();
+    let min = families.iter().find(|family| family["name"] == "solana_holder_bot_holders_min").unwrap();
+    assert_eq!(min["samples"][0]["labels"]["mint"], mint.to_string());
+    assert_eq!(min["samples"][0]["value"], 10.0);
+
+    let response = router.clone().oneshot(request("/health".to_string())).await.unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["status"], "ok");
+    assert_eq!(json["monitor"]["last_success_at"], 1_700_000_000);
+    assert_eq!(json["monitor"]["consecutive_failures"], 1);
+    assert_eq!(json["rpc"]["consecutive_failures"], 0);
+
+    let response = router.oneshot(request("/version".to_string())).await.unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["version"], env!("CARGO_PKG_VERSION"));
+    assert!(json["features"].as_array().unwrap().iter().any(|feature| feature == "test-util"));
+}
+
+#[tokio::test]
+async fn test_reports_cached_per_sample_size() {
+    let mint = Pubkey::new_unique();
+    let server = MockRpcServer::builder()
+        .accounts(canned_program_accounts(&mint))
+        .start()
+        .await;
+    let client = Arc::new(SolanaRpcClient::new(server.url.clone(), 1, 5));
+    let router = create_api_router(Arc::new(HolderCache::new(client, 60, 2)));
+    let get = |uri: String| Request::builder().uri(uri).body(Body::empty()).unwrap();
+    let response = router.clone().oneshot(get(format!("/holders/{}", mint))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(server.request_count("getProgramAccounts"), 1);
+
+    // Each report refetches the accounts; a cached report is reused only for the same sample size
+    for (sample, expected_fetches) in [(1, 2), (1, 2), (3, 3), (1, 3)] {
+        let response = router
+            .clone()
+            .oneshot(get(format!("/holders/{}/quality?sample={}", mint, sample)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(server.request_count("getProgramAccounts"), expected_fetches, "sample={}", sample);
+    }
+
+    // Profiles sample the cached holders: a larger sample is not answered from the smaller one
+    for sample in [1, 3, 1] {
+        let response = router
+            .clone()
+            .oneshot(get(format!("/holders/{}/profiles?sample={}", mint, sample)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["sampled_wallets"], sample, "sample={}", sample);
+    }
+}
+
+#[tokio::test]
+async fn test_api_refresh_requires_key() {
+    let mint = Pubkey::new_unique();
+    let server = MockRpcServer::builder()
+        .accounts(canned_program_accounts(&mint))
+        .start()
+        .await;
+    let client = Arc::new(SolanaRpcClient::new(server.url.clone(), 1, 5));
+    let router = create_api_router(Arc::new(
+        HolderCache::new(client.clone(), 60, 2).with_api_keys(ApiKeys::from_keys(vec!["secret".to_string()]).unwrap()),
+    ));
+    let refresh = |key: Option<&str>| {
+        let mut request = Request::builder().method("POST").uri(format!("/refresh/{}", mint));
+        if let Some(key) = key {
+            request = request.header("authorization", format!("Bearer {}", key));
+        }
+        request.body(Body::empty()).unwrap()
+    };
+
+    let response = router.clone().oneshot(refresh(None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let response = router.clone().oneshot(refresh(Some("wrong"))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    // Each refresh hits RPC, even when the mint is already cached
+    for expected_fetches in 1..=2 {
+        let response = router.clone().oneshot(refresh(Some("secret"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["holders"], 4);
+        assert_eq!(server.request_count("getProgramAccounts"), expected_fetches);
+    }
+
+    // Webhooks to loopback, link-local or private hosts are rejected before any fetch
+    let internal = ["http://127.0.0.1:8080/hook", "http://localhost/hook", "http://169.254.169.254/", "ftp://example.com/"];
+    for webhook_url in internal {
+        let request = Request::builder()
+            .method("POST")
+            .uri(format!("/refresh/{}?webhook_url={}", mint, webhook_url))
+            .header("authorization", "Bearer secret")
+            .body(Body::empty())
+            .unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{}", webhook_url);
+    }
+    assert_eq!(server.request_count("getProgramAccounts"), 2);
+
+    // No keys configured: the endpoint is disabled
+    let router = create_api_router(Arc::new(HolderCache::new(client, 60, 2)));
+    let response = router.oneshot(refresh(Some("secret"))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_api_key_limits_and_usage() {
+    let mint = Pubkey::new_unique();
+    let server = MockRpcServer::builder()
+        .accounts(canned_program_accounts(&mint))
+        .start()
+        .await;
+    let client = Arc::new(SolanaRpcClient::new(server.url.clone(), 1, 5));
+    let configured = std::collections::HashMap::from([(
+        "team".to_string(),
+        ApiKeyConfig {
+            key: "team-key".to_string(),
+            requests_per_minute: Some(2),
+            mints: Some(vec![mint.to_string()]),
+            admin: false,
+        },
+    )]);
+    let keys = ApiKeys::new(&["admin-key".to_string()], &configured).unwrap();
+    let router = create_api_router(Arc::new(
+        HolderCache::new(client, 60, 2)
+            .with_api_keys(keys)
+            .with_required_api_key(true),
+    ));
+    let get = |uri: String, key: Option<&str>| {
+        let mut request = Request::builder().uri(uri);
+        if let Some(key) = key {
+            request = request.header("x-api-key", key);
+        }
+        request.body(Body::empty()).unwrap()
+    };
+
+    let response = router.clone().oneshot(get(format!("/holders/{}", mint), None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let response = router.clone().oneshot(get("/health".to_string(), None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let other = Pubkey::new_unique();
+    let response = router.clone().oneshot(get(format!("/holders/{}", other), Some("team-key"))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let response = router.clone().oneshot(get("/admin/usage".to_string(), Some("team-key"))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let response = router.clone().oneshot(get(format!("/holders/{}", mint), Some("team-key"))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    // Two requests per minute, the admin attempt included
+    let response = router.clone().oneshot(get(format!("/holders/{}", mint), Some("team-key"))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(response.headers().contains_key("retry-after"));
+
+    let response = router.clone().oneshot(get("/admin/usage".to_string(), Some("admin-key"))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let usage: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(usage[1]["name"], "team");
+    assert_eq!(usage[1]["requests"], 2);
+    assert_eq!(usage[1]["rate_limited"], 1);
+    assert_eq!(usage[1]["mint_denied"], 1);
+}
+
+#[tokio::test]
+async fn test_dashboard_and_history() {
+    let mint = Pubkey::new_unique();
+    let server = MockRpcServer::builder().start().await;
+    let client = Arc::new(SolanaRpcClient::new(server.url.clone(), 1, 5));
+
+    let dir = std::env::temp_dir().join(format!("holder-bot-history-{}", mint));
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let mut history = CountHistory::new(&mint.to_string());
+    history.record(100, now - 30 * 86_400, None);
+    history.record(150, now - 2 * 3600, None);
+    history.record(160, now, None);
+    let mut spike = calculate_stats(160, Some(150));
+    spike.timestamp = now;
+    history.annotate(AlertRule::Milestone, "MILESTONE: 160", &spike);
+    history.save(&dir).unwrap();
+
+    let mut metrics = Metrics::new();
+    metrics.update(170);
+    let monitor_metrics = MonitorMetrics::default();
+    monitor_metrics.write().await.insert(mint.to_string(), metrics.summary());
+
+    let keys = ApiKeys::from_keys(vec!["secret".to_string()]).unwrap();
+    let router = create_api_router(Arc::new(
+        HolderCache::new(client, 60, 2)
+            .with_ledger_dir(Some(dir.clone()))
+            .with_monitor_metrics(monitor_metrics)
+            .with_api_keys(keys)
+            .with_required_api_key(true),
+    ));
+    let get = |uri: String| Request::builder().uri(uri).body(Body::empty()).unwrap();
+
+    // The dashboard itself is public; its API calls carry the key
+    let response = router.clone().oneshot(get("/".to_string())).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers()["content-type"].to_str().unwrap().starts_with("text/html"));
+    let response = router.clone().oneshot(get("/assets/app.js".to_string())).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let request = Request::builder()
+        .uri(format!("/holders/{}/history?days=7", mint))
+        .header("x-api-key", "secret")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let points = json["points"].as_array().unwrap();
+    assert_eq!(points.len(), 2);
+    assert_eq!(points.last().unwrap()["max"], 160);
+    assert_eq!(json["annotations"][0]["rule"], "milestone");
+    assert_eq!(json["annotations"][0]["bucket"], points.last().unwrap()["start"]);
+
+    // Two hourly points are too few for a forecast
+    let forecast = |query: &str| {
+        Request::builder()
+            .uri(format!("/holders/{}/forecast?{}", mint, query))
+            .header("x-api-key", "secret")
+            .body(Body::empty())
+            .unwrap()
+    };
+    let response = router.clone().oneshot(forecast("horizon=24h")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let response = router.clone().oneshot(forecast("horizon=1y")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // Growth against the count at the start of the window: 170 now, 100 a day ago
+    let leaderboard = |query: &str| {
+        Request::builder()
+            .uri(format!("/leaderboard?{}", query))
+            .header("x-api-key", "secret")
+            .body(Body::empty())
+            .unwrap()
+    };
+    let response = router.clone().oneshot(leaderboard("window=24h")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["window"], "24h");
+    assert_eq!(json["entries"][0]["mint"], mint.to_string());
+    assert_eq!(json["entries"][0]["holders_before"], 100);
+    assert_eq!(json["entries"][0]["change"], 70);
+    let response = router.clone().oneshot(leaderboard("window=2h")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_monitor_publishes_into_api_cache() {
+    let mint = Pubkey::new_unique();
+    let other_mint = Pubkey::new_unique();
+    let mut accounts = canned_program_accounts(&mint);
+    accounts.extend(canned_program_accounts(&other_mint));
+    let server = MockRpcServer::builder().accounts(accounts).start().await;
+    let client = Arc::new(SolanaRpcClient::new(server.url.clone(), 1, 5));
+    let cache = Arc::new(HolderCache::new(client.clone(), 60, 1).with_monitored_mints([mint.to_string()]));
+    let bus = EventBus::default();
+    let mut registry = SinkRegistry::default();
+    registry.register(cache.clone());
+    let sinks = registry.spawn(&bus);
+
+    // The monitoring loop's poll is the only fetch of the monitored mint
+    let snapshot = client.get_token_accounts_snapshot(&mint).await.unwrap();
+    let holders = extract_holders(&snapshot.accounts).unwrap();
+    bus.publish(HolderEvent::Observation(Observation {
+        mint,
+        stats: calculate_stats(holders.len(), None),
+        previous_count: None,
+        elapsed: std::time::Duration::ZERO,
+        churn: None,
+        holders: Arc::new(holders),
+        metadata: Arc::new(client.count_metadata(&mint, &snapshot)),
+    }));
+    bus.close();
+    for sink in sinks {
+        sink.await.unwrap();
+    }
+    let (entry, cached) = cache.get_holder_count(&mint.to_string()).await.unwrap();
+    assert!(cached);
+    assert_eq!(entry.count(), 4);
+    assert_eq!(server.request_count("getProgramAccounts"), 1);
+
+    // Monitored mints don't take a --cache-max-tokens slot and are never evicted
+    let (_, cached) = cache.get_holder_count(&other_mint.to_string()).await.unwrap();
+    assert!(!cached);
+    let (entry, cached) = cache.get_holder_count(&mint.to_string()).await.unwrap();
+    assert!(cached);
+    assert_eq!(entry.request_count(), 2);
+    assert_eq!(server.request_count("getProgramAccounts"), 2);
+}
+
+#[tokio::test]
+async fn test_alerts_annotate_history() {
+    let mint = Pubkey::new_unique();
+    let server = MockRpcServer::builder().start().await;
+    let client = Arc::new(SolanaRpcClient::new(server.url.clone(), 1, 5));
+    // No --ledger-dir and no --ath-alerts: the history is the one the cache keeps in memory
+    let cache = Arc::new(HolderCache::new(client, 60, 1).with_monitored_mints([mint.to_string()]));
+    let router = create_api_router(cache.clone());
+    let bus = EventBus::default();
+    let mut registry = SinkRegistry::default();
+    registry.register(cache);
+    let sinks = registry.spawn(&bus);
+
+    let holders: std::collections::HashSet<Pubkey> = (0..150).map(|_| Pubkey::new_unique()).collect();
+    let stats = calculate_stats(holders.len(), Some(100));
+    bus.publish(HolderEvent::Observation(Observation {
+        mint,
+        stats: stats.clone(),
+        previous_count: Some(100),
+        elapsed: std::time::Duration::ZERO,
+        churn: None,
+        holders: Arc::new(holders),
+        metadata: Arc::default(),
+    }));
+    bus.publish(HolderEvent::Alert(AlertEvent {
+        mint,
+        rule: AlertRule::Growth,
+        message: "GROWTH: +50 holders".to_string(),
+        stats: stats.clone(),
+        previous_count: Some(100),
+        streak: 1,
+    }));
+    bus.close();
+    for sink in sinks {
+        sink.await.unwrap();
+    }
+
+    let request = Request::builder()
+        .uri(format!("/holders/{}/history?days=1", mint))
+        .body(Body::empty())
+        .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["points"][0]["max"], 150);
+    assert_eq!(json["annotations"][0]["rule"], "growth");
+    assert_eq!(json["annotations"][0]["message"], "GROWTH: +50 holders");
+
+    // The leaderboard lists the cached mint too; its history does not reach back a day yet
+    let request = Request::builder().uri("/leaderboard?window=24h").body(Body::empty()).unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["entries"].as_array().unwrap().len(), 0);
+    assert_eq!(json["unranked"][0], mint.to_string());
+}
+
+#[tokio::test]
+async fn test_owner_breakdown_endpoint() {
+    let mint = Pubkey::new_unique();
+    let system_program = Pubkey::from_str("11111111111111111111111111111111").unwrap();
+    let squads = Pubkey::from_str("SQDS4ep65T869zMMBKyuUq6aD6EgTu8psMjkvj52pCf").unwrap();
+    let token_program = Pubkey::from_str(solana_holder_bot::rpc_client::TOKEN_PROGRAM_ID).unwrap();
+    let (wallet, squads_account, spl_multisig) =
+        (Keypair::new().pubkey(), Keypair::new().pubkey(), Keypair::new().pubkey());
+    let (pda, _) = Pubkey::find_program_address(&[b"vault"], &squads);
+    let signers = [Keypair::new().pubkey(), Keypair::new().pubkey()];
+    // SPL Token multisig: 1 of 2
+    let mut multisig_data = vec![1, 2, 1];
+    for signer in &signers {
+        multisig_data.extend_from_slice(signer.as_ref());
+    }
+    multisig_data.resize(355, 0);
+    let accounts = vec![
+        token_account(&mint, &wallet, 10),
+        token_account(&mint, &squads_account, 20),
+        token_account(&mint, &pda, 30),
+        token_account(&mint, &spl_multisig, 40),
+        owner_account(&wallet, &system_program, Vec::new()),
+        owner_account(&squads_account, &squads, vec![0; 64]),
+        owner_account(&spl_multisig, &token_program, multisig_data),
+    ];
+    let server = MockRpcServer::builder().accounts(accounts).start().await;
+    let client = Arc::new(SolanaRpcClient::new(server.url.clone(), 1, 5));
+    let router = create_api_router(Arc::new(HolderCache::new(client, 60, 2)));
+    let get = |uri: String| Request::builder().uri(uri).body(Body::empty()).unwrap();
+
+    // Only tracked mints have a holder set to break down
+    let uri = format!("/holders/{}/breakdown?full=true", mint);
+    let response = router.clone().oneshot(get(uri.clone())).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    router.clone().oneshot(get(format!("/holders/{}", mint))).await.unwrap();
+
+    let response = router.clone().oneshot(get(uri.clone())).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["examined_owners"], 4);
+    assert_eq!(json["wallets"], 1);
+    assert_eq!(json["no_account"], 1);
+    assert_eq!(json["program_owned"], 2);
+    assert_eq!(json["program_derived"], 1);
+    assert_eq!(json["personal_wallets"], 1);
+    // The Squads account isn't a Multisig account; the SPL one resolves to its signers
+    assert_eq!(json["multisigs"].as_array().unwrap().len(), 1);
+    assert_eq!(json["multisigs"][0]["address"], spl_multisig.to_string());
+    assert_eq!(json["multisigs"][0]["kind"], "spl_token");
+    assert_eq!(json["multisigs"][0]["threshold"], 1);
+    assert_eq!(json["multisigs"][0]["members"][1], signers[1].to_string());
+
+    // Served from the analytics cache; multisig data took a second call
+    router.oneshot(get(uri)).await.unwrap();
+    assert_eq!(server.request_count("getMultipleAccounts"), 2);
+}
+
+#[tokio::test]
+async fn test_protocol_deposits_endpoint() {
+    let (mint, receipt_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let kamino = Pubkey::from_str("KLend2g3cP87fffoy8q1mQqGKjrxjC8boSyAYavgmjD").unwrap();
+    let (reserve, farm_authority, wallet, depositor) =
+        (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    let accounts = vec![
+        token_account(&mint, &reserve, 500),
+        token_account(&mint, &farm_authority, 300),
+        token_account(&mint, &wallet, 200),
+        owner_account(&reserve, &kamino, vec![0; 8]),
+        // kTokens: the wallet and another depositor that no longer holds the mint itself
+        token_account(&receipt_mint, &wallet, 10),
+        token_account(&receipt_mint, &depositor, 10),
+    ];
+    let server = MockRpcServer::builder().accounts(accounts).start().await;
+    let client = Arc::new(SolanaRpcClient::new(server.url.clone(), 1, 5));
+    let protocols: ProtocolConfig = toml::from_str(&format!(
+        "[authorities]\n\"{}\" = \"Raydium Farm v5\"\n[receipt_mints]\n\"{}\" = [\"{}\"]",
+        farm_authority, mint, receipt_mint
+    ))
+    .unwrap();
+    let router = create_api_router(Arc::new(HolderCache::new(client, 60, 2).with_protocols(protocols)));
+    let get = |uri: String| Request::builder().uri(uri).body(Body::empty()).unwrap();
+
+    router.clone().oneshot(get(format!("/holders/{}", mint))).await.unwrap();
+    let response = router.oneshot(get(format!("/holders/{}/deposits", mint))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["holders"], 3);
+    assert_eq!(json["protocol_holdings"][0]["protocol"], "Kamino Lending");
+    assert_eq!(json["protocol_holdings"][0]["kind"], "lending");
+    assert_eq!(json["protocol_holdings"][1]["address"], farm_authority.to_string());
+    assert_eq!(json["protocol_amount_percent"], 80.0);
+    assert_eq!(json["holders_excluding_protocols"], 1);
+    assert_eq!(json["depositors"], 2);
+    assert_eq!(json["holders_including_depositors"], 2);
+}
+
+#[tokio::test]
+async fn test_holder_export_endpoint() {
+    let mint = Pubkey::new_unique();
+    let server = MockRpcServer::builder().accounts(canned_program_accounts(&mint)).start().await;
+    let client = Arc::new(SolanaRpcClient::new(server.url.clone(), 1, 5));
+    let router = create_api_router(Arc::new(HolderCache::new(client, 60, 2)));
+    let get = |uri: String| Request::builder().uri(uri).body(Body::empty()).unwrap();
+
+    // Only tracked mints can be exported
+    let response = router.clone().oneshot(get(format!("/holders/{}/export", mint))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    router.clone().oneshot(get(format!("/holders/{}", mint))).await.unwrap();
+    let response = router.clone().oneshot(get(format!("/holders/{}/export", mint))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "text/csv; charset=utf-8");
+    let disposition = response.headers()["content-disposition"].to_str().unwrap().to_string();
+    assert_eq!(disposition, format!("attachment; filename=\"{}-holders.csv\"", mint));
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let csv = String::from_utf8(body.to_vec()).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 5);
+    assert_eq!(lines[0], "owner,amount");
+    // The whale's two accounts are summed
+    assert!(lines[1].ends_with(",1500000"));
+
+    let response = router.oneshot(get(format!("/holders/{}/export?format=json&gzip=true", mint))).await.unwrap();
+    assert_eq!(response.headers()["content-type"], "application/gzip");
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let mut json = String::new();
+    flate2::read::GzDecoder::new(&body[..]).read_to_string(&mut json).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(json.as_array().unwrap().len(), 4);
+    assert_eq!(json[3]["amount"], 1);
+}
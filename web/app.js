@@ -0,0 +1,84 @@
+// Dashboard for non-technical teammates: tracked tokens, live counts, 7-day sparklines, alerts
+const REFRESH_MS = 30000;
+const keyInput = document.getElementById("api-key");
+keyInput.value = localStorage.getItem("apiKey") || "";
+keyInput.addEventListener("change", () => {
+  localStorage.setItem("apiKey", keyInput.value);
+  refresh();
+});
+
+async function api(path) {
+  const headers = keyInput.value ? { "X-API-Key": keyInput.value } : {};
+  const response = await fetch(path, { headers });
+  if (!response.ok) throw new Error(`${path}: ${response.status}`);
+  return response.json();
+}
+
+function sparkline(points) {
+  if (points.length < 2) return "—";
+  const values = points.map((p) => p.max);
+  const min = Math.min(...values);
+  const span = Math.max(...values) - min || 1;
+  const coords = values
+    .map((v, i) => `${(i / (values.length - 1)) * 140},${28 - ((v - min) / span) * 26 - 1}`)
+    .join(" ");
+  return `<svg class="spark" viewBox="0 0 140 28"><polyline points="${coords}"/></svg>`;
+}
+
+function ago(secs) {
+  const diff = Math.max(0, Math.floor(Date.now() / 1000) - secs);
+  return diff < 120 ? `${diff}s ago` : `${Math.floor(diff / 60)}m ago`;
+}
+
+function cell(text, className) {
+  const td = document.createElement("td");
+  td.textContent = text;
+  if (className) td.className = className;
+  return td;
+}
+
+async function refresh() {
+  const status = document.getElementById("status");
+  try {
+    const [tokens, monitored] = await Promise.all([api("tokens"), api("monitor")]);
+    const rows = new Map();
+    for (const t of tokens) rows.set(t.mint, { holders: t.holders, updated: t.last_updated, growth: t.growth });
+    for (const m of monitored) {
+      const row = rows.get(m.mint) || {};
+      rows.set(m.mint, { ...row, holders: row.holders ?? m.last_holders, alerts: m.alerts });
+    }
+
+    const body = document.getElementById("tokens");
+    body.replaceChildren();
+    const alerts = [];
+    for (const [mint, row] of rows) {
+      const tr = document.createElement("tr");
+      tr.append(cell(mint, "mint"), cell(row.holders ?? "—"));
+      const growth = row.growth ?? 0;
+      tr.append(cell(growth > 0 ? `+${growth}` : `${growth}`, growth > 0 ? "up" : growth < 0 ? "down" : ""));
+      const spark = document.createElement("td");
+      spark.textContent = "…";
+      tr.append(spark, cell(row.updated ? ago(row.updated) : "—"));
+      body.append(tr);
+      api(`holders/${mint}/history?days=7`)
+        .then((history) => (spark.innerHTML = sparkline(history.points)))
+        .catch(() => (spark.textContent = "—"));
+      for (const text of row.alerts || []) alerts.push({ mint, text });
+    }
+
+    const list = document.getElementById("alerts");
+    list.replaceChildren();
+    for (const alert of alerts.slice(-20).reverse()) {
+      const li = document.createElement("li");
+      li.textContent = `${alert.mint.slice(0, 6)}…: ${alert.text}`;
+      list.append(li);
+    }
+    if (!alerts.length) list.append(Object.assign(document.createElement("li"), { textContent: "No alerts yet" }));
+    status.textContent = `${rows.size} tokens · updated ${new Date().toLocaleTimeString()}`;
+  } catch (e) {
+    status.textContent = `error: ${e.message}`;
+  }
+}
+
+refresh();
+setInterval(refresh, REFRESH_MS);